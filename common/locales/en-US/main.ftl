@@ -9,6 +9,7 @@ uplink = Uplink
     .users = Users
     .groups = Groups
     .members = Members
+    .messages = Messages
     .members-count = Members ({ $num })
     .reset-account = Reset Account (Irreversible!)
     .coming-soon = Coming soon
@@ -22,6 +23,17 @@ uplink = Uplink
     .leave-group = Leave Group
     .delete-conversation = Delete Chat
     .hide-chat = Hide Chat
+    .unmute-chat = Unmute
+    .mute-chat-hour = Mute for 1 hour
+    .mute-chat-8-hours = Mute for 8 hours
+    .mute-chat-forever = Mute until turned back on
+    .notify-all = Notify for all messages
+    .notify-mentions = Notify for mentions only
+    .export-json = Export as JSON
+    .export-html = Export as HTML
+    .export-markdown = Export as Markdown
+    .export-complete = Chat exported
+    .export-failed = Failed to export chat
     .search-placeholder = Search...
     .nothing-here = Nothing Here...
     .end = End
@@ -76,6 +88,14 @@ warning-messages = Warning Messages
 messages = Messages
     .edit = Edit
     .cancel-edit = Cancel Edit
+    .send-later = Send Later
+    .send-at = Send at
+    .send-at-invalid = Pick a time in the future.
+    .schedule = Schedule
+    .scheduled-count = Scheduled messages ({ $num })
+    .scheduled-attachments = { $num } attachment(s)
+    .cancel-scheduled = Cancel scheduled message
+    .save-scheduled = Save
     .new = New Message
     .replying = Replying to:
     .react = React
@@ -105,16 +125,70 @@ messages = Messages
     .pin-button-unpin = Unpin
     .pinned-none = There are no pinned messages in this chat
     .pinned-max = You reached the maximum amount of pinned messages for this chat
+    .reply-in-thread = Reply in Thread
+    .thread = Thread
+    .thread-replies = Replies ({ $num })
+    .thread-placeholder = Reply to thread...
+    .thread-unavailable = The thread could not be loaded
+    .code-copied = Copied!
+    .code-show-all = Show all { $num } lines
+    .code-show-less = Show less
+    .open-link = Open Link
+    .open-link-description = This link leads to the address below. Do you want to open it in your browser?
+    .load-embed = Load player from { $host }
+    .open = Open
+    .format-bold = Bold
+    .format-italic = Italic
+    .format-strikethrough = Strikethrough
+    .format-code = Code
+    .format-code-block = Code Block
+    .format-link = Link
+    .format-quote = Quote
+    .format-list = List
+    .format-task = Task
+    .format-spoiler = Spoiler
+    .preview = Preview
+    .send-waiting = Not sent yet, retrying...
+    .send-failed = Failed to send
+    .send-retry = Retry
+    .send-discard = Discard
+    .search = Search Chat
+    .search-placeholder = Search messages...
+    .search-hint = Type and press enter, or pick a filter.
+    .search-anyone = Anyone
+    .search-any-time = Any time
+    .search-past-day = Past day
+    .search-past-week = Past week
+    .search-past-month = Past month
+    .search-past-year = Past year
+    .search-attachments = With attachments
+    .search-has-attachment = Has attachments
+    .search-no-results = No messages found
+    .search-failed = The search failed
     .missing-emoji-picker = Emoji extension is disabled
     .unknown-identity = User not found:
     .emoji-suggestion = Suggested Emoji
     .username-suggestion = Suggested Users
+    .command-suggestion = Commands
     .control-group = More
     .no-chats = No chats available
     .attachments-fail = Upload Failed!
     .attachments-fail-msg = Upload failed: { $reason }
     .attachments-fail-no-storage = No more storage left!
     
+commands = Commands
+    .me = Send an action, ex: /me waves
+    .shrug = Append ¯\_(ツ)_/¯ to your message
+    .tableflip = Append (╯°□°)╯︵ ┻━┻ to your message
+    .unflip = Append ┬─┬ノ( º _ ºノ) to your message
+    .pin = Pin the latest message in this chat
+    .mute = Mute or unmute your microphone
+    .call = Start a call in this chat
+    .me-usage = Usage: /me <action>
+    .nothing-to-pin = There are no messages to pin.
+    .not-in-call = You are not in a call.
+    .already-in-call = You are already in a call.
+
 favorites = Favorites
     .favorites = Favorites
     .add = Add to Favorites
@@ -211,6 +285,7 @@ settings = Settings
     .messages = Messages
     .privacy = Privacy
     .audio = Sounds & Audio
+    .recordings = Call Recordings
     .files = Files
     .extensions = Extensions
     .keybinds = Keyboard Shortcuts
@@ -241,6 +316,22 @@ settings-profile = Profile Settings
     .status-idle = Idle
     .status-do-not-disturb = Do not disturb
     .status-offline = Offline
+    .status-expiry = Clear Status Message
+    .status-expiry-description = Clear your status message automatically after a while
+    .status-expires = Your status message clears on { $time }
+    .status-expiry-never = Never
+    .status-expiry-minutes = { $count } minutes
+    .status-expiry-hours = { $count ->
+        [one] 1 hour
+        *[other] { $count } hours
+    }
+    .status-expiry-today = End of today
+    .auto-away = Automatic Idle
+    .auto-away-description = Show as idle when you haven't used Uplink for a while
+    .away-after = Idle After
+    .away-after-description = How long Uplink has to go unused before you are shown as idle
+    .busy-in-calls = Do Not Disturb In Calls
+    .busy-in-calls-description = Show as do not disturb while you are in a call
     .recovery-seed = Recovery Seed
     .recovery-seed-description = This seed represents the "master key" for your account. Keep this safe and secure somewhere in order to maintain proper control and security over your Uplink account.
     .reveal-recovery-seed = Reveal Recovery Seed
@@ -261,18 +352,46 @@ settings-general = General Settings
     .font = Font
     .font-description = Change the font of the app.
     .clear-accent = Clear accent color
+    .backup = Backup
+    .backup-description = Save your account, settings, themes, fonts and extensions to a file encrypted with a passphrase. The file can be restored when you set up Uplink on another device.
+    .backup-passphrase = Backup passphrase
+    .backup-create = Create Backup
+    .backup-created = Backup created
+    .backup-failed = Failed to create the backup
 
 settings-messages = Message Settings
     .emoji-conversion = Convert Emoji
     .emoji-conversion-description = Convert Emoji text like ':)' into an emoji symbol like '😊'.
     .markdown-support = Markdown support
     .markdown-support-description = Enables the support of the Markdown markup language in messaging. 
+    .link-previews = Link Previews
+    .link-previews-description = Show a preview of the first link in a message. Loading a preview reveals your IP address to the linked site.
+    .link-previews-all = All links
+    .link-previews-sent = Only links I sent
+    .link-previews-off = Off
+    .clear-link-previews = Clear Link Previews
+    .clear-link-previews-description = Forget the previews which were already loaded, so they are fetched again.
+    .clear = Clear
+    .link-previews-cleared = Link previews cleared
 
 settings-privacy = Settings Privacy 
     .backup-recovery-phrase = Backup Recovery Phrase
     .backup-phrase = Backup Phrase
     .backup-phrase-description = Back this phrase up! Along with your password this represents your account. If you lose it, we can't help you get it back.
 
+settings-recordings = Call Recordings
+    .open-folder = Open Recordings Folder
+    .open-folder-description = Calls you record are saved here. Each recording is a folder with one track per participant.
+    .refresh = Refresh
+    .empty = You haven't recorded any calls yet.
+    .participants = With { $names }
+    .name-placeholder = Recording name
+    .confirm-delete = Delete?
+    .shared = Recording shared.
+    .share-failed = Failed to share the recording.
+    .rename-failed = Failed to rename the recording.
+    .delete-failed = Failed to delete the recording.
+
 settings-audio = Audio & Sound Settings 
     .input-device = Input Device
     .input-device-description = Select your input device (microphone, usually).
@@ -294,6 +413,16 @@ settings-audio = Audio & Sound Settings
     .message-sounds = Message Sounds
     .message-sounds-description = When enabled you will hear a notification when a new message is received.
     .failed = Failed to update settings
+    .devices-changed = Audio device changed
+    .device-disconnected = { $name } was disconnected, switched to { $fallback }.
+    .device-restored = { $name } is back and in use again.
+    .voice-mode = Input Mode
+    .voice-mode-description = With push-to-talk your microphone is muted during calls unless you hold { $key } while Uplink is focused. The key can be changed in the keybind settings.
+    .voice-activity = Voice Activity
+    .push-to-talk = Push to Talk
+    .release-delay = Push-to-Talk Release Delay
+    .release-delay-description = Keeps your microphone open for a moment after the key is released so the end of a sentence isn't cut off.
+    .release-delay-none = None
 
 settings-files = Files Settings
     .local-sync = Local Sync
@@ -311,11 +440,50 @@ settings-keybinds = Keybind Settings
     .toggle-mute = Mute & un-mute your microphone.
     .toggle-deafen = Toggle turning off all sounds including your microphone and headphones.
     .conflicting-keybinds = Conflicting Keybind.
+    .conflicts-with = Conflicts with: { $names }
+    .reserved-copy = Copy
+    .reserved-cut = Cut
+    .reserved-paste = Paste
+    .reserved-select-all = Select All
+    .reserved-undo = Undo
+    .reserved-terminal = Open Terminal (desktop shortcut)
+    .reserved-lock-screen = Lock Screen (desktop shortcut)
+    .system-shortcuts = System-wide Shortcuts
+    .system-shortcuts-description = When enabled, mute, deafen and hide/focus also work while Uplink is not focused. Other applications will no longer receive these key combinations.
+    .system-shortcuts-unavailable = System-wide shortcuts need an X11 session. Under Wayland keybinds only work while Uplink is focused.
     .change-keybind = Record New keybind
     .open-close-dev-tools = Open/Close Web Inspector
     .toggle-devmode = Toggle Developer Mode
     .cancel-change-keybind = Cancel Recording
     .hide-focus-uplink = Hide/Focus Uplink
+    .category-general = General
+    .category-navigation = Navigation
+    .category-chat = Chat
+    .category-call = Calls
+    .next-chat = Go to the next chat
+    .previous-chat = Go to the previous chat
+    .jump-to-unread = Jump to the next unread chat
+    .open-chats = Open Chats
+    .open-friends = Open Friends
+    .open-files = Open Files
+    .open-settings = Open Settings
+    .edit-last-message = Edit your last message
+    .reply-to-last-message = Reply to the last message
+    .mark-all-read = Mark all chats as read
+    .toggle-favorite = Add/remove the current chat from favorites
+    .answer-call = Answer an incoming call
+    .hang-up = Hang up the current call
+    .push-to-talk = Hold to talk while push-to-talk is enabled.
+    .unknown = Unknown action
+    .unbound = Not bound
+    .keymap = Keymap
+    .keymap-description = Export your keybinds to a file or import a keymap shared by someone else.
+    .export-keymap = Export
+    .import-keymap = Import
+    .keymap-exported = Keymap exported.
+    .keymap-export-failed = Failed to export keymap.
+    .keymap-imported = Keymap imported.
+    .keymap-import-failed = Failed to import keymap. The file is not a valid Uplink keymap.
 
 settings-extensions = Extension Settings
     .open-extensions-folder = Open Extensions Folder
@@ -338,7 +506,25 @@ settings-notifications = Notifications
     .grant-permissions-description = Grant permissions to receive notifications.
     .friends-description = Enable notifications for friend requests.
     .messages-description = Enable notifications for new messages.
+    .calls = Calls
+    .calls-description = Enable notifications for incoming and missed calls.
     .settings-description = Enable notifications for updates and important alerts.
+    .keywords = Keyword Alerts
+    .keywords-description = Messages containing these words notify you, even in chats set to mentions only.
+    .add-keyword = Add a keyword...
+    .quiet-hours = Quiet Hours
+    .quiet-hours-description = Silence notifications during the same hours every week. Unread counts are still updated.
+    .quiet-hours-time = Time
+    .quiet-hours-time-description = Quiet hours which pass midnight end the next morning.
+    .quiet-hours-days = Days
+    .quiet-hours-days-description = The days quiet hours start on.
+    .day-mon = Mon
+    .day-tue = Tue
+    .day-wed = Wed
+    .day-thu = Thu
+    .day-fri = Fri
+    .day-sat = Sat
+    .day-sun = Sun
 
 settings-developer = Developer Settings 
     .developer-mode = Developer Mode
@@ -392,6 +578,38 @@ remote-controls = Remote Controls
     .incoming-call = Incoming Transmission ...
     .outgoing-call = Outgoing Transmission ...
     .empty = Nobody is here
+    .transmitting = Transmitting
+    .hold-to-talk = Hold { $key } to talk
+
+call-history = Call History
+    .recent-calls = Recent Calls
+    .incoming-title = Incoming Call
+    .missed-title = Missed Call
+    .outgoing = Outgoing call
+    .incoming = Incoming call from { $name }
+    .missed = Missed call from { $name }
+    .rejected = Declined call from { $name }
+    .no-answer = Outgoing call, no answer
+    .joined = Joined: { $names }
+    .call-back = Call back
+
+call-stats = Call Statistics
+    .title = Call statistics
+    .you = You
+    .connected = Connected
+    .not-connected = Not connected
+    .muted = Muted
+    .deafened = Deafened
+    .reconnects = Reconnected { $count } times
+    .activity = Speaking { $percent }% of the last minute
+    .degraded = Audio degraded { $percent }% of the last minute
+    .degradations = { $count } audio degradations, the last { $ago }s ago
+    .no-degradations = No audio degradation
+    .no-issues = No connection issues recently.
+    .issue-local = The audio of everyone degrades, your connection is likely the problem.
+    .issue-remote = The connection of { $names } is likely the problem.
+    .issue-unknown = The audio degrades, but with only one other participant it's unclear whose connection is the problem.
+    .note = Blink doesn't report packet loss, jitter or codec details yet.
 
 unlock = Unlock
     .notice = (this is used to encrypt all of the data Uplink stores on your computer when you're not using it so nobody can read your data.)
@@ -406,10 +624,26 @@ unlock = Unlock
     .help = Help (right-click)
     .logging-in = Logging in...
 
+profiles = Profiles
+    .add = Add Profile
+    .new-profile = New profile name...
+    .switch-failed = Failed to switch profiles.
+
 create-or-recover = Account Creation
     .create = Create New Account
     .instructions = We're going to create an account for you. On the next screen, you'll see a set of words. Screenshot this or write it down. This is the only way to backup your account.
     .recover = Import an Account
+    .restore = Restore a Backup
+
+restore-backup = Restore Backup
+    .instructions = Choose an Uplink backup and enter the passphrase it was created with. This replaces the account on this device. Afterwards, unlock the restored account with its pin.
+    .choose-file = Choose Backup File
+    .passphrase = Backup passphrase
+    .restore = Restore
+    .not-a-backup = This file is not an Uplink backup.
+    .wrong-passphrase = Wrong passphrase, or the backup is corrupted.
+    .incompatible = This backup was made by Uplink { $version }. Update Uplink to restore it.
+    .failed = Failed to restore the backup.
 
 copy-seed-words = Recovery Seed
     .instructions = Write these words down in the order that they appear. Having the correct order is crucial when you are recovering your account.
@@ -429,6 +663,7 @@ auth = Create Account
 sidebar = Sidebar 
     .subtext = { $user } sent multiple attachments
     .chat-new = No messages sent yet, send one!
+    .draft = Draft:
 
 quickprofile = Quick Profile
     .profile = Profile
@@ -445,5 +680,7 @@ quickprofile = Quick Profile
 
 toast_actions = Toast Actions
     .DisplayChat = Open Chat
+    .AcceptCall = Accept
+    .DeclineCall = Decline
     .FriendListPending = Friend List
     .Dummy = Dummy Action
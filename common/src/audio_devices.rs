@@ -0,0 +1,149 @@
+//! Blink only reads the configured audio devices when they are set. The watcher polls the devices known to the system
+//! and broadcasts an `AudioDeviceEvent` whenever one is plugged in or removed, so the UI can switch devices mid-call.
+
+use std::time::Duration;
+
+use derive_more::Display;
+use once_cell::sync::Lazy;
+use rodio::cpal::{
+    self,
+    traits::{DeviceTrait, HostTrait},
+};
+use tokio::sync::broadcast;
+use tracing::log;
+
+// how often the devices are listed
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+pub enum AudioDeviceKind {
+    #[display(fmt = "input")]
+    Input,
+    #[display(fmt = "output")]
+    Output,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Display)]
+pub enum AudioDeviceEvent {
+    #[display(fmt = "Added {_0} {_1}")]
+    Added(AudioDeviceKind, String),
+    #[display(fmt = "Removed {_0} {_1}")]
+    Removed(AudioDeviceKind, String),
+}
+
+pub static AUDIO_DEVICE_EVENTS: Lazy<broadcast::Sender<AudioDeviceEvent>> = Lazy::new(|| {
+    let (tx, _) = broadcast::channel(32);
+    tx
+});
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Devices {
+    pub inputs: Vec<String>,
+    pub outputs: Vec<String>,
+}
+
+impl Devices {
+    /// blocks while the system is queried
+    pub fn list() -> Self {
+        let host = cpal::default_host();
+        Self {
+            inputs: device_names(host.input_devices()),
+            outputs: device_names(host.output_devices()),
+        }
+    }
+
+    /// the events which turn `self` into `new`
+    pub fn diff(&self, new: &Devices) -> Vec<AudioDeviceEvent> {
+        let mut events = vec![];
+        for (kind, old, new) in [
+            (AudioDeviceKind::Input, &self.inputs, &new.inputs),
+            (AudioDeviceKind::Output, &self.outputs, &new.outputs),
+        ] {
+            events.extend(
+                old.iter()
+                    .filter(|name| !new.contains(name))
+                    .map(|name| AudioDeviceEvent::Removed(kind, name.clone())),
+            );
+            events.extend(
+                new.iter()
+                    .filter(|name| !old.contains(name))
+                    .map(|name| AudioDeviceEvent::Added(kind, name.clone())),
+            );
+        }
+        events
+    }
+}
+
+fn device_names(
+    devices: Result<impl Iterator<Item = cpal::Device>, cpal::DevicesError>,
+) -> Vec<String> {
+    match devices {
+        Ok(devices) => devices.filter_map(|device| device.name().ok()).collect(),
+        Err(e) => {
+            log::warn!("failed to list audio devices: {e}");
+            vec![]
+        }
+    }
+}
+
+/// the device the system uses when none is selected
+pub fn default_device(kind: AudioDeviceKind) -> Option<String> {
+    let host = cpal::default_host();
+    let device = match kind {
+        AudioDeviceKind::Input => host.default_input_device(),
+        AudioDeviceKind::Output => host.default_output_device(),
+    };
+    device.and_then(|device| device.name().ok())
+}
+
+/// Runs until Uplink is closed. Should only be started once.
+pub async fn watch() {
+    let mut devices = match tokio::task::spawn_blocking(Devices::list).await {
+        Ok(devices) => devices,
+        Err(e) => {
+            log::error!("failed to list audio devices: {e}");
+            return;
+        }
+    };
+    loop {
+        tokio::time::sleep(POLL_INTERVAL).await;
+        let new = match tokio::task::spawn_blocking(Devices::list).await {
+            Ok(new) => new,
+            Err(e) => {
+                log::error!("failed to list audio devices: {e}");
+                continue;
+            }
+        };
+        for event in devices.diff(&new) {
+            log::debug!("audio device event: {event}");
+            // nobody may be listening
+            let _ = AUDIO_DEVICE_EVENTS.send(event);
+        }
+        devices = new;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_diff() {
+        let old = Devices {
+            inputs: vec!["mic".into(), "headset".into()],
+            outputs: vec!["speakers".into()],
+        };
+        let new = Devices {
+            inputs: vec!["mic".into()],
+            outputs: vec!["speakers".into(), "headset".into()],
+        };
+        assert_eq!(
+            old.diff(&new),
+            vec![
+                AudioDeviceEvent::Removed(AudioDeviceKind::Input, "headset".into()),
+                AudioDeviceEvent::Added(AudioDeviceKind::Output, "headset".into()),
+            ]
+        );
+        assert!(new.diff(&new).is_empty());
+    }
+}
@@ -0,0 +1,399 @@
+//! Passphrase encrypted backups of the whole Uplink profile: `.user` (state.json, login_config.json and the warp
+//! store) plus the custom themes, fonts and extensions in `~/.uplink`.
+//!
+//! A backup file is `MAGIC`, the `KdfHeader` and a zip archive encrypted with the warp cipher. The key is derived
+//! from the passphrase with Argon2id and a random salt, since the file is meant to leave the machine. The archive
+//! holds a `manifest.json` and the files, using paths relative to `STATIC_ARGS.dot_uplink`.
+//!
+//! Warp keeps its store open while an account is unlocked, so a backup made from a running app may miss writes
+//! which happen while it is being created.
+
+use std::{
+    fs::{self, File},
+    io::{Cursor, Read, Seek, Write},
+    path::{Path, PathBuf},
+};
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use chrono::{DateTime, Utc};
+use derive_more::Display;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use tracing::log;
+use walkdir::WalkDir;
+use warp::crypto::cipher::Cipher;
+use zip::{write::FileOptions, ZipArchive, ZipWriter};
+
+use crate::STATIC_ARGS;
+
+const MAGIC: &[u8] = b"UPLINK-BACKUP";
+const MANIFEST: &str = "manifest.json";
+/// bumped whenever the layout of the archive changes
+const FORMAT_VERSION: u32 = 1;
+// temporary directory used while restoring. .user is only replaced once everything was extracted
+const RESTORE_DIR: &str = "restore";
+// memory cost in KiB, iterations and lanes used for new backups
+const KDF_MEMORY: u32 = 64 * 1024;
+const KDF_ITERATIONS: u32 = 3;
+const KDF_LANES: u32 = 1;
+// a backup asking for more than this is rejected instead of stalling the app
+const KDF_MAX_MEMORY: u32 = 1024 * 1024;
+const KDF_MAX_ITERATIONS: u32 = 16;
+const KDF_MAX_LANES: u32 = 8;
+const SALT_LEN: usize = 16;
+const KEY_LEN: usize = 32;
+
+#[derive(Debug, Display)]
+pub enum BackupError {
+    #[display(fmt = "the file is not an Uplink backup")]
+    NotABackup,
+    #[display(fmt = "wrong passphrase or corrupted backup")]
+    Decrypt,
+    /// the backup was made by a newer version of Uplink
+    #[display(fmt = "the backup was made by Uplink {_0}, which is newer than this version")]
+    Incompatible(String),
+    #[display(fmt = "{_0}")]
+    Other(anyhow::Error),
+}
+
+impl std::error::Error for BackupError {}
+
+impl From<std::io::Error> for BackupError {
+    fn from(e: std::io::Error) -> Self {
+        BackupError::Other(e.into())
+    }
+}
+
+impl From<zip::result::ZipError> for BackupError {
+    fn from(e: zip::result::ZipError) -> Self {
+        BackupError::Other(e.into())
+    }
+}
+
+impl From<serde_json::Error> for BackupError {
+    fn from(e: serde_json::Error) -> Self {
+        BackupError::Other(e.into())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupManifest {
+    pub format_version: u32,
+    pub app_version: String,
+    pub created: DateTime<Utc>,
+}
+
+/// Stored after `MAGIC`: the Argon2id parameters and salt used to derive the key from the passphrase
+struct KdfHeader {
+    memory: u32,
+    iterations: u32,
+    lanes: u32,
+    salt: [u8; SALT_LEN],
+}
+
+impl KdfHeader {
+    const LEN: usize = 12 + SALT_LEN;
+
+    fn new() -> Self {
+        let mut salt = [0u8; SALT_LEN];
+        rand::thread_rng().fill_bytes(&mut salt);
+        Self {
+            memory: KDF_MEMORY,
+            iterations: KDF_ITERATIONS,
+            lanes: KDF_LANES,
+            salt,
+        }
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(Self::LEN);
+        bytes.extend_from_slice(&self.memory.to_le_bytes());
+        bytes.extend_from_slice(&self.iterations.to_le_bytes());
+        bytes.extend_from_slice(&self.lanes.to_le_bytes());
+        bytes.extend_from_slice(&self.salt);
+        bytes
+    }
+
+    /// returns the header and the rest of the data
+    fn parse(data: &[u8]) -> Option<(Self, &[u8])> {
+        if data.len() < Self::LEN {
+            return None;
+        }
+        let (header, rest) = data.split_at(Self::LEN);
+        let u32_at =
+            |idx: usize| u32::from_le_bytes(header[idx..idx + 4].try_into().unwrap_or_default());
+        let header = Self {
+            memory: u32_at(0),
+            iterations: u32_at(4),
+            lanes: u32_at(8),
+            salt: header[12..].try_into().ok()?,
+        };
+        // argon2 needs at least 8 KiB of memory per lane
+        let in_range = (1..=KDF_MAX_ITERATIONS).contains(&header.iterations)
+            && (1..=KDF_MAX_LANES).contains(&header.lanes)
+            && (8 * header.lanes..=KDF_MAX_MEMORY).contains(&header.memory);
+        if !in_range {
+            return None;
+        }
+        Some((header, rest))
+    }
+
+    fn derive_key(&self, passphrase: &str) -> anyhow::Result<[u8; KEY_LEN]> {
+        let params = Params::new(self.memory, self.iterations, self.lanes, Some(KEY_LEN))
+            .map_err(|e| anyhow::anyhow!("invalid key derivation parameters: {e}"))?;
+        let mut key = [0u8; KEY_LEN];
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(passphrase.as_bytes(), &self.salt, &mut key)
+            .map_err(|e| anyhow::anyhow!("failed to derive key: {e}"))?;
+        Ok(key)
+    }
+}
+
+// these are relative to dot_uplink
+fn backup_roots() -> Vec<PathBuf> {
+    let rel = |p: &Path| {
+        p.strip_prefix(&STATIC_ARGS.dot_uplink)
+            .map(Path::to_path_buf)
+            .unwrap_or_default()
+    };
+    vec![
+        rel(&STATIC_ARGS.uplink_path),
+        rel(&STATIC_ARGS.themes_path),
+        rel(&STATIC_ARGS.fonts_path),
+        rel(&STATIC_ARGS.extensions_path),
+    ]
+    .into_iter()
+    .filter(|p| !p.as_os_str().is_empty())
+    .collect()
+}
+
+// the debug log and temporary files aren't worth restoring. the search index is rebuilt as messages are loaded.
+fn is_excluded(path: &Path) -> bool {
+    path == STATIC_ARGS.logger_path
+        || path == STATIC_ARGS.search_index_path
+        || path.starts_with(&STATIC_ARGS.temp_files)
+}
+
+/// Blocks while the profile is compressed. Call it from spawn_blocking.
+pub fn create_backup(passphrase: &str, dest: &Path) -> Result<BackupManifest, BackupError> {
+    let manifest = BackupManifest {
+        format_version: FORMAT_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").into(),
+        created: Utc::now(),
+    };
+
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = FileOptions::default()
+        .compression_method(zip::CompressionMethod::Bzip2)
+        .large_file(true);
+
+    zip.start_file(MANIFEST, options)?;
+    zip.write_all(&serde_json::to_vec_pretty(&manifest)?)?;
+
+    for root in backup_roots() {
+        let src = STATIC_ARGS.dot_uplink.join(&root);
+        for entry in WalkDir::new(&src).into_iter().filter_map(|e| e.ok()) {
+            let path = entry.path();
+            if !path.is_file() || is_excluded(path) {
+                continue;
+            }
+            let Ok(name) = path.strip_prefix(&STATIC_ARGS.dot_uplink) else {
+                continue;
+            };
+            log::trace!("adding {name:?} to backup");
+            #[allow(deprecated)]
+            zip.start_file_from_path(name, options)?;
+            let mut f = File::open(path)?;
+            std::io::copy(&mut f, &mut zip)?;
+        }
+    }
+    let data = zip.finish()?.into_inner();
+
+    let kdf = KdfHeader::new();
+    let key = kdf.derive_key(passphrase).map_err(BackupError::Other)?;
+    let encrypted =
+        Cipher::direct_encrypt(&data, &key).map_err(|e| BackupError::Other(e.into()))?;
+    let mut file = File::create(dest)?;
+    file.write_all(MAGIC)?;
+    file.write_all(&kdf.to_bytes())?;
+    file.write_all(&encrypted)?;
+    Ok(manifest)
+}
+
+/// Checks the backup and replaces the current profile with it. Must not be called while an account is unlocked.
+pub fn restore_backup(passphrase: &str, src: &Path) -> Result<BackupManifest, BackupError> {
+    let mut archive = open_backup(passphrase, src)?;
+    let manifest = read_manifest(&mut archive)?;
+    check_compatible(&manifest)?;
+
+    let staging = STATIC_ARGS.dot_uplink.join(RESTORE_DIR);
+    if staging.exists() {
+        fs::remove_dir_all(&staging)?;
+    }
+    let roots = backup_roots();
+    for idx in 0..archive.len() {
+        let mut file = archive.by_index(idx)?;
+        // enclosed_name rejects absolute paths and paths containing ..
+        let Some(name) = file.enclosed_name().map(Path::to_path_buf) else {
+            log::warn!("skipping {} in backup", file.name());
+            continue;
+        };
+        if file.is_dir() || name == Path::new(MANIFEST) {
+            continue;
+        }
+        if !roots.iter().any(|root| name.starts_with(root)) {
+            log::warn!("skipping {name:?} in backup");
+            continue;
+        }
+        let dest = staging.join(&name);
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut out = File::create(&dest)?;
+        std::io::copy(&mut file, &mut out)?;
+    }
+
+    // everything was extracted. now it is safe to overwrite the profile.
+    for root in roots {
+        let restored = staging.join(&root);
+        if !restored.exists() {
+            continue;
+        }
+        let dest = STATIC_ARGS.dot_uplink.join(&root);
+        if dest == STATIC_ARGS.uplink_path {
+            // the account is replaced as a whole. anything left over from the old account would confuse warp.
+            replace_dir(&restored, &dest)?;
+        } else {
+            // themes, fonts and extensions are merged with the ones already installed
+            merge_dir(&restored, &dest)?;
+        }
+    }
+    fs::remove_dir_all(&staging)?;
+    Ok(manifest)
+}
+
+// the old directory is moved aside first and put back if the new one can't be moved into place, so a failed
+// restore never leaves the user without an account
+fn replace_dir(new: &Path, dest: &Path) -> std::io::Result<()> {
+    if !dest.exists() {
+        return fs::rename(new, dest);
+    }
+    let mut old_name = dest.file_name().unwrap_or_default().to_os_string();
+    old_name.push(".old");
+    let old = dest.with_file_name(old_name);
+    if old.exists() {
+        fs::remove_dir_all(&old)?;
+    }
+    fs::rename(dest, &old)?;
+    if let Err(e) = fs::rename(new, dest) {
+        if let Err(e) = fs::rename(&old, dest) {
+            log::error!("failed to put back {}: {e}", dest.display());
+        }
+        return Err(e);
+    }
+    if let Err(e) = fs::remove_dir_all(&old) {
+        log::warn!("failed to remove {}: {e}", old.display());
+    }
+    Ok(())
+}
+
+fn open_backup(passphrase: &str, src: &Path) -> Result<ZipArchive<Cursor<Vec<u8>>>, BackupError> {
+    let data = fs::read(src)?;
+    let Some((kdf, encrypted)) = data.strip_prefix(MAGIC).and_then(KdfHeader::parse) else {
+        return Err(BackupError::NotABackup);
+    };
+    let key = kdf
+        .derive_key(passphrase)
+        .map_err(|_| BackupError::NotABackup)?;
+    let data = Cipher::direct_decrypt(encrypted, &key).map_err(|_| BackupError::Decrypt)?;
+    ZipArchive::new(Cursor::new(data)).map_err(|_| BackupError::Decrypt)
+}
+
+fn read_manifest<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+) -> Result<BackupManifest, BackupError> {
+    let file = archive
+        .by_name(MANIFEST)
+        .map_err(|_| BackupError::NotABackup)?;
+    serde_json::from_reader(file).map_err(|_| BackupError::NotABackup)
+}
+
+// older backups can be restored. the state.json and warp store migrate themselves when they are loaded.
+fn check_compatible(manifest: &BackupManifest) -> Result<(), BackupError> {
+    let newer_app = parse_version(&manifest.app_version) > parse_version(env!("CARGO_PKG_VERSION"));
+    if manifest.format_version > FORMAT_VERSION || newer_app {
+        return Err(BackupError::Incompatible(manifest.app_version.clone()));
+    }
+    Ok(())
+}
+
+fn parse_version(version: &str) -> Vec<u64> {
+    version
+        .trim_start_matches('v')
+        .split(['.', '-'])
+        .map_while(|x| x.parse().ok())
+        .collect()
+}
+
+fn merge_dir(src: &Path, dest: &Path) -> Result<(), BackupError> {
+    for entry in WalkDir::new(src).into_iter().filter_map(|e| e.ok()) {
+        let Ok(rel) = entry.path().strip_prefix(src) else {
+            continue;
+        };
+        let target = dest.join(rel);
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&target)?;
+        } else {
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(parse_version("v1.2.3"), vec![1, 2, 3]);
+        assert!(parse_version("1.10.0") > parse_version("1.9.5"));
+        assert!(parse_version("1.2.3-beta") == parse_version("1.2.3"));
+    }
+
+    #[test]
+    fn test_kdf_header_bounds() {
+        let header = KdfHeader::new();
+        let bytes = header.to_bytes();
+        assert!(KdfHeader::parse(&bytes).is_some());
+
+        let out_of_range = [
+            KdfHeader {
+                memory: KDF_MAX_MEMORY + 1,
+                ..KdfHeader::new()
+            },
+            KdfHeader {
+                iterations: 0,
+                ..KdfHeader::new()
+            },
+            KdfHeader {
+                iterations: u32::MAX,
+                ..KdfHeader::new()
+            },
+            KdfHeader {
+                lanes: 0,
+                ..KdfHeader::new()
+            },
+            KdfHeader {
+                lanes: KDF_MAX_LANES + 1,
+                ..KdfHeader::new()
+            },
+        ];
+        for header in out_of_range {
+            assert!(KdfHeader::parse(&header.to_bytes()).is_none());
+        }
+    }
+}
@@ -1,6 +1,11 @@
+pub mod audio_devices;
+pub mod backup;
 pub mod language;
+pub mod local_key;
 pub mod notifications;
 pub mod profile_update_channel;
+pub mod profiles;
+pub mod recordings;
 pub mod sounds;
 pub mod state;
 pub mod testing;
@@ -55,6 +60,9 @@ pub struct Args {
     /// configures log output
     #[clap(long, default_value_t = false)]
     pub log_to_file: bool,
+    /// the unix domain socket the headless daemon listens on. defaults to `.uplink/warp_runner.sock`
+    #[clap(long)]
+    socket: Option<PathBuf>,
 }
 
 #[derive(Default, Debug, Clone, PartialEq)]
@@ -88,11 +96,17 @@ impl std::str::FromStr for DiscoveryMode {
 #[derive(Debug)]
 pub struct StaticArgs {
     /// ~/.uplink
-    /// contains the following: extra (folder), extensions (folder), themes (folder), fonts (folder), .user
+    /// contains the following: extra (folder), extensions (folder), themes (folder), fonts (folder), profiles (folder), profiles.json, .user
     pub dot_uplink: PathBuf,
     /// ~/.uplink/.user
     /// contains the following: warp (folder), state.json, debug.log
+    /// this is always the active profile
     pub uplink_path: PathBuf,
+    /// ~/.uplink/profiles
+    /// the inactive profiles. each one is a directory named after the profile id with the same layout as .user
+    pub profiles_path: PathBuf,
+    /// lists the profiles and which one is active
+    pub profile_registry_path: PathBuf,
     /// Directory for temporary files and deleted everytime app is closed or opened
     pub temp_files: PathBuf,
     /// custom themes for the user
@@ -118,6 +132,14 @@ pub struct StaticArgs {
     pub crash_logs: PathBuf,
     /// recordings
     pub recordings: PathBuf,
+    /// control socket used by the headless daemon
+    pub control_socket: PathBuf,
+    /// local full text index of messages, used to search across conversations
+    pub search_index_path: PathBuf,
+    /// link previews which were already fetched
+    pub link_previews_path: PathBuf,
+    /// messages which were written but not sent yet
+    pub drafts_path: PathBuf,
     /// seconds
     pub typing_indicator_refresh: u64,
     /// seconds
@@ -132,8 +154,17 @@ pub struct StaticArgs {
     pub production_mode: bool,
 }
 
+// binaries with their own command line (uplink-cli) can't let STATIC_ARGS parse it. they set the Args here instead.
+static ARGS_OVERRIDE: Lazy<parking_lot::Mutex<Option<Args>>> =
+    Lazy::new(|| parking_lot::Mutex::new(None));
+
+/// Supplies the `Args` used to build `STATIC_ARGS`. Must be called before `STATIC_ARGS` is first used.
+pub fn override_args(args: Args) {
+    *ARGS_OVERRIDE.lock() = Some(args);
+}
+
 pub static STATIC_ARGS: Lazy<StaticArgs> = Lazy::new(|| {
-    let args = Args::parse();
+    let args = ARGS_OVERRIDE.lock().take().unwrap_or_else(Args::parse);
     #[allow(unused_mut)]
     #[allow(unused_assignments)]
     let mut use_mock = false;
@@ -152,6 +183,8 @@ pub static STATIC_ARGS: Lazy<StaticArgs> = Lazy::new(|| {
     StaticArgs {
         dot_uplink: uplink_container.clone(),
         uplink_path: uplink_path.clone(), // TODO: Should this be "User path" instead?
+        profiles_path: uplink_container.join("profiles"),
+        profile_registry_path: uplink_container.join("profiles.json"),
         temp_files: uplink_container.join("temp_files"),
         themes_path: uplink_container.join("themes"),
         fonts_path: uplink_container.join("fonts"),
@@ -159,6 +192,12 @@ pub static STATIC_ARGS: Lazy<StaticArgs> = Lazy::new(|| {
         extensions_path: uplink_container.join("extensions"),
         crash_logs: uplink_container.join("crash-logs"),
         recordings: uplink_container.join("recordings"),
+        control_socket: args
+            .socket
+            .unwrap_or_else(|| uplink_container.join("warp_runner.sock")),
+        search_index_path: uplink_path.join("search_index"),
+        link_previews_path: uplink_path.join("link_previews.json"),
+        drafts_path: uplink_path.join("drafts.json"),
         mock_cache_path: uplink_path.join("mock-state.json"),
         warp_path: warp_path.clone(),
         logger_path: uplink_path.join("debug.log"),
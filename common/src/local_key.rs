@@ -0,0 +1,155 @@
+//! Data which Uplink keeps next to the warp store, like the search index, drafts or unsent messages, holds message
+//! text. It is encrypted with a random key which is kept in Tesseract, so it is as safe as the rest of the account.
+//!
+//! The key is loaded when the user logs in. It stays in memory until another account logs in, so State can still be
+//! saved while warp shuts down.
+
+use std::{
+    ops::{Deref, DerefMut},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use base64::{engine::general_purpose, Engine};
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use rand::RngCore;
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize, Serializer};
+use tracing::log;
+use warp::{crypto::cipher::Cipher, tesseract::Tesseract};
+
+/// the Tesseract entry holding the key
+const KEY_NAME: &str = "local_key";
+const KEY_LEN: usize = 32;
+
+static LOCAL_KEY: Lazy<RwLock<Option<Vec<u8>>>> = Lazy::new(Default::default);
+// incremented on every login
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Loads the key of the account which just logged in, creating it on first use
+pub fn unlock(tesseract: &Tesseract) {
+    GENERATION.fetch_add(1, Ordering::SeqCst);
+    match get_or_create(tesseract) {
+        Ok(key) => *LOCAL_KEY.write() = Some(key),
+        Err(e) => {
+            // nothing is saved without a key
+            log::error!("failed to get local key: {e}");
+            *LOCAL_KEY.write() = None;
+        }
+    }
+}
+
+fn get_or_create(tesseract: &Tesseract) -> anyhow::Result<Vec<u8>> {
+    if tesseract.exist(KEY_NAME) {
+        let encoded = tesseract.retrieve(KEY_NAME)?;
+        return Ok(general_purpose::STANDARD.decode(encoded.as_bytes())?);
+    }
+    let mut key = vec![0u8; KEY_LEN];
+    rand::thread_rng().fill_bytes(&mut key);
+    tesseract.set(KEY_NAME, &general_purpose::STANDARD.encode(&key))?;
+    Ok(key)
+}
+
+/// Changes whenever an account logs in. Data cached in memory was read for another account if this changed since.
+pub fn generation() -> u64 {
+    GENERATION.load(Ordering::SeqCst)
+}
+
+pub fn is_unlocked() -> bool {
+    LOCAL_KEY.read().is_some()
+}
+
+pub fn encrypt(data: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let key = LOCAL_KEY.read();
+    let Some(key) = key.as_deref() else {
+        anyhow::bail!("no account is logged in");
+    };
+    Ok(Cipher::direct_encrypt(data, key)?)
+}
+
+pub fn decrypt(data: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let key = LOCAL_KEY.read();
+    let Some(key) = key.as_deref() else {
+        anyhow::bail!("no account is logged in");
+    };
+    Ok(Cipher::direct_decrypt(data, key)?)
+}
+
+/// A field of State which is saved encrypted. State is also loaded by the login pages, before the account is
+/// unlocked, so data which can't be decrypted yet is kept as it was read and written back unchanged. Fields saved
+/// before they were encrypted are still read.
+#[derive(Debug, Clone, Default)]
+pub struct Sealed<T> {
+    value: T,
+    /// the saved data, if it couldn't be decrypted. changes to `value` aren't saved while it is set
+    sealed: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Stored<T> {
+    Encrypted(String),
+    Plain(T),
+}
+
+impl<T> Deref for Sealed<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> DerefMut for Sealed<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+impl<T: Serialize> Serialize for Sealed<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if let Some(sealed) = &self.sealed {
+            return serializer.serialize_str(sealed);
+        }
+        if !is_unlocked() {
+            // only happens for data which was read unencrypted
+            return self.value.serialize(serializer);
+        }
+        let json = serde_json::to_vec(&self.value).map_err(serde::ser::Error::custom)?;
+        let encrypted = encrypt(&json).map_err(serde::ser::Error::custom)?;
+        serializer.serialize_str(&general_purpose::STANDARD.encode(encrypted))
+    }
+}
+
+impl<'de, T: DeserializeOwned + Default> Deserialize<'de> for Sealed<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let encoded = match Stored::<T>::deserialize(deserializer)? {
+            Stored::Plain(value) => {
+                return Ok(Self {
+                    value,
+                    sealed: None,
+                })
+            }
+            Stored::Encrypted(encoded) => encoded,
+        };
+        let value = general_purpose::STANDARD
+            .decode(&encoded)
+            .map_err(anyhow::Error::from)
+            .and_then(|encrypted| decrypt(&encrypted))
+            .and_then(|json| serde_json::from_slice(&json).map_err(anyhow::Error::from));
+        match value {
+            Ok(value) => Ok(Self {
+                value,
+                sealed: None,
+            }),
+            Err(e) => {
+                if is_unlocked() {
+                    log::error!("failed to decrypt saved data: {e}");
+                }
+                Ok(Self {
+                    value: T::default(),
+                    sealed: Some(encoded),
+                })
+            }
+        }
+    }
+}
@@ -16,6 +16,14 @@ use tokio::sync::{
     Mutex,
 };
 
+/// an incoming call notification is closed after this long, in case it isn't closed when the call is handled
+pub const CALL_NOTIFICATION_TIMEOUT_MS: u32 = 30_000;
+
+// call id -> id of the notification for the incoming call
+#[cfg(target_os = "linux")]
+static CALL_NOTIFICATIONS: Lazy<parking_lot::Mutex<std::collections::HashMap<Uuid, u32>>> =
+    Lazy::new(Default::default);
+
 #[cfg(target_os = "windows")]
 pub const POWERSHELL_APP_ID: &str = "{1AC14E77-02E7-4E5D-B744-2EB1AE5198B7}\
 \\WindowsPowerShell\\v1.0\\powershell.exe";
@@ -26,10 +34,21 @@ pub enum NotificationAction {
     DisplayChat(Uuid),
     #[display(fmt = "FriendListPending")]
     FriendListPending,
+    #[display(fmt = "AcceptCall")]
+    AcceptCall(Uuid),
+    #[display(fmt = "DeclineCall")]
+    DeclineCall(Uuid),
     #[display(fmt = "Dummy")]
     Dummy,
 }
 
+impl NotificationAction {
+    // declining a call can be done without bringing up the app
+    fn focuses_window(&self) -> bool {
+        !matches!(self, NotificationAction::DeclineCall(_))
+    }
+}
+
 pub struct NotificationChannel {
     pub tx: broadcast::Sender<NotificationAction>,
 }
@@ -61,20 +80,33 @@ pub fn push_notification(
     notification_sound: Option<Sounds>,
     timeout: notify_rust::Timeout,
     action: NotificationAction,
+) {
+    push_notification_with_actions(title, content, notification_sound, timeout, vec![action]);
+}
+
+/// Shows a button for every action. macOS only supports two of them.
+pub fn push_notification_with_actions(
+    title: String,
+    content: String,
+    notification_sound: Option<Sounds>,
+    timeout: notify_rust::Timeout,
+    actions: Vec<NotificationAction>,
 ) {
     let summary = format!("Uplink - {title}");
     thread::spawn(move || {
-        let action_id = format!("toast_actions.{}", action);
-        show_with_action(
-            Notification::new()
-                .summary(summary.as_ref())
-                .body(&content)
-                .timeout(timeout)
-                .action(&action_id, &get_local_text(&action_id))
-                .finalize(),
-            action_id,
-            action,
-        );
+        let actions: Vec<(String, NotificationAction)> = actions
+            .into_iter()
+            .map(|action| (format!("toast_actions.{}", action), action))
+            .collect();
+        let mut notification = Notification::new();
+        notification
+            .summary(summary.as_ref())
+            .body(&content)
+            .timeout(timeout);
+        for (action_id, _) in actions.iter() {
+            notification.action(action_id, &get_local_text(action_id));
+        }
+        show_with_actions(notification, actions, None);
     });
 
     if let Some(sound) = notification_sound {
@@ -82,6 +114,69 @@ pub fn push_notification(
     }
 }
 
+/// Shows accept and decline buttons for an incoming call. The notification is closed by `close_call_notification`
+/// once the call is answered, declined or cancelled, or after `CALL_NOTIFICATION_TIMEOUT_MS`.
+pub fn push_call_notification(title: String, content: String, call_id: Uuid) {
+    let summary = format!("Uplink - {title}");
+    thread::spawn(move || {
+        let actions: Vec<(String, NotificationAction)> = [
+            NotificationAction::AcceptCall(call_id),
+            NotificationAction::DeclineCall(call_id),
+        ]
+        .into_iter()
+        .map(|action| (format!("toast_actions.{}", action), action))
+        .collect();
+        let mut notification = Notification::new();
+        notification
+            .summary(summary.as_ref())
+            .body(&content)
+            .timeout(notify_rust::Timeout::Milliseconds(
+                CALL_NOTIFICATION_TIMEOUT_MS,
+            ));
+        for (action_id, _) in actions.iter() {
+            notification.action(action_id, &get_local_text(action_id));
+        }
+        show_with_actions(notification, actions, Some(call_id));
+    });
+}
+
+/// Only Linux can close a notification which is already shown. Elsewhere it disappears after its timeout, and its
+/// buttons do nothing once the call isn't pending anymore.
+pub fn close_call_notification(call_id: Uuid) {
+    #[cfg(target_os = "linux")]
+    if let Some(id) = CALL_NOTIFICATIONS.lock().remove(&call_id) {
+        // notify-rust only closes a notification through its handle, which is busy waiting for an action. replacing
+        // the notification with one that expires right away closes it and ends the wait.
+        thread::spawn(move || {
+            if let Err(e) = Notification::new()
+                .id(id)
+                .summary("Uplink")
+                .timeout(notify_rust::Timeout::Milliseconds(1))
+                .show()
+            {
+                log::error!("failed to close call notification: {e}");
+            }
+        });
+    }
+    #[cfg(not(target_os = "linux"))]
+    let _ = call_id;
+}
+
+// forwards the action to the app
+fn dispatch_action(action: NotificationAction) {
+    let focus_window = action.focuses_window();
+    let tx = NOTIFICATION_LISTENER.tx.clone();
+    if let Err(e) = tx.send(action) {
+        log::error!("failed to send notification action {}", e);
+    }
+    if focus_window {
+        let focus = FOCUS_SCHEDULER.tx.clone();
+        if let Err(e) = focus.send(()) {
+            log::error!("failed to send focus command {}", e);
+        }
+    }
+}
+
 pub fn set_badge(count: u32) -> Result<(), String> {
     #[cfg(not(target_os = "macos"))]
     let _ = count;
@@ -102,7 +197,15 @@ pub fn set_badge(count: u32) -> Result<(), String> {
 }
 
 // We need to handle them all differently as there isnt a single lib that covers it for all
-fn show_with_action(notification: Notification, action_id: String, action: NotificationAction) {
+// `call_id` is set for incoming call notifications, so they can be closed by close_call_notification
+fn show_with_actions(
+    notification: Notification,
+    actions: Vec<(String, NotificationAction)>,
+    call_id: Option<Uuid>,
+) {
+    #[cfg(not(target_os = "linux"))]
+    let _ = call_id;
+
     #[cfg(target_os = "windows")]
     {
         // Notify-rust does not support windows actions so we use the underlying system directly
@@ -121,11 +224,16 @@ fn show_with_action(notification: Notification, action_id: String, action: Notif
         //TODO set proper app id
         let app_id = POWERSHELL_APP_ID.to_string();
         let template_binding = "ToastGeneric";
-        let actions = format!(
-            r#"<action content="{}" arguments="{}"/>"#,
-            &get_local_text(&action_id),
-            &action_id
-        );
+        let actions_xml: String = actions
+            .iter()
+            .map(|(action_id, _)| {
+                format!(
+                    r#"<action content="{}" arguments="{}"/>"#,
+                    &get_local_text(action_id),
+                    action_id
+                )
+            })
+            .collect();
 
         let toast_xml = windows::Data::Xml::Dom::XmlDocument::new().unwrap();
         if let Err(err) = toast_xml.LoadXml(&windows::core::HSTRING::from(format!(
@@ -149,7 +257,7 @@ fn show_with_action(notification: Notification, action_id: String, action: Notif
             notification.subtitle.as_ref().map_or("", AsRef::as_ref),
             &notification.body,
             r#"<audio silent='true'/>"#, //Already handled in uplink
-            actions
+            actions_xml
         ))) {
             log::error!("Error creating windows toast xml {}", err);
             return;
@@ -176,16 +284,10 @@ fn show_with_action(notification: Notification, action_id: String, action: Notif
                     .and_then(|val| val.ok())
                     .and_then(|args| args.Arguments().ok());
                 if let Some(val) = arguments {
-                    if val.to_string_lossy().eq(&action_id) {
+                    let val = val.to_string_lossy();
+                    if let Some((_, action)) = actions.iter().find(|(id, _)| val.eq(id)) {
                         log::trace!("toast action activated {:?}", val);
-                        let tx = NOTIFICATION_LISTENER.tx.clone();
-                        if let Err(e) = tx.send(action.to_owned()) {
-                            log::error!("failed to send notification action {}", e);
-                        }
-                        let focus = FOCUS_SCHEDULER.tx.clone();
-                        if let Err(e) = focus.send(()) {
-                            log::error!("failed to send focus command {}", e);
-                        }
+                        dispatch_action(action.to_owned());
                     }
                 };
                 Ok(())
@@ -210,28 +312,41 @@ fn show_with_action(notification: Notification, action_id: String, action: Notif
 
     #[cfg(target_os = "macos")]
     {
-        // Notify-rust does not support macos actions but the underlying mac_notification library does
-        let action_name = &get_local_text(&action_id);
-        match mac_notification_sys::Notification::default()
+        // Notify-rust does not support macos actions but the underlying mac_notification library does.
+        // The first action is the main button and the second one replaces the close button.
+        let mut actions = actions.into_iter();
+        let main_action = actions.next();
+        let close_action = actions.next();
+        let main_name = main_action
+            .as_ref()
+            .map(|(action_id, _)| get_local_text(action_id))
+            .unwrap_or_default();
+        let close_name = close_action
+            .as_ref()
+            .map(|(action_id, _)| get_local_text(action_id));
+        let mut mac_notification = mac_notification_sys::Notification::default();
+        mac_notification
             .title(notification.summary.as_str())
             .message(&notification.body)
             .maybe_subtitle(notification.subtitle.as_deref())
-            .main_button(mac_notification_sys::MainButton::SingleAction(action_name))
-            .send()
-        {
+            .main_button(mac_notification_sys::MainButton::SingleAction(&main_name));
+        if let Some(close_name) = close_name.as_deref() {
+            mac_notification.close_button(close_name);
+        }
+        match mac_notification.send() {
             Ok(response) => match response {
                 mac_notification_sys::NotificationResponse::ActionButton(id) => {
-                    if action_name.eq(&id) {
-                        let tx = NOTIFICATION_LISTENER.tx.clone();
-                        if let Err(e) = tx.send(action) {
-                            log::error!("failed to send notification action {}", e);
-                        }
-                        let focus = FOCUS_SCHEDULER.tx.clone();
-                        if let Err(e) = focus.send(()) {
-                            log::error!("failed to send focus command {}", e);
+                    if main_name.eq(&id) {
+                        if let Some((_, action)) = main_action {
+                            dispatch_action(action);
                         }
                     };
                 }
+                mac_notification_sys::NotificationResponse::CloseButton(_) => {
+                    if let Some((_, action)) = close_action {
+                        dispatch_action(action);
+                    }
+                }
                 mac_notification_sys::NotificationResponse::Click => {
                     let focus = FOCUS_SCHEDULER.tx.clone();
                     if let Err(e) = focus.send(()) {
@@ -247,18 +362,21 @@ fn show_with_action(notification: Notification, action_id: String, action: Notif
     #[cfg(target_os = "linux")]
     {
         match notification.show() {
-            Ok(handle) => handle.wait_for_action(|id| {
-                if action_id.eq(id) {
-                    let tx = NOTIFICATION_LISTENER.tx.clone();
-                    if let Err(e) = tx.send(action) {
-                        log::error!("failed to send notification action {}", e);
-                    }
-                    let focus = FOCUS_SCHEDULER.tx.clone();
-                    if let Err(e) = focus.send(()) {
-                        log::error!("failed to send focus command {}", e);
-                    }
-                };
-            }),
+            Ok(handle) => {
+                if let Some(call_id) = call_id {
+                    CALL_NOTIFICATIONS.lock().insert(call_id, handle.id());
+                }
+                handle.wait_for_action(|id| {
+                    if let Some((_, action)) =
+                        actions.into_iter().find(|(action_id, _)| action_id.eq(id))
+                    {
+                        dispatch_action(action);
+                    };
+                });
+                if let Some(call_id) = call_id {
+                    CALL_NOTIFICATIONS.lock().remove(&call_id);
+                }
+            }
             Err(err) => log::error!("Error handling notification {}", err),
         }
     }
@@ -0,0 +1,161 @@
+//! Several accounts can be kept on one device. The active profile always lives in `STATIC_ARGS.uplink_path`, so
+//! nothing else has to know about profiles. The inactive ones are parked in `STATIC_ARGS.profiles_path/<id>`.
+//!
+//! Switching profiles swaps these directories. Warp keeps its store open, so the WarpRunner has to be stopped first
+//! (see `WarpRunner::stop`) and a new one started afterwards.
+
+use std::{fs, path::PathBuf};
+
+use anyhow::bail;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::log;
+use uuid::Uuid;
+
+use crate::STATIC_ARGS;
+
+const DEFAULT_PROFILE_NAME: &str = "Default";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Profile {
+    pub id: Uuid,
+    pub name: String,
+    pub created: DateTime<Utc>,
+}
+
+impl Profile {
+    fn new(name: &str) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            name: name.trim().into(),
+            created: Utc::now(),
+        }
+    }
+
+    // where the profile is stored while it isn't active
+    fn parked_path(&self) -> PathBuf {
+        STATIC_ARGS.profiles_path.join(self.id.to_string())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProfileRegistry {
+    pub active: Uuid,
+    pub profiles: Vec<Profile>,
+}
+
+impl Default for ProfileRegistry {
+    fn default() -> Self {
+        let profile = Profile::new(DEFAULT_PROFILE_NAME);
+        Self {
+            active: profile.id,
+            profiles: vec![profile],
+        }
+    }
+}
+
+impl ProfileRegistry {
+    /// The registry is created on first use. Whatever is in `.user` at that point becomes the default profile.
+    pub fn load() -> Self {
+        let path = &STATIC_ARGS.profile_registry_path;
+        let registry = fs::read_to_string(path)
+            .map_err(anyhow::Error::from)
+            .and_then(|s| serde_json::from_str::<Self>(&s).map_err(anyhow::Error::from));
+        match registry {
+            Ok(registry) if registry.get(registry.active).is_some() => registry,
+            Ok(_) => {
+                log::error!("profile registry has no active profile. creating a new one");
+                Self::create()
+            }
+            Err(e) => {
+                if path.exists() {
+                    log::error!("failed to load profile registry: {e}");
+                }
+                Self::create()
+            }
+        }
+    }
+
+    fn create() -> Self {
+        let registry = Self::default();
+        if let Err(e) = registry.save() {
+            log::error!("failed to save profile registry: {e}");
+        }
+        registry
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        let s = serde_json::to_string_pretty(self)?;
+        fs::write(&STATIC_ARGS.profile_registry_path, s)?;
+        Ok(())
+    }
+
+    pub fn get(&self, id: Uuid) -> Option<&Profile> {
+        self.profiles.iter().find(|p| p.id == id)
+    }
+
+    pub fn active_profile(&self) -> Option<&Profile> {
+        self.get(self.active)
+    }
+
+    /// Adds an empty profile. It is set up by the account creation flow once it becomes active.
+    pub fn add(&mut self, name: &str) -> anyhow::Result<Profile> {
+        let name = name.trim();
+        if name.is_empty() {
+            bail!("profile name is empty");
+        }
+        if self.profiles.iter().any(|p| p.name == name) {
+            bail!("a profile named {name} already exists");
+        }
+        let profile = Profile::new(name);
+        fs::create_dir_all(profile.parked_path())?;
+        self.profiles.push(profile.clone());
+        self.save()?;
+        Ok(profile)
+    }
+
+    /// Parks the active profile and moves `id` into `STATIC_ARGS.uplink_path`. Must not be called while a
+    /// WarpRunner is running.
+    pub fn switch_to(&mut self, id: Uuid) -> anyhow::Result<()> {
+        if id == self.active {
+            return Ok(());
+        }
+        let Some(next) = self.get(id).cloned() else {
+            bail!("profile {id} does not exist");
+        };
+        let Some(current) = self.active_profile().cloned() else {
+            bail!("no active profile");
+        };
+
+        fs::create_dir_all(&STATIC_ARGS.profiles_path)?;
+        let parked = current.parked_path();
+        if parked.exists() {
+            bail!("{parked:?} already exists");
+        }
+        if STATIC_ARGS.uplink_path.exists() {
+            fs::rename(&STATIC_ARGS.uplink_path, &parked)?;
+        }
+
+        let next_path = next.parked_path();
+        let r = if next_path.exists() {
+            fs::rename(&next_path, &STATIC_ARGS.uplink_path)
+        } else {
+            // a profile which was never used
+            fs::create_dir_all(&STATIC_ARGS.uplink_path)
+        };
+        if let Err(e) = r {
+            // put the old profile back so the app is left the way it was
+            if parked.exists() {
+                let _ = fs::remove_dir_all(&STATIC_ARGS.uplink_path);
+                if let Err(e) = fs::rename(&parked, &STATIC_ARGS.uplink_path) {
+                    log::error!("failed to restore profile {}: {e}", current.id);
+                }
+            }
+            return Err(e.into());
+        }
+        fs::create_dir_all(&STATIC_ARGS.warp_path)?;
+
+        self.active = id;
+        self.save()
+    }
+}
@@ -0,0 +1,217 @@
+//! Calls recorded with `BlinkCmd::StartRecording` are written to one directory per recording in
+//! `STATIC_ARGS.recordings`. Blink only writes the audio tracks, so the UI stores the call details next to them in
+//! `METADATA_FILE` when the recording starts and stops.
+//!
+//! Recordings made before the metadata existed are still listed. Their start time is taken from the directory name.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::bail;
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::log;
+use uuid::Uuid;
+use warp::crypto::DID;
+
+use crate::STATIC_ARGS;
+
+pub const METADATA_FILE: &str = "recording.json";
+// the name of the directories created for new recordings
+const DIR_NAME_FORMAT: &str = "%d-%m-%Y_%H-%M-%S";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordingMetadata {
+    /// set when the user renames the recording
+    #[serde(default)]
+    pub name: Option<String>,
+    pub conversation_id: Option<Uuid>,
+    #[serde(default)]
+    pub participants: Vec<DID>,
+    pub started: DateTime<Utc>,
+    /// not set if Uplink was closed during the recording
+    #[serde(default)]
+    pub ended: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recording {
+    pub path: PathBuf,
+    pub metadata: RecordingMetadata,
+    /// the files written by blink, one per participant
+    pub tracks: Vec<PathBuf>,
+    pub size: u64,
+    // when a track was last written to
+    last_modified: Option<DateTime<Utc>>,
+}
+
+impl Recording {
+    fn load(path: PathBuf) -> anyhow::Result<Self> {
+        let mut tracks = vec![];
+        let mut size = 0;
+        let mut last_modified: Option<DateTime<Utc>> = None;
+        for entry in fs::read_dir(&path)? {
+            let entry = entry?;
+            let meta = entry.metadata()?;
+            if !meta.is_file() || entry.file_name() == METADATA_FILE {
+                continue;
+            }
+            size += meta.len();
+            if let Ok(modified) = meta.modified() {
+                let modified = DateTime::<Utc>::from(modified);
+                last_modified = Some(last_modified.map_or(modified, |x| x.max(modified)));
+            }
+            tracks.push(entry.path());
+        }
+        tracks.sort();
+
+        let metadata = match read_metadata(&path) {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                log::debug!("no metadata for recording {}: {e}", path.display());
+                RecordingMetadata {
+                    name: None,
+                    conversation_id: None,
+                    participants: vec![],
+                    started: started_from_dir_name(&path)
+                        .or(last_modified)
+                        .unwrap_or_default(),
+                    ended: None,
+                }
+            }
+        };
+
+        Ok(Self {
+            path,
+            metadata,
+            tracks,
+            size,
+            last_modified,
+        })
+    }
+
+    /// the name given by the user or else the name of the directory
+    pub fn name(&self) -> String {
+        self.metadata.name.clone().unwrap_or_else(|| {
+            self.path
+                .file_name()
+                .map(|x| x.to_string_lossy().to_string())
+                .unwrap_or_default()
+        })
+    }
+
+    pub fn duration(&self) -> Option<chrono::Duration> {
+        let ended = self.metadata.ended.or(self.last_modified)?;
+        Some(ended - self.metadata.started).filter(|x| *x >= chrono::Duration::zero())
+    }
+}
+
+fn read_metadata(dir: &Path) -> anyhow::Result<RecordingMetadata> {
+    let s = fs::read_to_string(dir.join(METADATA_FILE))?;
+    Ok(serde_json::from_str(&s)?)
+}
+
+fn write_metadata(dir: &Path, metadata: &RecordingMetadata) -> anyhow::Result<()> {
+    fs::create_dir_all(dir)?;
+    fs::write(
+        dir.join(METADATA_FILE),
+        serde_json::to_string_pretty(metadata)?,
+    )?;
+    Ok(())
+}
+
+fn started_from_dir_name(dir: &Path) -> Option<DateTime<Utc>> {
+    let name = dir.file_name()?.to_str()?;
+    let naive = NaiveDateTime::parse_from_str(name, DIR_NAME_FORMAT).ok()?;
+    Local
+        .from_local_datetime(&naive)
+        .single()
+        .map(|x| x.with_timezone(&Utc))
+}
+
+/// a directory which does not exist yet, to pass to `BlinkCmd::StartRecording`
+pub fn new_recording_dir() -> PathBuf {
+    STATIC_ARGS
+        .recordings
+        .join(Local::now().format(DIR_NAME_FORMAT).to_string())
+}
+
+/// call once blink started recording into `dir`
+pub fn start(dir: &Path, conversation_id: Uuid, participants: Vec<DID>) -> anyhow::Result<()> {
+    write_metadata(
+        dir,
+        &RecordingMetadata {
+            name: None,
+            conversation_id: Some(conversation_id),
+            participants,
+            started: Utc::now(),
+            ended: None,
+        },
+    )
+}
+
+/// call once blink stopped recording into `dir`
+pub fn finish(dir: &Path) -> anyhow::Result<()> {
+    let mut metadata = read_metadata(dir)?;
+    metadata.ended = Some(Utc::now());
+    write_metadata(dir, &metadata)
+}
+
+/// every recording, newest first
+pub fn list() -> Vec<Recording> {
+    let entries = match fs::read_dir(&STATIC_ARGS.recordings) {
+        Ok(entries) => entries,
+        // nothing was recorded yet
+        Err(_) => return vec![],
+    };
+    let mut recordings: Vec<Recording> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| match Recording::load(entry.path()) {
+            Ok(recording) => Some(recording),
+            Err(e) => {
+                log::error!("failed to load recording {}: {e}", entry.path().display());
+                None
+            }
+        })
+        .collect();
+    recordings.sort_by(|a, b| b.metadata.started.cmp(&a.metadata.started));
+    recordings
+}
+
+pub fn rename(recording: &Recording, name: &str) -> anyhow::Result<()> {
+    let name = name.trim();
+    if name.is_empty() {
+        bail!("the name of a recording can't be empty");
+    }
+    let mut metadata = recording.metadata.clone();
+    metadata.name = Some(name.into());
+    write_metadata(&recording.path, &metadata)
+}
+
+pub fn delete(recording: &Recording) -> anyhow::Result<()> {
+    // never remove anything outside the recordings directory
+    if !recording.path.starts_with(&STATIC_ARGS.recordings) {
+        bail!("{} is not a recording", recording.path.display());
+    }
+    fs::remove_dir_all(&recording.path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_started_from_dir_name() {
+        let started = started_from_dir_name(Path::new("/tmp/05-03-2024_14-30-00"))
+            .map(|x| x.with_timezone(&Local).naive_local());
+        assert_eq!(
+            started,
+            NaiveDateTime::parse_from_str("2024-03-05 14:30:00", "%Y-%m-%d %H:%M:%S").ok()
+        );
+        assert!(started_from_dir_name(Path::new("/tmp/renamed")).is_none());
+    }
+}
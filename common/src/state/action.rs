@@ -1,4 +1,6 @@
 use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
 use std::{collections::HashMap, rc::Weak};
 
 use derive_more::Display;
@@ -14,9 +16,14 @@ use crate::warp_runner::ui_adapter;
 
 use super::{
     call,
+    configuration::LinkPreviews,
     identity::Identity,
+    notification_rules::{ChatRule, QuietHours},
     notifications::NotificationKind,
+    outbox::OutboxMessage,
     route::To,
+    scheduled_messages::ScheduledMessage,
+    settings::{GlobalShortcut, Shortcut},
     ui::{EmojiDestination, Font, Theme, ToastNotification, WindowMeta},
 };
 
@@ -113,6 +120,12 @@ pub enum Action<'a> {
     PauseGlobalKeybinds(bool), // If provided false, keybinds will be un-paused.
     #[display(fmt = "ResetKeybinds")]
     ResetKeybinds,
+    /// Replaces all keybinds, ex: when importing a keymap
+    #[display(fmt = "SetKeybinds")]
+    SetKeybinds(Vec<(GlobalShortcut, Shortcut)>),
+    /// Registers the system shortcuts with X11 on Linux
+    #[display(fmt = "SetLinuxSystemShortcuts")]
+    SetLinuxSystemShortcuts(bool),
     // Routes
     /// Set the active route
     #[display(fmt = "Navigate")]
@@ -172,6 +185,9 @@ pub enum Action<'a> {
     #[display(fmt = "SetEmojiDestination")]
     SetEmojiDestination(Option<EmojiDestination>),
     /// chat id, message id
+    /// Starts editing the newest message the user sent in the chat
+    #[display(fmt = "EditLastMessage")]
+    EditLastMessage(Uuid),
     #[display(fmt = "StartReplying")]
     StartReplying(&'a Uuid, &'a ui_adapter::Message),
     /// Sets a draft message for the chatbar for a given chat.
@@ -192,6 +208,30 @@ pub enum Action<'a> {
     /// Clears the reply for a given chat
     #[display(fmt = "CancelReply")]
     CancelReply(Uuid),
+    /// Queues a message to be sent later
+    #[display(fmt = "ScheduleMessage")]
+    ScheduleMessage(ScheduledMessage),
+    /// scheduled message id, new text, new time
+    #[display(fmt = "EditScheduledMessage")]
+    EditScheduledMessage(Uuid, Vec<String>, DateTime<Utc>),
+    /// Removes a scheduled message, either because it was cancelled or because it was sent
+    #[display(fmt = "RemoveScheduledMessage")]
+    RemoveScheduledMessage(Uuid),
+    /// Keeps a message which couldn't be sent, to retry it later
+    #[display(fmt = "QueueOutgoingMessage")]
+    QueueOutgoingMessage(OutboxMessage),
+    /// outbox message id, error of the attempt
+    #[display(fmt = "OutgoingMessageFailed")]
+    OutgoingMessageFailed(Uuid, String),
+    /// Sends an outbox message again right away
+    #[display(fmt = "RetryOutgoingMessage")]
+    RetryOutgoingMessage(Uuid),
+    /// Removes a message from the outbox, either because it was discarded or because it was sent
+    #[display(fmt = "RemoveOutgoingMessage")]
+    RemoveOutgoingMessage(Uuid),
+    /// When the status message clears itself. None keeps it until it is changed
+    #[display(fmt = "SetStatusMessageExpiry")]
+    SetStatusMessageExpiry(Option<DateTime<Utc>>),
     /// fakes sending a message to the specified chat
     /// for normal operation, warp sends a message, Uplink receives an event when that message was sent, and state is updated accordingly.
     /// for mock data, warp is not used and this is needed to fake sending a message
@@ -202,6 +242,12 @@ pub enum Action<'a> {
     ClearUnreads(Uuid),
     #[display(fmt = "ClearActiveUnreads")]
     ClearActiveUnreads,
+    /// opens the thread side panel and clears the thread's unreads
+    /// (conversation id, root message id)
+    #[display(fmt = "OpenThread")]
+    OpenThread(Uuid, Uuid),
+    #[display(fmt = "CloseThread")]
+    CloseThread(Uuid),
     #[display(fmt = "ClearAllUnreads")]
     ClearAllUnreads,
     #[display(fmt = "Config {_0}")]
@@ -234,8 +280,28 @@ pub enum ConfigAction {
     SetMessagesNotificationsEnabled(bool),
     #[display(fmt = "SetSettingsNotificationsEnabled {_0}")]
     SetSettingsNotificationsEnabled(bool),
+    #[display(fmt = "SetCallsNotificationsEnabled {_0}")]
+    SetCallsNotificationsEnabled(bool),
     #[display(fmt = "SetAutoEnableExtensions {_0}")]
     SetAutoEnableExtensions(bool),
     #[display(fmt = "SetEchoCancellation {_0}")]
     SetEchoCancellation(bool),
+    #[display(fmt = "SetPushToTalk {_0}")]
+    SetPushToTalk(bool),
+    #[display(fmt = "SetPushToTalkReleaseDelay {_0}")]
+    SetPushToTalkReleaseDelay(u64),
+    #[display(fmt = "SetChatNotificationRule {_0}")]
+    SetChatNotificationRule(Uuid, ChatRule),
+    #[display(fmt = "SetNotificationKeywords")]
+    SetNotificationKeywords(Vec<String>),
+    #[display(fmt = "SetQuietHours")]
+    SetQuietHours(QuietHours),
+    #[display(fmt = "SetAutoAway {_0}")]
+    SetAutoAway(bool),
+    #[display(fmt = "SetAwayAfterMinutes {_0}")]
+    SetAwayAfterMinutes(u32),
+    #[display(fmt = "SetBusyInCalls {_0}")]
+    SetBusyInCalls(bool),
+    #[display(fmt = "SetLinkPreviews {_0:?}")]
+    SetLinkPreviews(LinkPreviews),
 }
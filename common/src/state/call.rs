@@ -1,5 +1,5 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     time::{Duration, Instant},
 };
 
@@ -9,6 +9,8 @@ use dioxus_desktop::wry::application::window::WindowId;
 use uuid::Uuid;
 use warp::{blink::ParticipantState, crypto::DID};
 
+use super::call_stats::CallStats;
+
 #[derive(Clone, Default)]
 pub struct CallInfo {
     active_call: Option<ActiveCall>,
@@ -20,14 +22,20 @@ pub struct ActiveCall {
     pub call: Call,
     pub answer_time: DateTime<Local>,
     pub popout_window_id: Option<WindowId>,
+    pub stats: CallStats,
 }
 
 impl From<Call> for ActiveCall {
     fn from(value: Call) -> Self {
+        let mut stats = CallStats::default();
+        for id in value.participants_joined.keys() {
+            stats.joined(id.clone());
+        }
         Self {
             call: value,
             answer_time: Local::now(),
             popout_window_id: None,
+            stats,
         }
     }
 }
@@ -38,9 +46,13 @@ pub struct Call {
     pub conversation_id: Uuid,
     pub participants: Vec<DID>,
     pub participants_joined: HashMap<DID, ParticipantState>,
+    /// everyone who joined at some point. kept for the call history
+    pub participants_seen: HashSet<DID>,
     pub participants_speaking: HashMap<DID, Instant>,
     pub self_muted: bool,
     pub call_silenced: bool,
+    pub caller: DID,
+    pub started: DateTime<Local>,
 }
 
 impl CallInfo {
@@ -53,13 +65,19 @@ impl CallInfo {
     pub fn pending_calls(&self) -> Vec<Call> {
         self.pending_calls.clone()
     }
-    pub fn offer_call(&mut self, id: Uuid, conversation_id: Uuid, participants: Vec<DID>) {
+    pub fn offer_call(
+        &mut self,
+        id: Uuid,
+        conversation_id: Uuid,
+        participants: Vec<DID>,
+        caller: DID,
+    ) {
         self.active_call
-            .replace(Call::new(id, conversation_id, participants).into());
+            .replace(Call::new(id, conversation_id, participants, caller).into());
     }
 
-    pub fn end_call(&mut self) {
-        self.active_call.take();
+    pub fn end_call(&mut self) -> Option<ActiveCall> {
+        self.active_call.take()
     }
 
     pub fn answer_call(&mut self, id: Uuid, did: Option<DID>) -> anyhow::Result<Call> {
@@ -76,8 +94,8 @@ impl CallInfo {
         }
     }
 
-    pub fn reject_call(&mut self, id: Uuid) {
-        self.pending_calls.retain(|x| x.id != id);
+    pub fn reject_call(&mut self, id: Uuid) -> Option<Call> {
+        self.remove_pending_call(id)
     }
 
     pub fn pending_call(
@@ -85,23 +103,26 @@ impl CallInfo {
         id: Uuid,
         conversation_id: Uuid,
         participants: Vec<DID>,
+        caller: DID,
     ) -> anyhow::Result<()> {
         if self.pending_calls.iter().any(|x| x.id == id) {
             bail!("call with that id was already pending");
         }
         self.pending_calls
-            .push(Call::new(id, conversation_id, participants));
+            .push(Call::new(id, conversation_id, participants, caller));
         Ok(())
     }
 
-    pub fn remove_pending_call(&mut self, id: Uuid) {
-        self.pending_calls.retain(|x| x.id != id);
+    pub fn remove_pending_call(&mut self, id: Uuid) -> Option<Call> {
+        let idx = self.pending_calls.iter().position(|x| x.id == id)?;
+        Some(self.pending_calls.remove(idx))
     }
 
     pub fn remove_participant(&mut self, conversation_id: Uuid, id: &DID) -> anyhow::Result<()> {
         if let Some(active_call) = self.active_call.as_mut() {
             if active_call.call.conversation_id.eq(&conversation_id) {
                 active_call.call.remove_participant(id);
+                active_call.stats.left(id);
             }
         }
         self.pending_calls.iter_mut().for_each(|c| {
@@ -117,10 +138,31 @@ impl CallInfo {
             Some(c) => c,
             None => bail!("call not in progress"),
         };
+        if active_call.call.participants.contains(&id) {
+            active_call.stats.speaking(id.clone());
+        }
         active_call.call.participant_speaking(id);
         Ok(())
     }
 
+    pub fn self_speaking(&mut self, own_id: DID) -> anyhow::Result<()> {
+        let active_call = match self.active_call.as_mut() {
+            Some(c) => c,
+            None => bail!("call not in progress"),
+        };
+        active_call.stats.speaking(own_id);
+        Ok(())
+    }
+
+    pub fn audio_degraded(&mut self, id: DID) -> anyhow::Result<()> {
+        let active_call = match self.active_call.as_mut() {
+            Some(c) => c,
+            None => bail!("call not in progress"),
+        };
+        active_call.stats.degraded(id);
+        Ok(())
+    }
+
     pub fn participant_joined(&mut self, call_id: Uuid, id: DID) -> anyhow::Result<()> {
         let active_call = match self.active_call.as_mut() {
             Some(c) => c,
@@ -129,6 +171,9 @@ impl CallInfo {
         if active_call.call.id != call_id {
             bail!("wrong call id");
         }
+        if active_call.call.participants.contains(&id) {
+            active_call.stats.joined(id.clone());
+        }
         active_call.call.participant_joined(id);
         Ok(())
     }
@@ -142,11 +187,14 @@ impl CallInfo {
             bail!("wrong call id");
         }
         active_call.call.participant_left(id);
+        active_call.stats.left(id);
         Ok(())
     }
 
     pub fn update_active_call(&mut self) -> bool {
         if let Some(active_call) = self.active_call.as_mut() {
+            // the stats panel refreshes on its own
+            active_call.stats.sample();
             return active_call.call.update_speaking_participants();
         };
         false
@@ -216,15 +264,18 @@ impl CallInfo {
 }
 
 impl Call {
-    pub fn new(id: Uuid, conversation_id: Uuid, participants: Vec<DID>) -> Self {
+    pub fn new(id: Uuid, conversation_id: Uuid, participants: Vec<DID>, caller: DID) -> Self {
         Self {
             id,
             conversation_id,
             participants,
             participants_joined: HashMap::new(),
+            participants_seen: HashSet::new(),
             participants_speaking: HashMap::new(),
             self_muted: false,
             call_silenced: false,
+            caller,
+            started: Local::now(),
         }
     }
 
@@ -236,6 +287,7 @@ impl Call {
 
     fn participant_joined(&mut self, id: DID) {
         if self.participants.contains(&id) && !self.participants_joined.contains_key(&id) {
+            self.participants_seen.insert(id.clone());
             self.participants_joined
                 .insert(id, ParticipantState::default());
         }
@@ -273,6 +325,7 @@ impl Call {
 
     fn update_participant_state(&mut self, id: DID, state: ParticipantState) {
         if self.participants.contains(&id) {
+            self.participants_seen.insert(id.clone());
             self.participants_joined.insert(id, state);
         }
     }
@@ -0,0 +1,144 @@
+use std::collections::VecDeque;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use warp::crypto::DID;
+
+use super::call::{ActiveCall, Call};
+
+// older entries are dropped once the history is full
+const MAX_ENTRIES: usize = 500;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CallStatus {
+    /// at least one other participant joined
+    Answered,
+    /// an incoming call which was cancelled before it was answered
+    Missed,
+    /// an incoming call which was declined
+    Rejected,
+    /// an outgoing call which nobody joined
+    NoAnswer,
+}
+
+/// A finished call. Saved with State so the history survives restarts.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CallRecord {
+    pub id: Uuid,
+    pub conversation_id: Uuid,
+    pub caller: DID,
+    pub participants: Vec<DID>,
+    /// everyone who was in the call at some point, including people who left before it ended
+    #[serde(default)]
+    pub participants_joined: Vec<DID>,
+    pub started: DateTime<Utc>,
+    #[serde(default)]
+    pub answer_time: Option<DateTime<Utc>>,
+    pub ended: DateTime<Utc>,
+    pub status: CallStatus,
+}
+
+impl CallRecord {
+    pub fn finished(active_call: ActiveCall, own_did: &DID) -> Self {
+        let call = active_call.call;
+        let answered = call.participants_seen.iter().any(|did| did != own_did);
+        let status = if answered || call.caller != *own_did {
+            CallStatus::Answered
+        } else {
+            CallStatus::NoAnswer
+        };
+        Self {
+            answer_time: Some(active_call.answer_time.with_timezone(&Utc)),
+            status,
+            ..Self::unanswered(call, status)
+        }
+    }
+
+    pub fn unanswered(call: Call, status: CallStatus) -> Self {
+        let mut participants_joined: Vec<DID> = call.participants_seen.into_iter().collect();
+        participants_joined.sort_by_key(|did| did.to_string());
+        Self {
+            id: call.id,
+            conversation_id: call.conversation_id,
+            caller: call.caller,
+            participants: call.participants,
+            participants_joined,
+            started: call.started.with_timezone(&Utc),
+            answer_time: None,
+            ended: Utc::now(),
+            status,
+        }
+    }
+
+    pub fn is_outgoing(&self, own_did: &DID) -> bool {
+        self.caller == *own_did
+    }
+
+    /// how long the call lasted once it was answered
+    pub fn duration(&self) -> Option<Duration> {
+        let answered = self.answer_time?;
+        Some(self.ended - answered).filter(|x| *x >= Duration::zero())
+    }
+}
+
+// newest first
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct CallHistory {
+    entries: VecDeque<CallRecord>,
+}
+
+impl CallHistory {
+    pub fn add(&mut self, record: CallRecord) {
+        if self.entries.iter().any(|x| x.id == record.id) {
+            return;
+        }
+        self.entries.push_front(record);
+        self.entries.truncate(MAX_ENTRIES);
+    }
+
+    pub fn recent(&self, limit: usize) -> Vec<CallRecord> {
+        self.entries.iter().take(limit).cloned().collect()
+    }
+
+    /// oldest first, to be shown between the messages of the chat
+    pub fn for_chat(&self, conversation_id: Uuid) -> Vec<CallRecord> {
+        self.entries
+            .iter()
+            .rev()
+            .filter(|x| x.conversation_id == conversation_id)
+            .cloned()
+            .collect()
+    }
+
+    pub fn remove_conversation(&mut self, conversation_id: Uuid) {
+        self.entries
+            .retain(|x| x.conversation_id != conversation_id);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn record(conversation_id: Uuid) -> CallRecord {
+        CallRecord::unanswered(
+            Call::new(Uuid::new_v4(), conversation_id, vec![], DID::default()),
+            CallStatus::Missed,
+        )
+    }
+
+    #[test]
+    fn test_history_is_capped() {
+        let conversation_id = Uuid::new_v4();
+        let mut history = CallHistory::default();
+        for _ in 0..MAX_ENTRIES + 10 {
+            history.add(record(conversation_id));
+        }
+        let last = record(conversation_id);
+        history.add(last.clone());
+        history.add(last.clone());
+        assert_eq!(history.for_chat(conversation_id).len(), MAX_ENTRIES);
+        assert_eq!(history.recent(1), vec![last]);
+    }
+}
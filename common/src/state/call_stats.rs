@@ -0,0 +1,205 @@
+//! Connection statistics for the participants of the active call, built from the events blink sends: participants
+//! joining and leaving, speaking, and `AudioDegradation` when the audio of a peer is no longer received properly.
+//! Blink doesn't report audio levels, packet loss, jitter or codec details, so those aren't shown and degradations are
+//! the only measure of the network. Showing them needs a stats event or getter in Blink first.
+//!
+//! Every second a `Sample` is taken per participant, which keeps the last minute of history.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    time::Instant,
+};
+
+use warp::crypto::DID;
+
+// one sample per second
+pub const HISTORY_LEN: usize = 60;
+// the samples considered when guessing where degradations come from
+const RECENT_SAMPLES: usize = 10;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Sample {
+    pub connected: bool,
+    pub speaking: bool,
+    pub degraded: bool,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ParticipantStats {
+    /// not set while the participant isn't in the call
+    pub connected_since: Option<Instant>,
+    /// how often the participant joined again after leaving
+    pub reconnects: u32,
+    pub degradations: u32,
+    pub last_degradation: Option<Instant>,
+    /// oldest first
+    pub history: VecDeque<Sample>,
+    // events since the last sample
+    spoke: bool,
+    degraded: bool,
+    joined_before: bool,
+}
+
+impl ParticipantStats {
+    fn joined(&mut self, now: Instant) {
+        if self.connected_since.is_some() {
+            return;
+        }
+        if self.joined_before {
+            self.reconnects += 1;
+        }
+        self.joined_before = true;
+        self.connected_since = Some(now);
+    }
+
+    fn left(&mut self) {
+        self.connected_since = None;
+    }
+
+    fn degraded(&mut self, now: Instant) {
+        self.degradations += 1;
+        self.last_degradation = Some(now);
+        self.degraded = true;
+    }
+
+    fn sample(&mut self) {
+        self.history.push_back(Sample {
+            connected: self.connected_since.is_some(),
+            speaking: std::mem::take(&mut self.spoke),
+            degraded: std::mem::take(&mut self.degraded),
+        });
+        while self.history.len() > HISTORY_LEN {
+            self.history.pop_front();
+        }
+    }
+
+    /// the share of the history the participant was speaking, from 0 to 1
+    pub fn activity(&self) -> f32 {
+        ratio(&self.history, |sample| sample.speaking)
+    }
+
+    /// the share of the history the audio of the participant degraded, from 0 to 1
+    pub fn degraded_ratio(&self) -> f32 {
+        ratio(&self.history, |sample| sample.degraded)
+    }
+
+    fn recently_degraded(&self) -> bool {
+        self.degraded
+            || self
+                .history
+                .iter()
+                .rev()
+                .take(RECENT_SAMPLES)
+                .any(|sample| sample.degraded)
+    }
+}
+
+fn ratio(history: &VecDeque<Sample>, f: impl Fn(&Sample) -> bool) -> f32 {
+    if history.is_empty() {
+        return 0.0;
+    }
+    history.iter().filter(|sample| f(sample)).count() as f32 / history.len() as f32
+}
+
+/// Where degraded audio most likely comes from
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DegradationSource {
+    /// the audio of every peer degrades
+    Local,
+    /// the audio of only some peers degrades
+    Remote(Vec<DID>),
+    /// only one peer is connected, so it can't be told apart
+    Unknown,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CallStats {
+    pub participants: HashMap<DID, ParticipantStats>,
+}
+
+impl CallStats {
+    pub fn joined(&mut self, id: DID) {
+        self.participants
+            .entry(id)
+            .or_default()
+            .joined(Instant::now());
+    }
+
+    pub fn left(&mut self, id: &DID) {
+        if let Some(stats) = self.participants.get_mut(id) {
+            stats.left();
+        }
+    }
+
+    pub fn speaking(&mut self, id: DID) {
+        self.participants.entry(id).or_default().spoke = true;
+    }
+
+    pub fn degraded(&mut self, id: DID) {
+        self.participants
+            .entry(id)
+            .or_default()
+            .degraded(Instant::now());
+    }
+
+    pub fn sample(&mut self) {
+        for stats in self.participants.values_mut() {
+            stats.sample();
+        }
+    }
+
+    pub fn get(&self, id: &DID) -> ParticipantStats {
+        self.participants.get(id).cloned().unwrap_or_default()
+    }
+
+    /// None if no audio degraded recently
+    pub fn degradation_source(&self, own_id: &DID) -> Option<DegradationSource> {
+        let peers: Vec<(&DID, &ParticipantStats)> = self
+            .participants
+            .iter()
+            .filter(|(id, stats)| *id != own_id && stats.connected_since.is_some())
+            .collect();
+        let degraded: Vec<DID> = peers
+            .iter()
+            .filter(|(_, stats)| stats.recently_degraded())
+            .map(|(id, _)| (*id).clone())
+            .collect();
+        if degraded.is_empty() {
+            None
+        } else if peers.len() < 2 {
+            Some(DegradationSource::Unknown)
+        } else if degraded.len() == peers.len() {
+            Some(DegradationSource::Local)
+        } else {
+            Some(DegradationSource::Remote(degraded))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn did() -> DID {
+        DID::default()
+    }
+
+    #[test]
+    fn test_history_and_reconnects() {
+        let id = did();
+        let mut stats = CallStats::default();
+        stats.joined(id.clone());
+        stats.speaking(id.clone());
+        stats.sample();
+        stats.left(&id);
+        stats.joined(id.clone());
+        for _ in 0..HISTORY_LEN {
+            stats.sample();
+        }
+        let participant = stats.get(&id);
+        assert_eq!(participant.reconnects, 1);
+        assert_eq!(participant.history.len(), HISTORY_LEN);
+        // the sample with speech was dropped
+        assert_eq!(participant.activity(), 0.0);
+    }
+}
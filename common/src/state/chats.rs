@@ -55,6 +55,15 @@ pub struct Chat {
     // Unread count for this chat, should be cleared when we view the chat.
     #[serde(default)]
     unreads: HashSet<Uuid>,
+    // Unread replies per thread, keyed by the id of the thread's root message. Cleared when the thread is opened.
+    #[serde(default)]
+    thread_unreads: HashMap<Uuid, HashSet<Uuid>>,
+    // Root message of the thread shown in the side panel
+    #[serde(skip)]
+    pub open_thread: Option<Uuid>,
+    // set by the EditLastMessage shortcut. Cleared by the messages view once it starts editing
+    #[serde(skip)]
+    pub edit_last_message: bool,
     // This tracks the messages that mentions the user. For future use
     // E.g. displaying a list of mentions to the user in a pop up
     #[serde(default, skip)]
@@ -99,6 +108,9 @@ impl Default for Chat {
             creator: Default::default(),
             messages: Default::default(),
             unreads: Default::default(),
+            thread_unreads: Default::default(),
+            open_thread: None,
+            edit_last_message: false,
             mentions: Default::default(),
             replying_to: Default::default(),
             typing_indicator: Default::default(),
@@ -205,6 +217,28 @@ impl Chat {
     pub fn add_unread(&mut self, id: Uuid) {
         self.unreads.insert(id);
     }
+
+    pub fn thread_unreads(&self, root: &Uuid) -> u32 {
+        self.thread_unreads
+            .get(root)
+            .map(|x| x.len() as _)
+            .unwrap_or_default()
+    }
+
+    pub fn add_thread_unread(&mut self, root: Uuid, id: Uuid) {
+        self.thread_unreads.entry(root).or_default().insert(id);
+    }
+
+    pub fn clear_thread_unreads(&mut self, root: &Uuid) {
+        self.thread_unreads.remove(root);
+    }
+
+    pub fn remove_thread_unread(&mut self, id: &Uuid) {
+        for unreads in self.thread_unreads.values_mut() {
+            unreads.remove(id);
+        }
+        self.thread_unreads.retain(|_, x| !x.is_empty());
+    }
 }
 
 // warning: Chats implements Serialize
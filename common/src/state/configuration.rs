@@ -1,9 +1,10 @@
+use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use tracing::log;
 
 use crate::STATIC_ARGS;
 
-use super::action::ConfigAction;
+use super::{action::ConfigAction, notification_rules::NotificationRules};
 
 /// A struct that represents the configuration of the application.
 #[derive(Debug, Default, Deserialize, Serialize, Clone)]
@@ -31,6 +32,10 @@ pub struct Configuration {
     /// Notification-related configuration options.
     #[serde(default)]
     pub notifications: Notifications,
+
+    /// Automatic status changes.
+    #[serde(default)]
+    pub presence: Presence,
 }
 
 #[derive(Debug, Default, Deserialize, Serialize, Clone)]
@@ -53,6 +58,28 @@ pub struct Privacy {
     pub satellite_sync_nodes: bool,
     #[serde(default)]
     pub safer_file_scanning: bool,
+    #[serde(default)]
+    pub link_previews: LinkPreviews,
+}
+
+/// Which links in messages get a preview. Fetching a preview reveals the IP address to the linked site.
+#[derive(Debug, Default, Deserialize, Serialize, Copy, Clone, Eq, PartialEq)]
+pub enum LinkPreviews {
+    #[default]
+    All,
+    /// only links in messages sent by the user
+    Sent,
+    Off,
+}
+
+impl LinkPreviews {
+    pub fn should_fetch(&self, remote: bool) -> bool {
+        match self {
+            LinkPreviews::All => true,
+            LinkPreviews::Sent => !remote,
+            LinkPreviews::Off => false,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, Copy, Clone, Eq, PartialEq)]
@@ -62,6 +89,12 @@ pub struct AudioVideo {
     pub interface_sounds: bool,
     pub message_sounds: bool,
     pub media_sounds: bool,
+    /// the microphone is only open while the push-to-talk key is held
+    #[serde(default)]
+    pub push_to_talk: bool,
+    /// how long the microphone stays open after the push-to-talk key is released
+    #[serde(default)]
+    pub push_to_talk_release_delay_ms: u64,
 }
 
 impl Default for AudioVideo {
@@ -72,6 +105,8 @@ impl Default for AudioVideo {
             interface_sounds: false,
             message_sounds: true,
             media_sounds: true,
+            push_to_talk: false,
+            push_to_talk_release_delay_ms: 0,
         }
     }
 }
@@ -96,15 +131,46 @@ fn bool_true() -> bool {
     true
 }
 
+fn default_away_after_minutes() -> u32 {
+    10
+}
+
+#[derive(Debug, Deserialize, Serialize, Copy, Clone, Eq, PartialEq)]
+pub struct Presence {
+    /// switch to Away after `away_after_minutes` without activity
+    #[serde(default = "bool_true")]
+    pub auto_away: bool,
+    #[serde(default = "default_away_after_minutes")]
+    pub away_after_minutes: u32,
+    /// switch to Busy during calls
+    #[serde(default = "bool_true")]
+    pub busy_in_calls: bool,
+}
+
+impl Default for Presence {
+    fn default() -> Self {
+        Self {
+            auto_away: true,
+            away_after_minutes: default_away_after_minutes(),
+            busy_in_calls: true,
+        }
+    }
+}
+
 // We may want to give the user the ability to pick and choose which notifications they want to see.
 // This is a good place to start.
-#[derive(Debug, Deserialize, Serialize, Copy, Clone, Eq, PartialEq)]
+#[derive(Debug, Deserialize, Serialize, Clone, Eq, PartialEq)]
 pub struct Notifications {
     pub enabled: bool,
     pub show_app_icon: bool,
     pub friends_notifications: bool,
     pub messages_notifications: bool,
     pub settings_notifications: bool,
+    #[serde(default = "bool_true")]
+    pub calls_notifications: bool,
+    // per chat mutes, keyword alerts and quiet hours
+    #[serde(default)]
+    pub rules: NotificationRules,
 }
 
 impl Default for Notifications {
@@ -116,6 +182,8 @@ impl Default for Notifications {
             messages_notifications: true,
             // By default we leave this one off.
             settings_notifications: false,
+            calls_notifications: true,
+            rules: NotificationRules::default(),
         }
     }
 }
@@ -164,10 +232,33 @@ impl Configuration {
             ConfigAction::SetSettingsNotificationsEnabled(flag) => {
                 self.notifications.settings_notifications = flag
             }
+            ConfigAction::SetCallsNotificationsEnabled(flag) => {
+                self.notifications.calls_notifications = flag
+            }
             ConfigAction::SetAutoEnableExtensions(flag) => {
                 self.extensions.enable_automatically = flag
             }
             ConfigAction::SetEchoCancellation(flag) => self.audiovideo.echo_cancellation = flag,
+            ConfigAction::SetPushToTalk(flag) => self.audiovideo.push_to_talk = flag,
+            ConfigAction::SetPushToTalkReleaseDelay(ms) => {
+                self.audiovideo.push_to_talk_release_delay_ms = ms
+            }
+            ConfigAction::SetChatNotificationRule(conversation_id, rule) => {
+                self.notifications.rules.set_chat(conversation_id, rule);
+                self.notifications.rules.clear_expired(Utc::now());
+            }
+            ConfigAction::SetNotificationKeywords(keywords) => {
+                self.notifications.rules.keywords = keywords
+            }
+            ConfigAction::SetQuietHours(quiet_hours) => {
+                self.notifications.rules.quiet_hours = quiet_hours
+            }
+            ConfigAction::SetAutoAway(flag) => self.presence.auto_away = flag,
+            ConfigAction::SetAwayAfterMinutes(minutes) => {
+                self.presence.away_after_minutes = minutes
+            }
+            ConfigAction::SetBusyInCalls(flag) => self.presence.busy_in_calls = flag,
+            ConfigAction::SetLinkPreviews(previews) => self.privacy.link_previews = previews,
         }
 
         if self.audiovideo != old_audiovideo {
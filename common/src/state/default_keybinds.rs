@@ -69,6 +69,63 @@ pub fn get_default_keybinds() -> Vec<(GlobalShortcut, Shortcut)> {
                 true,
             )),
         ),
+        (
+            GlobalShortcut::NextChat,
+            Shortcut::from((
+                vec![KeyCode::CloseBraket],
+                vec![ModifiersState::CONTROL, ModifiersState::SHIFT],
+                false,
+            )),
+        ),
+        (
+            GlobalShortcut::PreviousChat,
+            Shortcut::from((
+                vec![KeyCode::OpenBracket],
+                vec![ModifiersState::CONTROL, ModifiersState::SHIFT],
+                false,
+            )),
+        ),
+        (
+            GlobalShortcut::JumpToUnread,
+            Shortcut::from((
+                vec![KeyCode::U],
+                vec![alt_or_command_modifierstate, ModifiersState::SHIFT],
+                false,
+            )),
+        ),
+        (
+            GlobalShortcut::OpenChats,
+            Shortcut::from((
+                vec![KeyCode::Num1],
+                vec![alt_or_command_modifierstate],
+                false,
+            )),
+        ),
+        (
+            GlobalShortcut::OpenFriends,
+            Shortcut::from((
+                vec![KeyCode::Num2],
+                vec![alt_or_command_modifierstate],
+                false,
+            )),
+        ),
+        (
+            GlobalShortcut::OpenFiles,
+            Shortcut::from((
+                vec![KeyCode::Num3],
+                vec![alt_or_command_modifierstate],
+                false,
+            )),
+        ),
+        (
+            GlobalShortcut::OpenSettings,
+            Shortcut::from((
+                vec![KeyCode::Num4],
+                vec![alt_or_command_modifierstate],
+                false,
+            )),
+        ),
+        // the chat and call actions are unbound by default
     ])
 }
 
@@ -87,3 +144,45 @@ pub fn get_keycode_and_modifier_from_a_shortcut(
     }
     (keycodes, modifiers)
 }
+
+/// Shortcuts already taken by text editing in the webview or, on Linux, by the desktop. Keybinds which use them
+/// either never fire or break the original shortcut.
+/// Returns (name, shortcut). The name is the locale key `settings-keybinds.<name>`
+pub fn get_reserved_shortcuts() -> Vec<(&'static str, Shortcut)> {
+    let primary_modifier = if cfg!(target_os = "macos") {
+        ModifiersState::SUPER
+    } else {
+        ModifiersState::CONTROL
+    };
+    let mut reserved = vec![
+        ("reserved-copy", KeyCode::C),
+        ("reserved-cut", KeyCode::X),
+        ("reserved-paste", KeyCode::V),
+        ("reserved-select-all", KeyCode::A),
+        ("reserved-undo", KeyCode::Z),
+    ]
+    .into_iter()
+    .map(|(name, key)| {
+        (
+            name,
+            Shortcut::from((vec![key], vec![primary_modifier], false)),
+        )
+    })
+    .collect::<Vec<_>>();
+
+    if cfg!(target_os = "linux") {
+        reserved.push((
+            "reserved-terminal",
+            Shortcut::from((
+                vec![KeyCode::T],
+                vec![ModifiersState::CONTROL, ModifiersState::ALT],
+                false,
+            )),
+        ));
+        reserved.push((
+            "reserved-lock-screen",
+            Shortcut::from((vec![KeyCode::L], vec![ModifiersState::SUPER], false)),
+        ));
+    }
+    reserved
+}
@@ -0,0 +1,242 @@
+//! Unsent messages are kept in their own file, `STATIC_ARGS.drafts_path`, instead of state.json. Chats only serialize
+//! what is needed to list them, and the draft has to be saved while typing. The file is encrypted with the account's
+//! local key.
+//!
+//! While typing, the file is written by a background thread at most once every `SAVE_DELAY`, so typing never waits for
+//! the disk. Removing a draft, when its message was sent or it was cleared, is written right away, and `flush` writes
+//! a pending save when Uplink closes, loses focus or panics. Only if Uplink is killed can the last `SAVE_DELAY` of
+//! typing be lost.
+//!
+//! Every window of Uplink reads the same State, so they all show the same drafts.
+
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use parking_lot::{Condvar, Mutex};
+use serde::{Deserialize, Serialize};
+use tracing::log;
+use uuid::Uuid;
+use warp::raygun::{self, Location};
+
+use super::{pending_message::FileLocation, Chat};
+use crate::{local_key, utils::atomic_write::write_atomic, STATIC_ARGS};
+
+// changes made while a save waits are written together
+const SAVE_DELAY: Duration = Duration::from_millis(500);
+// how long `flush` waits for a write which is in progress
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// the drafts to write, with the `local_key::generation` they belong to
+type Pending = Option<(u64, HashMap<Uuid, Draft>)>;
+
+static PENDING: Lazy<Arc<(Mutex<Pending>, Condvar)>> = Lazy::new(|| {
+    let pending: Arc<(Mutex<Pending>, Condvar)> = Default::default();
+    let writer = pending.clone();
+    std::thread::spawn(move || write_drafts(&writer));
+    pending
+});
+// held while the pending drafts are taken and written, so an older copy is never written after a newer one
+static WRITING: Mutex<()> = parking_lot::const_mutex(());
+
+fn write_drafts(pending: &(Mutex<Pending>, Condvar)) {
+    let (lock, changed) = pending;
+    loop {
+        {
+            let mut pending = lock.lock();
+            while pending.is_none() {
+                changed.wait(&mut pending);
+            }
+        }
+        std::thread::sleep(SAVE_DELAY);
+        let _writing = WRITING.lock();
+        let latest = lock.lock().take();
+        write(latest);
+    }
+}
+
+fn write(pending: Pending) {
+    let Some((generation, drafts)) = pending else {
+        return;
+    };
+    // the account changed since the drafts were saved
+    if generation != local_key::generation() {
+        return;
+    }
+    let result = serde_json::to_vec(&drafts)
+        .map_err(anyhow::Error::from)
+        .and_then(|data| local_key::encrypt(&data))
+        .and_then(|encrypted| {
+            write_atomic(&STATIC_ARGS.drafts_path, encrypted).map_err(anyhow::Error::from)
+        });
+    if let Err(e) = result {
+        log::error!("failed to save drafts: {e}");
+    }
+}
+
+/// Writes drafts which are waiting to be saved
+pub fn flush() {
+    let Some(_writing) = WRITING.try_lock_for(FLUSH_TIMEOUT) else {
+        log::error!("failed to save drafts: a write is stuck");
+        return;
+    };
+    let latest = match PENDING.0.try_lock_for(FLUSH_TIMEOUT) {
+        Some(mut pending) => pending.take(),
+        None => return,
+    };
+    write(latest);
+}
+
+/// What was written in a chat, but not sent yet
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Draft {
+    #[serde(default)]
+    pub text: String,
+    #[serde(default)]
+    pub replying_to: Option<raygun::Message>,
+    #[serde(default)]
+    pub attachments: Vec<FileLocation>,
+    pub updated: DateTime<Utc>,
+}
+
+impl Draft {
+    /// None if nothing was written, attached or replied to
+    fn from_chat(chat: &Chat) -> Option<Self> {
+        let text = chat.draft.clone().unwrap_or_default();
+        if text.is_empty() && chat.replying_to.is_none() && chat.files_attached_to_send.is_empty() {
+            return None;
+        }
+        Some(Self {
+            text,
+            replying_to: chat.replying_to.clone(),
+            attachments: chat
+                .files_attached_to_send
+                .iter()
+                .cloned()
+                .map(FileLocation::from)
+                .collect(),
+            updated: Utc::now(),
+        })
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Drafts {
+    drafts: HashMap<Uuid, Draft>,
+}
+
+impl Drafts {
+    pub fn load() -> Self {
+        if STATIC_ARGS.use_mock {
+            return Self::default();
+        }
+        let Ok(contents) = std::fs::read(&STATIC_ARGS.drafts_path) else {
+            return Self::default();
+        };
+        // drafts were saved unencrypted at first
+        let drafts = match local_key::decrypt(&contents) {
+            Ok(data) => serde_json::from_slice(&data),
+            Err(_) => serde_json::from_slice(&contents),
+        };
+        match drafts {
+            Ok(drafts) => Self { drafts },
+            Err(e) => {
+                log::error!("failed to load drafts: {e}");
+                Self::default()
+            }
+        }
+    }
+
+    fn save(&self) {
+        if STATIC_ARGS.use_mock || !local_key::is_unlocked() {
+            return;
+        }
+        let (lock, changed) = &**PENDING;
+        *lock.lock() = Some((local_key::generation(), self.drafts.clone()));
+        changed.notify_one();
+    }
+
+    fn save_now(&self) {
+        self.save();
+        flush();
+    }
+
+    /// Saves the draft of a chat after it changed
+    pub fn update(&mut self, chat: &Chat) {
+        let draft = Draft::from_chat(chat);
+        let current = self.drafts.get(&chat.id);
+        // the timestamp is always new, so only the contents are compared
+        let changed = match (&draft, current) {
+            (Some(new), Some(old)) => {
+                new.text != old.text
+                    || new.replying_to != old.replying_to
+                    || new.attachments != old.attachments
+            }
+            (None, None) => false,
+            _ => true,
+        };
+        if !changed {
+            return;
+        }
+        match draft {
+            Some(draft) => {
+                self.drafts.insert(chat.id, draft);
+                self.save();
+            }
+            // the message was sent or the draft cleared. it must not come back after a restart
+            None => {
+                self.drafts.remove(&chat.id);
+                self.save_now();
+            }
+        }
+    }
+
+    pub fn remove(&mut self, conversation_id: &Uuid) {
+        if self.drafts.remove(conversation_id).is_some() {
+            self.save_now();
+        }
+    }
+
+    /// Puts a saved draft back into its chat. Files which were attached from disk and don't exist anymore are dropped.
+    pub fn restore(&self, chat: &mut Chat) {
+        let Some(draft) = self.drafts.get(&chat.id) else {
+            return;
+        };
+        if !draft.text.is_empty() {
+            chat.draft = Some(draft.text.clone());
+        }
+        chat.replying_to = draft.replying_to.clone();
+        chat.files_attached_to_send = draft
+            .attachments
+            .iter()
+            .filter(|location| match location {
+                FileLocation::Disk { path } => path.exists(),
+                FileLocation::Constellation { .. } => true,
+            })
+            .cloned()
+            .map(Location::from)
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_empty_chats_have_no_draft() {
+        let mut chat = Chat::default();
+        assert_eq!(Draft::from_chat(&chat), None);
+
+        chat.draft = Some("hello".into());
+        let draft = Draft::from_chat(&chat).unwrap();
+        assert_eq!(draft.text, "hello");
+
+        let mut restored = Chat::default();
+        let drafts = Drafts {
+            drafts: HashMap::from([(chat.id, draft)]),
+        };
+        drafts.restore(&mut restored);
+        assert_eq!(restored.draft.as_deref(), Some("hello"));
+    }
+}
@@ -1,14 +1,21 @@
 pub mod action;
 pub mod call;
+pub mod call_history;
+pub mod call_stats;
 pub mod chats;
 pub mod configuration;
 pub mod data_transfer;
 pub mod default_keybinds;
+pub mod drafts;
 pub mod friends;
 pub mod identity;
+pub mod notification_rules;
 pub mod notifications;
+pub mod outbox;
 pub mod pending_message;
+pub mod presence;
 pub mod route;
+pub mod scheduled_messages;
 pub mod scope_ids;
 pub mod settings;
 pub mod storage;
@@ -16,6 +23,7 @@ pub mod ui;
 pub mod utils;
 
 use crate::language::{change_language, get_local_text_with_args};
+use crate::local_key::Sealed;
 use crate::notifications::NotificationAction;
 use crate::warp_runner::WarpCmdTx;
 // export specific structs which the UI expects. these structs used to be in src/state.rs, before state.rs was turned into the `state` folder
@@ -41,6 +49,7 @@ use crate::{
         WarpEvent,
     },
 };
+use chrono::{Local, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::{HashSet, VecDeque};
 
@@ -72,6 +81,17 @@ pub struct State {
     pub route: route::Route,
     chats: chats::Chats,
     friends: friends::Friends,
+    #[serde(default)]
+    scheduled_messages: Sealed<scheduled_messages::ScheduledMessages>,
+    #[serde(default)]
+    call_history: call_history::CallHistory,
+    #[serde(default)]
+    auto_status: presence::AutoStatus,
+    #[serde(default)]
+    outbox: Sealed<outbox::Outbox>,
+    // saved to its own file
+    #[serde(skip)]
+    drafts: drafts::Drafts,
     #[serde(skip)]
     pub storage: storage::Storage,
     pub scope_ids: scope_ids::ScopeIds,
@@ -105,6 +125,11 @@ impl Clone for State {
             route: self.route.clone(),
             chats: self.chats.clone(),
             friends: self.friends.clone(),
+            scheduled_messages: self.scheduled_messages.clone(),
+            call_history: self.call_history.clone(),
+            auto_status: self.auto_status.clone(),
+            drafts: self.drafts.clone(),
+            outbox: self.outbox.clone(),
             storage: self.storage.clone(),
             settings: Default::default(),
             scope_ids: Default::default(),
@@ -240,6 +265,10 @@ impl State {
             Action::ResetKeybinds => {
                 self.settings.keybinds = default_keybinds::get_default_keybinds()
             }
+            Action::SetKeybinds(keybinds) => self.settings.keybinds = keybinds,
+            Action::SetLinuxSystemShortcuts(enabled) => {
+                self.settings.linux_system_shortcuts = enabled
+            }
             // Themes
             Action::SetTheme(theme) => self.set_theme(theme),
             // Fonts
@@ -256,13 +285,49 @@ impl State {
                 self.clear_active_chat();
             }
             Action::StartReplying(chat, message) => self.start_replying(chat, message),
+            Action::EditLastMessage(chat_id) => {
+                if let Some(chat) = self.chats.all.get_mut(&chat_id) {
+                    chat.edit_last_message = true;
+                }
+            }
             Action::CancelReply(chat_id) => self.cancel_reply(chat_id),
+            Action::ScheduleMessage(msg) => self.scheduled_messages.add(msg),
+            Action::EditScheduledMessage(id, lines, send_at) => {
+                if !self.scheduled_messages.edit(id, lines, send_at) {
+                    log::warn!("scheduled message {id} no longer exists");
+                }
+            }
+            Action::RemoveScheduledMessage(id) => {
+                let _ = self.scheduled_messages.remove(id);
+            }
+            Action::QueueOutgoingMessage(msg) => self.outbox.add(msg),
+            Action::OutgoingMessageFailed(id, error) => {
+                self.outbox.attempt_failed(id, error, Utc::now())
+            }
+            Action::RetryOutgoingMessage(id) => self.outbox.retry(id, Utc::now()),
+            Action::RemoveOutgoingMessage(id) => {
+                let _ = self.outbox.remove(id);
+            }
+            Action::SetStatusMessageExpiry(expires) => {
+                self.auto_status.status_message_expires = expires
+            }
             Action::ClearUnreads(id) => self.clear_unreads(id),
             Action::ClearActiveUnreads => {
                 if let Some(id) = self.chats.active {
                     self.clear_unreads(id);
                 }
             }
+            Action::OpenThread(conv_id, root) => {
+                if let Some(chat) = self.chats.all.get_mut(&conv_id) {
+                    chat.clear_thread_unreads(&root);
+                    chat.open_thread = Some(root);
+                }
+            }
+            Action::CloseThread(conv_id) => {
+                if let Some(chat) = self.chats.all.get_mut(&conv_id) {
+                    chat.open_thread = None;
+                }
+            }
             Action::ClearAllUnreads => self.chats.all.values_mut().for_each(|c| c.clear_unreads()),
             Action::SetChatDraft(chat_id, value) => self.set_chat_draft(&chat_id, value),
             Action::ClearChatDraft(chat_id) => self.clear_chat_draft(&chat_id),
@@ -305,25 +370,37 @@ impl State {
             Action::ToggleMute => self.toggle_mute(),
             Action::ToggleSilence => self.toggle_silence(),
             Action::SetId(identity) => self.set_own_identity(identity),
-            Action::AnswerCall(id) => match self
-                .ui
-                .call_info
-                .answer_call(id, Some(self.get_own_identity().did_key())) //Update call with own did immediately
-            {
-                Ok(call) => {
-                    self.set_active_media(call.conversation_id);
-                    self.send_chat_to_top_of_sidebar(call.conversation_id);
+            Action::AnswerCall(id) => {
+                crate::notifications::close_call_notification(id);
+                match self
+                    .ui
+                    .call_info
+                    .answer_call(id, Some(self.get_own_identity().did_key())) //Update call with own did immediately
+                {
+                    Ok(call) => {
+                        self.set_active_media(call.conversation_id);
+                        self.send_chat_to_top_of_sidebar(call.conversation_id);
+                    }
+                    Err(e) => {
+                        log::error!("failed to answer call: {e}");
+                    }
                 }
-                Err(e) => {
-                    log::error!("failed to answer call: {e}");
+            }
+            Action::RejectCall(id) => {
+                crate::notifications::close_call_notification(id);
+                if let Some(call) = self.ui.call_info.reject_call(id) {
+                    self.call_history.add(call_history::CallRecord::unanswered(
+                        call,
+                        call_history::CallStatus::Rejected,
+                    ));
                 }
-            },
-            Action::RejectCall(id) => self.ui.call_info.reject_call(id),
+            }
             Action::OfferCall(call) => {
                 let _ = self.ui.call_info.pending_call(
                     call.id,
                     call.conversation_id,
                     call.participants,
+                    call.caller,
                 );
                 let _ = self.ui.call_info.answer_call(call.id, None);
                 self.set_active_chat(&call.conversation_id, true);
@@ -332,7 +409,7 @@ impl State {
             Action::EndCall => {
                 self.chats.active_media = None;
                 self.ui.popout_media_player = false;
-                self.ui.call_info.end_call();
+                self.end_call();
             }
             // ===== Configuration =====
             Action::Config(action) => self.configuration.mutate(action),
@@ -388,8 +465,13 @@ impl State {
                 // TODO: Get state available in this scope.
                 // Dispatch notifications only when we're not already focused on the application.
                 let notifications_enabled = self.configuration.notifications.friends_notifications;
+                let is_quiet = self
+                    .configuration
+                    .notifications
+                    .rules
+                    .is_quiet(Local::now());
 
-                if !self.ui.metadata.focused && notifications_enabled {
+                if !self.ui.metadata.focused && notifications_enabled && !is_quiet {
                     crate::notifications::push_notification(
                         get_local_text("friends.new-request"),
                         get_local_text_with_args(
@@ -418,6 +500,15 @@ impl State {
                 if let Some(ident) = self.identities.get_mut(&identity.did_key()) {
                     ident.set_identity_status(identity.identity_status());
                 }
+                // messages which couldn't reach them are sent again without waiting for their backoff
+                let conversations: Vec<Uuid> = self
+                    .chats
+                    .all
+                    .values()
+                    .filter(|chat| chat.participants.contains(&identity.did_key()))
+                    .map(|chat| chat.id)
+                    .collect();
+                self.outbox.wake(&conversations, Utc::now());
             }
             MultiPassEvent::FriendOffline(identity) => {
                 if let Some(ident) = self.identities.get_mut(&identity.did_key()) {
@@ -452,6 +543,11 @@ impl State {
             RayGunEvent::ConversationDeleted(id) => {
                 self.chats.in_sidebar.retain(|x| *x != id);
                 self.chats.all.remove(&id);
+                self.drafts.remove(&id);
+                self.scheduled_messages.remove_conversation(id);
+                self.outbox.remove_conversation(id);
+                self.call_history.remove_conversation(id);
+                self.configuration.notifications.rules.remove_chat(id);
                 if self.chats.active == Some(id) {
                     self.chats.active = None;
                 }
@@ -467,6 +563,7 @@ impl State {
             } => {
                 let own = self.get_own_identity().did_key();
                 let ping = message.is_mention_self(&own);
+                let text = message.inner.lines().join("\n");
                 self.update_identity_status_hack(&message.inner.sender());
                 let id = self.identities.get(&message.inner.sender()).cloned();
                 // todo: don't load all the messages by default. if the user scrolled up, for example, this incoming message may not need to be fetched yet.
@@ -488,8 +585,17 @@ impl State {
                 let notifications_enabled = self.configuration.notifications.enabled;
                 let should_play_sound = self.ui.current_layout != Layout::Compose
                     && self.configuration.audiovideo.message_sounds;
-                let should_dispatch_notification =
-                    should_play_sound && message_notifications_enabled && notifications_enabled;
+                // muted chats, mentions only chats and quiet hours still count towards the unreads
+                let rules_allow = self.configuration.notifications.rules.should_alert(
+                    conversation_id,
+                    ping,
+                    &text,
+                    Utc::now(),
+                );
+                let should_dispatch_notification = should_play_sound
+                    && message_notifications_enabled
+                    && notifications_enabled
+                    && rules_allow;
 
                 // This should be called if we have notifications enabled for new messages
                 if should_dispatch_notification {
@@ -568,6 +674,10 @@ impl State {
                     if chat.remove_unread(&message_id) {
                         should_decrement_notifications = true;
                     }
+                    chat.remove_thread_unread(&message_id);
+                    if chat.open_thread == Some(message_id) {
+                        chat.open_thread = None;
+                    }
                     chat.messages.retain(|msg| msg.inner.id() != message_id);
                     chat.pinned_messages.retain(|msg| msg.id() != message_id);
                     chat.mentions.retain(|msg| msg.inner.id() != message_id);
@@ -670,7 +780,7 @@ impl State {
             BlinkEventKind::IncomingCall {
                 call_id,
                 conversation_id,
-                sender: _,
+                sender,
                 participants,
             } => {
                 let conversation_id = match conversation_id {
@@ -681,16 +791,30 @@ impl State {
                     }
                 };
                 self.send_chat_to_top_of_sidebar(conversation_id);
-                if let Err(e) =
-                    self.ui
-                        .call_info
-                        .pending_call(call_id, conversation_id, participants)
-                {
-                    log::error!("failed to process IncomingCall event: {e}");
+                match self.ui.call_info.pending_call(
+                    call_id,
+                    conversation_id,
+                    participants,
+                    sender.clone(),
+                ) {
+                    Ok(_) => self.push_call_notification(call_id, conversation_id, &sender, false),
+                    Err(e) => log::error!("failed to process IncomingCall event: {e}"),
                 }
             }
             BlinkEventKind::CallCancelled { call_id } => {
-                self.ui.call_info.remove_pending_call(call_id);
+                crate::notifications::close_call_notification(call_id);
+                if let Some(call) = self.ui.call_info.remove_pending_call(call_id) {
+                    self.mutate(Action::AddNotification(
+                        notifications::NotificationKind::MissedCall,
+                        1,
+                        false,
+                    ));
+                    self.push_call_notification(call_id, call.conversation_id, &call.caller, true);
+                    self.call_history.add(call_history::CallRecord::unanswered(
+                        call,
+                        call_history::CallStatus::Missed,
+                    ));
+                }
             }
             BlinkEventKind::ParticipantJoined { call_id, peer_id } => {
                 if let Err(e) = self.ui.call_info.participant_joined(call_id, peer_id) {
@@ -710,7 +834,7 @@ impl State {
                     .map(|x| x.call.id == call_id)
                     .unwrap_or(false)
                 {
-                    self.ui.call_info.end_call();
+                    self.end_call();
                 }
             }
             BlinkEventKind::ParticipantSpeaking { peer_id } => {
@@ -719,11 +843,15 @@ impl State {
                 }
             }
             BlinkEventKind::SelfSpeaking => {
-                // todo
+                if let Err(e) = self.ui.call_info.self_speaking(self.did_key()) {
+                    log::error!("failed to process SelfSpeaking event : {e}");
+                }
             }
             BlinkEventKind::AudioDegradation { peer_id } => {
-                // todo
                 log::info!("audio degradation for peer {}", peer_id);
+                if let Err(e) = self.ui.call_info.audio_degraded(peer_id) {
+                    log::error!("failed to process AudioDegradation event : {e}");
+                }
             }
             BlinkEventKind::AudioOutputDeviceNoLongerAvailable
             | BlinkEventKind::AudioInputDeviceNoLongerAvailable => {
@@ -807,6 +935,11 @@ impl State {
         // protection in the future
         state.initialized = false;
 
+        state.drafts = drafts::Drafts::load();
+        for chat in state.chats.all.values_mut() {
+            state.drafts.restore(chat);
+        }
+
         if state.settings.font_scale() == 0.0 {
             state.settings.set_font_scale(1.0);
         }
@@ -816,12 +949,13 @@ impl State {
         if state.settings.keybinds.len() < default_keybinds.len() {
             let new_keybinds = default_keybinds
                 .iter()
-                .filter(|default_keybind| {
+                .filter(|(default_shortcut, _)| {
+                    // compare the actions so bindings the user changed aren't duplicated
                     !state
                         .settings
                         .keybinds
                         .iter()
-                        .any(|keybind| &keybind == default_keybind)
+                        .any(|(global_shortcut, _)| global_shortcut == default_shortcut)
                 })
                 .cloned()
                 .collect::<Vec<_>>();
@@ -874,6 +1008,8 @@ impl State {
                 conv.creator = chat.creator;
                 conv.pinned_messages = chat.pinned_messages;
             } else {
+                let mut chat = chat;
+                self.drafts.restore(&mut chat);
                 self.chats.all.insert(id, chat);
             }
         }
@@ -907,6 +1043,24 @@ impl State {
     pub fn chats(&self) -> &chats::Chats {
         &self.chats
     }
+    pub fn scheduled_messages(&self) -> &scheduled_messages::ScheduledMessages {
+        &self.scheduled_messages
+    }
+    pub fn call_history(&self) -> &call_history::CallHistory {
+        &self.call_history
+    }
+    pub fn outbox(&self) -> &outbox::Outbox {
+        &self.outbox
+    }
+    pub fn auto_status(&self) -> &presence::AutoStatus {
+        &self.auto_status
+    }
+    /// Returns the status to switch to if Uplink should change it on its own
+    pub fn update_auto_status(&mut self, idle: bool, in_call: bool) -> Option<IdentityStatus> {
+        let current = self.get_own_identity().identity_status();
+        let config = self.configuration.presence;
+        self.auto_status.update(current, idle, in_call, &config)
+    }
     pub fn chats_favorites(&self) -> Vec<Chat> {
         self.chats
             .favorites
@@ -942,6 +1096,8 @@ impl State {
     }
     fn add_msg_to_chat(&mut self, conversation_id: Uuid, message: ui_adapter::Message) {
         let msg_id = message.inner.id();
+        let thread_root = message.thread_root;
+        let is_own = message.inner.sender() == self.did_key();
         let is_active_scrolled = self.chats.active_chat_is_scrolled();
         if let Some(chat) = self.chats.all.get_mut(&conversation_id) {
             chat.typing_indicator.remove(&message.inner.sender());
@@ -958,13 +1114,29 @@ impl State {
             {
                 chat.add_unread(msg_id);
             }
+            if let Some(root) = thread_root {
+                let thread_open = self.ui.current_layout == ui::Layout::Compose
+                    && self.chats.active == Some(conversation_id)
+                    && chat.open_thread == Some(root);
+                if !thread_open && !is_own {
+                    chat.add_thread_unread(root, msg_id);
+                }
+            }
         }
     }
 
     pub fn active_chat_has_draft(&self) -> bool {
-        self.get_active_chat()
-            .as_ref()
-            .and_then(|d| d.draft.as_ref())
+        self.chats
+            .active
+            .map(|id| self.chat_has_draft(&id))
+            .unwrap_or(false)
+    }
+
+    pub fn chat_has_draft(&self, chat_id: &Uuid) -> bool {
+        self.chats
+            .all
+            .get(chat_id)
+            .and_then(|c| c.draft.as_ref())
             .map(|d| !d.is_empty())
             .unwrap_or(false)
     }
@@ -982,6 +1154,7 @@ impl State {
     fn cancel_reply(&mut self, chat_id: Uuid) {
         if let Some(c) = self.chats.all.get_mut(&chat_id) {
             c.replying_to = None;
+            self.drafts.update(c);
         }
     }
     pub fn can_use_active_chat(&self) -> bool {
@@ -1030,12 +1203,14 @@ impl State {
     fn clear_chat_draft(&mut self, chat_id: &Uuid) {
         if let Some(c) = self.chats.all.get_mut(chat_id) {
             c.draft = None;
+            self.drafts.update(c);
         }
     }
 
     fn clear_chat_attachments(&mut self, chat_id: &Uuid) {
         if let Some(c) = self.chats.all.get_mut(chat_id) {
             c.files_attached_to_send.clear();
+            self.drafts.update(c);
         }
     }
 
@@ -1293,6 +1468,7 @@ impl State {
     fn set_chat_attachments(&mut self, chat_id: &Uuid, value: Vec<Location>) {
         if let Some(c) = self.chats.all.get_mut(chat_id) {
             c.files_attached_to_send = value;
+            self.drafts.update(c);
         }
     }
 
@@ -1300,12 +1476,14 @@ impl State {
     fn set_chat_draft(&mut self, chat_id: &Uuid, value: String) {
         if let Some(c) = self.chats.all.get_mut(chat_id) {
             c.draft = Some(value);
+            self.drafts.update(c);
         }
     }
     /// Begins replying to a message in the specified chat in the `State` struct.
     fn start_replying(&mut self, chat: &Uuid, message: &ui_adapter::Message) {
         if let Some(c) = self.chats.all.get_mut(chat) {
             c.replying_to = Some(message.inner.clone());
+            self.drafts.update(c);
         }
     }
     /// Toggles the specified chat as a favorite in the `State` struct. If the chat
@@ -1495,6 +1673,59 @@ impl State {
         self.ui.toggle_silenced();
     }
 
+    // Dispatch notifications only when we're not already focused on the application.
+    fn push_call_notification(
+        &self,
+        call_id: Uuid,
+        conversation_id: Uuid,
+        caller: &DID,
+        missed: bool,
+    ) {
+        let notifications = &self.configuration.notifications;
+        // a call always concerns the user, so chats set to mentions only are alerted too
+        let rules_allow = notifications
+            .rules
+            .should_alert(conversation_id, true, "", Utc::now());
+        if self.ui.metadata.focused
+            || !notifications.enabled
+            || !notifications.calls_notifications
+            || !rules_allow
+        {
+            return;
+        }
+
+        let name = vec![(
+            "name",
+            self.get_identity(caller)
+                .map(|identity| identity.username())
+                .unwrap_or_else(|| get_local_text("uplink.unknown")),
+        )];
+        if missed {
+            crate::notifications::push_notification(
+                get_local_text("call-history.missed-title"),
+                get_local_text_with_args("call-history.missed", name),
+                Some(crate::sounds::Sounds::Notification),
+                notify_rust::Timeout::Milliseconds(4),
+                NotificationAction::DisplayChat(conversation_id),
+            );
+        } else {
+            // the ringtone is already playing
+            crate::notifications::push_call_notification(
+                get_local_text("call-history.incoming-title"),
+                get_local_text_with_args("call-history.incoming", name),
+                call_id,
+            );
+        }
+    }
+
+    // the call is kept in the call history once it ends
+    fn end_call(&mut self) {
+        if let Some(active_call) = self.ui.call_info.end_call() {
+            let record = call_history::CallRecord::finished(active_call, &self.did_key());
+            self.call_history.add(record);
+        }
+    }
+
     pub fn remove_toast(&mut self, id: &Uuid) {
         let _ = self.ui.toast_notifications.remove(id);
     }
@@ -1562,6 +1793,13 @@ impl State {
     pub fn get_identity(&self, did: &DID) -> Option<Identity> {
         self.identities.get(did).cloned()
     }
+    // every cached identity, including people who are no longer friends or have left a group
+    pub fn get_usernames(&self) -> HashMap<DID, String> {
+        self.identities
+            .iter()
+            .map(|(did, identity)| (did.clone(), identity.username()))
+            .collect()
+    }
     pub fn get_own_identity(&self) -> Identity {
         self.identities
             .get(&self.did_key())
@@ -0,0 +1,208 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Datelike, Local, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// How long a chat is muted for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Mute {
+    Until(DateTime<Utc>),
+    Forever,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChatRule {
+    // only mentions and keywords notify
+    #[serde(default)]
+    pub mentions_only: bool,
+    #[serde(default)]
+    pub muted: Option<Mute>,
+}
+
+impl ChatRule {
+    pub fn is_muted(&self, now: DateTime<Utc>) -> bool {
+        match self.muted {
+            Some(Mute::Forever) => true,
+            Some(Mute::Until(until)) => now < until,
+            None => false,
+        }
+    }
+
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// A recurring time range during which no notifications are shown or played. Ranges which pass midnight belong to
+/// the day they start on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuietHours {
+    pub enabled: bool,
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+    // indexed from Monday
+    pub days: [bool; 7],
+}
+
+impl Default for QuietHours {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            start: NaiveTime::from_hms_opt(22, 0, 0).unwrap_or_default(),
+            end: NaiveTime::from_hms_opt(7, 0, 0).unwrap_or_default(),
+            days: [true; 7],
+        }
+    }
+}
+
+impl QuietHours {
+    pub fn is_active(&self, now: DateTime<Local>) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        let today = now.weekday().num_days_from_monday() as usize;
+        let yesterday = (today + 6) % 7;
+        let time = now.time();
+        if self.start <= self.end {
+            self.days[today] && time >= self.start && time < self.end
+        } else {
+            (self.days[today] && time >= self.start) || (self.days[yesterday] && time < self.end)
+        }
+    }
+}
+
+/// Decides which incoming messages are shown as OS notifications and play `Sounds::Notification`. The notification
+/// counters are updated either way.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NotificationRules {
+    #[serde(default)]
+    chats: HashMap<Uuid, ChatRule>,
+    // messages containing one of these notify even in mentions only chats
+    #[serde(default)]
+    pub keywords: Vec<String>,
+    #[serde(default)]
+    pub quiet_hours: QuietHours,
+}
+
+impl NotificationRules {
+    pub fn chat(&self, conversation_id: Uuid) -> ChatRule {
+        self.chats
+            .get(&conversation_id)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    pub fn set_chat(&mut self, conversation_id: Uuid, rule: ChatRule) {
+        if rule.is_default() {
+            self.chats.remove(&conversation_id);
+        } else {
+            self.chats.insert(conversation_id, rule);
+        }
+    }
+
+    pub fn remove_chat(&mut self, conversation_id: Uuid) {
+        self.chats.remove(&conversation_id);
+    }
+
+    /// Drops mutes which have run out
+    pub fn clear_expired(&mut self, now: DateTime<Utc>) {
+        for rule in self.chats.values_mut() {
+            if !rule.is_muted(now) {
+                rule.muted = None;
+            }
+        }
+        self.chats.retain(|_, rule| !rule.is_default());
+    }
+
+    pub fn matches_keyword(&self, text: &str) -> bool {
+        let text = text.to_lowercase();
+        self.keywords
+            .iter()
+            .map(|x| x.trim())
+            .filter(|x| !x.is_empty())
+            .any(|x| text.contains(&x.to_lowercase()))
+    }
+
+    pub fn is_quiet(&self, now: DateTime<Local>) -> bool {
+        self.quiet_hours.is_active(now)
+    }
+
+    /// `ping` is true if the message mentions the user
+    pub fn should_alert(
+        &self,
+        conversation_id: Uuid,
+        ping: bool,
+        text: &str,
+        now: DateTime<Utc>,
+    ) -> bool {
+        if self.is_quiet(now.with_timezone(&Local)) {
+            return false;
+        }
+        let rule = self.chat(conversation_id);
+        if rule.is_muted(now) {
+            return false;
+        }
+        !rule.mentions_only || ping || self.matches_keyword(text)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    #[test]
+    fn test_quiet_hours() {
+        let mut quiet = QuietHours {
+            enabled: true,
+            ..Default::default()
+        };
+        // a Friday
+        let at = |h, m| Local.with_ymd_and_hms(2023, 6, 2, h, m, 0).unwrap();
+        assert!(quiet.is_active(at(23, 0)));
+        assert!(quiet.is_active(at(6, 59)));
+        assert!(!quiet.is_active(at(7, 0)));
+        assert!(!quiet.is_active(at(12, 0)));
+
+        // the early morning belongs to Thursday night
+        quiet.days[3] = false;
+        assert!(!quiet.is_active(at(6, 0)));
+        assert!(quiet.is_active(at(23, 0)));
+    }
+
+    #[test]
+    fn test_should_alert() {
+        let now = Utc::now();
+        let conv = Uuid::new_v4();
+        let mut rules = NotificationRules {
+            keywords: vec!["Release".into()],
+            ..Default::default()
+        };
+        assert!(rules.should_alert(conv, false, "hi", now));
+
+        rules.set_chat(
+            conv,
+            ChatRule {
+                mentions_only: true,
+                muted: None,
+            },
+        );
+        assert!(!rules.should_alert(conv, false, "hi", now));
+        assert!(rules.should_alert(conv, true, "hi", now));
+        assert!(rules.should_alert(conv, false, "the release is out", now));
+
+        rules.set_chat(
+            conv,
+            ChatRule {
+                mentions_only: false,
+                muted: Some(Mute::Until(now + Duration::hours(1))),
+            },
+        );
+        assert!(!rules.should_alert(conv, true, "hi", now));
+        assert!(rules.should_alert(conv, false, "hi", now + Duration::hours(2)));
+
+        rules.clear_expired(now + Duration::hours(2));
+        assert_eq!(rules.chat(conv), ChatRule::default());
+    }
+}
@@ -9,6 +9,7 @@ pub enum NotificationKind {
     FriendRequest,
     Message,
     Settings,
+    MissedCall,
 }
 
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
@@ -16,6 +17,8 @@ pub struct Notifications {
     pub friends: u32, // For notifications about new friends, friend requests and related CTAs.
     pub messages: u32, // For notifications about new messages, mentions.
     pub settings: u32, // For notifications about updates, issues and more.
+    #[serde(default)]
+    pub calls: u32, // For calls which were not answered.
     // displays above the app icon on the desktop
     #[serde(skip)]
     pub badge: u32,
@@ -31,6 +34,8 @@ impl Notifications {
             messages: 0,
             // Represents total notification count for all settings events. E.g. updates, issues, etc.
             settings: 0,
+            // Represents the calls missed since the user last looked at the recent calls.
+            calls: 0,
             badge: 0,
         }
     }
@@ -68,6 +73,14 @@ impl Notifications {
                     }
                 }
             }
+            NotificationKind::MissedCall => {
+                if config.notifications.calls_notifications {
+                    self.calls = self.calls.saturating_add(count);
+                    if increment_badge {
+                        self.badge = self.badge.saturating_add(count);
+                    }
+                }
+            }
         };
 
         if increment_badge {
@@ -91,6 +104,10 @@ impl Notifications {
                 self.settings = self.settings.saturating_sub(count);
                 self.badge = self.badge.saturating_sub(count);
             }
+            NotificationKind::MissedCall => {
+                self.calls = self.calls.saturating_sub(count);
+                self.badge = self.badge.saturating_sub(count);
+            }
         };
 
         // Update the badge any time notifications are removed.
@@ -103,6 +120,7 @@ impl Notifications {
             NotificationKind::FriendRequest => self.friends,
             NotificationKind::Message => self.messages,
             NotificationKind::Settings => self.settings,
+            NotificationKind::MissedCall => self.calls,
         }
     }
 
@@ -121,6 +139,10 @@ impl Notifications {
                 self.badge = self.badge.saturating_sub(self.settings);
                 self.settings = 0;
             }
+            NotificationKind::MissedCall => {
+                self.badge = self.badge.saturating_sub(self.calls);
+                self.calls = 0;
+            }
         };
         // Update the badge with new possible totals.
         let _ = set_badge(self.badge);
@@ -131,6 +153,7 @@ impl Notifications {
         self.friends = 0;
         self.messages = 0;
         self.settings = 0;
+        self.calls = 0;
 
         self.badge = 0;
         let _ = set_badge(self.badge);
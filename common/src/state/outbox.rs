@@ -0,0 +1,180 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::pending_message::FileLocation;
+
+// the first retry waits this long, and every one after it twice as long as the one before
+const BASE_DELAY_SECS: i64 = 5;
+const MAX_DELAY_SECS: i64 = 5 * 60;
+// the message is marked as failed after this many attempts, and only sent again when the user retries it
+const MAX_ATTEMPTS: u32 = 8;
+
+/// A message which couldn't be sent, for example because the other side was unreachable or its upload failed. Saved
+/// encrypted with State, so it is still sent after a restart.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutboxMessage {
+    /// also used as the id of the message shown while it isn't sent
+    pub id: Uuid,
+    pub conversation_id: Uuid,
+    pub lines: Vec<String>,
+    #[serde(default)]
+    pub replying_to: Option<Uuid>,
+    #[serde(default)]
+    pub attachments: Vec<FileLocation>,
+    pub created: DateTime<Utc>,
+    pub attempts: u32,
+    pub next_attempt: DateTime<Utc>,
+    /// the error of the last attempt
+    #[serde(default)]
+    pub error: Option<String>,
+    /// set once the message ran out of attempts
+    #[serde(default)]
+    pub failed: bool,
+}
+
+impl OutboxMessage {
+    /// A message whose first attempt failed
+    pub fn new(
+        conversation_id: Uuid,
+        lines: Vec<String>,
+        replying_to: Option<Uuid>,
+        attachments: Vec<FileLocation>,
+        error: String,
+    ) -> Self {
+        let now = Utc::now();
+        let mut msg = Self {
+            id: Uuid::new_v4(),
+            conversation_id,
+            lines,
+            replying_to,
+            attachments,
+            created: now,
+            attempts: 0,
+            next_attempt: now,
+            error: None,
+            failed: false,
+        };
+        msg.attempt_failed(error, now);
+        msg
+    }
+
+    /// Schedules the next attempt, or marks the message as failed once it ran out of attempts
+    pub fn attempt_failed(&mut self, error: String, now: DateTime<Utc>) {
+        self.attempts += 1;
+        self.error = Some(error);
+        self.failed = self.attempts >= MAX_ATTEMPTS;
+        let delay = BASE_DELAY_SECS
+            .saturating_mul(1 << (self.attempts - 1).min(16))
+            .min(MAX_DELAY_SECS);
+        self.next_attempt = now + Duration::seconds(delay);
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Outbox {
+    messages: Vec<OutboxMessage>,
+}
+
+impl Outbox {
+    pub fn add(&mut self, msg: OutboxMessage) {
+        self.messages.push(msg);
+    }
+
+    pub fn remove(&mut self, id: Uuid) -> Option<OutboxMessage> {
+        let idx = self.messages.iter().position(|m| m.id == id)?;
+        Some(self.messages.remove(idx))
+    }
+
+    pub fn remove_conversation(&mut self, conversation_id: Uuid) {
+        self.messages
+            .retain(|m| m.conversation_id != conversation_id);
+    }
+
+    pub fn get(&self, id: Uuid) -> Option<&OutboxMessage> {
+        self.messages.iter().find(|m| m.id == id)
+    }
+
+    pub fn for_chat(&self, conversation_id: Uuid) -> Vec<OutboxMessage> {
+        self.messages
+            .iter()
+            .filter(|m| m.conversation_id == conversation_id)
+            .cloned()
+            .collect()
+    }
+
+    /// the messages which should be sent again by `now`, oldest first. failed messages wait for the user.
+    pub fn due(&self, now: DateTime<Utc>) -> Vec<OutboxMessage> {
+        self.messages
+            .iter()
+            .filter(|m| !m.failed && m.next_attempt <= now)
+            .cloned()
+            .collect()
+    }
+
+    /// Schedules the next attempt, or marks the message as failed once it ran out of attempts
+    pub fn attempt_failed(&mut self, id: Uuid, error: String, now: DateTime<Utc>) {
+        if let Some(msg) = self.messages.iter_mut().find(|m| m.id == id) {
+            msg.attempt_failed(error, now);
+        }
+    }
+
+    /// Sends the message again right away, with a fresh set of attempts
+    pub fn retry(&mut self, id: Uuid, now: DateTime<Utc>) {
+        if let Some(msg) = self.messages.iter_mut().find(|m| m.id == id) {
+            msg.attempts = 0;
+            msg.failed = false;
+            msg.next_attempt = now;
+        }
+    }
+
+    /// Called when one of the conversations can be reached again, so its messages don't wait for their backoff
+    pub fn wake(&mut self, conversation_ids: &[Uuid], now: DateTime<Utc>) {
+        for msg in self
+            .messages
+            .iter_mut()
+            .filter(|m| !m.failed && conversation_ids.contains(&m.conversation_id))
+        {
+            msg.next_attempt = now;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_backoff() {
+        let conv = Uuid::new_v4();
+        let mut outbox = Outbox::default();
+        let msg = OutboxMessage::new(conv, vec!["hi".into()], None, vec![], "offline".into());
+        let now = Utc::now();
+        let id = msg.id;
+        outbox.add(msg);
+
+        assert!(outbox.due(now).is_empty());
+        assert_eq!(
+            outbox.due(now + Duration::seconds(BASE_DELAY_SECS)).len(),
+            1
+        );
+
+        outbox.attempt_failed(id, "offline".into(), now);
+        assert!(outbox
+            .due(now + Duration::seconds(BASE_DELAY_SECS))
+            .is_empty());
+        outbox.wake(&[conv], now);
+        assert_eq!(outbox.due(now).len(), 1);
+
+        for _ in 0..MAX_ATTEMPTS {
+            outbox.attempt_failed(id, "offline".into(), now);
+        }
+        assert!(outbox.get(id).map(|m| m.failed).unwrap_or_default());
+        assert!(outbox
+            .due(now + Duration::seconds(MAX_DELAY_SECS))
+            .is_empty());
+
+        outbox.retry(id, now);
+        assert_eq!(outbox.due(now).len(), 1);
+    }
+}
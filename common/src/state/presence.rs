@@ -0,0 +1,90 @@
+//! Uplink changes the online status on its own: to Away once the user was inactive for a while, and to Busy during
+//! calls. Only an Online status is changed, and the status picked by the user is restored afterwards.
+//! Status messages can be set to clear themselves at a given time.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use warp::multipass::identity::IdentityStatus;
+
+use super::configuration::Presence;
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct AutoStatus {
+    /// the status set automatically and the status to restore. None while the status picked by the user is shown
+    active: Option<(IdentityStatus, IdentityStatus)>,
+    /// when the status message is cleared
+    pub status_message_expires: Option<DateTime<Utc>>,
+}
+
+impl AutoStatus {
+    /// Returns the status to switch to, if it should change
+    pub fn update(
+        &mut self,
+        current: IdentityStatus,
+        idle: bool,
+        in_call: bool,
+        config: &Presence,
+    ) -> Option<IdentityStatus> {
+        // the user picked another status in the meantime
+        if matches!(self.active, Some((applied, _)) if applied != current) {
+            self.active = None;
+        }
+        let chosen = self.active.map(|(_, chosen)| chosen).unwrap_or(current);
+        let automatic = if chosen != IdentityStatus::Online {
+            None
+        } else if in_call && config.busy_in_calls {
+            Some(IdentityStatus::Busy)
+        } else if idle && config.auto_away {
+            Some(IdentityStatus::Away)
+        } else {
+            None
+        };
+        self.active = automatic.map(|status| (status, chosen));
+        let target = automatic.unwrap_or(chosen);
+        (target != current).then_some(target)
+    }
+
+    /// the status picked by the user, even if an automatic one is shown
+    pub fn chosen(&self, current: IdentityStatus) -> IdentityStatus {
+        match self.active {
+            Some((applied, chosen)) if applied == current => chosen,
+            _ => current,
+        }
+    }
+
+    pub fn status_message_expired(&self, now: DateTime<Utc>) -> bool {
+        self.status_message_expires
+            .map(|expires| expires <= now)
+            .unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_auto_status() {
+        let config = Presence::default();
+        let mut auto = AutoStatus::default();
+        assert_eq!(
+            auto.update(IdentityStatus::Online, true, false, &config),
+            Some(IdentityStatus::Away)
+        );
+        // a call takes precedence over being idle
+        assert_eq!(
+            auto.update(IdentityStatus::Away, true, true, &config),
+            Some(IdentityStatus::Busy)
+        );
+        assert_eq!(auto.chosen(IdentityStatus::Busy), IdentityStatus::Online);
+        assert_eq!(
+            auto.update(IdentityStatus::Busy, false, false, &config),
+            Some(IdentityStatus::Online)
+        );
+        // a status picked by the user is left alone
+        assert_eq!(
+            auto.update(IdentityStatus::Offline, true, true, &config),
+            None
+        );
+    }
+}
@@ -0,0 +1,125 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::pending_message::FileLocation;
+
+/// A message which will be sent once `send_at` has passed. Saved encrypted with State, so messages which came due
+/// while Uplink was closed are sent the next time it starts. If sending fails, the message is moved to the outbox.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScheduledMessage {
+    pub id: Uuid,
+    pub conversation_id: Uuid,
+    pub lines: Vec<String>,
+    #[serde(default)]
+    pub attachments: Vec<FileLocation>,
+    pub send_at: DateTime<Utc>,
+    pub created: DateTime<Utc>,
+}
+
+impl ScheduledMessage {
+    pub fn new(
+        conversation_id: Uuid,
+        lines: Vec<String>,
+        attachments: Vec<FileLocation>,
+        send_at: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            conversation_id,
+            lines,
+            attachments,
+            send_at,
+            created: Utc::now(),
+        }
+    }
+}
+
+// sorted by send_at
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ScheduledMessages {
+    messages: Vec<ScheduledMessage>,
+}
+
+impl ScheduledMessages {
+    pub fn add(&mut self, msg: ScheduledMessage) {
+        let idx = self.messages.partition_point(|m| m.send_at <= msg.send_at);
+        self.messages.insert(idx, msg);
+    }
+
+    /// returns false if the message was already sent or cancelled
+    pub fn edit(&mut self, id: Uuid, lines: Vec<String>, send_at: DateTime<Utc>) -> bool {
+        let Some(mut msg) = self.remove(id) else {
+            return false;
+        };
+        msg.lines = lines;
+        msg.send_at = send_at;
+        self.add(msg);
+        true
+    }
+
+    pub fn remove(&mut self, id: Uuid) -> Option<ScheduledMessage> {
+        let idx = self.messages.iter().position(|m| m.id == id)?;
+        Some(self.messages.remove(idx))
+    }
+
+    pub fn remove_conversation(&mut self, conversation_id: Uuid) {
+        self.messages
+            .retain(|m| m.conversation_id != conversation_id);
+    }
+
+    pub fn get(&self, id: Uuid) -> Option<&ScheduledMessage> {
+        self.messages.iter().find(|m| m.id == id)
+    }
+
+    pub fn for_chat(&self, conversation_id: Uuid) -> Vec<ScheduledMessage> {
+        self.messages
+            .iter()
+            .filter(|m| m.conversation_id == conversation_id)
+            .cloned()
+            .collect()
+    }
+
+    /// the messages which should have been sent by `now`, oldest first
+    pub fn due(&self, now: DateTime<Utc>) -> Vec<ScheduledMessage> {
+        self.messages
+            .iter()
+            .take_while(|m| m.send_at <= now)
+            .cloned()
+            .collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_due() {
+        let now = Utc::now();
+        let conv = Uuid::new_v4();
+        let mut scheduled = ScheduledMessages::default();
+        let later =
+            ScheduledMessage::new(conv, vec!["later".into()], vec![], now + Duration::hours(1));
+        let earlier = ScheduledMessage::new(
+            conv,
+            vec!["earlier".into()],
+            vec![],
+            now - Duration::hours(1),
+        );
+        let later_id = later.id;
+        scheduled.add(later);
+        scheduled.add(earlier.clone());
+
+        assert_eq!(scheduled.due(now), vec![earlier]);
+        assert!(scheduled.edit(later_id, vec!["now".into()], now));
+        assert_eq!(scheduled.due(now).len(), 2);
+        assert!(scheduled.remove(later_id).is_some());
+        assert!(!scheduled.edit(later_id, vec![], now));
+    }
+}
@@ -19,25 +19,175 @@ pub enum GlobalShortcut {
     OpenCloseDevTools,
     ToggleDevmode,
     SetAppVisible,
+    NextChat,
+    PreviousChat,
+    JumpToUnread,
+    OpenChats,
+    OpenFriends,
+    OpenFiles,
+    OpenSettings,
+    EditLastMessage,
+    ReplyToLastMessage,
+    MarkAllRead,
+    ToggleFavorite,
+    AnswerCall,
+    HangUp,
+    PushToTalk,
     #[default]
     Unknown,
 }
 
 impl fmt::Display for GlobalShortcut {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// Groups the shortcuts on the keybinds page
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+pub enum ShortcutCategory {
+    General,
+    Navigation,
+    Chat,
+    Call,
+}
+
+impl ShortcutCategory {
+    pub const ALL: [ShortcutCategory; 4] = [
+        ShortcutCategory::General,
+        ShortcutCategory::Navigation,
+        ShortcutCategory::Chat,
+        ShortcutCategory::Call,
+    ];
+
+    // locale key under `settings-keybinds`
+    pub fn label_key(&self) -> &'static str {
         match self {
-            GlobalShortcut::ToggleMute => write!(f, "ToggleMute"),
-            GlobalShortcut::ToggleDeafen => write!(f, "ToggleDeafen"),
-            GlobalShortcut::IncreaseFontSize => write!(f, "IncreaseFontSize"),
-            GlobalShortcut::DecreaseFontSize => write!(f, "DecreaseFontSize"),
-            GlobalShortcut::OpenCloseDevTools => write!(f, "OpenCloseDevTools"),
-            GlobalShortcut::ToggleDevmode => write!(f, "ToggleDevmode"),
-            GlobalShortcut::SetAppVisible => write!(f, "SetAppVisible"),
-            GlobalShortcut::Unknown => write!(f, "Unknown"),
+            ShortcutCategory::General => "category-general",
+            ShortcutCategory::Navigation => "category-navigation",
+            ShortcutCategory::Chat => "category-chat",
+            ShortcutCategory::Call => "category-call",
         }
     }
 }
 
+impl GlobalShortcut {
+    /// Every action which can be bound to a shortcut, in the order they are listed on the keybinds page
+    pub const ALL: [GlobalShortcut; 21] = [
+        GlobalShortcut::IncreaseFontSize,
+        GlobalShortcut::DecreaseFontSize,
+        GlobalShortcut::OpenCloseDevTools,
+        GlobalShortcut::ToggleDevmode,
+        GlobalShortcut::SetAppVisible,
+        GlobalShortcut::NextChat,
+        GlobalShortcut::PreviousChat,
+        GlobalShortcut::JumpToUnread,
+        GlobalShortcut::OpenChats,
+        GlobalShortcut::OpenFriends,
+        GlobalShortcut::OpenFiles,
+        GlobalShortcut::OpenSettings,
+        GlobalShortcut::EditLastMessage,
+        GlobalShortcut::ReplyToLastMessage,
+        GlobalShortcut::MarkAllRead,
+        GlobalShortcut::ToggleFavorite,
+        GlobalShortcut::ToggleMute,
+        GlobalShortcut::ToggleDeafen,
+        GlobalShortcut::AnswerCall,
+        GlobalShortcut::HangUp,
+        GlobalShortcut::PushToTalk,
+    ];
+
+    pub fn category(&self) -> ShortcutCategory {
+        match self {
+            GlobalShortcut::NextChat
+            | GlobalShortcut::PreviousChat
+            | GlobalShortcut::JumpToUnread
+            | GlobalShortcut::OpenChats
+            | GlobalShortcut::OpenFriends
+            | GlobalShortcut::OpenFiles
+            | GlobalShortcut::OpenSettings => ShortcutCategory::Navigation,
+            GlobalShortcut::EditLastMessage
+            | GlobalShortcut::ReplyToLastMessage
+            | GlobalShortcut::MarkAllRead
+            | GlobalShortcut::ToggleFavorite => ShortcutCategory::Chat,
+            GlobalShortcut::ToggleMute
+            | GlobalShortcut::ToggleDeafen
+            | GlobalShortcut::AnswerCall
+            | GlobalShortcut::HangUp
+            | GlobalShortcut::PushToTalk => ShortcutCategory::Call,
+            _ => ShortcutCategory::General,
+        }
+    }
+
+    /// Navigation and chat actions only make sense while uplink is focused, so they are never registered
+    /// system-wide. Push-to-talk needs the key release, which is only reported by the webview.
+    pub fn is_app_action(&self) -> bool {
+        matches!(
+            self.category(),
+            ShortcutCategory::Navigation | ShortcutCategory::Chat
+        ) || *self == GlobalShortcut::PushToTalk
+    }
+
+    // locale key under `settings-keybinds`
+    pub fn label_key(&self) -> &'static str {
+        match self {
+            GlobalShortcut::ToggleMute => "toggle-mute",
+            GlobalShortcut::ToggleDeafen => "toggle-deafen",
+            GlobalShortcut::IncreaseFontSize => "increase-font-size",
+            GlobalShortcut::DecreaseFontSize => "decrease-font-size",
+            GlobalShortcut::OpenCloseDevTools => "open-close-dev-tools",
+            GlobalShortcut::ToggleDevmode => "toggle-devmode",
+            GlobalShortcut::SetAppVisible => "hide-focus-uplink",
+            GlobalShortcut::NextChat => "next-chat",
+            GlobalShortcut::PreviousChat => "previous-chat",
+            GlobalShortcut::JumpToUnread => "jump-to-unread",
+            GlobalShortcut::OpenChats => "open-chats",
+            GlobalShortcut::OpenFriends => "open-friends",
+            GlobalShortcut::OpenFiles => "open-files",
+            GlobalShortcut::OpenSettings => "open-settings",
+            GlobalShortcut::EditLastMessage => "edit-last-message",
+            GlobalShortcut::ReplyToLastMessage => "reply-to-last-message",
+            GlobalShortcut::MarkAllRead => "mark-all-read",
+            GlobalShortcut::ToggleFavorite => "toggle-favorite",
+            GlobalShortcut::AnswerCall => "answer-call",
+            GlobalShortcut::HangUp => "hang-up",
+            GlobalShortcut::PushToTalk => "push-to-talk",
+            GlobalShortcut::Unknown => "unknown",
+        }
+    }
+}
+
+/// The file format used to import and export keybinds
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Keymap {
+    pub version: u32,
+    pub keybinds: Vec<(GlobalShortcut, Shortcut)>,
+}
+
+impl Keymap {
+    pub const VERSION: u32 = 1;
+
+    pub fn new(keybinds: Vec<(GlobalShortcut, Shortcut)>) -> Self {
+        Self {
+            version: Self::VERSION,
+            keybinds,
+        }
+    }
+
+    /// Drops unknown actions and keeps the first binding of each action
+    pub fn into_keybinds(self) -> Vec<(GlobalShortcut, Shortcut)> {
+        let mut keybinds: Vec<(GlobalShortcut, Shortcut)> = vec![];
+        for (global_shortcut, shortcut) in self.keybinds {
+            if global_shortcut != GlobalShortcut::Unknown
+                && !keybinds.iter().any(|(gs, _)| *gs == global_shortcut)
+            {
+                keybinds.push((global_shortcut, shortcut));
+            }
+        }
+        keybinds
+    }
+}
+
 #[derive(Eq, PartialEq, Debug, Clone, Deserialize, Serialize, Default)]
 pub struct Shortcut {
     pub keys: Vec<KeyCode>,             // Keys required
@@ -79,6 +229,15 @@ impl Shortcut {
         keybinds
     }
 
+    /// True if pressing `keys` while holding `modifiers` triggers this shortcut. The order of the modifiers doesn't matter.
+    pub fn matches(&self, keys: &[KeyCode], modifiers: &[ModifiersState]) -> bool {
+        let combine = |m: &[ModifiersState]| {
+            m.iter()
+                .fold(ModifiersState::empty(), |acc, modifier| acc | *modifier)
+        };
+        !self.keys.is_empty() && self.keys == keys && combine(&self.modifiers) == combine(modifiers)
+    }
+
     pub fn get_keys_and_modifiers_as_string(&self) -> Vec<String> {
         let key_code_strs: Vec<String> = self
             .keys
@@ -277,6 +436,9 @@ pub struct Settings {
     pub pause_global_keybinds: bool,
     pub is_recording_new_keybind: bool,
     pub keybinds: Vec<(GlobalShortcut, Shortcut)>,
+    // On Linux shortcuts only work while uplink is focused unless this is enabled. Only supported on X11.
+    #[serde(default)]
+    pub linux_system_shortcuts: bool,
 }
 
 impl Default for Settings {
@@ -292,6 +454,7 @@ impl Default for Settings {
             pause_global_keybinds: false,
             keybinds: super::default_keybinds::get_default_keybinds(),
             is_recording_new_keybind: false,
+            linux_system_shortcuts: false,
         }
     }
 }
@@ -1,5 +1,6 @@
 use crate::icons::outline::Shape as Icon;
 
+use chrono::{DateTime, Utc};
 use dioxus_desktop::DesktopService;
 use dioxus_desktop::{tao::window::WindowId, DesktopContext};
 use extensions::UplinkExtension;
@@ -172,6 +173,14 @@ pub enum EmojiDestination {
     Message(Uuid, Uuid),
 }
 
+/// A message the chat view should scroll to once its conversation is loaded. Set by the message search results.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct JumpToMessage {
+    pub conversation_id: Uuid,
+    pub message_id: Uuid,
+    pub date: DateTime<Utc>,
+}
+
 impl Default for EmojiDestination {
     fn default() -> Self {
         Self::Chatbar
@@ -228,6 +237,8 @@ pub struct UI {
     pub cached_username: Option<String>,
     #[serde(skip)]
     pub ignore_focus: bool,
+    #[serde(skip)]
+    pub jump_to_message: Option<JumpToMessage>,
 }
 
 impl Default for UI {
@@ -259,6 +270,7 @@ impl Default for UI {
             show_dev_settings: false,
             cached_username: Default::default(),
             ignore_focus: Default::default(),
+            jump_to_message: None,
             transform_markdown_text: true,
             transform_ascii_emojis: true,
         }
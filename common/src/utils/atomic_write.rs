@@ -0,0 +1,19 @@
+use std::{
+    fs::File,
+    io::{self, Write},
+    path::Path,
+};
+
+/// Replaces the file at `path` without leaving a partly written file behind if Uplink stops during the write: the
+/// contents are written to a temporary file next to it which is then renamed.
+pub fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = Path::new(&tmp);
+
+    let mut file = File::create(tmp)?;
+    file.write_all(contents.as_ref())?;
+    file.sync_all()?;
+    drop(file);
+    std::fs::rename(tmp, path)
+}
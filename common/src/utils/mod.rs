@@ -1,3 +1,4 @@
+pub mod atomic_write;
 pub mod clear_temp_files_dir;
 pub mod img_dimensions_preview;
 pub mod lifecycle;
@@ -0,0 +1,468 @@
+//! Writes the full history of a conversation to a zip archive. Used by `OtherCmd::ExportConversation`.
+//!
+//! The export runs in its own task and talks to warp_runner through WARP_CMD_CH, the same as the UI does. This keeps
+//! a long export from blocking other commands.
+//!
+//! The archive contains the conversation in the requested format plus an `attachments` folder with one sub folder
+//! per message.
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt::Write,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Duration, Utc};
+use derive_more::Display;
+use futures::{channel::oneshot, StreamExt};
+use serde::{Deserialize, Serialize};
+use tracing::log;
+use uuid::Uuid;
+use warp::{crypto::DID, error::Error};
+
+use crate::{
+    state::pending_message::FileProgression,
+    warp_runner::{
+        ui_adapter, FetchMessagesConfig, FetchMessagesResponse, OtherCmd, RayGunCmd, WarpCmd,
+    },
+    STATIC_ARGS, WARP_CMD_CH,
+};
+
+const ATTACHMENTS_DIR: &str = "attachments";
+
+// FetchMessagesBetween has no limit, so the history is requested one week at a time
+fn page_size() -> Duration {
+    Duration::days(7)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[display(fmt = "json")]
+    Json,
+    #[display(fmt = "html")]
+    Html,
+    #[display(fmt = "markdown")]
+    Markdown,
+}
+
+impl ExportFormat {
+    fn file_name(&self) -> &'static str {
+        match self {
+            ExportFormat::Json => "conversation.json",
+            ExportFormat::Html => "conversation.html",
+            ExportFormat::Markdown => "conversation.md",
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct ExportedConversation {
+    id: Uuid,
+    name: String,
+    exported_at: DateTime<Utc>,
+    participants: BTreeMap<String, String>,
+    messages: Vec<ExportedMessage>,
+}
+
+#[derive(Serialize)]
+struct ExportedMessage {
+    id: Uuid,
+    sender: DID,
+    sender_name: String,
+    date: DateTime<Utc>,
+    edited: Option<DateTime<Utc>>,
+    in_reply_to: Option<Uuid>,
+    pinned: bool,
+    lines: Vec<String>,
+    // emoji -> usernames
+    reactions: BTreeMap<String, Vec<String>>,
+    attachments: Vec<ExportedAttachment>,
+}
+
+#[derive(Serialize)]
+struct ExportedAttachment {
+    name: String,
+    size: usize,
+    // relative to the root of the archive. None if the download failed
+    path: Option<String>,
+}
+
+/// `identities` maps the DIDs known to the UI to usernames. Anyone missing from it is shown by their short id.
+pub async fn export_conversation(
+    conv_id: Uuid,
+    conversation_name: String,
+    identities: HashMap<DID, String>,
+    format: ExportFormat,
+    dest: PathBuf,
+) -> Result<(), Error> {
+    let dir = STATIC_ARGS.temp_files.join(format!("export-{conv_id}"));
+    if dir.exists() {
+        std::fs::remove_dir_all(&dir).map_err(other)?;
+    }
+    std::fs::create_dir_all(dir.join(ATTACHMENTS_DIR)).map_err(other)?;
+
+    let r = write_export(&dir, conv_id, conversation_name, identities, format, dest).await;
+    if let Err(e) = std::fs::remove_dir_all(&dir) {
+        log::warn!("failed to remove {}: {e}", dir.display());
+    }
+    r
+}
+
+async fn write_export(
+    dir: &Path,
+    conv_id: Uuid,
+    conversation_name: String,
+    identities: HashMap<DID, String>,
+    format: ExportFormat,
+    dest: PathBuf,
+) -> Result<(), Error> {
+    let name_of = |did: &DID| {
+        identities
+            .get(did)
+            .cloned()
+            .unwrap_or_else(|| short_id(did))
+    };
+
+    let messages = fetch_all_messages(conv_id).await?;
+    log::debug!("exporting {} messages", messages.len());
+
+    let mut participants = BTreeMap::new();
+    let mut exported = Vec::with_capacity(messages.len());
+    for msg in messages {
+        let msg = msg.inner;
+        let sender_name = name_of(&msg.sender());
+        participants.insert(msg.sender().to_string(), sender_name.clone());
+
+        let mut attachments = vec![];
+        for file in msg.attachments() {
+            let path = download_attachment(dir, conv_id, msg.id(), &file.name()).await;
+            attachments.push(ExportedAttachment {
+                name: file.name(),
+                size: file.size(),
+                path,
+            });
+        }
+
+        exported.push(ExportedMessage {
+            id: msg.id(),
+            sender: msg.sender(),
+            sender_name,
+            date: msg.date(),
+            edited: msg.modified(),
+            in_reply_to: msg.replied(),
+            pinned: msg.pinned(),
+            lines: msg.lines(),
+            reactions: msg
+                .reactions()
+                .iter()
+                .map(|(emoji, users)| (emoji.clone(), users.iter().map(name_of).collect()))
+                .collect(),
+            attachments,
+        });
+    }
+
+    let conversation = ExportedConversation {
+        id: conv_id,
+        name: conversation_name,
+        exported_at: Utc::now(),
+        participants,
+        messages: exported,
+    };
+
+    let contents = match format {
+        ExportFormat::Json => serde_json::to_string_pretty(&conversation).map_err(other)?,
+        ExportFormat::Html => to_html(&conversation),
+        ExportFormat::Markdown => to_markdown(&conversation),
+    };
+    std::fs::write(dir.join(format.file_name()), contents).map_err(other)?;
+
+    let src = dir.to_path_buf();
+    warp_cmd(|rsp| WarpCmd::Other(OtherCmd::CompressFolder { src, dest, rsp })).await
+}
+
+// the oldest message tells where to start paging. from there, walk forward until the time the export started.
+async fn fetch_all_messages(conv_id: Uuid) -> Result<Vec<ui_adapter::Message>, Error> {
+    let end = Utc::now() + Duration::seconds(1);
+    let FetchMessagesResponse { messages, .. } = warp_cmd(|rsp| {
+        WarpCmd::RayGun(RayGunCmd::FetchMessages {
+            conv_id,
+            config: FetchMessagesConfig::Later {
+                start_date: DateTime::<Utc>::default(),
+                limit: 1,
+            },
+            rsp,
+        })
+    })
+    .await?;
+    let Some(mut start) = messages.first().map(|m| m.inner.date()) else {
+        return Ok(vec![]);
+    };
+
+    let mut ids = HashSet::new();
+    let mut all = vec![];
+    while start < end {
+        let page_end = std::cmp::min(start + page_size(), end);
+        let (messages, _) = warp_cmd(|rsp| {
+            WarpCmd::RayGun(RayGunCmd::FetchMessagesBetween {
+                conv_id,
+                date_range: start..page_end,
+                rsp,
+            })
+        })
+        .await?;
+        all.extend(messages.into_iter().filter(|m| ids.insert(m.inner.id())));
+        start = page_end;
+    }
+    all.sort_by_key(|m| m.inner.date());
+    Ok(all)
+}
+
+// a failed download shouldn't fail the whole export. the attachment is listed without a path instead.
+async fn download_attachment(
+    dir: &Path,
+    conv_id: Uuid,
+    msg_id: Uuid,
+    file_name: &str,
+) -> Option<String> {
+    // don't trust the remote file name to be a plain file name
+    let local_name = Path::new(file_name)
+        .file_name()?
+        .to_string_lossy()
+        .to_string();
+    let relative = format!("{ATTACHMENTS_DIR}/{msg_id}/{local_name}");
+    let path = dir.join(ATTACHMENTS_DIR).join(msg_id.to_string());
+    if let Err(e) = std::fs::create_dir_all(&path) {
+        log::error!("failed to create {}: {e}", path.display());
+        return None;
+    }
+    let path = path.join(&local_name);
+
+    let r = warp_cmd(|rsp| {
+        WarpCmd::RayGun(RayGunCmd::DownloadAttachment {
+            conv_id,
+            msg_id,
+            file_name: file_name.to_string(),
+            file_path_to_download: path,
+            rsp,
+        })
+    })
+    .await;
+    let stream = match r {
+        Ok(stream) => stream,
+        Err(e) => {
+            log::warn!("failed to download {file_name}: {e}");
+            return None;
+        }
+    };
+
+    // the download only makes progress while the stream is polled
+    let mut stream = stream.map(FileProgression::from);
+    while let Some(progress) = stream.next().await {
+        match progress {
+            FileProgression::ProgressComplete { .. } => return Some(relative),
+            FileProgression::ProgressFailed { name, error, .. } => {
+                log::warn!("failed to download {name}: {error}");
+                return None;
+            }
+            FileProgression::CurrentProgress { .. } => {}
+        }
+    }
+    Some(relative)
+}
+
+fn to_markdown(conv: &ExportedConversation) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "# {}\n", conv.name);
+    let _ = writeln!(
+        out,
+        "Exported {} · {} messages\n",
+        format_date(&conv.exported_at),
+        conv.messages.len()
+    );
+    for msg in &conv.messages {
+        let _ = write!(out, "**{}** · {}", msg.sender_name, format_date(&msg.date));
+        if msg.edited.is_some() {
+            out.push_str(" (edited)");
+        }
+        if msg.pinned {
+            out.push_str(" 📌");
+        }
+        out.push_str("\n\n");
+        for line in &msg.lines {
+            let _ = writeln!(out, "{line}  ");
+        }
+        for attachment in &msg.attachments {
+            match &attachment.path {
+                Some(path) => {
+                    let _ = writeln!(out, "📎 [{}](<{path}>)  ", attachment.name);
+                }
+                None => {
+                    let _ = writeln!(out, "📎 {} (not downloaded)  ", attachment.name);
+                }
+            }
+        }
+        if !msg.reactions.is_empty() {
+            let reactions: Vec<String> = msg
+                .reactions
+                .iter()
+                .map(|(emoji, users)| format!("{emoji} {}", users.len()))
+                .collect();
+            let _ = writeln!(out, "{}", reactions.join(" "));
+        }
+        out.push('\n');
+    }
+    out
+}
+
+fn to_html(conv: &ExportedConversation) -> String {
+    let mut out = String::new();
+    let title = escape_html(&conv.name);
+    let _ = write!(
+        out,
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n<style>{HTML_STYLE}</style>\n</head>\n<body>\n"
+    );
+    let _ = writeln!(out, "<h1>{title}</h1>");
+    let _ = writeln!(
+        out,
+        "<p class=\"meta\">Exported {} · {} messages</p>",
+        format_date(&conv.exported_at),
+        conv.messages.len()
+    );
+    for msg in &conv.messages {
+        let _ = writeln!(out, "<div class=\"message\" id=\"{}\">", msg.id);
+        let _ = write!(
+            out,
+            "<div class=\"header\"><span class=\"sender\" title=\"{}\">{}</span> <span class=\"date\">{}</span>",
+            escape_html(&msg.sender.to_string()),
+            escape_html(&msg.sender_name),
+            format_date(&msg.date)
+        );
+        if msg.edited.is_some() {
+            out.push_str(" <span class=\"date\">(edited)</span>");
+        }
+        if msg.pinned {
+            out.push_str(" 📌");
+        }
+        out.push_str("</div>\n");
+        if let Some(reply) = msg.in_reply_to {
+            let _ = writeln!(
+                out,
+                "<a class=\"reply\" href=\"#{reply}\">in reply to an earlier message</a>"
+            );
+        }
+        if !msg.lines.is_empty() {
+            let _ = writeln!(
+                out,
+                "<div class=\"text\">{}</div>",
+                escape_html(&msg.lines.join("\n"))
+            );
+        }
+        for attachment in &msg.attachments {
+            let name = escape_html(&attachment.name);
+            match &attachment.path {
+                Some(path) if is_image(path) => {
+                    let path = escape_html(path);
+                    let _ = writeln!(
+                        out,
+                        "<a href=\"{path}\"><img src=\"{path}\" alt=\"{name}\"></a>"
+                    );
+                }
+                Some(path) => {
+                    let _ = writeln!(
+                        out,
+                        "<div class=\"attachment\">📎 <a href=\"{}\">{name}</a></div>",
+                        escape_html(path)
+                    );
+                }
+                None => {
+                    let _ = writeln!(
+                        out,
+                        "<div class=\"attachment\">📎 {name} (not downloaded)</div>"
+                    );
+                }
+            }
+        }
+        if !msg.reactions.is_empty() {
+            out.push_str("<div class=\"reactions\">");
+            for (emoji, users) in &msg.reactions {
+                let _ = write!(
+                    out,
+                    "<span title=\"{}\">{} {}</span>",
+                    escape_html(&users.join(", ")),
+                    escape_html(emoji),
+                    users.len()
+                );
+            }
+            out.push_str("</div>\n");
+        }
+        out.push_str("</div>\n");
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+const HTML_STYLE: &str = "body{font-family:sans-serif;max-width:48em;margin:2em auto;color:#222}\
+.meta,.date{color:#777;font-size:.85em}\
+.message{padding:.5em 0;border-bottom:1px solid #eee}\
+.sender{font-weight:bold}\
+.text{white-space:pre-wrap;margin-top:.25em}\
+.reply{font-size:.85em;color:#555}\
+img{max-width:100%;max-height:20em;margin-top:.25em}\
+.reactions span{margin-right:.5em}";
+
+fn escape_html(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+fn is_image(path: &str) -> bool {
+    let ext = Path::new(path)
+        .extension()
+        .map(|x| x.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    matches!(
+        ext.as_str(),
+        "png" | "jpg" | "jpeg" | "gif" | "webp" | "svg" | "bmp"
+    )
+}
+
+fn format_date(date: &DateTime<Utc>) -> String {
+    date.format("%Y-%m-%d %H:%M UTC").to_string()
+}
+
+fn short_id(did: &DID) -> String {
+    let id = did.to_string();
+    id.chars()
+        .rev()
+        .take(8)
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .collect()
+}
+
+fn other(e: impl std::fmt::Display) -> Error {
+    Error::OtherWithContext(e.to_string())
+}
+
+async fn warp_cmd<T>(
+    cmd: impl FnOnce(oneshot::Sender<Result<T, Error>>) -> WarpCmd,
+) -> Result<T, Error> {
+    let (tx, rx) = oneshot::channel();
+    WARP_CMD_CH
+        .tx
+        .send(cmd(tx))
+        .map_err(|e| other(format!("failed to send warp command: {e}")))?;
+    rx.await.map_err(|_| other("command canceled"))?
+}
@@ -1,4 +1,5 @@
 use std::{
+    collections::HashMap,
     fs::File,
     io::{Seek, Write},
     path::{Path, PathBuf},
@@ -8,7 +9,10 @@ use zip::{result::ZipError, write::FileOptions};
 
 use derive_more::Display;
 use futures::channel::oneshot;
-use warp::error;
+use uuid::Uuid;
+use warp::{crypto::DID, error};
+
+use crate::warp_runner::export::{self, ExportFormat};
 
 use tracing::log;
 
@@ -20,6 +24,19 @@ pub enum OtherCmd {
         dest: PathBuf,
         rsp: oneshot::Sender<Result<(), error::Error>>,
     },
+    // writes the conversation history and its attachments to a zip file at dest
+    #[display(
+        fmt = "ExportConversation {{ conv_id: {conv_id}, format: {format}, dest: {dest:?} }} "
+    )]
+    ExportConversation {
+        conv_id: Uuid,
+        conversation_name: String,
+        // usernames from the UI's identity cache
+        identities: HashMap<DID, String>,
+        format: ExportFormat,
+        dest: PathBuf,
+        rsp: oneshot::Sender<Result<(), error::Error>>,
+    },
 }
 
 pub async fn handle_other_cmd(cmd: OtherCmd) {
@@ -28,6 +45,27 @@ pub async fn handle_other_cmd(cmd: OtherCmd) {
             let r = compress_folder(src, dest).await;
             let _ = rsp.send(r);
         }
+        OtherCmd::ExportConversation {
+            conv_id,
+            conversation_name,
+            identities,
+            format,
+            dest,
+            rsp,
+        } => {
+            // the export sends its own commands to warp_runner, so it can't be awaited here
+            tokio::spawn(async move {
+                let r = export::export_conversation(
+                    conv_id,
+                    conversation_name,
+                    identities,
+                    format,
+                    dest,
+                )
+                .await;
+                let _ = rsp.send(r);
+            });
+        }
     }
 }
 
@@ -23,9 +23,11 @@ use crate::{
     state::{chats, identity, Friends},
     warp_runner::{
         conv_stream,
+        search_index::{SearchQuery, SearchResult},
         ui_adapter::{
             self, conversation_to_chat, dids_to_identity, fetch_messages2, fetch_messages_between,
-            fetch_messages_from_chat, fetch_pinned_messages_from_chat, get_uninitialized_identity,
+            fetch_messages_from_chat, fetch_pinned_messages_from_chat, fetch_thread,
+            get_uninitialized_identity, search_conversation,
         },
         Account, FetchMessagesConfig, FetchMessagesResponse, Messaging,
     },
@@ -110,6 +112,19 @@ pub enum RayGunCmd {
         conv_id: Uuid,
         rsp: oneshot::Sender<Result<Vec<ui_adapter::Message>, warp::error::Error>>,
     },
+    // the root message followed by its replies
+    #[display(fmt = "FetchThread {{ root_id: {root_id} }} ")]
+    FetchThread {
+        conv_id: Uuid,
+        root_id: Uuid,
+        rsp: oneshot::Sender<Result<Vec<ui_adapter::Message>, warp::error::Error>>,
+    },
+    #[display(fmt = "SearchConversation {{ conv_id: {conv_id} }} ")]
+    SearchConversation {
+        conv_id: Uuid,
+        query: SearchQuery,
+        rsp: oneshot::Sender<Result<Vec<SearchResult>, warp::error::Error>>,
+    },
     #[display(fmt = "SendMessage")]
     SendMessage {
         conv_id: Uuid,
@@ -290,6 +305,22 @@ pub async fn handle_raygun_cmd(
             let r = fetch_pinned_messages_from_chat(conv_id, messaging).await;
             let _ = rsp.send(r);
         }
+        RayGunCmd::FetchThread {
+            conv_id,
+            root_id,
+            rsp,
+        } => {
+            let r = fetch_thread(conv_id, root_id, messaging).await;
+            let _ = rsp.send(r);
+        }
+        RayGunCmd::SearchConversation {
+            conv_id,
+            query,
+            rsp,
+        } => {
+            let r = search_conversation(conv_id, messaging, query).await;
+            let _ = rsp.send(r);
+        }
         RayGunCmd::SendMessage {
             conv_id,
             msg,
@@ -13,7 +13,7 @@ use warp::{
 
 use tracing::log;
 
-use super::{conv_stream, Account, Calling, Messaging, Storage};
+use super::{conv_stream, search_index, Account, Calling, Messaging, Storage};
 use crate::WARP_CMD_CH;
 
 pub use commands::{BlinkCmd, ConstellationCmd, MultiPassCmd, OtherCmd, RayGunCmd, TesseractCmd};
@@ -43,6 +43,10 @@ pub async fn run(mut warp: Warp, notify: Arc<Notify>) {
     let mut multipass_stream = get_multipass_stream(&mut warp.multipass).await;
     let mut blink_stream = get_blink_stream(&mut warp.blink).await;
 
+    search_index::open();
+    // the search index is updated for every message. writing it to disk is batched
+    let mut save_search_index = tokio::time::interval(std::time::Duration::from_secs(30));
+
     log::debug!("warp_runner::manager::run");
     loop {
         tokio::select! {
@@ -74,11 +78,14 @@ pub async fn run(mut warp: Warp, notify: Arc<Notify>) {
                     break;
                 }
             },
+            _ = save_search_index.tick() => search_index::save_if_dirty(),
             // the WarpRunner has been dropped. stop the task
             _ = notify.notified() => break,
         }
     }
 
+    search_index::close();
+
     log::debug!("terminating warp_runner thread");
 }
 
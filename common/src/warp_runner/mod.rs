@@ -2,10 +2,13 @@
 use derive_more::Display;
 use std::sync::Arc;
 
-use tokio::sync::{
-    broadcast,
-    mpsc::{UnboundedReceiver, UnboundedSender},
-    Mutex, Notify,
+use tokio::{
+    sync::{
+        broadcast,
+        mpsc::{UnboundedReceiver, UnboundedSender},
+        Mutex, Notify,
+    },
+    task::JoinHandle,
 };
 use tracing::log;
 use warp::{
@@ -24,13 +27,16 @@ use warp_ipfs::{
     WarpIpfsBuilder,
 };
 
-use crate::{DiscoveryMode, STATIC_ARGS, WARP_CMD_CH};
+use crate::{local_key, DiscoveryMode, STATIC_ARGS, WARP_CMD_CH};
 
 use self::ui_adapter::{MultiPassEvent, RayGunEvent};
 
 mod conv_stream;
 mod data;
+pub mod export;
 mod manager;
+pub mod rpc;
+pub mod search_index;
 pub mod ui_adapter;
 
 pub use data::*;
@@ -97,7 +103,7 @@ pub enum WarpCmd {
 pub struct WarpRunner {
     // perhaps collecting a JoinHandle and calling abort() would be better than using Notify.
     notify: Arc<Notify>,
-    ran_once: bool,
+    handle: Option<JoinHandle<()>>,
 }
 
 impl std::ops::Drop for WarpRunner {
@@ -116,19 +122,33 @@ impl WarpRunner {
     pub fn new() -> Self {
         Self {
             notify: Arc::new(Notify::new()),
-            ran_once: false,
+            handle: None,
         }
     }
 
     // spawns a task which will terminate when WarpRunner is dropped
     pub fn run(&mut self) {
-        assert!(!self.ran_once, "WarpRunner called run() multiple times");
-        self.ran_once = true;
+        assert!(
+            self.handle.is_none(),
+            "WarpRunner called run() multiple times"
+        );
 
         let notify = self.notify.clone();
-        tokio::spawn(async move {
+        self.handle = Some(tokio::spawn(async move {
             handle_login(notify.clone()).await;
-        });
+        }));
+    }
+
+    /// Terminates the task and waits for warp to be dropped, which releases the warp store. Used to switch
+    /// profiles without restarting Uplink: stop the runner, change the files and start a new WarpRunner.
+    pub async fn stop(mut self) {
+        // unlike notify_waiters, notify_one stores a permit if the task isn't waiting on the Notify right now
+        self.notify.notify_one();
+        if let Some(handle) = self.handle.take() {
+            if let Err(e) = handle.await {
+                log::error!("warp_runner task failed: {e}");
+            }
+        }
     }
 }
 
@@ -187,6 +207,7 @@ async fn handle_login(notify: Arc<Notify>) {
                             passphrase: seed_words
                         }).await {
                             Ok(ident) => {
+                                local_key::unlock(&warp.tesseract);
                                 let _ = rsp.send(Ok(ident));
                                 break Some(warp);
                             },
@@ -226,6 +247,7 @@ async fn handle_login(notify: Arc<Notify>) {
                         match warp.multipass.create_identity(Some(&username), Some(&seed_words)).await {
                             Ok(_id) =>  match wait_for_multipass(&mut warp, notify.clone()).await {
                                 Ok(ident) => {
+                                    local_key::unlock(&warp.tesseract);
                                     let _ = rsp.send(Ok(ident));
                                     break Some(warp);
                                 },
@@ -249,6 +271,7 @@ async fn handle_login(notify: Arc<Notify>) {
                         };
                         match wait_for_multipass(&mut warp, notify.clone()).await {
                             Ok(ident) => {
+                                local_key::unlock(&warp.tesseract);
                                 let _ = rsp.send(Ok(ident));
                                 break Some(warp);
                             },
@@ -0,0 +1,146 @@
+use serde::Deserialize;
+use serde_json::{json, Value};
+use uuid::Uuid;
+use warp::{blink::AudioTestEvent, crypto::DID};
+
+use super::{call, parse, to_value, RpcError};
+use crate::{
+    warp_runner::{BlinkCmd, WarpCmd},
+    STATIC_ARGS,
+};
+
+#[derive(Deserialize)]
+struct OfferCallParams {
+    conversation_id: Uuid,
+    participants: Vec<DID>,
+}
+
+#[derive(Deserialize)]
+struct CallParams {
+    call_id: Uuid,
+}
+
+#[derive(Deserialize)]
+struct VolumeParams {
+    user: DID,
+    volume: f32,
+}
+
+#[derive(Deserialize)]
+struct DeviceParams {
+    device_name: String,
+}
+
+#[derive(Deserialize)]
+struct RecordingParams {
+    #[serde(default)]
+    output_dir: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct FlagParams {
+    flag: bool,
+}
+
+pub(super) async fn dispatch(cmd: &str, params: Value) -> Result<Value, RpcError> {
+    match cmd {
+        "offer_call" => {
+            let OfferCallParams {
+                conversation_id,
+                participants,
+            } = parse(params)?;
+            let call_id = call(|rsp| {
+                WarpCmd::Blink(BlinkCmd::OfferCall {
+                    conversation_id,
+                    participants,
+                    rsp,
+                })
+            })
+            .await??;
+            to_value(call_id)
+        }
+        "answer_call" => {
+            let CallParams { call_id } = parse(params)?;
+            call(|rsp| WarpCmd::Blink(BlinkCmd::AnswerCall { call_id, rsp })).await??;
+            Ok(Value::Null)
+        }
+        "reject_call" => {
+            let CallParams { call_id } = parse(params)?;
+            call(|rsp| WarpCmd::Blink(BlinkCmd::RejectCall { call_id, rsp })).await??;
+            Ok(Value::Null)
+        }
+        "leave_call" | "mute_self" | "unmute_self" | "silence_call" | "unsilence_call"
+        | "stop_recording" => {
+            call(|rsp| {
+                WarpCmd::Blink(match cmd {
+                    "leave_call" => BlinkCmd::LeaveCall { rsp },
+                    "mute_self" => BlinkCmd::MuteSelf { rsp },
+                    "unmute_self" => BlinkCmd::UnmuteSelf { rsp },
+                    "silence_call" => BlinkCmd::SilenceCall { rsp },
+                    "unsilence_call" => BlinkCmd::UnsilenceCall { rsp },
+                    _ => BlinkCmd::StopRecording { rsp },
+                })
+            })
+            .await??;
+            Ok(Value::Null)
+        }
+        "adjust_volume" => {
+            let VolumeParams { user, volume } = parse(params)?;
+            call(|rsp| WarpCmd::Blink(BlinkCmd::AdjustVolume { user, volume, rsp })).await??;
+            Ok(Value::Null)
+        }
+        "set_microphone" => {
+            let DeviceParams { device_name } = parse(params)?;
+            call(|rsp| WarpCmd::Blink(BlinkCmd::SetMicrophone { device_name, rsp })).await??;
+            Ok(Value::Null)
+        }
+        "set_speaker" => {
+            let DeviceParams { device_name } = parse(params)?;
+            call(|rsp| WarpCmd::Blink(BlinkCmd::SetSpeaker { device_name, rsp })).await??;
+            Ok(Value::Null)
+        }
+        "start_recording" => {
+            let RecordingParams { output_dir } = parse(params)?;
+            let output_dir =
+                output_dir.unwrap_or_else(|| STATIC_ARGS.recordings.to_string_lossy().to_string());
+            call(|rsp| WarpCmd::Blink(BlinkCmd::StartRecording { output_dir, rsp })).await??;
+            Ok(Value::Null)
+        }
+        "get_audio_device_config" => {
+            let config =
+                call(|rsp| WarpCmd::Blink(BlinkCmd::GetAudioDeviceConfig { rsp })).await??;
+            Ok(json!({
+                "microphone": config.microphone_device_name(),
+                "speaker": config.speaker_device_name(),
+                "available_microphones": config.get_available_microphones().unwrap_or_default(),
+                "available_speakers": config.get_available_speakers().unwrap_or_default(),
+            }))
+        }
+        "set_echo_cancellation" => {
+            let FlagParams { flag } = parse(params)?;
+            call(|rsp| WarpCmd::Blink(BlinkCmd::SetEchoCancellation { flag, rsp })).await??;
+            Ok(Value::Null)
+        }
+        // the tests stream loudness samples until they are done. report the peak loudness.
+        "test_speaker" | "test_microphone" => {
+            let mut rx = call(|rsp| {
+                WarpCmd::Blink(if cmd == "test_speaker" {
+                    BlinkCmd::TestSpeaker { rsp }
+                } else {
+                    BlinkCmd::TestMicrophone { rsp }
+                })
+            })
+            .await?;
+            let mut peak: f64 = 0.0;
+            while let Some(evt) = rx.recv().await {
+                match evt {
+                    AudioTestEvent::Done => break,
+                    AudioTestEvent::Output { loudness } => peak = peak.max(loudness as f64),
+                    _ => {}
+                }
+            }
+            Ok(json!({ "peak_loudness": peak }))
+        }
+        _ => Err(RpcError::method_not_found(&format!("blink.{cmd}"))),
+    }
+}
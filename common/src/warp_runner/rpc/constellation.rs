@@ -0,0 +1,198 @@
+use std::path::PathBuf;
+
+use futures::StreamExt;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use warp::constellation::{item::Item, ConstellationProgressStream};
+
+use super::{call, parse, RpcError, WARP_ERROR};
+use crate::{
+    state::{pending_message::FileProgression, storage::Storage},
+    upload_file_channel::{UploadFileAction, UPLOAD_FILE_LISTENER},
+    warp_runner::{ConstellationCmd, WarpCmd},
+};
+
+#[derive(Deserialize)]
+struct DirectoryParams {
+    directory_name: String,
+}
+
+#[derive(Deserialize)]
+struct UploadParams {
+    files: Vec<PathBuf>,
+}
+
+#[derive(Deserialize)]
+struct RenameParams {
+    old_name: String,
+    new_name: String,
+}
+
+#[derive(Deserialize)]
+struct DownloadParams {
+    file_name: String,
+    path: PathBuf,
+}
+
+#[derive(Deserialize)]
+struct ItemParams {
+    name: String,
+}
+
+pub(super) async fn dispatch(cmd: &str, params: Value) -> Result<Value, RpcError> {
+    match cmd {
+        "get_items_from_current_directory" => {
+            let storage = call(|rsp| {
+                WarpCmd::Constellation(ConstellationCmd::GetItemsFromCurrentDirectory { rsp })
+            })
+            .await??;
+            Ok(storage_to_json(&storage))
+        }
+        "create_new_directory" => {
+            let DirectoryParams { directory_name } = parse(params)?;
+            call(|rsp| {
+                WarpCmd::Constellation(ConstellationCmd::CreateNewDirectory {
+                    directory_name,
+                    rsp,
+                })
+            })
+            .await??;
+            Ok(Value::Null)
+        }
+        "open_directory" => {
+            let DirectoryParams { directory_name } = parse(params)?;
+            let storage = call(|rsp| {
+                WarpCmd::Constellation(ConstellationCmd::OpenDirectory {
+                    directory_name,
+                    rsp,
+                })
+            })
+            .await??;
+            Ok(storage_to_json(&storage))
+        }
+        // goes up one level from the current directory
+        "back_to_previous_directory" => {
+            let current = current_directory().await?;
+            let Some(directory) = current.directories_opened.iter().rev().nth(1).cloned() else {
+                return Ok(storage_to_json(&current));
+            };
+            let storage = call(|rsp| {
+                WarpCmd::Constellation(ConstellationCmd::BackToPreviousDirectory { directory, rsp })
+            })
+            .await??;
+            Ok(storage_to_json(&storage))
+        }
+        // uploads report their progress over UPLOAD_FILE_LISTENER rather than a response channel.
+        // the UI does not run in the daemon, so it is safe to consume the listener here.
+        "upload_files" => {
+            let UploadParams { files } = parse(params)?;
+            let rx = UPLOAD_FILE_LISTENER.rx.clone();
+            let mut rx = rx.lock().await;
+            send_cmd(ConstellationCmd::UploadFiles { files_path: files })?;
+            let mut failed = vec![];
+            while let Some(action) = rx.recv().await {
+                match action {
+                    UploadFileAction::Finished(storage) => {
+                        let mut r = storage_to_json(&storage);
+                        r["failed"] = json!(failed);
+                        return Ok(r);
+                    }
+                    UploadFileAction::SizeNotAvailable(path, _)
+                    | UploadFileAction::Error(Some(path), _) => failed.push(path),
+                    UploadFileAction::Error(None, _) => {
+                        return Err(RpcError::new(WARP_ERROR, "failed to upload files"))
+                    }
+                    _ => {}
+                }
+            }
+            Err(RpcError::new(WARP_ERROR, "upload listener closed"))
+        }
+        "rename_item" => {
+            let RenameParams { old_name, new_name } = parse(params)?;
+            let storage = call(|rsp| {
+                WarpCmd::Constellation(ConstellationCmd::RenameItem {
+                    old_name,
+                    new_name,
+                    rsp,
+                })
+            })
+            .await??;
+            Ok(storage_to_json(&storage))
+        }
+        "download_file" => {
+            let DownloadParams { file_name, path } = parse(params)?;
+            let stream = call(|rsp| {
+                WarpCmd::Constellation(ConstellationCmd::DownloadFile {
+                    file_name,
+                    local_path_to_save_file: path.clone(),
+                    rsp,
+                })
+            })
+            .await??;
+            wait_for_progress(stream).await?;
+            Ok(json!(path))
+        }
+        "delete_items" => {
+            let ItemParams { name } = parse(params)?;
+            let current = current_directory().await?;
+            let item = if let Some(file) = current.files.iter().find(|f| f.name() == name) {
+                Item::from(file.clone())
+            } else if let Some(dir) = current.directories.iter().find(|d| d.name() == name) {
+                Item::from(dir.clone())
+            } else {
+                return Err(RpcError::invalid_params(format!("no such item: {name}")));
+            };
+            let storage =
+                call(|rsp| WarpCmd::Constellation(ConstellationCmd::DeleteItems { item, rsp }))
+                    .await??;
+            Ok(storage_to_json(&storage))
+        }
+        _ => Err(RpcError::method_not_found(&format!("constellation.{cmd}"))),
+    }
+}
+
+fn send_cmd(cmd: ConstellationCmd) -> Result<(), RpcError> {
+    crate::WARP_CMD_CH
+        .tx
+        .send(WarpCmd::Constellation(cmd))
+        .map_err(|_| RpcError::new(super::INTERNAL_ERROR, "warp_runner is not running"))
+}
+
+async fn current_directory() -> Result<Storage, RpcError> {
+    call(|rsp| WarpCmd::Constellation(ConstellationCmd::GetItemsFromCurrentDirectory { rsp }))
+        .await?
+        .map_err(RpcError::from)
+}
+
+// Storage skips every field when serialized, so it can't be passed to serde_json directly
+fn storage_to_json(storage: &Storage) -> Value {
+    json!({
+        "current_dir": storage.current_dir.name(),
+        "directories": storage.directories.iter().map(|d| d.name()).collect::<Vec<_>>(),
+        "files": storage
+            .files
+            .iter()
+            .map(|f| json!({ "name": f.name(), "size": f.size() }))
+            .collect::<Vec<_>>(),
+        "current_size": storage.current_size,
+        "max_size": storage.max_size,
+    })
+}
+
+/// downloads only progress while the stream is polled. drive it to completion before responding.
+pub(super) async fn wait_for_progress(stream: ConstellationProgressStream) -> Result<(), RpcError> {
+    let mut stream = stream.map(FileProgression::from);
+    while let Some(progress) = stream.next().await {
+        match progress {
+            FileProgression::ProgressComplete { .. } => return Ok(()),
+            FileProgression::ProgressFailed { name, error, .. } => {
+                return Err(RpcError::new(
+                    WARP_ERROR,
+                    format!("failed to transfer {name}: {error}"),
+                ))
+            }
+            FileProgression::CurrentProgress { .. } => {}
+        }
+    }
+    Ok(())
+}
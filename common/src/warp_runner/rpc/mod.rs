@@ -0,0 +1,241 @@
+//! A JSON-RPC style front end for warp_runner. Requests are translated into `WarpCmd`s, sent over `WARP_CMD_CH`,
+//! and the response from warp_runner is serialized back into JSON. Used by the headless daemon, which does not
+//! have a UI to drive warp_runner.
+//!
+//! Method names are `<module>.<command>`, for example `raygun.send_message` or `multipass.request_friend`.
+
+mod blink;
+mod constellation;
+mod multipass;
+mod other;
+mod raygun;
+mod tesseract;
+
+use futures::channel::oneshot;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::{
+    warp_runner::{
+        ui_adapter::{MessageEvent, MultiPassEvent, RayGunEvent},
+        WarpCmd, WarpEvent,
+    },
+    WARP_CMD_CH,
+};
+
+pub const PARSE_ERROR: i32 = -32700;
+pub const INVALID_REQUEST: i32 = -32600;
+pub const METHOD_NOT_FOUND: i32 = -32601;
+pub const INVALID_PARAMS: i32 = -32602;
+pub const INTERNAL_ERROR: i32 = -32603;
+/// returned when warp_runner responds with a `warp::error::Error`
+pub const WARP_ERROR: i32 = -32000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RpcRequest {
+    #[serde(default)]
+    pub id: Option<Value>,
+    pub method: String,
+    #[serde(default)]
+    pub params: Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RpcResponse {
+    pub id: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<RpcError>,
+}
+
+impl RpcResponse {
+    pub fn new(id: Option<Value>, result: Result<Value, RpcError>) -> Self {
+        match result {
+            Ok(result) => Self {
+                id,
+                result: Some(result),
+                error: None,
+            },
+            Err(error) => Self {
+                id,
+                result: None,
+                error: Some(error),
+            },
+        }
+    }
+}
+
+/// pushed to clients which called `events.subscribe`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RpcNotification {
+    pub method: String,
+    pub params: Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RpcError {
+    pub code: i32,
+    pub message: String,
+}
+
+impl RpcError {
+    pub fn new(code: i32, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+
+    pub fn method_not_found(method: &str) -> Self {
+        Self::new(METHOD_NOT_FOUND, format!("unknown method: {method}"))
+    }
+
+    pub fn invalid_params(e: impl std::fmt::Display) -> Self {
+        Self::new(INVALID_PARAMS, e.to_string())
+    }
+}
+
+impl std::fmt::Display for RpcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.message, self.code)
+    }
+}
+
+impl std::error::Error for RpcError {}
+
+impl From<warp::error::Error> for RpcError {
+    fn from(e: warp::error::Error) -> Self {
+        Self::new(WARP_ERROR, e.to_string())
+    }
+}
+
+impl From<serde_json::Error> for RpcError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::new(INTERNAL_ERROR, e.to_string())
+    }
+}
+
+/// routes a request to the handler for its module. returns the serialized response from warp_runner.
+pub async fn dispatch(method: &str, params: Value) -> Result<Value, RpcError> {
+    let (module, cmd) = method
+        .split_once('.')
+        .ok_or_else(|| RpcError::method_not_found(method))?;
+    match module {
+        "raygun" => raygun::dispatch(cmd, params).await,
+        "multipass" => multipass::dispatch(cmd, params).await,
+        "constellation" => constellation::dispatch(cmd, params).await,
+        "blink" => blink::dispatch(cmd, params).await,
+        "tesseract" => tesseract::dispatch(cmd, params).await,
+        "other" => other::dispatch(cmd, params).await,
+        _ => Err(RpcError::method_not_found(method)),
+    }
+}
+
+/// sends a command to warp_runner and waits for the response
+async fn call<T>(cmd: impl FnOnce(oneshot::Sender<T>) -> WarpCmd) -> Result<T, RpcError> {
+    let (tx, rx) = oneshot::channel();
+    WARP_CMD_CH
+        .tx
+        .send(cmd(tx))
+        .map_err(|_| RpcError::new(INTERNAL_ERROR, "warp_runner is not running"))?;
+    rx.await
+        .map_err(|_| RpcError::new(INTERNAL_ERROR, "warp_runner dropped the request"))
+}
+
+fn parse<T: DeserializeOwned>(params: Value) -> Result<T, RpcError> {
+    // allow methods without arguments to be called without params
+    let params = if params.is_null() { json!({}) } else { params };
+    serde_json::from_value(params).map_err(RpcError::invalid_params)
+}
+
+fn to_value<T: Serialize>(t: T) -> Result<Value, RpcError> {
+    serde_json::to_value(t).map_err(RpcError::from)
+}
+
+/// converts an event from warp_runner into a notification for subscribers
+pub fn event_to_notification(event: &WarpEvent) -> RpcNotification {
+    let (method, params) = match event {
+        WarpEvent::RayGun(evt) => (
+            "event.raygun",
+            match evt {
+                RayGunEvent::ConversationCreated(chat) => json!({
+                    "kind": "ConversationCreated",
+                    "conversation_id": chat.inner.id,
+                    "participants": chat.inner.participants,
+                    "name": chat.inner.conversation_name,
+                }),
+                RayGunEvent::ConversationDeleted(id) => json!({
+                    "kind": "ConversationDeleted",
+                    "conversation_id": id,
+                }),
+            },
+        ),
+        WarpEvent::Message(evt) => ("event.message", message_event_to_json(evt)),
+        WarpEvent::MultiPass(evt) => ("event.multipass", multipass_event_to_json(evt)),
+        WarpEvent::Blink(evt) => ("event.blink", json!({ "kind": evt.to_string() })),
+    };
+    RpcNotification {
+        method: method.into(),
+        params,
+    }
+}
+
+fn message_event_to_json(evt: &MessageEvent) -> Value {
+    match evt {
+        MessageEvent::Received {
+            conversation_id,
+            message,
+        }
+        | MessageEvent::Sent {
+            conversation_id,
+            message,
+        }
+        | MessageEvent::Edited {
+            conversation_id,
+            message,
+        } => json!({
+            "kind": evt.to_string(),
+            "conversation_id": conversation_id,
+            "message": message,
+        }),
+        MessageEvent::Deleted {
+            conversation_id,
+            message_id,
+            ..
+        } => json!({
+            "kind": evt.to_string(),
+            "conversation_id": conversation_id,
+            "message_id": message_id,
+        }),
+        MessageEvent::TypingIndicator {
+            conversation_id,
+            participant,
+        } => json!({
+            "kind": evt.to_string(),
+            "conversation_id": conversation_id,
+            "participant": participant,
+        }),
+        _ => json!({ "kind": evt.to_string() }),
+    }
+}
+
+fn multipass_event_to_json(evt: &MultiPassEvent) -> Value {
+    match evt {
+        MultiPassEvent::None => json!({ "kind": evt.to_string() }),
+        MultiPassEvent::FriendRequestReceived(identity)
+        | MultiPassEvent::FriendRequestSent(identity)
+        | MultiPassEvent::FriendAdded(identity)
+        | MultiPassEvent::FriendRemoved(identity)
+        | MultiPassEvent::FriendRequestCancelled(identity)
+        | MultiPassEvent::FriendOnline(identity)
+        | MultiPassEvent::FriendOffline(identity)
+        | MultiPassEvent::Blocked(identity)
+        | MultiPassEvent::Unblocked(identity)
+        | MultiPassEvent::IdentityUpdate(identity) => json!({
+            "kind": evt.to_string(),
+            "did": identity.did_key(),
+            "username": identity.username(),
+        }),
+    }
+}
@@ -0,0 +1,219 @@
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+use warp::{crypto::DID, multipass::identity::IdentityStatus};
+
+use super::{call, parse, to_value, RpcError};
+use crate::warp_runner::{MultiPassCmd, RayGunCmd, WarpCmd};
+
+#[derive(Deserialize)]
+struct LogInParams {
+    passphrase: String,
+}
+
+#[derive(Deserialize)]
+struct CreateIdentityParams {
+    username: String,
+    passphrase: String,
+    seed_words: String,
+}
+
+#[derive(Deserialize)]
+struct RecoverIdentityParams {
+    passphrase: String,
+    seed_words: String,
+}
+
+#[derive(Deserialize)]
+struct DidParams {
+    did: DID,
+}
+
+#[derive(Deserialize)]
+struct RequestFriendParams {
+    // either a DID or Username#short_id
+    id: String,
+}
+
+#[derive(Deserialize)]
+struct ImageParams {
+    path: PathBuf,
+}
+
+#[derive(Deserialize)]
+struct StatusMessageParams {
+    #[serde(default)]
+    status: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct UsernameParams {
+    username: String,
+}
+
+#[derive(Deserialize)]
+struct StatusParams {
+    status: IdentityStatus,
+}
+
+pub(super) async fn dispatch(cmd: &str, params: Value) -> Result<Value, RpcError> {
+    match cmd {
+        // warp_runner only initializes RayGun and the conversation streams once InitializeWarp is sent.
+        // the UI does this after logging in; headless clients get it for free.
+        "try_log_in" => {
+            let LogInParams { passphrase } = parse(params)?;
+            let identity =
+                call(|rsp| WarpCmd::MultiPass(MultiPassCmd::TryLogIn { passphrase, rsp }))
+                    .await??;
+            initialize_warp().await?;
+            to_value(identity)
+        }
+        "create_identity" => {
+            let CreateIdentityParams {
+                username,
+                passphrase,
+                seed_words,
+            } = parse(params)?;
+            let identity = call(|rsp| {
+                WarpCmd::MultiPass(MultiPassCmd::CreateIdentity {
+                    username,
+                    tesseract_passphrase: passphrase,
+                    seed_words,
+                    rsp,
+                })
+            })
+            .await??;
+            initialize_warp().await?;
+            to_value(identity)
+        }
+        "recover_identity" => {
+            let RecoverIdentityParams {
+                passphrase,
+                seed_words,
+            } = parse(params)?;
+            let identity = call(|rsp| {
+                WarpCmd::MultiPass(MultiPassCmd::RecoverIdentity {
+                    passphrase,
+                    seed_words,
+                    rsp,
+                })
+            })
+            .await??;
+            initialize_warp().await?;
+            to_value(identity)
+        }
+        "request_friend" => {
+            let RequestFriendParams { id } = parse(params)?;
+            call(|rsp| {
+                WarpCmd::MultiPass(MultiPassCmd::RequestFriend {
+                    id,
+                    outgoing_requests: vec![],
+                    rsp,
+                })
+            })
+            .await??;
+            Ok(Value::Null)
+        }
+        "refresh_friends" => {
+            let friends =
+                call(|rsp| WarpCmd::MultiPass(MultiPassCmd::RefreshFriends { rsp })).await??;
+            to_value(friends.into_values().collect::<Vec<_>>())
+        }
+        "get_own_did" => {
+            let did = call(|rsp| WarpCmd::MultiPass(MultiPassCmd::GetOwnDid { rsp })).await??;
+            to_value(did)
+        }
+        "remove_friend" | "unblock" | "block" | "accept_request" | "deny_request"
+        | "cancel_request" => {
+            let DidParams { did } = parse(params)?;
+            call(|rsp| {
+                WarpCmd::MultiPass(match cmd {
+                    "remove_friend" => MultiPassCmd::RemoveFriend { did, rsp },
+                    "unblock" => MultiPassCmd::Unblock { did, rsp },
+                    "block" => MultiPassCmd::Block { did, rsp },
+                    "accept_request" => MultiPassCmd::AcceptRequest { did, rsp },
+                    "deny_request" => MultiPassCmd::DenyRequest { did, rsp },
+                    _ => MultiPassCmd::CancelRequest { did, rsp },
+                })
+            })
+            .await??;
+            Ok(Value::Null)
+        }
+        "get_profile_picture" => {
+            let DidParams { did } = parse(params)?;
+            let image =
+                call(|rsp| WarpCmd::MultiPass(MultiPassCmd::GetProfilePicture { did, rsp }))
+                    .await??;
+            Ok(json!({ "image": image }))
+        }
+        "get_profile_banner" => {
+            let DidParams { did } = parse(params)?;
+            let image = call(|rsp| WarpCmd::MultiPass(MultiPassCmd::GetProfileBanner { did, rsp }))
+                .await??;
+            Ok(json!({ "image": image }))
+        }
+        "update_profile_picture" => {
+            let ImageParams { path } = parse(params)?;
+            let pfp = tokio::fs::read(&path)
+                .await
+                .map_err(RpcError::invalid_params)?;
+            let identity =
+                call(|rsp| WarpCmd::MultiPass(MultiPassCmd::UpdateProfilePicture { pfp, rsp }))
+                    .await??;
+            to_value(identity)
+        }
+        "clear_profile_picture" => {
+            let identity =
+                call(|rsp| WarpCmd::MultiPass(MultiPassCmd::ClearProfilePicture { rsp })).await??;
+            to_value(identity)
+        }
+        "clear_banner" => {
+            let identity =
+                call(|rsp| WarpCmd::MultiPass(MultiPassCmd::ClearBanner { rsp })).await??;
+            to_value(identity)
+        }
+        "update_banner" => {
+            let ImageParams { path } = parse(params)?;
+            let banner = tokio::fs::read(&path)
+                .await
+                .map_err(RpcError::invalid_params)?;
+            let identity =
+                call(|rsp| WarpCmd::MultiPass(MultiPassCmd::UpdateBanner { banner, rsp }))
+                    .await??;
+            to_value(identity)
+        }
+        "update_status_message" => {
+            let StatusMessageParams { status } = parse(params)?;
+            let identity =
+                call(|rsp| WarpCmd::MultiPass(MultiPassCmd::UpdateStatusMessage { status, rsp }))
+                    .await??;
+            to_value(identity)
+        }
+        "update_username" => {
+            let UsernameParams { username } = parse(params)?;
+            let identity =
+                call(|rsp| WarpCmd::MultiPass(MultiPassCmd::UpdateUsername { username, rsp }))
+                    .await??;
+            to_value(identity)
+        }
+        "get_identity" => {
+            let DidParams { did } = parse(params)?;
+            let identity =
+                call(|rsp| WarpCmd::MultiPass(MultiPassCmd::GetIdentity { did, rsp })).await??;
+            to_value(identity)
+        }
+        "set_status" => {
+            let StatusParams { status } = parse(params)?;
+            let identity =
+                call(|rsp| WarpCmd::MultiPass(MultiPassCmd::SetStatus { status, rsp })).await??;
+            to_value(identity)
+        }
+        _ => Err(RpcError::method_not_found(&format!("multipass.{cmd}"))),
+    }
+}
+
+async fn initialize_warp() -> Result<(), RpcError> {
+    call(|rsp| WarpCmd::RayGun(RayGunCmd::InitializeWarp { rsp })).await??;
+    Ok(())
+}
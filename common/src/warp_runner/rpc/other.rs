@@ -0,0 +1,59 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use serde::Deserialize;
+use serde_json::Value;
+use uuid::Uuid;
+use warp::crypto::DID;
+
+use super::{call, parse, RpcError};
+use crate::warp_runner::{export::ExportFormat, OtherCmd, WarpCmd};
+
+#[derive(Deserialize)]
+struct CompressParams {
+    src: PathBuf,
+    dest: PathBuf,
+}
+
+#[derive(Deserialize)]
+struct ExportParams {
+    conversation_id: Uuid,
+    #[serde(default)]
+    conversation_name: String,
+    // usernames to show instead of short ids
+    #[serde(default)]
+    identities: HashMap<DID, String>,
+    format: ExportFormat,
+    dest: PathBuf,
+}
+
+pub(super) async fn dispatch(cmd: &str, params: Value) -> Result<Value, RpcError> {
+    match cmd {
+        "compress_folder" => {
+            let CompressParams { src, dest } = parse(params)?;
+            call(|rsp| WarpCmd::Other(OtherCmd::CompressFolder { src, dest, rsp })).await??;
+            Ok(Value::Null)
+        }
+        "export_conversation" => {
+            let ExportParams {
+                conversation_id,
+                conversation_name,
+                identities,
+                format,
+                dest,
+            } = parse(params)?;
+            call(|rsp| {
+                WarpCmd::Other(OtherCmd::ExportConversation {
+                    conv_id: conversation_id,
+                    conversation_name,
+                    identities,
+                    format,
+                    dest,
+                    rsp,
+                })
+            })
+            .await??;
+            Ok(Value::Null)
+        }
+        _ => Err(RpcError::method_not_found(&format!("other.{cmd}"))),
+    }
+}
@@ -0,0 +1,596 @@
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use uuid::Uuid;
+use warp::{
+    crypto::DID,
+    raygun::{
+        self, AttachmentEventStream, AttachmentKind, ConversationSettings, GroupSettings, Location,
+        PinState, ReactionState,
+    },
+};
+
+use super::{call, parse, to_value, RpcError};
+use crate::{
+    state::pending_message::FileProgression,
+    warp_runner::{
+        search_index::{self, SearchQuery},
+        FetchMessagesConfig, RayGunCmd, WarpCmd,
+    },
+};
+
+const DEFAULT_FETCH_LIMIT: usize = 50;
+
+#[derive(Deserialize)]
+struct ConvParams {
+    conversation_id: Uuid,
+}
+
+#[derive(Deserialize)]
+struct MessageParams {
+    conversation_id: Uuid,
+    message_id: Uuid,
+}
+
+#[derive(Deserialize)]
+struct RecipientParams {
+    recipient: DID,
+}
+
+#[derive(Deserialize)]
+struct CreateGroupParams {
+    recipients: Vec<DID>,
+    #[serde(default)]
+    group_name: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ParticipantsParams {
+    conversation_id: Uuid,
+    recipients: Vec<DID>,
+}
+
+#[derive(Deserialize)]
+struct RenameParams {
+    conversation_id: Uuid,
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct SettingsParams {
+    conversation_id: Uuid,
+    settings: ConversationSettings,
+}
+
+#[derive(Deserialize)]
+struct FetchParams {
+    conversation_id: Uuid,
+    #[serde(default)]
+    limit: Option<usize>,
+    // fetch messages older than this date
+    #[serde(default)]
+    before: Option<DateTime<Utc>>,
+    // fetch messages newer than this date
+    #[serde(default)]
+    after: Option<DateTime<Utc>>,
+}
+
+#[derive(Deserialize)]
+struct FetchDeprecatedParams {
+    conversation_id: Uuid,
+    to_fetch: usize,
+    #[serde(default)]
+    current_len: usize,
+}
+
+#[derive(Deserialize)]
+struct FetchBetweenParams {
+    conversation_id: Uuid,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+struct ThreadParams {
+    conversation_id: Uuid,
+    root_id: Uuid,
+}
+
+#[derive(Deserialize)]
+struct SearchConversationParams {
+    conversation_id: Uuid,
+    #[serde(flatten)]
+    query: SearchQuery,
+}
+
+#[derive(Deserialize)]
+struct SendParams {
+    conversation_id: Uuid,
+    lines: Vec<String>,
+    #[serde(default)]
+    attachments: Vec<PathBuf>,
+}
+
+#[derive(Deserialize)]
+struct SendSeveralParams {
+    conversation_ids: Vec<Uuid>,
+    lines: Vec<String>,
+    #[serde(default)]
+    attachments: Vec<PathBuf>,
+}
+
+#[derive(Deserialize)]
+struct EditParams {
+    conversation_id: Uuid,
+    message_id: Uuid,
+    lines: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct DownloadParams {
+    conversation_id: Uuid,
+    message_id: Uuid,
+    file_name: String,
+    path: PathBuf,
+}
+
+#[derive(Deserialize)]
+struct ReplyParams {
+    conversation_id: Uuid,
+    reply_to: Uuid,
+    lines: Vec<String>,
+    #[serde(default)]
+    attachments: Vec<PathBuf>,
+}
+
+#[derive(Deserialize)]
+struct ReactParams {
+    conversation_id: Uuid,
+    message_id: Uuid,
+    emoji: String,
+    #[serde(default)]
+    remove: bool,
+}
+
+#[derive(Deserialize)]
+struct PinParams {
+    conversation_id: Uuid,
+    message_id: Uuid,
+    #[serde(default = "default_true")]
+    pin: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn to_locations(paths: Vec<PathBuf>) -> Vec<Location> {
+    paths
+        .into_iter()
+        .map(|path| Location::Disk { path })
+        .collect()
+}
+
+pub(super) async fn dispatch(cmd: &str, params: Value) -> Result<Value, RpcError> {
+    match cmd {
+        "initialize_warp" => {
+            let init = call(|rsp| WarpCmd::RayGun(RayGunCmd::InitializeWarp { rsp })).await??;
+            Ok(json!({
+                "friends": {
+                    "all": init.friends.all,
+                    "blocked": init.friends.blocked,
+                    "incoming_requests": init.friends.incoming_requests,
+                    "outgoing_requests": init.friends.outgoing_requests,
+                },
+                "identities": init.converted_identities.into_values().collect::<Vec<_>>(),
+                "chats": init.chats.into_values().collect::<Vec<_>>(),
+            }))
+        }
+        "create_conversation" => {
+            let RecipientParams { recipient } = parse(params)?;
+            let id = call(|rsp| WarpCmd::RayGun(RayGunCmd::CreateConversation { recipient, rsp }))
+                .await??;
+            to_value(id)
+        }
+        "create_group_conversation" => {
+            let CreateGroupParams {
+                recipients,
+                group_name,
+            } = parse(params)?;
+            let id = call(|rsp| {
+                WarpCmd::RayGun(RayGunCmd::CreateGroupConversation {
+                    recipients,
+                    group_name,
+                    settings: GroupSettings::default(),
+                    rsp,
+                })
+            })
+            .await??;
+            to_value(id)
+        }
+        "add_group_participants" => {
+            let ParticipantsParams {
+                conversation_id,
+                recipients,
+            } = parse(params)?;
+            let id = call(|rsp| {
+                WarpCmd::RayGun(RayGunCmd::AddGroupParticipants {
+                    conv_id: conversation_id,
+                    recipients,
+                    rsp,
+                })
+            })
+            .await??;
+            to_value(id)
+        }
+        "remove_group_participants" => {
+            let ParticipantsParams {
+                conversation_id,
+                recipients,
+            } = parse(params)?;
+            let id = call(|rsp| {
+                WarpCmd::RayGun(RayGunCmd::RemoveGroupParticipants {
+                    conv_id: conversation_id,
+                    recipients,
+                    rsp,
+                })
+            })
+            .await??;
+            to_value(id)
+        }
+        "update_conversation_name" => {
+            let RenameParams {
+                conversation_id,
+                name,
+            } = parse(params)?;
+            let id = call(|rsp| {
+                WarpCmd::RayGun(RayGunCmd::UpdateConversationName {
+                    conv_id: conversation_id,
+                    new_conversation_name: name,
+                    rsp,
+                })
+            })
+            .await??;
+            to_value(id)
+        }
+        "delete_conversation" => {
+            let ConvParams { conversation_id } = parse(params)?;
+            let id = call(|rsp| {
+                WarpCmd::RayGun(RayGunCmd::DeleteConversation {
+                    conv_id: conversation_id,
+                    rsp,
+                })
+            })
+            .await??;
+            to_value(id)
+        }
+        "update_conversation_settings" => {
+            let SettingsParams {
+                conversation_id,
+                settings,
+            } = parse(params)?;
+            let id = call(|rsp| {
+                WarpCmd::RayGun(RayGunCmd::UpdateConversationSettings {
+                    conv_id: conversation_id,
+                    settings,
+                    rsp,
+                })
+            })
+            .await??;
+            to_value(id)
+        }
+        "fetch_messages" => {
+            let FetchParams {
+                conversation_id,
+                limit,
+                before,
+                after,
+            } = parse(params)?;
+            let limit = limit.unwrap_or(DEFAULT_FETCH_LIMIT);
+            let config = match (before, after) {
+                (Some(start_date), _) => FetchMessagesConfig::Earlier { start_date, limit },
+                (None, Some(start_date)) => FetchMessagesConfig::Later { start_date, limit },
+                (None, None) => FetchMessagesConfig::MostRecent { limit },
+            };
+            let r = call(|rsp| {
+                WarpCmd::RayGun(RayGunCmd::FetchMessages {
+                    conv_id: conversation_id,
+                    config,
+                    rsp,
+                })
+            })
+            .await??;
+            Ok(json!({
+                "messages": r.messages,
+                "has_more": r.has_more,
+                "most_recent": r.most_recent,
+            }))
+        }
+        "fetch_messages_deprecated" => {
+            let FetchDeprecatedParams {
+                conversation_id,
+                to_fetch,
+                current_len,
+            } = parse(params)?;
+            let (messages, has_more) = call(|rsp| {
+                WarpCmd::RayGun(RayGunCmd::FetchMessagesDeprecated {
+                    conv_id: conversation_id,
+                    to_fetch,
+                    current_len,
+                    rsp,
+                })
+            })
+            .await??;
+            Ok(json!({ "messages": messages, "has_more": has_more }))
+        }
+        "fetch_messages_between" => {
+            let FetchBetweenParams {
+                conversation_id,
+                from,
+                to,
+            } = parse(params)?;
+            let (messages, has_more) = call(|rsp| {
+                WarpCmd::RayGun(RayGunCmd::FetchMessagesBetween {
+                    conv_id: conversation_id,
+                    date_range: from..to,
+                    rsp,
+                })
+            })
+            .await??;
+            Ok(json!({ "messages": messages, "has_more": has_more }))
+        }
+        "fetch_pinned_messages" => {
+            let ConvParams { conversation_id } = parse(params)?;
+            let messages = call(|rsp| {
+                WarpCmd::RayGun(RayGunCmd::FetchPinnedMessages {
+                    conv_id: conversation_id,
+                    rsp,
+                })
+            })
+            .await??;
+            to_value(messages)
+        }
+        // the root message followed by its replies
+        "fetch_thread" => {
+            let ThreadParams {
+                conversation_id,
+                root_id,
+            } = parse(params)?;
+            let messages = call(|rsp| {
+                WarpCmd::RayGun(RayGunCmd::FetchThread {
+                    conv_id: conversation_id,
+                    root_id,
+                    rsp,
+                })
+            })
+            .await??;
+            to_value(messages)
+        }
+        // searches the local index rather than asking RayGun. params are a SearchQuery
+        "search_messages" => {
+            let query: SearchQuery = parse(params)?;
+            to_value(search_index::search(&query))
+        }
+        // searches the conversation's history in RayGun, including messages the index hasn't seen
+        "search_conversation" => {
+            let SearchConversationParams {
+                conversation_id,
+                query,
+            } = parse(params)?;
+            let results = call(|rsp| {
+                WarpCmd::RayGun(RayGunCmd::SearchConversation {
+                    conv_id: conversation_id,
+                    query,
+                    rsp,
+                })
+            })
+            .await??;
+            to_value(results)
+        }
+        "send_message" => {
+            let SendParams {
+                conversation_id,
+                lines,
+                attachments,
+            } = parse(params)?;
+            let (id, stream) = call(|rsp| {
+                WarpCmd::RayGun(RayGunCmd::SendMessage {
+                    conv_id: conversation_id,
+                    msg: lines,
+                    attachments: to_locations(attachments),
+                    rsp,
+                })
+            })
+            .await??;
+            if let Some(stream) = stream {
+                wait_for_attachments(stream).await?;
+            }
+            to_value(id)
+        }
+        "send_message_for_several_chats" => {
+            let SendSeveralParams {
+                conversation_ids,
+                lines,
+                attachments,
+            } = parse(params)?;
+            let results = call(|rsp| {
+                WarpCmd::RayGun(RayGunCmd::SendMessageForSeveralChats {
+                    convs_id: conversation_ids,
+                    msg: lines,
+                    attachments: to_locations(attachments),
+                    rsp,
+                })
+            })
+            .await??;
+            let mut sent = Vec::new();
+            for (conversation_id, (message_id, stream)) in results {
+                if let Some(stream) = stream {
+                    wait_for_attachments(stream).await?;
+                }
+                sent.push(json!({
+                    "conversation_id": conversation_id,
+                    "message_id": message_id,
+                }));
+            }
+            Ok(Value::Array(sent))
+        }
+        "edit_message" => {
+            let EditParams {
+                conversation_id,
+                message_id,
+                lines,
+            } = parse(params)?;
+            call(|rsp| {
+                WarpCmd::RayGun(RayGunCmd::EditMessage {
+                    conv_id: conversation_id,
+                    msg_id: message_id,
+                    msg: lines,
+                    rsp,
+                })
+            })
+            .await??;
+            Ok(Value::Null)
+        }
+        "download_attachment" => {
+            let DownloadParams {
+                conversation_id,
+                message_id,
+                file_name,
+                path,
+            } = parse(params)?;
+            let stream = call(|rsp| {
+                WarpCmd::RayGun(RayGunCmd::DownloadAttachment {
+                    conv_id: conversation_id,
+                    msg_id: message_id,
+                    file_name,
+                    file_path_to_download: path.clone(),
+                    rsp,
+                })
+            })
+            .await??;
+            super::constellation::wait_for_progress(stream).await?;
+            to_value(path)
+        }
+        "delete_message" => {
+            let MessageParams {
+                conversation_id,
+                message_id,
+            } = parse(params)?;
+            call(|rsp| {
+                WarpCmd::RayGun(RayGunCmd::DeleteMessage {
+                    conv_id: conversation_id,
+                    msg_id: message_id,
+                    rsp,
+                })
+            })
+            .await??;
+            Ok(Value::Null)
+        }
+        "reply" => {
+            let ReplyParams {
+                conversation_id,
+                reply_to,
+                lines,
+                attachments,
+            } = parse(params)?;
+            let (id, stream) = call(|rsp| {
+                WarpCmd::RayGun(RayGunCmd::Reply {
+                    conv_id: conversation_id,
+                    reply_to,
+                    msg: lines,
+                    attachments: to_locations(attachments),
+                    rsp,
+                })
+            })
+            .await??;
+            if let Some(stream) = stream {
+                wait_for_attachments(stream).await?;
+            }
+            to_value(id)
+        }
+        "remove_direct_convs" => {
+            let RecipientParams { recipient } = parse(params)?;
+            call(|rsp| WarpCmd::RayGun(RayGunCmd::RemoveDirectConvs { recipient, rsp })).await??;
+            Ok(Value::Null)
+        }
+        "react" => {
+            let ReactParams {
+                conversation_id,
+                message_id,
+                emoji,
+                remove,
+            } = parse(params)?;
+            let reaction_state = if remove {
+                ReactionState::Remove
+            } else {
+                ReactionState::Add
+            };
+            call(|rsp| {
+                WarpCmd::RayGun(RayGunCmd::React {
+                    conversation_id,
+                    message_id,
+                    reaction_state,
+                    emoji,
+                    rsp,
+                })
+            })
+            .await??;
+            Ok(Value::Null)
+        }
+        "pin" => {
+            let PinParams {
+                conversation_id,
+                message_id,
+                pin,
+            } = parse(params)?;
+            let pinstate = if pin { PinState::Pin } else { PinState::Unpin };
+            call(|rsp| {
+                WarpCmd::RayGun(RayGunCmd::Pin {
+                    conversation_id,
+                    message_id,
+                    pinstate,
+                    rsp,
+                })
+            })
+            .await??;
+            Ok(Value::Null)
+        }
+        // the only event raygun supports is the typing indicator
+        "send_event" => {
+            let ConvParams { conversation_id } = parse(params)?;
+            call(|rsp| {
+                WarpCmd::RayGun(RayGunCmd::SendEvent {
+                    conv_id: conversation_id,
+                    event: raygun::MessageEvent::Typing,
+                    rsp,
+                })
+            })
+            .await??;
+            Ok(Value::Null)
+        }
+        _ => Err(RpcError::method_not_found(&format!("raygun.{cmd}"))),
+    }
+}
+
+/// the attachments are only uploaded while the stream is polled. drive it to completion before responding.
+async fn wait_for_attachments(mut stream: AttachmentEventStream) -> Result<(), RpcError> {
+    while let Some(kind) = stream.next().await {
+        match kind {
+            AttachmentKind::Pending(res) => return res.map_err(RpcError::from),
+            AttachmentKind::AttachedProgress(_, progress) => {
+                if let FileProgression::ProgressFailed { name, error, .. } =
+                    FileProgression::from(progress)
+                {
+                    return Err(RpcError::new(
+                        super::WARP_ERROR,
+                        format!("failed to attach {name}: {error:?}"),
+                    ));
+                }
+            }
+        }
+    }
+    Ok(())
+}
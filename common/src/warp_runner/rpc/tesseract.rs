@@ -0,0 +1,30 @@
+use serde_json::{json, Value};
+
+use super::{call, RpcError};
+use crate::warp_runner::{TesseractCmd, WarpCmd};
+
+pub(super) async fn dispatch(cmd: &str, _params: Value) -> Result<Value, RpcError> {
+    match cmd {
+        // only answered before logging in
+        "account_exists" => {
+            let exists =
+                call(|rsp| WarpCmd::Tesseract(TesseractCmd::AccountExists { rsp })).await?;
+            Ok(json!(exists))
+        }
+        "get_mnemonic" => {
+            let mnemonic =
+                call(|rsp| WarpCmd::Tesseract(TesseractCmd::GetMnemonic { rsp })).await??;
+            Ok(json!(mnemonic))
+        }
+        "delete_mnemonic" => {
+            call(|rsp| WarpCmd::Tesseract(TesseractCmd::DeleteMnemonic { rsp })).await??;
+            Ok(Value::Null)
+        }
+        "check_mnemonic_exist" => {
+            let exists =
+                call(|rsp| WarpCmd::Tesseract(TesseractCmd::CheckMnemonicExist { rsp })).await??;
+            Ok(json!(exists))
+        }
+        _ => Err(RpcError::method_not_found(&format!("tesseract.{cmd}"))),
+    }
+}
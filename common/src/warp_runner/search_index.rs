@@ -0,0 +1,525 @@
+//! A local full text index of messages. RayGun can only search one conversation at a time, so every message which
+//! passes through ui_adapter is added to this index. manager::run opens the index once the account is unlocked,
+//! periodically saves it to `STATIC_ARGS.search_index_path` and closes it when warp stops, so the index of one
+//! profile is never saved to another.
+//!
+//! The index holds the text of every message, so it is encrypted with the key from `local_key`. Until the index is
+//! opened nothing is written to disk.
+//!
+//! Search terms are matched as prefixes: "hel" matches "hello".
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use tracing::log;
+use uuid::Uuid;
+use warp::{crypto::DID, raygun};
+
+use crate::{local_key, utils::atomic_write::write_atomic, STATIC_ARGS};
+
+/// the number of characters shown on either side of the first match
+const SNIPPET_CONTEXT: usize = 40;
+
+static SEARCH_INDEX: Lazy<RwLock<SearchIndex>> = Lazy::new(|| RwLock::new(SearchIndex::default()));
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexedMessage {
+    pub id: Uuid,
+    pub conversation_id: Uuid,
+    pub sender: DID,
+    pub date: DateTime<Utc>,
+    pub has_attachment: bool,
+    pub text: String,
+    /// the first message of the thread, or the message itself if it isn't a reply. None if it wasn't known when the
+    /// message was indexed
+    #[serde(default)]
+    pub thread_root: Option<Uuid>,
+}
+
+impl From<&raygun::Message> for IndexedMessage {
+    fn from(msg: &raygun::Message) -> Self {
+        Self {
+            id: msg.id(),
+            conversation_id: msg.conversation_id(),
+            sender: msg.sender(),
+            date: msg.date(),
+            has_attachment: !msg.attachments().is_empty(),
+            text: msg.lines().join("\n"),
+            thread_root: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SearchQuery {
+    /// every term must match. may be empty if one of the filters is set
+    pub text: String,
+    #[serde(default)]
+    pub sender: Option<DID>,
+    #[serde(default)]
+    pub conversation_id: Option<Uuid>,
+    #[serde(default)]
+    pub after: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub before: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub has_attachment: Option<bool>,
+    /// 0 means no limit
+    #[serde(default)]
+    pub limit: usize,
+}
+
+impl SearchQuery {
+    pub fn new(text: &str) -> Self {
+        Self {
+            text: text.into(),
+            ..Default::default()
+        }
+    }
+
+    /// true if neither text nor a filter is set
+    pub fn is_empty(&self) -> bool {
+        tokenize(&self.text).is_empty()
+            && self.sender.is_none()
+            && self.conversation_id.is_none()
+            && self.after.is_none()
+            && self.before.is_none()
+            && self.has_attachment.is_none()
+    }
+
+    fn matches(&self, msg: &IndexedMessage) -> bool {
+        self.sender.as_ref().map_or(true, |x| x == &msg.sender)
+            && self
+                .conversation_id
+                .map_or(true, |x| x == msg.conversation_id)
+            && self.after.map_or(true, |x| msg.date >= x)
+            && self.before.map_or(true, |x| msg.date < x)
+            && self
+                .has_attachment
+                .map_or(true, |x| x == msg.has_attachment)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SearchResult {
+    pub message_id: Uuid,
+    pub conversation_id: Uuid,
+    pub sender: DID,
+    pub date: DateTime<Utc>,
+    pub has_attachment: bool,
+    /// part of the message text surrounding the first match
+    pub snippet: String,
+}
+
+#[derive(Debug, Default)]
+struct SearchIndex {
+    // term -> ids of the messages containing it
+    terms: BTreeMap<String, HashSet<Uuid>>,
+    messages: HashMap<Uuid, IndexedMessage>,
+    // thread root -> ids of the replies in the thread
+    threads: HashMap<Uuid, HashSet<Uuid>>,
+    /// set by `open`. the index is only saved while it is open
+    open: bool,
+    /// incremented on every change, so a save knows whether the index changed while it was written
+    changes: u64,
+    saved: u64,
+}
+
+impl SearchIndex {
+    fn insert(&mut self, mut msg: IndexedMessage) {
+        if let Some(prev) = self.messages.get(&msg.id) {
+            // the root only has to be found once
+            if msg.thread_root.is_none() {
+                msg.thread_root = prev.thread_root;
+            }
+            if prev.text == msg.text
+                && prev.has_attachment == msg.has_attachment
+                && prev.thread_root == msg.thread_root
+            {
+                return;
+            }
+            self.remove(msg.id);
+        }
+        for term in tokenize(&msg.text) {
+            self.terms.entry(term).or_default().insert(msg.id);
+        }
+        if let Some(root) = msg.thread_root.filter(|root| *root != msg.id) {
+            self.threads.entry(root).or_default().insert(msg.id);
+        }
+        self.messages.insert(msg.id, msg);
+        self.changes += 1;
+    }
+
+    fn remove(&mut self, id: Uuid) {
+        let Some(msg) = self.messages.remove(&id) else {
+            return;
+        };
+        for term in tokenize(&msg.text) {
+            if let Some(ids) = self.terms.get_mut(&term) {
+                ids.remove(&id);
+                if ids.is_empty() {
+                    self.terms.remove(&term);
+                }
+            }
+        }
+        if let Some(root) = msg.thread_root {
+            if let Some(ids) = self.threads.get_mut(&root) {
+                ids.remove(&id);
+                if ids.is_empty() {
+                    self.threads.remove(&root);
+                }
+            }
+        }
+        self.changes += 1;
+    }
+
+    fn remove_conversation(&mut self, conversation_id: Uuid) {
+        let ids: Vec<Uuid> = self
+            .messages
+            .values()
+            .filter(|m| m.conversation_id == conversation_id)
+            .map(|m| m.id)
+            .collect();
+        for id in ids {
+            self.remove(id);
+        }
+    }
+
+    // ids of the messages with a term starting with `prefix`
+    fn prefix_matches(&self, prefix: &str) -> HashSet<Uuid> {
+        self.terms
+            .range(prefix.to_string()..)
+            .take_while(|(term, _)| term.starts_with(prefix))
+            .flat_map(|(_, ids)| ids.iter().copied())
+            .collect()
+    }
+
+    fn search(&self, query: &SearchQuery) -> Vec<SearchResult> {
+        if query.is_empty() {
+            return vec![];
+        }
+        let terms = tokenize(&query.text);
+        let candidates: Box<dyn Iterator<Item = &IndexedMessage>> = if terms.is_empty() {
+            Box::new(self.messages.values())
+        } else {
+            let mut ids: Option<HashSet<Uuid>> = None;
+            for term in &terms {
+                let matches = self.prefix_matches(term);
+                ids = Some(match ids {
+                    Some(ids) => ids.intersection(&matches).copied().collect(),
+                    None => matches,
+                });
+            }
+            Box::new(
+                ids.unwrap_or_default()
+                    .into_iter()
+                    .filter_map(|id| self.messages.get(&id)),
+            )
+        };
+
+        let mut results: Vec<&IndexedMessage> = candidates.filter(|m| query.matches(m)).collect();
+        results.sort_by(|a, b| b.date.cmp(&a.date));
+        if query.limit > 0 {
+            results.truncate(query.limit);
+        }
+        results
+            .into_iter()
+            .map(|m| m.to_result(terms.first().map(String::as_str)))
+            .collect()
+    }
+}
+
+impl IndexedMessage {
+    fn to_result(&self, term: Option<&str>) -> SearchResult {
+        SearchResult {
+            message_id: self.id,
+            conversation_id: self.conversation_id,
+            sender: self.sender.clone(),
+            date: self.date,
+            has_attachment: self.has_attachment,
+            snippet: snippet(&self.text, term),
+        }
+    }
+}
+
+/// splits text into lowercase words
+pub fn tokenize(text: &str) -> Vec<String> {
+    let mut terms: Vec<String> = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|s| !s.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    terms.sort();
+    terms.dedup();
+    terms
+}
+
+fn snippet(text: &str, term: Option<&str>) -> String {
+    let text = text.replace('\n', " ");
+    let lower = text.to_lowercase();
+    // lowercasing may change byte offsets for some scripts. only use the match position if it is still valid
+    let start = term
+        .and_then(|t| lower.find(t))
+        .filter(|idx| text.is_char_boundary(*idx))
+        .unwrap_or(0);
+    let before: String = text[..start]
+        .chars()
+        .rev()
+        .take(SNIPPET_CONTEXT)
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .collect();
+    let after: String = text[start..].chars().take(SNIPPET_CONTEXT * 2).collect();
+    let prefix = if before.len() < start { "…" } else { "" };
+    let suffix = if start + after.len() < text.len() {
+        "…"
+    } else {
+        ""
+    };
+    format!("{prefix}{before}{after}{suffix}")
+}
+
+/// byte range of the first occurrence of `term` in `text`, ignoring case
+pub fn find_highlight(text: &str, term: &str) -> Option<(usize, usize)> {
+    if term.is_empty() {
+        return None;
+    }
+    let start = text.to_lowercase().find(term)?;
+    let end = start + term.len();
+    // lowercasing can change byte offsets for some scripts
+    (text.is_char_boundary(start) && text.is_char_boundary(end)).then_some((start, end))
+}
+
+/// Searches messages which were fetched from RayGun instead of the index. Terms are matched the same way as in
+/// `search`. The order of `messages` is kept.
+pub fn search_messages<'a>(
+    query: &SearchQuery,
+    messages: impl IntoIterator<Item = &'a raygun::Message>,
+) -> Vec<SearchResult> {
+    if query.is_empty() {
+        return vec![];
+    }
+    let terms = tokenize(&query.text);
+    let results = messages
+        .into_iter()
+        .map(IndexedMessage::from)
+        .filter(|m| query.matches(m))
+        .filter(|m| {
+            let words = tokenize(&m.text);
+            terms
+                .iter()
+                .all(|term| words.iter().any(|word| word.starts_with(term.as_str())))
+        })
+        .map(|m| m.to_result(terms.first().map(String::as_str)));
+    if query.limit > 0 {
+        results.take(query.limit).collect()
+    } else {
+        results.collect()
+    }
+}
+
+/// `thread_root` is the first message of the thread, or the message itself if it isn't a reply
+pub fn index_message(msg: &raygun::Message, thread_root: Option<Uuid>) {
+    let mut indexed = IndexedMessage::from(msg);
+    indexed.thread_root = thread_root;
+    SEARCH_INDEX.write().insert(indexed);
+}
+
+pub fn index_messages<'a>(msgs: impl IntoIterator<Item = &'a raygun::Message>) {
+    let mut index = SEARCH_INDEX.write();
+    for msg in msgs {
+        index.insert(msg.into());
+    }
+}
+
+pub fn remove_message(id: Uuid) {
+    SEARCH_INDEX.write().remove(id);
+}
+
+pub fn remove_conversation(conversation_id: Uuid) {
+    SEARCH_INDEX.write().remove_conversation(conversation_id);
+}
+
+/// the first message of the thread containing `id`, if it was found before
+pub fn thread_root(id: Uuid) -> Option<Uuid> {
+    SEARCH_INDEX
+        .read()
+        .messages
+        .get(&id)
+        .and_then(|m| m.thread_root)
+}
+
+/// the number of replies in a thread, including the ones which aren't loaded in the chat
+pub fn thread_replies(root: Uuid) -> usize {
+    SEARCH_INDEX
+        .read()
+        .threads
+        .get(&root)
+        .map(HashSet::len)
+        .unwrap_or_default()
+}
+
+pub fn search(query: &SearchQuery) -> Vec<SearchResult> {
+    SEARCH_INDEX.read().search(query)
+}
+
+/// Loads the saved index. Called once the account is unlocked. Messages indexed before this are kept.
+pub fn open() {
+    if !local_key::is_unlocked() {
+        // without a key the index still works, it just isn't saved
+        log::error!("search index opened without a key");
+        return;
+    }
+    // earlier versions saved the index as plain text
+    let _ = std::fs::remove_file(STATIC_ARGS.search_index_path.with_extension("json"));
+
+    let saved = match load() {
+        Ok(saved) => saved,
+        Err(e) => {
+            // the index can be rebuilt from messages as they are loaded
+            log::error!("failed to load search index: {e}");
+            vec![]
+        }
+    };
+    let mut index = SEARCH_INDEX.write();
+    for msg in saved {
+        if !index.messages.contains_key(&msg.id) {
+            index.insert(msg);
+        }
+    }
+    index.open = true;
+}
+
+/// Saves the index and empties it. Called when warp stops, since the next login may be to another profile.
+pub fn close() {
+    save_if_dirty();
+    *SEARCH_INDEX.write() = SearchIndex::default();
+}
+
+fn load() -> anyhow::Result<Vec<IndexedMessage>> {
+    let path = &STATIC_ARGS.search_index_path;
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    let encrypted = std::fs::read(path)?;
+    let data = local_key::decrypt(&encrypted)?;
+    Ok(serde_json::from_slice(&data)?)
+}
+
+/// writes the index to disk if it changed since the last save. the index is only locked while it is copied, so
+/// messages can be indexed while it is encrypted and written.
+pub fn save_if_dirty() {
+    let (messages, changes) = {
+        let index = SEARCH_INDEX.read();
+        if !index.open || index.changes == index.saved {
+            return;
+        }
+        let messages: Vec<IndexedMessage> = index.messages.values().cloned().collect();
+        (messages, index.changes)
+    };
+
+    let result = serde_json::to_vec(&messages)
+        .map_err(anyhow::Error::from)
+        .and_then(|data| local_key::encrypt(&data))
+        .and_then(|encrypted| {
+            write_atomic(&STATIC_ARGS.search_index_path, encrypted).map_err(anyhow::Error::from)
+        });
+    match result {
+        Ok(()) => {
+            let mut index = SEARCH_INDEX.write();
+            index.saved = index.saved.max(changes);
+        }
+        Err(e) => log::error!("failed to save search index: {e}"),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn msg(text: &str, conversation_id: Uuid, date: DateTime<Utc>) -> IndexedMessage {
+        IndexedMessage {
+            id: Uuid::new_v4(),
+            conversation_id,
+            sender: DID::default(),
+            date,
+            has_attachment: false,
+            text: text.into(),
+            thread_root: None,
+        }
+    }
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            tokenize("Hello, world! hello-THERE"),
+            vec!["hello", "there", "world"]
+        );
+    }
+
+    #[test]
+    fn test_search() {
+        let conv = Uuid::new_v4();
+        let now = Utc::now();
+        let mut index = SearchIndex::default();
+        let first = msg(
+            "the quick brown fox",
+            conv,
+            now - chrono::Duration::minutes(1),
+        );
+        let second = msg("a quicker fox", Uuid::new_v4(), now);
+        let first_id = first.id;
+        index.insert(first);
+        index.insert(second);
+
+        let r = index.search(&SearchQuery::new("QUICK fox"));
+        assert_eq!(r.len(), 2);
+        assert!(r[0].date > r[1].date);
+
+        let r = index.search(&SearchQuery {
+            conversation_id: Some(conv),
+            ..SearchQuery::new("fox")
+        });
+        assert_eq!(r.len(), 1);
+        assert_eq!(r[0].message_id, first_id);
+
+        index.remove(first_id);
+        assert_eq!(index.search(&SearchQuery::new("brown")).len(), 0);
+        assert!(!index.terms.contains_key("brown"));
+    }
+
+    #[test]
+    fn test_thread_replies() {
+        let conv = Uuid::new_v4();
+        let now = Utc::now();
+        let mut index = SearchIndex::default();
+        let root = msg("root", conv, now);
+        let root_id = root.id;
+        let mut reply = msg("reply", conv, now);
+        reply.thread_root = Some(root_id);
+        let reply_id = reply.id;
+        index.insert(root);
+        index.insert(reply.clone());
+        assert_eq!(index.threads.get(&root_id).map(HashSet::len), Some(1));
+
+        // indexing the message again without its root keeps the root
+        reply.thread_root = None;
+        index.insert(reply);
+        assert_eq!(index.messages[&reply_id].thread_root, Some(root_id));
+
+        index.remove(reply_id);
+        assert!(!index.threads.contains_key(&root_id));
+    }
+
+    #[test]
+    fn test_snippet() {
+        let text = format!("{} needle {}", "a".repeat(100), "b".repeat(100));
+        let s = snippet(&text, Some("needle"));
+        assert!(s.starts_with('…') && s.ends_with('…'));
+        assert!(s.contains("needle"));
+    }
+}
@@ -11,6 +11,7 @@ use super::Message;
 use crate::{
     state::{self, pending_message::FileProgression},
     warp_runner::{
+        search_index,
         ui_adapter::{convert_raygun_message, did_to_identity},
         Messaging,
     },
@@ -108,11 +109,14 @@ pub async fn convert_message_event(
         MessageEventKind::MessageDeleted {
             conversation_id,
             message_id,
-        } => MessageEvent::Deleted {
-            conversation_id,
-            message_id,
-            most_recent_message: fetch_latest(messaging, conversation_id).await,
-        },
+        } => {
+            search_index::remove_message(message_id);
+            MessageEvent::Deleted {
+                conversation_id,
+                message_id,
+                most_recent_message: fetch_latest(messaging, conversation_id).await,
+            }
+        }
         MessageEventKind::MessageReactionAdded {
             conversation_id,
             message_id,
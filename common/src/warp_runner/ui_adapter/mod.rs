@@ -33,7 +33,17 @@ use warp::{
 
 use tracing::log;
 
-use super::{FetchMessagesConfig, FetchMessagesResponse};
+use super::{
+    search_index::{self, SearchQuery, SearchResult},
+    FetchMessagesConfig, FetchMessagesResponse,
+};
+
+// how far up a reply chain to look for the start of a thread
+const MAX_THREAD_DEPTH: usize = 50;
+// the most messages searched for the replies in a thread
+const MAX_THREAD_MESSAGES: usize = 500;
+// the most recent messages of a conversation searched by search_conversation
+const MAX_SEARCHED_MESSAGES: usize = 5000;
 
 /// the UI needs additional information for message replies, namely the text of the message being replied to.
 /// fetch that before sending the message to the UI.
@@ -41,6 +51,9 @@ use super::{FetchMessagesConfig, FetchMessagesResponse};
 pub struct Message {
     pub inner: warp::raygun::Message,
     pub in_reply_to: Option<(String, Vec<File>, DID)>,
+    /// the first message of the reply chain this message belongs to. None if it isn't a reply.
+    #[serde(default)]
+    pub thread_root: Option<Uuid>,
     is_mention: Option<bool>,
     /// this field exists so that the UI can tell Dioxus when a message has been edited and thus
     /// needs to be re-rendered. Before the addition of this field, the compose view was
@@ -82,7 +95,9 @@ pub struct ChatAdapter {
     pub identities: HashSet<state::identity::Identity>,
 }
 
-/// if a raygun::Message is in reply to another message, attempt to fetch part of the message text
+/// if a raygun::Message is in reply to another message, attempt to fetch part of the message text.
+/// every message passed to the UI goes through here, so this is also where messages are added to the search index.
+/// the index also remembers the thread each message belongs to, so the thread is only looked up once.
 pub async fn convert_raygun_message(
     messaging: &super::Messaging,
     msg: &raygun::Message,
@@ -91,9 +106,22 @@ pub async fn convert_raygun_message(
         Some(id) => messaging.get_message(msg.conversation_id(), id).await.ok(),
         None => None,
     };
+    let thread_root = match msg.replied() {
+        None => None,
+        Some(parent_id) => Some(
+            match (search_index::thread_root(msg.id()), reply.as_ref()) {
+                (Some(root), _) => root,
+                (None, Some(parent)) => find_thread_root(messaging, parent).await,
+                // the parent was deleted. it still started the thread
+                (None, None) => parent_id,
+            },
+        ),
+    };
+    search_index::index_message(msg, Some(thread_root.unwrap_or(msg.id())));
 
     Message {
         inner: msg.clone(),
+        thread_root,
         in_reply_to: reply.map(|msg: raygun::Message| {
             (
                 msg.lines().first().cloned().unwrap_or_default(),
@@ -106,6 +134,73 @@ pub async fn convert_raygun_message(
     }
 }
 
+// follows `replied` until a message which isn't a reply, or whose thread is already known, is found. the messages
+// passed on the way are indexed with the root, so the next reply in the thread doesn't walk the chain again.
+async fn find_thread_root(messaging: &super::Messaging, parent: &raygun::Message) -> Uuid {
+    if let Some(root) = search_index::thread_root(parent.id()) {
+        return root;
+    }
+    let mut chain = vec![parent.clone()];
+    let mut root = parent.id();
+    let mut next = parent.replied();
+    for _ in 0..MAX_THREAD_DEPTH {
+        let Some(id) = next else {
+            break;
+        };
+        if let Some(known) = search_index::thread_root(id) {
+            root = known;
+            break;
+        }
+        match messaging.get_message(parent.conversation_id(), id).await {
+            Ok(msg) => {
+                root = msg.id();
+                next = msg.replied();
+                chain.push(msg);
+            }
+            Err(_) => {
+                // the rest of the chain was deleted
+                root = id;
+                break;
+            }
+        }
+    }
+    for msg in &chain {
+        search_index::index_message(msg, Some(root));
+    }
+    root
+}
+
+/// fetches the first message of a thread followed by every reply to it, oldest first. replies are only searched for
+/// in the `MAX_THREAD_MESSAGES` messages sent after the root.
+pub async fn fetch_thread(
+    conv_id: Uuid,
+    root_id: Uuid,
+    messaging: &mut super::Messaging,
+) -> Result<Vec<Message>, Error> {
+    let root = messaging.get_message(conv_id, root_id).await?;
+    let messages = messaging
+        .get_messages(
+            conv_id,
+            MessageOptions::default()
+                .set_date_range(root.date()..chrono::offset::Utc::now())
+                .set_limit(MAX_THREAD_MESSAGES as _),
+        )
+        .await
+        .and_then(Vec::<_>::try_from)?;
+
+    let replies = FuturesOrdered::from_iter(
+        messages
+            .iter()
+            .filter(|message| message.replied().is_some())
+            .map(|message| convert_raygun_message(messaging, message).boxed()),
+    )
+    .filter(|message| futures::future::ready(message.thread_root == Some(root_id)));
+
+    let mut thread = vec![convert_raygun_message(messaging, &root).await];
+    thread.extend(replies.collect::<Vec<_>>().await);
+    Ok(thread)
+}
+
 pub fn get_uninitialized_identity(did: &DID) -> Result<state::Identity, Error> {
     let mut default: Identity = Default::default();
     default.set_did_key(did.clone());
@@ -243,6 +338,31 @@ pub async fn fetch_messages_between(
     Ok((messages, has_more))
 }
 
+/// searches the history of one conversation in RayGun, newest first. unlike the search index this finds messages
+/// which were never loaded by the UI. only the `MAX_SEARCHED_MESSAGES` most recent messages in the date range are
+/// searched.
+pub async fn search_conversation(
+    conv_id: Uuid,
+    messaging: &mut super::Messaging,
+    query: SearchQuery,
+) -> Result<Vec<SearchResult>, Error> {
+    let date_range = query.after.unwrap_or_default()..query.before.unwrap_or_else(Utc::now);
+    let messages = messaging
+        .get_messages(
+            conv_id,
+            MessageOptions::default()
+                .set_date_range(date_range)
+                .set_reverse()
+                .set_limit(MAX_SEARCHED_MESSAGES as _),
+        )
+        .await
+        .and_then(Vec::<_>::try_from)?;
+
+    // the messages were fetched anyway. this way the global search finds them too
+    search_index::index_messages(&messages);
+    Ok(search_index::search_messages(&query, &messages))
+}
+
 pub async fn fetch_pinned_messages_from_chat(
     conv_id: Uuid,
     messaging: &mut super::Messaging,
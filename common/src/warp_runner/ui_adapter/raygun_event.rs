@@ -1,4 +1,7 @@
-use super::{super::conv_stream, init_conversation, ChatAdapter};
+use super::{
+    super::{conv_stream, search_index},
+    init_conversation, ChatAdapter,
+};
 use derive_more::Display;
 use tracing::log;
 use uuid::Uuid;
@@ -29,6 +32,7 @@ pub async fn convert_raygun_event(
         }
         RayGunEventKind::ConversationDeleted { conversation_id } => {
             stream_manager.remove_stream(conversation_id);
+            search_index::remove_conversation(conversation_id);
             RayGunEvent::ConversationDeleted(conversation_id)
         }
     };
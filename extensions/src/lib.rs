@@ -17,6 +17,14 @@ pub trait Extension {
     fn details(&self) -> Details;
     fn stylesheet(&self) -> String;
     fn render<'a>(&self, cx: &'a ScopeState, runtime: std::rc::Rc<Runtime>) -> Element<'a>;
+    /// Slash commands this extension adds to the chatbar
+    fn commands(&self) -> Vec<SlashCommand> {
+        vec![]
+    }
+    /// Called when one of `commands` is submitted. Returns the message to send in its place, if any.
+    fn run_command(&self, _name: &str, _args: Vec<String>) -> Option<String> {
+        None
+    }
     fn rustc_version(&self) -> &'static str {
         RUSTC_VERSION
     }
@@ -48,6 +56,18 @@ macro_rules! export_extension {
             $a.render(cx, runtime)
         }
 
+        #[doc(hidden)]
+        #[no_mangle]
+        pub extern "C" fn commands() -> Vec<$crate::SlashCommand> {
+            $a.commands()
+        }
+
+        #[doc(hidden)]
+        #[no_mangle]
+        pub extern "C" fn run_command(name: &str, args: Vec<String>) -> Option<String> {
+            $a.run_command(name, args)
+        }
+
         #[doc(hidden)]
         #[no_mangle]
         pub extern "C" fn rustc_version() -> &'static str {
@@ -68,6 +88,7 @@ pub struct UplinkExtension {
     lib: libloading::Library,
     details: Details,
     stylesheet: String,
+    commands: Vec<SlashCommand>,
     rustc_version: &'static str,
     cargo_version: &'static str,
 }
@@ -82,10 +103,16 @@ impl UplinkExtension {
                 lib.get::<unsafe extern "C" fn() -> &'static str>(b"rustc_version\0")?();
             let cargo_version =
                 lib.get::<unsafe extern "C" fn() -> &'static str>(b"cargo_version\0")?();
+            // extensions built before slash commands were added don't export this
+            let commands = lib
+                .get::<unsafe extern "C" fn() -> Vec<SlashCommand>>(b"commands\0")
+                .map(|f| f())
+                .unwrap_or_default();
             Ok(Self {
                 lib,
                 details,
                 stylesheet,
+                commands,
                 rustc_version,
                 cargo_version,
             })
@@ -112,6 +139,24 @@ impl UplinkExtension {
         }
     }
 
+    pub fn commands(&self) -> &[SlashCommand] {
+        &self.commands
+    }
+
+    pub fn run_command(&self, name: &str, args: Vec<String>) -> Option<String> {
+        unsafe {
+            let res = self
+                .lib
+                .get::<unsafe extern "C" fn(name: &str, args: Vec<String>) -> Option<String>>(
+                    b"run_command\0",
+                );
+            match res {
+                Ok(f) => f(name, args),
+                Err(_) => None,
+            }
+        }
+    }
+
     pub fn rustc_version(&self) -> &'static str {
         self.rustc_version
     }
@@ -163,3 +208,14 @@ pub struct Meta {
     pub pretty_name: &'static str,
     pub description: &'static str,
 }
+
+// A command typed into the chatbar as `/<name> <args>`
+#[repr(C)]
+#[derive(Clone)]
+pub struct SlashCommand {
+    // Without the leading slash
+    pub name: &'static str,
+    // Shown after the name when suggesting commands. ex: "<text>"
+    pub args: &'static str,
+    pub description: &'static str,
+}
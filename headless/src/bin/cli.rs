@@ -0,0 +1,222 @@
+//! uplink-cli: runs a single command against an Uplink account and exits. Intended for scripts and cron jobs.
+//!
+//! The account is unlocked with the `UPLINK_PASSPHRASE` environment variable or `--passphrase-file`. Warp keeps
+//! a lock on its store, so the cli can't be used on a profile that is open in Uplink or uplink-daemon.
+//!
+//! Results are written to stdout as JSON.
+
+use std::path::PathBuf;
+
+use anyhow::Context;
+use clap::{Parser, Subcommand};
+use common::{
+    warp_runner::{rpc, WarpRunner},
+    STATIC_ARGS,
+};
+use serde_json::{json, Value};
+
+#[derive(Debug, Parser)]
+#[clap(name = "uplink-cli")]
+struct Cli {
+    #[clap(flatten)]
+    args: common::Args,
+    /// read the passphrase from this file instead of UPLINK_PASSPHRASE
+    #[clap(long)]
+    passphrase_file: Option<PathBuf>,
+    #[clap(subcommand)]
+    cmd: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// print your DID
+    Whoami,
+    /// list conversations
+    Chats,
+    /// send a message. the chat may be a conversation id or a group name
+    Send {
+        chat: String,
+        message: Vec<String>,
+        /// attach a file from disk. may be repeated
+        #[clap(long = "file")]
+        files: Vec<PathBuf>,
+    },
+    /// print the most recent messages of a conversation
+    Messages {
+        chat: String,
+        #[clap(long, default_value_t = 20)]
+        limit: usize,
+    },
+    /// search the local message index across all conversations
+    Search {
+        query: Vec<String>,
+        /// only search this chat
+        #[clap(long)]
+        chat: Option<String>,
+        #[clap(long, default_value_t = 20)]
+        limit: usize,
+    },
+    /// list friends and pending friend requests
+    Friends,
+    /// send a friend request to a DID or Username#short_id
+    RequestFriend { id: String },
+    /// accept an incoming friend request
+    AcceptRequest { did: String },
+    /// upload files to the root of your storage
+    Upload { files: Vec<PathBuf> },
+    /// download a file from the root of your storage
+    Download { file_name: String, dest: PathBuf },
+}
+
+fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+    common::override_args(cli.args);
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn")).init();
+
+    let passphrase = match cli.passphrase_file {
+        Some(path) => std::fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?
+            .trim_end_matches(['\r', '\n'])
+            .to_string(),
+        None => std::env::var("UPLINK_PASSPHRASE")
+            .context("set UPLINK_PASSPHRASE or pass --passphrase-file")?,
+    };
+
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()?;
+
+    runtime.block_on(async move {
+        std::fs::create_dir_all(&STATIC_ARGS.warp_path)?;
+
+        let mut runner = WarpRunner::new();
+        runner.run();
+
+        call("multipass.try_log_in", json!({ "passphrase": passphrase })).await?;
+        let r = run(cli.cmd).await?;
+        println!("{}", serde_json::to_string_pretty(&r)?);
+
+        drop(runner);
+        Ok(())
+    })
+}
+
+async fn run(cmd: Command) -> anyhow::Result<Value> {
+    match cmd {
+        Command::Whoami => call("multipass.get_own_did", Value::Null).await,
+        Command::Chats => {
+            let init = call("raygun.initialize_warp", Value::Null).await?;
+            let chats = init["chats"]
+                .as_array()
+                .cloned()
+                .unwrap_or_default()
+                .into_iter()
+                .map(|chat| {
+                    json!({
+                        "id": chat["id"],
+                        "name": chat["conversation_name"],
+                        "participants": chat["participants"],
+                    })
+                })
+                .collect();
+            Ok(Value::Array(chats))
+        }
+        Command::Send {
+            chat,
+            message,
+            files,
+        } => {
+            let conversation_id = resolve_chat(&chat).await?;
+            // each argument is a line, same as the lines of a message typed into the chatbar
+            let message_id = call(
+                "raygun.send_message",
+                json!({
+                    "conversation_id": conversation_id,
+                    "lines": message,
+                    "attachments": files,
+                }),
+            )
+            .await?;
+            Ok(json!({ "conversation_id": conversation_id, "message_id": message_id }))
+        }
+        Command::Messages { chat, limit } => {
+            let conversation_id = resolve_chat(&chat).await?;
+            let r = call(
+                "raygun.fetch_messages",
+                json!({ "conversation_id": conversation_id, "limit": limit }),
+            )
+            .await?;
+            Ok(r["messages"].clone())
+        }
+        Command::Search { query, chat, limit } => {
+            let conversation_id = match chat {
+                Some(chat) => Some(resolve_chat(&chat).await?),
+                None => None,
+            };
+            call(
+                "raygun.search_messages",
+                json!({
+                    "text": query.join(" "),
+                    "conversation_id": conversation_id,
+                    "limit": limit,
+                }),
+            )
+            .await
+        }
+        Command::Friends => {
+            let init = call("raygun.initialize_warp", Value::Null).await?;
+            Ok(init["friends"].clone())
+        }
+        Command::RequestFriend { id } => {
+            call("multipass.request_friend", json!({ "id": id })).await
+        }
+        Command::AcceptRequest { did } => {
+            call("multipass.accept_request", json!({ "did": did })).await
+        }
+        Command::Upload { files } => {
+            // files passed on the command line are relative to the caller
+            let files = files
+                .into_iter()
+                .map(std::fs::canonicalize)
+                .collect::<Result<Vec<_>, _>>()?;
+            call("constellation.upload_files", json!({ "files": files })).await
+        }
+        Command::Download { file_name, dest } => {
+            call(
+                "constellation.download_file",
+                json!({ "file_name": file_name, "path": dest }),
+            )
+            .await
+        }
+    }
+}
+
+// accepts either a conversation id or the name of a group chat
+async fn resolve_chat(chat: &str) -> anyhow::Result<String> {
+    if uuid_like(chat) {
+        return Ok(chat.to_string());
+    }
+    let init = call("raygun.initialize_warp", Value::Null).await?;
+    let matches: Vec<String> = init["chats"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|c| c["conversation_name"].as_str() == Some(chat))
+        .filter_map(|c| c["id"].as_str().map(String::from))
+        .collect();
+    match matches.as_slice() {
+        [id] => Ok(id.clone()),
+        [] => anyhow::bail!("no conversation named {chat}"),
+        _ => anyhow::bail!("more than one conversation is named {chat}. use the conversation id"),
+    }
+}
+
+fn uuid_like(s: &str) -> bool {
+    s.len() == 36 && s.chars().all(|c| c.is_ascii_hexdigit() || c == '-')
+}
+
+async fn call(method: &str, params: Value) -> anyhow::Result<Value> {
+    rpc::dispatch(method, params)
+        .await
+        .with_context(|| format!("{method} failed"))
+}
@@ -0,0 +1,59 @@
+//! uplink-daemon: boots warp_runner without a window and serves the control socket.
+//!
+//! Accepts the same arguments as uplink (`--path`, `--discovery`, ...) plus `--socket`. If the `UPLINK_PASSPHRASE`
+//! environment variable is set, the account is unlocked on startup. Otherwise a client must call
+//! `multipass.try_log_in` (or `create_identity` / `recover_identity`) before anything else.
+
+#[cfg(unix)]
+fn main() -> anyhow::Result<()> {
+    use clap::Parser;
+    use common::{warp_runner::WarpRunner, STATIC_ARGS};
+    use serde_json::json;
+
+    // validates the arguments. STATIC_ARGS parses them again on first use.
+    let _ = common::Args::parse();
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()?;
+
+    runtime.block_on(async {
+        std::fs::create_dir_all(&STATIC_ARGS.uplink_path)?;
+        std::fs::create_dir_all(&STATIC_ARGS.warp_path)?;
+
+        // dropping the runner stops warp_runner
+        let mut runner = WarpRunner::new();
+        runner.run();
+
+        if let Ok(passphrase) = std::env::var("UPLINK_PASSPHRASE") {
+            match common::warp_runner::rpc::dispatch(
+                "multipass.try_log_in",
+                json!({ "passphrase": passphrase }),
+            )
+            .await
+            {
+                Ok(identity) => log::info!("logged in as {}", identity["username"]),
+                Err(e) => anyhow::bail!("failed to unlock account: {e}"),
+            }
+        }
+
+        let socket = STATIC_ARGS.control_socket.clone();
+        let r = tokio::select! {
+            r = headless::server::listen(&socket) => r,
+            _ = tokio::signal::ctrl_c() => {
+                log::info!("shutting down");
+                Ok(())
+            }
+        };
+        let _ = std::fs::remove_file(&socket);
+        drop(runner);
+        r
+    })
+}
+
+#[cfg(not(unix))]
+fn main() {
+    eprintln!("uplink-daemon requires unix domain sockets");
+    std::process::exit(1);
+}
@@ -0,0 +1,6 @@
+//! Runs warp_runner without the Dioxus desktop app. The daemon exposes every `WarpCmd` over a unix domain socket
+//! using newline delimited JSON-RPC messages (see `common::warp_runner::rpc`). uplink-cli uses the same rpc layer to run
+//! one command against an account and exit.
+
+#[cfg(unix)]
+pub mod server;
@@ -0,0 +1,174 @@
+//! Accepts connections on the control socket. Each line received is an `RpcRequest` and each line sent is either an
+//! `RpcResponse` or, for clients which called `events.subscribe`, an `RpcNotification`.
+//!
+//! Requests are handled concurrently, so responses may arrive out of order. Use the request `id` to match them up.
+
+use std::{
+    fs::DirBuilder,
+    os::unix::fs::{DirBuilderExt, PermissionsExt},
+    path::Path,
+};
+
+use common::{
+    warp_runner::rpc::{self, RpcError, RpcRequest, RpcResponse, INVALID_REQUEST, PARSE_ERROR},
+    WARP_EVENT_CH,
+};
+use serde_json::{json, Value};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::{UnixListener, UnixStream},
+    sync::mpsc,
+    task::JoinHandle,
+};
+
+pub async fn listen(path: &Path) -> anyhow::Result<()> {
+    // a stale socket is left behind if the daemon was killed
+    if path.exists() {
+        std::fs::remove_file(path)?;
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let listener = bind_private(path)?;
+    log::info!("listening on {}", path.display());
+
+    loop {
+        let (stream, _) = listener.accept().await?;
+        tokio::spawn(async move {
+            if let Err(e) = handle_connection(stream).await {
+                log::warn!("connection closed: {e}");
+            }
+        });
+    }
+}
+
+/// The socket grants full control over the account, so only the owner may connect. `bind` creates it with the
+/// process umask, which would leave it open to other users until its permissions are changed. It is bound in a
+/// directory only the owner can enter instead, and moved into place once it is 0600.
+fn bind_private(path: &Path) -> anyhow::Result<UnixListener> {
+    let parent = path.parent().unwrap_or_else(|| Path::new("."));
+    let private_dir = parent.join(format!(".warp_runner-{}", std::process::id()));
+    if private_dir.exists() {
+        std::fs::remove_dir_all(&private_dir)?;
+    }
+    DirBuilder::new().mode(0o700).create(&private_dir)?;
+
+    let tmp_path = private_dir.join("sock");
+    let bound = UnixListener::bind(&tmp_path).and_then(|listener| {
+        std::fs::set_permissions(&tmp_path, std::fs::Permissions::from_mode(0o600))?;
+        std::fs::rename(&tmp_path, path)?;
+        Ok(listener)
+    });
+    let _ = std::fs::remove_dir_all(&private_dir);
+    Ok(bound?)
+}
+
+async fn handle_connection(stream: UnixStream) -> anyhow::Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+
+    // everything written to the socket goes through this channel so that request tasks and the event task
+    // don't interleave their output
+    let (tx, mut rx) = mpsc::unbounded_channel::<String>();
+    let write_task = tokio::spawn(async move {
+        while let Some(mut line) = rx.recv().await {
+            line.push('\n');
+            if writer.write_all(line.as_bytes()).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut events: Option<JoinHandle<()>> = None;
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let req = match serde_json::from_str::<RpcRequest>(&line) {
+            Ok(r) => r,
+            Err(e) => {
+                send(
+                    &tx,
+                    RpcResponse::new(None, Err(RpcError::new(PARSE_ERROR, e.to_string()))),
+                );
+                continue;
+            }
+        };
+
+        match req.method.as_str() {
+            "events.subscribe" => {
+                if events.is_none() {
+                    events = Some(forward_events(tx.clone()));
+                }
+                send(&tx, RpcResponse::new(req.id, Ok(Value::Null)));
+            }
+            "events.unsubscribe" => {
+                if let Some(handle) = events.take() {
+                    handle.abort();
+                }
+                send(&tx, RpcResponse::new(req.id, Ok(Value::Null)));
+            }
+            "" => send(
+                &tx,
+                RpcResponse::new(
+                    req.id,
+                    Err(RpcError::new(INVALID_REQUEST, "missing method")),
+                ),
+            ),
+            _ => {
+                let tx = tx.clone();
+                tokio::spawn(async move {
+                    let r = rpc::dispatch(&req.method, req.params).await;
+                    if let Err(e) = &r {
+                        log::debug!("{} failed: {e}", req.method);
+                    }
+                    send(&tx, RpcResponse::new(req.id, r));
+                });
+            }
+        }
+    }
+
+    if let Some(handle) = events.take() {
+        handle.abort();
+    }
+    drop(tx);
+    let _ = write_task.await;
+    Ok(())
+}
+
+fn forward_events(tx: mpsc::UnboundedSender<String>) -> JoinHandle<()> {
+    let mut rx = WARP_EVENT_CH.tx.subscribe();
+    tokio::spawn(async move {
+        loop {
+            let evt = match rx.recv().await {
+                Ok(evt) => evt,
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(n)) => {
+                    let lagged = json!({ "method": "event.lagged", "params": { "skipped": n } });
+                    if tx.send(lagged.to_string()).is_err() {
+                        break;
+                    }
+                    continue;
+                }
+                Err(_) => break,
+            };
+            let notification = rpc::event_to_notification(&evt);
+            match serde_json::to_string(&notification) {
+                Ok(s) => {
+                    if tx.send(s).is_err() {
+                        break;
+                    }
+                }
+                Err(e) => log::error!("failed to serialize event: {e}"),
+            }
+        }
+    })
+}
+
+fn send(tx: &mpsc::UnboundedSender<String>, rsp: RpcResponse) {
+    match serde_json::to_string(&rsp) {
+        Ok(s) => {
+            let _ = tx.send(s);
+        }
+        Err(e) => log::error!("failed to serialize response: {e}"),
+    }
+}
@@ -0,0 +1,191 @@
+//! Link previews are kept on disk so a page is only fetched again once its preview is older than `TTL`. Links without
+//! a preview are remembered too, for a shorter time, so they aren't requested again on every render.
+//!
+//! The cache shows which links were opened, so it is encrypted with the account's local key and only kept in memory
+//! until an account is logged in. Changes are written by a background thread, at most once every `SAVE_DELAY`.
+
+use std::{
+    collections::HashMap,
+    sync::{
+        mpsc::{self, Receiver, RecvTimeoutError, Sender},
+        Mutex,
+    },
+    time::{Duration as StdDuration, Instant},
+};
+
+use chrono::{DateTime, Duration, Utc};
+use common::{local_key, utils::atomic_write::write_atomic, STATIC_ARGS};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tracing::log;
+
+use super::SiteMeta;
+
+const TTL_HOURS: i64 = 24;
+const FAILED_TTL_HOURS: i64 = 1;
+// the oldest previews are dropped past this
+const MAX_ENTRIES: usize = 1000;
+// changes made while a save waits are written together
+const SAVE_DELAY: StdDuration = StdDuration::from_secs(2);
+
+static CACHE: Lazy<Mutex<Cache>> = Lazy::new(Default::default);
+
+static WRITER: Lazy<Sender<(u64, HashMap<String, Entry>)>> = Lazy::new(|| {
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || write_cache(rx));
+    tx
+});
+
+#[derive(Default)]
+struct Cache {
+    entries: HashMap<String, Entry>,
+    /// the `local_key::generation` the entries were loaded for. None until an account is logged in
+    generation: Option<u64>,
+}
+
+impl Cache {
+    /// Loads the previews of the logged in account, if they aren't loaded yet
+    fn refresh(&mut self) {
+        let generation = local_key::generation();
+        if self.generation == Some(generation) || !local_key::is_unlocked() {
+            return;
+        }
+        self.entries = load();
+        self.generation = Some(generation);
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Entry {
+    /// None if the link has no preview
+    meta: Option<SiteMeta>,
+    fetched: DateTime<Utc>,
+}
+
+impl Entry {
+    fn is_fresh(&self, now: DateTime<Utc>) -> bool {
+        let ttl = if self.meta.is_some() {
+            TTL_HOURS
+        } else {
+            FAILED_TTL_HOURS
+        };
+        now < self.fetched + Duration::hours(ttl)
+    }
+}
+
+fn load() -> HashMap<String, Entry> {
+    let Ok(contents) = std::fs::read(&STATIC_ARGS.link_previews_path) else {
+        return HashMap::new();
+    };
+    let cache = match local_key::decrypt(&contents) {
+        Ok(data) => serde_json::from_slice(&data).map_err(|e| e.to_string()),
+        Err(e) => Err(e.to_string()),
+    };
+    match cache {
+        Ok(cache) => cache,
+        Err(e) => {
+            log::error!("failed to load link previews: {e}");
+            HashMap::new()
+        }
+    }
+}
+
+// only the newest cache received during SAVE_DELAY is written, and only if it belongs to the logged in account
+fn write_cache(rx: Receiver<(u64, HashMap<String, Entry>)>) {
+    while let Ok(mut latest) = rx.recv() {
+        let deadline = Instant::now() + SAVE_DELAY;
+        loop {
+            match rx.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                Ok(newer) => latest = newer,
+                Err(RecvTimeoutError::Timeout) | Err(RecvTimeoutError::Disconnected) => break,
+            }
+        }
+        let (generation, cache) = latest;
+        if generation != local_key::generation() {
+            continue;
+        }
+        let result = match serde_json::to_vec(&cache) {
+            Ok(data) => local_key::encrypt(&data).map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        }
+        .and_then(|encrypted| {
+            write_atomic(&STATIC_ARGS.link_previews_path, encrypted).map_err(|e| e.to_string())
+        });
+        if let Err(e) = result {
+            log::error!("failed to save link previews: {e}");
+        }
+    }
+}
+
+/// Returns None if the link wasn't fetched recently, and Some(None) if it has no preview
+pub fn get(url: &str) -> Option<Option<SiteMeta>> {
+    let mut cache = CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    cache.refresh();
+    cache
+        .entries
+        .get(url)
+        .filter(|entry| entry.is_fresh(Utc::now()))
+        .map(|entry| entry.meta.clone())
+}
+
+pub fn insert(url: &str, meta: Option<SiteMeta>) {
+    let mut cache = CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    cache.refresh();
+    let now = Utc::now();
+    let entries = &mut cache.entries;
+    entries.retain(|_, entry| entry.is_fresh(now));
+    entries.insert(url.to_string(), Entry { meta, fetched: now });
+    if entries.len() > MAX_ENTRIES {
+        let mut by_age: Vec<(String, DateTime<Utc>)> = entries
+            .iter()
+            .map(|(url, entry)| (url.clone(), entry.fetched))
+            .collect();
+        by_age.sort_by_key(|(_, fetched)| *fetched);
+        for (url, _) in by_age.into_iter().take(entries.len() - MAX_ENTRIES) {
+            entries.remove(&url);
+        }
+    }
+    let Some(generation) = cache.generation else {
+        return;
+    };
+    if WRITER.send((generation, cache.entries.clone())).is_err() {
+        log::error!("failed to save link previews: the writer stopped");
+    }
+}
+
+/// Forgets every preview, so links are fetched again
+pub fn clear() {
+    let mut cache = CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    cache.entries.clear();
+    // replaces a save which is still waiting
+    if let Some(generation) = cache.generation {
+        let _ = WRITER.send((generation, HashMap::new()));
+    }
+    if let Err(e) = std::fs::remove_file(&STATIC_ARGS.link_previews_path) {
+        if e.kind() != std::io::ErrorKind::NotFound {
+            log::error!("failed to remove link previews: {e}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_failed_fetches_expire_sooner() {
+        let fetched = Utc::now();
+        let later = fetched + Duration::hours(FAILED_TTL_HOURS) + Duration::minutes(1);
+        let preview = Entry {
+            meta: Some(SiteMeta::default()),
+            fetched,
+        };
+        let failed = Entry {
+            meta: None,
+            fetched,
+        };
+        assert!(preview.is_fresh(later));
+        assert!(!failed.is_fresh(later));
+        assert!(!preview.is_fresh(fetched + Duration::hours(TTL_HOURS)));
+    }
+}
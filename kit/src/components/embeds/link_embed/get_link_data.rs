@@ -3,6 +3,8 @@ use std::error::Error;
 use reqwest::Url;
 use scraper::{Html, Selector};
 
+use super::{Embed, CLIENT};
+
 pub fn get_image_data(document: Html, meta_selector: Selector) -> Option<String> {
     let mut image = None;
 
@@ -23,6 +25,22 @@ pub fn get_image_data(document: Html, meta_selector: Selector) -> Option<String>
     image
 }
 
+fn get_property(document: &Html, meta_selector: &Selector, property: &str) -> Option<String> {
+    document
+        .select(meta_selector)
+        .filter(|element| element.value().attr("property") == Some(property))
+        .filter_map(|element| element.value().attr("content"))
+        .find(|content| !content.is_empty())
+        .map(|content| content.to_string())
+}
+
+// the OpenGraph image of the page. its og:video isn't used: the page could load anything in the iframe
+pub fn get_embed_data(document: &Html, meta_selector: &Selector) -> Option<Embed> {
+    get_property(document, meta_selector, "og:image")
+        .filter(|src| src.starts_with("https://"))
+        .map(Embed::Image)
+}
+
 pub fn get_title_data(document: Html, meta_selector: Selector) -> String {
     let title_selector = match Selector::parse("title") {
         Ok(data) => data,
@@ -110,7 +128,7 @@ pub async fn fetch_icon(url: &str, document: Html) -> Result<Option<String>, Box
             };
             if let Some(path) = element.value().attr(attr) {
                 let full_url = base_url.join(path)?.to_string();
-                let resp = reqwest::get(&full_url).await?;
+                let resp = CLIENT.get(&full_url).send().await?;
                 if resp.status().is_success() {
                     return Ok(Some(full_url));
                 }
@@ -1,33 +1,92 @@
+use std::time::Duration;
+
 use crate::components::embeds::youtube::YouTubePlayer;
+use crate::elements::{button::Button, Appearance};
+use common::{icons::outline::Shape as Icon, language::get_local_text_with_args};
 use dioxus::prelude::*;
 use dioxus::prelude::{rsx, Props};
 use dioxus_core::{Element, Scope};
 use dioxus_hooks::use_future;
+use once_cell::sync::Lazy;
+use reqwest::header::CONTENT_TYPE;
 use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+use tracing::log;
 
 use self::get_link_data::*;
 
+mod cache;
 mod get_link_data;
+mod oembed;
+
+pub use cache::clear as clear_cache;
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(5);
+// the preview is in the head of a page, the rest isn't downloaded
+const MAX_PAGE_SIZE: usize = 512 * 1024;
+
+static CLIENT: Lazy<reqwest::Client> = Lazy::new(|| {
+    reqwest::Client::builder()
+        .timeout(FETCH_TIMEOUT)
+        .redirect(reqwest::redirect::Policy::limited(5))
+        .build()
+        .unwrap_or_default()
+});
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Embed {
+    /// the address of the player of a known provider, shown in an iframe once the user asks for it
+    Frame(String),
+    Image(String),
+}
 
-#[derive(Default, Debug, Clone, Eq, PartialEq)]
+#[derive(Default, Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct SiteMeta {
     pub title: String,
     pub description: String,
     pub icon: String,
     pub url: String,
+    #[serde(default)]
+    pub embed: Option<Embed>,
 }
 
-pub async fn get_meta(url: &str) -> Result<SiteMeta, reqwest::Error> {
-    let content = reqwest::get(url).await?.text().await?;
+// downloads at most MAX_PAGE_SIZE of a text document. anything else is skipped
+async fn fetch(url: &str) -> Result<String, reqwest::Error> {
+    let mut response = CLIENT.get(url).send().await?.error_for_status()?;
+    let is_text = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.starts_with("text/") || value.contains("json") || value.contains("xml"))
+        .unwrap_or(true);
+    if !is_text {
+        return Ok(String::new());
+    }
+
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        body.extend_from_slice(&chunk);
+        if body.len() >= MAX_PAGE_SIZE {
+            body.truncate(MAX_PAGE_SIZE);
+            break;
+        }
+    }
+    Ok(String::from_utf8_lossy(&body).into_owned())
+}
+
+async fn fetch_meta(url: &str) -> Result<SiteMeta, reqwest::Error> {
+    if let Some(meta) = oembed::from_provider(url).await {
+        return Ok(meta);
+    }
+
+    let content = fetch(url).await?;
     let document = Html::parse_document(&content);
     let meta_selector = match Selector::parse("meta") {
         Ok(data) => data,
         Err(_) => {
             return Ok(SiteMeta {
-                title: String::new(),
-                description: String::new(),
-                icon: String::new(),
                 url: String::from(url),
+                ..Default::default()
             });
         }
     };
@@ -39,15 +98,38 @@ pub async fn get_meta(url: &str) -> Result<SiteMeta, reqwest::Error> {
     };
     let title = get_title_data(document.clone(), meta_selector.clone());
     let description = get_description_data(document.clone(), meta_selector.clone());
+    let embed = match oembed::discover(url, &document).await {
+        Some(embed) => Some(embed),
+        None => get_embed_data(&document, &meta_selector),
+    };
 
     Ok(SiteMeta {
         title,
         description,
         icon,
         url: String::from(url),
+        embed,
     })
 }
 
+/// The preview of a link, None if it has none. Taken from the cache if the link was fetched recently
+pub async fn get_meta(url: &str) -> Option<SiteMeta> {
+    if let Some(meta) = cache::get(url) {
+        return meta;
+    }
+    let meta = match fetch_meta(url).await {
+        Ok(meta) => Some(meta).filter(|meta| !meta.title.is_empty()),
+        // may work once uplink is online again
+        Err(e) if e.is_connect() || e.is_timeout() => return None,
+        Err(e) => {
+            log::debug!("failed to fetch the preview of {url}: {e}");
+            None
+        }
+    };
+    cache::insert(url, meta.clone());
+    meta
+}
+
 #[derive(Props, PartialEq)]
 pub struct LinkEmbedProps {
     link: String,
@@ -59,12 +141,10 @@ pub fn EmbedLinks(cx: Scope<LinkEmbedProps>) -> Element {
     let fetch_meta = use_future(cx, &cx.props.link, |link| async move {
         get_meta(link.as_str()).await
     });
+    // players are only loaded when clicked, until then the provider doesn't know the message was seen
+    let load_frame = use_state(cx, || false);
 
-    let meta = match fetch_meta.value() {
-        Some(Ok(val)) => val.clone(),
-        Some(Err(_)) => SiteMeta::default(),
-        None => SiteMeta::default(),
-    };
+    let meta = fetch_meta.value().cloned().flatten().unwrap_or_default();
     let title = if meta.title.chars().count() > 100 {
         meta.title[0..97].to_string() + "..."
     } else {
@@ -82,6 +162,15 @@ pub fn EmbedLinks(cx: Scope<LinkEmbedProps>) -> Element {
     } else {
         None
     };
+    // youtube links are played without asking youtube first
+    let embed = if youtube_video.is_none() {
+        meta.embed.clone().filter(|embed| match embed {
+            Embed::Frame(src) => oembed::is_trusted_player(src),
+            Embed::Image(_) => true,
+        })
+    } else {
+        None
+    };
 
     cx.render(rsx! {
         if meta.title.is_empty() {
@@ -111,7 +200,7 @@ pub fn EmbedLinks(cx: Scope<LinkEmbedProps>) -> Element {
                                 })
                             }
                         },
-                        if desc.is_empty() && youtube_video.is_none() {
+                        if desc.is_empty() && youtube_video.is_none() && embed.is_none() {
                            rsx!(div {})
                         } else {
                             rsx!( div {
@@ -122,6 +211,40 @@ pub fn EmbedLinks(cx: Scope<LinkEmbedProps>) -> Element {
                                         video_url: youtube_video.unwrap(),
                                     }
                                 ))
+                                embed.map(|embed| match embed {
+                                    Embed::Frame(src) if *load_frame.get() => rsx!(div {
+                                        class: "embed-frame",
+                                        aria_label: "embed-frame",
+                                        iframe {
+                                            src: "{src}",
+                                            "sandbox": "allow-scripts allow-presentation",
+                                            allow: "autoplay; encrypted-media; fullscreen; picture-in-picture",
+                                            allowfullscreen: true,
+                                        }
+                                    }),
+                                    Embed::Frame(src) => {
+                                        let host = reqwest::Url::parse(&src)
+                                            .ok()
+                                            .and_then(|url| url.host_str().map(str::to_string))
+                                            .unwrap_or_default();
+                                        rsx!(div {
+                                            class: "embed-frame-placeholder",
+                                            Button {
+                                                aria_label: "load-embed".into(),
+                                                icon: Icon::Play,
+                                                appearance: Appearance::Secondary,
+                                                text: get_local_text_with_args("messages.load-embed", vec![("host", host)]),
+                                                onpress: move |_| load_frame.set(true),
+                                            }
+                                        })
+                                    }
+                                    Embed::Image(src) => rsx!(img {
+                                        class: "embed-image",
+                                        aria_label: "embed-image",
+                                        src: "{src}",
+                                        alt: "",
+                                    }),
+                                })
                                 p {
                                     "{desc}"
                                 }
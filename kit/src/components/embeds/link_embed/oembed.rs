@@ -0,0 +1,205 @@
+//! oEmbed (https://oembed.com) lets a site describe how its content is embedded. Known providers are asked directly,
+//! without loading the page. Other sites can point to their endpoint with a `<link type="application/json+oembed">`
+//! tag, see `discover`.
+//!
+//! Only the players of the known providers are shown in an iframe. Any other site could run its scripts in Uplink
+//! that way, so they only get an image.
+
+use reqwest::Url;
+use scraper::{Html, Selector};
+use serde::Deserialize;
+
+use super::{fetch, Embed, SiteMeta};
+
+struct Provider {
+    /// host and path prefixes of the links the provider embeds, without `www.`
+    schemes: &'static [&'static str],
+    endpoint: &'static str,
+    /// the hosts the provider's player is loaded from
+    player_hosts: &'static [&'static str],
+}
+
+const PROVIDERS: [Provider; 5] = [
+    Provider {
+        schemes: &["youtube.com/watch", "youtube.com/shorts/", "youtu.be/"],
+        endpoint: "https://www.youtube.com/oembed",
+        player_hosts: &["www.youtube.com", "www.youtube-nocookie.com"],
+    },
+    Provider {
+        schemes: &["vimeo.com/"],
+        endpoint: "https://vimeo.com/api/oembed.json",
+        player_hosts: &["player.vimeo.com"],
+    },
+    Provider {
+        schemes: &["open.spotify.com/"],
+        endpoint: "https://open.spotify.com/oembed",
+        player_hosts: &["open.spotify.com"],
+    },
+    Provider {
+        schemes: &["soundcloud.com/"],
+        endpoint: "https://soundcloud.com/oembed",
+        player_hosts: &["w.soundcloud.com"],
+    },
+    Provider {
+        schemes: &["flickr.com/photos/", "flic.kr/"],
+        endpoint: "https://www.flickr.com/services/oembed/",
+        // flickr links are photos
+        player_hosts: &[],
+    },
+];
+
+#[derive(Debug, Deserialize)]
+struct Response {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(default)]
+    author_name: Option<String>,
+    #[serde(default)]
+    provider_name: Option<String>,
+    /// the image, for the photo type
+    #[serde(default)]
+    url: Option<String>,
+    /// the markup, for the video and rich types
+    #[serde(default)]
+    html: Option<String>,
+    #[serde(default)]
+    thumbnail_url: Option<String>,
+}
+
+impl Response {
+    /// `player_hosts` are the hosts whose players may be shown in an iframe
+    fn embed(&self, player_hosts: &[&str]) -> Option<Embed> {
+        match self.kind.as_str() {
+            "photo" => self.url.clone().map(Embed::Image),
+            // the markup isn't trusted, only the address of its player is used
+            "video" | "rich" => self
+                .html
+                .as_deref()
+                .and_then(iframe_src)
+                .filter(|src| is_player(src, player_hosts))
+                .map(Embed::Frame)
+                .or_else(|| self.thumbnail_url.clone().map(Embed::Image)),
+            _ => None,
+        }
+    }
+}
+
+fn iframe_src(html: &str) -> Option<String> {
+    let selector = Selector::parse("iframe").ok()?;
+    let fragment = Html::parse_fragment(html);
+    let src = fragment.select(&selector).next()?.value().attr("src")?;
+    src.starts_with("https://").then(|| src.to_string())
+}
+
+fn is_player(src: &str, player_hosts: &[&str]) -> bool {
+    let Ok(url) = Url::parse(src) else {
+        return false;
+    };
+    url.scheme() == "https"
+        && url
+            .host_str()
+            .map(|host| player_hosts.contains(&host))
+            .unwrap_or_default()
+}
+
+/// true if `src` is the player of one of the known providers. checked again before an iframe is shown, since the
+/// cache may hold previews from older versions
+pub fn is_trusted_player(src: &str) -> bool {
+    PROVIDERS
+        .iter()
+        .any(|provider| is_player(src, provider.player_hosts))
+}
+
+fn find_provider(url: &str) -> Option<&'static Provider> {
+    let url = Url::parse(url).ok()?;
+    let host = url.host_str()?;
+    let host = host.strip_prefix("www.").unwrap_or(host);
+    let link = format!("{host}{}", url.path());
+    PROVIDERS
+        .iter()
+        .find(|provider| provider.schemes.iter().any(|s| link.starts_with(s)))
+}
+
+async fn request(endpoint: &str, url: &str) -> Option<Response> {
+    let mut endpoint = Url::parse(endpoint).ok()?;
+    // discovered endpoints already name the link
+    if !endpoint.query_pairs().any(|(key, _)| key == "url") {
+        endpoint
+            .query_pairs_mut()
+            .append_pair("url", url)
+            .append_pair("format", "json");
+    }
+    let body = fetch(endpoint.as_str()).await.ok()?;
+    serde_json::from_str(&body).ok()
+}
+
+/// The preview of a link of a known provider
+pub async fn from_provider(url: &str) -> Option<SiteMeta> {
+    let provider = find_provider(url)?;
+    let response = request(provider.endpoint, url).await?;
+    Some(SiteMeta {
+        title: response.title.clone().unwrap_or_default(),
+        description: response
+            .author_name
+            .clone()
+            .or_else(|| response.provider_name.clone())
+            .unwrap_or_default(),
+        icon: String::new(),
+        url: url.to_string(),
+        embed: response.embed(provider.player_hosts),
+    })
+}
+
+/// Follows the oEmbed link of a page, if it has one. The page chooses the endpoint, so only an image is taken from it
+pub async fn discover(url: &str, document: &Html) -> Option<Embed> {
+    let selector = Selector::parse(r#"link[type="application/json+oembed"]"#).ok()?;
+    let href = document.select(&selector).next()?.value().attr("href")?;
+    let endpoint = Url::parse(url).ok()?.join(href).ok()?;
+    request(endpoint.as_str(), url).await?.embed(&[])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_provider_endpoint() {
+        let endpoint = |url: &str| find_provider(url).map(|provider| provider.endpoint);
+        assert_eq!(
+            endpoint("https://www.youtube.com/watch?v=dQw4w9WgXcQ"),
+            Some("https://www.youtube.com/oembed")
+        );
+        assert_eq!(
+            endpoint("https://vimeo.com/76979871"),
+            Some("https://vimeo.com/api/oembed.json")
+        );
+        assert_eq!(endpoint("https://example.com/watch"), None);
+    }
+
+    #[test]
+    fn test_only_the_player_is_embedded() {
+        let response = Response {
+            kind: "video".into(),
+            title: None,
+            author_name: None,
+            provider_name: None,
+            url: None,
+            html: Some(r#"<iframe src="https://player.vimeo.com/video/1"></iframe><script>alert(1)</script>"#.into()),
+            thumbnail_url: Some("https://i.vimeocdn.com/video/1.jpg".into()),
+        };
+        assert_eq!(
+            response.embed(&["player.vimeo.com"]),
+            Some(Embed::Frame("https://player.vimeo.com/video/1".into()))
+        );
+        // a discovered endpoint can't choose what is loaded in an iframe
+        assert_eq!(
+            response.embed(&[]),
+            Some(Embed::Image("https://i.vimeocdn.com/video/1.jpg".into()))
+        );
+        assert!(!is_trusted_player(
+            "https://player.vimeo.com.example.com/video/1"
+        ));
+    }
+}
@@ -0,0 +1,153 @@
+//! Fenced code blocks in messages are highlighted here instead of by Prism, so they are readable as soon as the
+//! message renders. Tokens get `hl-` prefixed classes for their scopes, which are colored in `style.scss`.
+//! Long snippets get line numbers in a gutter next to the code, so copying the code leaves them out. Very long
+//! snippets are collapsed.
+
+use common::language::{get_local_text, get_local_text_with_args};
+use once_cell::sync::Lazy;
+use syntect::{
+    html::{ClassStyle, ClassedHTMLGenerator},
+    parsing::{SyntaxReference, SyntaxSet},
+    util::LinesWithEndings,
+};
+use tracing::log;
+
+use super::{escape, unescape};
+
+static SYNTAXES: Lazy<SyntaxSet> = Lazy::new(SyntaxSet::load_defaults_newlines);
+
+const CLASS_STYLE: ClassStyle = ClassStyle::SpacedPrefixed { prefix: "hl-" };
+// line numbers are shown from this many lines
+const LINE_NUMBERS_FROM: usize = 5;
+// blocks with more lines are collapsed
+const COLLAPSE_FROM: usize = 25;
+
+// syntax tokens and lines which are typical for a language, checked in order
+const DETECTION: [(&str, &[&str]); 12] = [
+    (
+        "rs",
+        &[
+            "fn main(",
+            "let mut ",
+            "impl ",
+            "pub fn ",
+            "use std::",
+            "#[derive(",
+        ],
+    ),
+    ("go", &["package main", "func main(", ":= "]),
+    ("py", &["def ", "elif ", "self.", "__init__"]),
+    (
+        "java",
+        &["public class ", "public static void ", "System.out."],
+    ),
+    ("cpp", &["#include <iostream>", "std::", "template<"]),
+    ("c", &["#include ", "printf(", "int main("]),
+    ("cs", &["using System", "namespace ", "Console.Write"]),
+    ("php", &["<?php"]),
+    ("html", &["<!DOCTYPE", "<html", "<div", "</"]),
+    (
+        "sql",
+        &["SELECT ", "INSERT INTO ", "CREATE TABLE ", "UPDATE "],
+    ),
+    ("js", &["function ", "const ", "=> ", "console.log("]),
+    ("sh", &["#!/bin/", "echo ", "sudo "]),
+];
+
+/// Picks the language from the first line, like a shebang, or from tokens which are typical for it
+fn detect_syntax(code: &str) -> Option<&'static SyntaxReference> {
+    let first_line = code.lines().next().unwrap_or_default();
+    if let Some(syntax) = SYNTAXES.find_syntax_by_first_line(first_line) {
+        return Some(syntax);
+    }
+    // JSON is valid JavaScript, so it's checked first
+    if matches!(
+        serde_json::from_str(code),
+        Ok(serde_json::Value::Object(_) | serde_json::Value::Array(_))
+    ) {
+        return SYNTAXES.find_syntax_by_extension("json");
+    }
+    DETECTION
+        .iter()
+        .find(|(_, tokens)| tokens.iter().any(|token| code.contains(token)))
+        .and_then(|(extension, _)| SYNTAXES.find_syntax_by_extension(extension))
+}
+
+fn highlight(code: &str, syntax: &SyntaxReference) -> String {
+    let mut generator = ClassedHTMLGenerator::new_with_class_style(syntax, &SYNTAXES, CLASS_STYLE);
+    for line in LinesWithEndings::from(code) {
+        if let Err(e) = generator.parse_html_for_line_which_includes_newline(line) {
+            log::error!("failed to highlight code: {e}");
+            return escape(code);
+        }
+    }
+    generator.finalize()
+}
+
+/// Renders a code block. `language` is the info string after the opening fence, empty to detect it
+pub fn render(language: &str, code: &str) -> String {
+    // format_text escaped the code before it was parsed as markdown
+    let code = unescape(code).trim_end().to_string() + "\n";
+    let language = language.split_whitespace().next().unwrap_or_default();
+    let syntax = if language.is_empty() {
+        detect_syntax(&code)
+    } else {
+        SYNTAXES.find_syntax_by_token(language)
+    };
+    let highlighted = match syntax {
+        Some(syntax) => highlight(&code, syntax),
+        None => escape(&code),
+    };
+    let line_count = code.lines().count();
+    let language_name = syntax
+        .map(|syntax| syntax.name.clone())
+        .unwrap_or_else(|| language.to_string());
+
+    let mut classes = vec!["code-block"];
+    if line_count >= COLLAPSE_FROM {
+        classes.push("collapsible collapsed");
+    }
+
+    let mut html = format!(
+        "<div class=\"{}\"><div class=\"code-header\"><span class=\"code-language\">{}</span>",
+        classes.join(" "),
+        escape(&language_name)
+    );
+    html.push_str(&format!(
+        "<button class=\"code-copy\" aria-label=\"code-copy\" onclick=\"navigator.clipboard.writeText(this.closest('.code-block').querySelector('code').innerText); this.classList.add('copied'); setTimeout(() => this.classList.remove('copied'), 2000);\"><span class=\"copy\">{}</span><span class=\"copied\">{}</span></button></div>",
+        get_local_text("uplink.copy"),
+        get_local_text("messages.code-copied")
+    ));
+    html.push_str("<pre>");
+    if line_count >= LINE_NUMBERS_FROM {
+        let numbers: Vec<String> = (1..=line_count).map(|n| n.to_string()).collect();
+        html.push_str(&format!(
+            "<span class=\"code-gutter\" aria-hidden=\"true\">{}</span>",
+            numbers.join("\n")
+        ));
+    }
+    html.push_str(&format!("<code>{highlighted}</code></pre>"));
+    if line_count >= COLLAPSE_FROM {
+        html.push_str(&format!(
+            "<button class=\"code-expand\" aria-label=\"code-expand\" onclick=\"this.closest('.code-block').classList.toggle('collapsed')\"><span class=\"expand\">{}</span><span class=\"collapse\">{}</span></button>",
+            get_local_text_with_args("messages.code-show-all", vec![("num", line_count)]),
+            get_local_text("messages.code-show-less")
+        ));
+    }
+    html.push_str("</div>");
+    html
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_detect_language() {
+        let name = |code: &str| detect_syntax(code).map(|syntax| syntax.name.as_str());
+        assert_eq!(name("#!/usr/bin/env python\nprint(1)"), Some("Python"));
+        assert_eq!(name("{\"a\": [1, 2]}"), Some("JSON"));
+        assert_eq!(name("fn main() {\n    let mut x = 1;\n}"), Some("Rust"));
+        assert_eq!(name("just some words"), None);
+    }
+}
@@ -0,0 +1,152 @@
+//! Renders messages as markdown. `format_text` escaped the text before, so the only markup passed through is the one
+//! uplink added, like mentions and spoilers. Links are limited to web and mail addresses and ask before they are
+//! opened. Images are shown as links, so they aren't loaded from a server picked by the sender.
+
+use once_cell::sync::Lazy;
+use pulldown_cmark::{CodeBlockKind, Event, Options, Parser, Tag, TagEnd};
+use regex::{Captures, Regex, Replacer};
+
+use super::{
+    code_block, escape, is_only_emojis, replace_emojis, unescape, MARKDOWN_PROCESSOR_REGEX,
+};
+
+// `||hidden||`, on a single line
+static SPOILER_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"\|\|([^|\n]+?)\|\|").unwrap());
+
+const SPOILER_START: &str = r#"<span class="spoiler" onclick="this.classList.add('revealed')">"#;
+const SPOILER_END: &str = "</span>";
+const LINK_SCHEMES: [&str; 3] = ["https://", "http://", "mailto:"];
+
+struct RegexReplacer;
+
+impl Replacer for RegexReplacer {
+    fn replace_append(&mut self, caps: &Captures<'_>, dst: &mut String) {
+        dst.push_str(&caps[1]);
+        dst.push_str(&caps[2].replace("&gt;", ">"));
+    }
+}
+
+// spoilers are added before parsing, code shows what was typed instead
+fn without_spoilers(code: &str) -> String {
+    code.replace(SPOILER_START, "||").replace(SPOILER_END, "||")
+}
+
+fn safe_url(url: &str) -> Option<String> {
+    let url = url.trim();
+    let lowercase = url.to_lowercase();
+    LINK_SCHEMES
+        .iter()
+        .any(|scheme| lowercase.starts_with(scheme))
+        .then(|| escape(url))
+}
+
+pub fn markdown(text: &str, emojis: bool) -> String {
+    let txt = text.trim();
+    if emojis {
+        let r = replace_emojis(txt);
+        // TODO: Watch this issue for a fix: https://github.com/open-i18n/rust-unic/issues/280
+        // This is a temporary workaround for some characters unic-emoji-char thinks are emojis
+        if !r.chars().all(char::is_alphanumeric) // for any numbers, eg 1, 11, 111
+           && r != "#"
+           && r != "*"
+           && r != "##"
+           && r != "**"
+           && r != "-"
+           && is_only_emojis(&r)
+        {
+            return format!("<span class=\"big-emoji\">{r}</span>");
+        } else if is_only_emojis(txt) || r == "-" {
+            return format!("<p>{txt}</p>");
+        }
+    }
+
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_STRIKETHROUGH);
+    options.insert(Options::ENABLE_TABLES);
+    options.insert(Options::ENABLE_TASKLISTS);
+
+    let text = MARKDOWN_PROCESSOR_REGEX.replace_all(txt, RegexReplacer);
+    let text = SPOILER_REGEX.replace_all(&text, format!("{SPOILER_START}$1{SPOILER_END}"));
+
+    let mut events = vec![];
+    // the language and text of the code block being parsed
+    let mut code_block: Option<(String, String)> = None;
+    // whether each open link is shown
+    let mut links: Vec<bool> = vec![];
+    for event in Parser::new_ext(&text, options) {
+        match event {
+            Event::Start(Tag::CodeBlock(kind)) => {
+                let language = match kind {
+                    CodeBlockKind::Fenced(language) => language.to_string(),
+                    CodeBlockKind::Indented => String::new(),
+                };
+                code_block = Some((language, String::new()));
+            }
+            Event::Text(t) if code_block.is_some() => {
+                if let Some((_, code)) = code_block.as_mut() {
+                    code.push_str(&t);
+                }
+            }
+            Event::End(TagEnd::CodeBlock) => {
+                if let Some((language, code)) = code_block.take() {
+                    let html = code_block::render(&language, &without_spoilers(&code));
+                    events.push(Event::Html(html.into()));
+                }
+            }
+            Event::Code(code) => {
+                events.push(Event::Code(unescape(&without_spoilers(&code)).into()));
+            }
+            Event::Start(Tag::Link { dest_url, .. })
+            | Event::Start(Tag::Image { dest_url, .. }) => {
+                let url = safe_url(&dest_url);
+                links.push(url.is_some());
+                if let Some(url) = url {
+                    events.push(Event::InlineHtml(
+                        format!("<a class=\"named-link\" href=\"{url}\" title=\"{url}\">").into(),
+                    ));
+                }
+            }
+            Event::End(TagEnd::Link) | Event::End(TagEnd::Image) => {
+                if links.pop() == Some(true) {
+                    events.push(Event::InlineHtml("</a>".into()));
+                }
+            }
+            Event::Text(t) if emojis => events.push(Event::Text(replace_emojis(&t).into())),
+            // a new line in a message is meant as one
+            Event::SoftBreak => events.push(Event::HardBreak),
+            event => events.push(event),
+        }
+    }
+
+    let mut html_output = String::from("<div class=\"markdown\">");
+    pulldown_cmark::html::push_html(&mut html_output, events.into_iter());
+    html_output.push_str("</div>\n");
+    html_output
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_links_are_sanitized() {
+        let html = markdown(
+            "[ok](https://satellite.im) [bad](javascript:alert(1))",
+            false,
+        );
+        assert!(html.contains(r#"<a class="named-link" href="https://satellite.im""#));
+        assert!(!html.contains("javascript"));
+        assert!(html.contains("bad"));
+    }
+
+    #[test]
+    fn test_spoilers_and_tables() {
+        let html = markdown("||secret|| and `||code||`", false);
+        assert!(html.contains(&format!("{SPOILER_START}secret{SPOILER_END}")));
+        assert!(html.contains("<code>||code||</code>"));
+
+        let html = markdown("| a | b |\n| --- | --- |\n| 1 | 2 |", false);
+        assert!(html.contains("<th>a</th>"));
+        assert!(html.contains("<td>2</td>"));
+    }
+}
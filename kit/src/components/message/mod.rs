@@ -14,7 +14,6 @@ use derive_more::Display;
 use dioxus::prelude::*;
 use futures::StreamExt;
 use once_cell::sync::Lazy;
-use pulldown_cmark::{CodeBlockKind, Options, Tag, TagEnd};
 use regex::{Captures, Regex, Replacer};
 use uuid::Uuid;
 use warp::error::Error;
@@ -30,8 +29,13 @@ use crate::{components::embeds::file_embed::FileEmbed, elements::textarea};
 
 use super::embeds::link_embed::EmbedLinks;
 
+mod code_block;
+mod markdown;
+
+use markdown::markdown;
+
 pub static MARKDOWN_PROCESSOR_REGEX: Lazy<Regex> =
-    Lazy::new(|| Regex::new("(^|\n)((?:&gt;(?: *&gt;)*)|(?: ))").unwrap());
+    Lazy::new(|| Regex::new("(^|\n)((?:&gt;(?: *&gt;)*))").unwrap());
 pub static LINK_TAGS_REGEX: Lazy<Regex> = Lazy::new(|| {
     Regex::new(r"((?:(?:www\.)|(?:https?:\/\/))[\w-]+(?:\.[\w-]+)+(?:\/[^)\s<]*)*)|((mailto: {0,1})([\w.+-]+@[\w-]+(?:\.[\w.-]+)+))").unwrap()
 });
@@ -44,6 +48,19 @@ const HTML_ESCAPES: [(&str, &str); 5] = [
     ("\'", "&#x27;"),
 ];
 
+fn escape(text: &str) -> String {
+    HTML_ESCAPES
+        .iter()
+        .fold(text.to_string(), |s, (from, to)| s.replace(from, to))
+}
+
+fn unescape(text: &str) -> String {
+    HTML_ESCAPES
+        .iter()
+        .rev()
+        .fold(text.to_string(), |s, (to, from)| s.replace(from, to))
+}
+
 #[derive(Eq, PartialEq, Clone, Copy, Display)]
 pub enum Order {
     #[display(fmt = "message-first")]
@@ -122,6 +139,11 @@ pub struct Props<'a> {
 
     is_mention: bool,
 
+    // (replies, unread replies) of the thread started by this message. nothing is rendered without replies
+    #[props(!optional)]
+    thread_summary: Option<(usize, u32)>,
+    on_open_thread: Option<EventHandler<'a, ()>>,
+
     state: &'a UseSharedState<State>,
 
     chat: Uuid,
@@ -166,11 +188,29 @@ impl Replacer for LinkReplacer {
     }
 }
 
+// only the text between tags is searched, so links made by markdown and links in code aren't wrapped again
 fn wrap_links_with_a_tags(text: &str) -> (String, Vec<String>) {
     let mut links = LinkReplacer(vec![]);
-    let res = LINK_TAGS_REGEX
-        .replace_all(text, links.by_ref())
-        .into_owned();
+    let mut res = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('<') {
+        res.push_str(&LINK_TAGS_REGEX.replace_all(&rest[..start], links.by_ref()));
+        rest = &rest[start..];
+        let tag_end = ["a", "pre", "code"]
+            .iter()
+            .find(|name| {
+                rest[1..].starts_with(*name) && rest[1 + name.len()..].starts_with([' ', '>'])
+            })
+            .and_then(|name| {
+                let closing = format!("</{name}>");
+                rest.find(&closing).map(|i| i + closing.len())
+            })
+            .or_else(|| rest.find('>').map(|i| i + 1))
+            .unwrap_or(rest.len());
+        res.push_str(&rest[..tag_end]);
+        rest = &rest[tag_end..];
+    }
+    res.push_str(&LINK_TAGS_REGEX.replace_all(rest, links.by_ref()));
     (res, links.0)
 }
 
@@ -381,7 +421,35 @@ pub fn Message<'a>(cx: Scope<'a, Props<'a>>) -> Element<'a> {
                     }
                 )
             })
-        }
+        },
+        cx.props.thread_summary.filter(|(replies, _)| *replies > 0).map(|(replies, unread)| {
+            let text = get_local_text_with_args("messages.thread-replies", vec![("num", replies)]);
+            rsx!(div {
+                class: "message-thread-summary {remote_class}",
+                aria_label: "message-thread-summary",
+                onclick: move |_| {
+                    if let Some(e) = &cx.props.on_open_thread {
+                        e.call(());
+                    }
+                },
+                common::icons::Icon {
+                    ..common::icons::IconProps {
+                        class: None,
+                        size: 14,
+                        fill:"currentColor",
+                        icon: Icon::ChatBubbleLeftRight,
+                        disabled: false,
+                        disabled_fill: "#9CA3AF"
+                    },
+                },
+                span { "{text}" },
+                (unread > 0).then(|| rsx!(span {
+                    class: "thread-unreads",
+                    aria_label: "thread-unreads",
+                    "{unread}"
+                }))
+            })
+        })
     ))
 }
 
@@ -439,6 +507,14 @@ pub fn ChatText<'a>(cx: Scope<'a, ChatMessageProps<'a>>) -> Element<'a> {
         Some((&cx.props.state.read(), &cx.props.chat, false)),
     );
     let (formatted_text, links) = wrap_links_with_a_tags(&formatted_text);
+    let fetch_preview = cx
+        .props
+        .state
+        .read()
+        .configuration
+        .privacy
+        .link_previews
+        .should_fetch(cx.props.remote);
 
     let text_type_class = if cx.props.pending {
         "pending-text"
@@ -454,7 +530,7 @@ pub fn ChatText<'a>(cx: Scope<'a, ChatMessageProps<'a>>) -> Element<'a> {
                 aria_label: "message-text-{cx.props.text}",
                 dangerous_inner_html: "{formatted_text}",
             },
-            links.first().and_then(|l| cx.render(rsx!(
+            links.first().filter(|_| fetch_preview).and_then(|l| cx.render(rsx!(
                 EmbedLinks {
                     link: l.to_string(),
                     remote: cx.props.remote
@@ -473,8 +549,13 @@ pub fn format_text(
     // warning: this will probably break markdown regarding block quotes. still seems like an improvement.
     let safe_text = HTML_ESCAPES
         .iter()
-        .fold(Cow::from(text), |s, (from, to)| s.replace(*from, to).into())
-        .replace('\n', "&nbsp;&nbsp;\n");
+        .fold(Cow::from(text), |s, (from, to)| s.replace(*from, to).into());
+    // markdown turns line breaks into <br/> itself
+    let safe_text = if should_markdown {
+        safe_text.into_owned()
+    } else {
+        safe_text.replace('\n', "&nbsp;&nbsp;\n")
+    };
     let mut text = safe_text;
     // We want to do this after we escape html tags
     if let Some((state, chat, visual)) = data {
@@ -502,18 +583,7 @@ pub fn format_text(
     }
 }
 
-fn stack_processor(stack: &str, unescape_html: bool, emojis: bool) -> &str {
-    if unescape_html {
-        if let Some((esc, _)) = HTML_ESCAPES.iter().find(|(_, s)| stack.eq(*s)) {
-            return esc;
-        }
-        if "&nbsp;".eq(stack) {
-            return " ";
-        }
-    }
-    if !emojis {
-        return stack;
-    }
+fn stack_processor(stack: &str) -> &str {
     match stack {
         "<3" => "❤️",
         ">:)" => "😈",
@@ -556,134 +626,7 @@ where
 }
 
 pub fn replace_emojis(input: &str) -> String {
-    process_string(input, |s| stack_processor(s, false, true))
-}
-
-struct RegexReplacer;
-
-impl Replacer for RegexReplacer {
-    fn replace_append(&mut self, caps: &Captures<'_>, dst: &mut String) {
-        dst.push_str(&caps[1]);
-        if caps[2].eq(" ") {
-            dst.push_str("&nbsp;");
-        } else {
-            dst.push_str(&caps[2].replace("&gt;", ">"));
-        }
-    }
-}
-
-fn markdown(text: &str, emojis: bool) -> String {
-    let txt = text.trim();
-    if emojis {
-        let r = replace_emojis(txt);
-        // TODO: Watch this issue for a fix: https://github.com/open-i18n/rust-unic/issues/280
-        // This is a temporary workaround for some characters unic-emoji-char thinks are emojis
-        if !r.chars().all(char::is_alphanumeric) // for any numbers, eg 1, 11, 111
-           && r != "#"
-           && r != "*"
-           && r != "##"
-           && r != "**"
-           && r != "-"
-           && is_only_emojis(&r)
-        {
-            return format!("<span class=\"big-emoji\">{r}</span>");
-        } else if is_only_emojis(txt) || r == "-" {
-            return format!("<p>{txt}</p>");
-        }
-    }
-
-    let mut options = Options::empty();
-    options.insert(Options::ENABLE_STRIKETHROUGH);
-
-    let text = MARKDOWN_PROCESSOR_REGEX.replace_all(txt, RegexReplacer);
-
-    let mut html_output = String::new();
-    let mut in_paragraph = false;
-    let mut in_code_block = false;
-    let (mut skipping, mut in_link) = (false, false);
-
-    let parser = pulldown_cmark::Parser::new_ext(&text, options);
-    for (event, range) in parser.into_offset_iter() {
-        if skipping {
-            skipping = if in_link {
-                matches!(event, pulldown_cmark::Event::End(TagEnd::Link))
-            } else {
-                matches!(event, pulldown_cmark::Event::End(TagEnd::Image))
-            };
-            continue;
-        }
-        match event {
-            pulldown_cmark::Event::Start(pulldown_cmark::Tag::CodeBlock(
-                CodeBlockKind::Indented,
-            )) => {
-                html_output.push_str("</p>\n<p> </p><p>");
-            }
-            pulldown_cmark::Event::Code(mut txt) => {
-                txt = HTML_ESCAPES
-                    .iter()
-                    .fold(txt, |s, (to, from)| s.replace(*from, to).into());
-                pulldown_cmark::html::push_html(
-                    &mut html_output,
-                    std::iter::once(pulldown_cmark::Event::Code(txt)),
-                )
-            }
-            pulldown_cmark::Event::End(TagEnd::CodeBlock) => {}
-            pulldown_cmark::Event::SoftBreak => {
-                if in_paragraph {
-                    html_output.push_str("</p>\n<p>");
-                }
-            }
-            pulldown_cmark::Event::Start(Tag::Paragraph) => {
-                in_paragraph = true;
-                html_output.push_str("<p>");
-            }
-            pulldown_cmark::Event::End(TagEnd::Paragraph) => {
-                in_paragraph = false;
-            }
-            pulldown_cmark::Event::Start(Tag::Image { .. })
-            | pulldown_cmark::Event::Start(Tag::Link { .. }) => {
-                // Ignore links and image parsing
-                // We only want Autolink but that doesn't work (or needs <> which we also dont weed)
-                skipping = true;
-                in_link = matches!(event, pulldown_cmark::Event::End(TagEnd::Link));
-                html_output.push_str(&text[range]);
-            }
-            pulldown_cmark::Event::Text(t) => {
-                let text = if emojis || in_code_block {
-                    process_string(&t, |s| stack_processor(s, in_code_block, emojis))
-                } else {
-                    t.to_string()
-                };
-                let txt: pulldown_cmark::CowStr<'_> = if in_paragraph {
-                    text.replace("\n\n", "<br/>").into()
-                } else {
-                    text.into()
-                };
-                if in_code_block {
-                    html_output.push_str(&txt);
-                } else {
-                    pulldown_cmark::html::push_html(
-                        &mut html_output,
-                        std::iter::once(pulldown_cmark::Event::Text(txt)),
-                    );
-                }
-            }
-            event => {
-                match event {
-                    pulldown_cmark::Event::Start(Tag::CodeBlock(_)) => {
-                        in_code_block = true;
-                    }
-                    pulldown_cmark::Event::End(TagEnd::CodeBlock) => {
-                        in_code_block = false;
-                    }
-                    _ => {}
-                }
-                pulldown_cmark::html::push_html(&mut html_output, std::iter::once(event))
-            }
-        }
-    }
-    html_output.push('\n');
-    html_output
+    process_string(input, stack_processor)
 }
 
 #[derive(Display)]
@@ -0,0 +1,28 @@
+// Wraps the selection in markdown, or prefixes the selected lines with it
+var e = document.getElementById("$ID")
+if (e && e.markdownEditor) {
+    let view = e.markdownEditor.codemirror
+    let before = $BEFORE
+    let after = $AFTER
+    let { from, to } = view.state.selection.main
+    if ($LINES) {
+        let doc = view.state.doc
+        let first = doc.lineAt(from).number
+        let last = doc.lineAt(to).number
+        let changes = []
+        for (let i = first; i <= last; i++) {
+            changes.push({ from: doc.line(i).from, insert: before })
+        }
+        view.dispatch({
+            changes,
+            selection: { anchor: from + before.length, head: to + before.length * changes.length },
+        })
+    } else {
+        let selected = view.state.sliceDoc(from, to)
+        view.dispatch({
+            changes: { from, to, insert: before + selected + after },
+            selection: { anchor: from + before.length, head: to + before.length },
+        })
+    }
+    view.focus()
+}
@@ -9,7 +9,13 @@ use crate::{
         embeds::file_embed::FileEmbed, message::format_text, message_typing::MessageTyping,
         user_image::UserImage,
     },
-    elements::{button::Button, label::Label, textarea, Appearance},
+    elements::{
+        button::Button,
+        label::Label,
+        textarea,
+        tooltip::{ArrowPosition, Tooltip},
+        Appearance,
+    },
 };
 
 use common::{icons, language::get_local_text, warp_runner::thumbnail_to_base64};
@@ -22,6 +28,8 @@ pub enum SuggestionType {
     Emoji(String, Vec<(String, String)>),
     // Username tag suggestions. First is the string that was matched. Second is the users that matched
     Tag(String, Vec<Identity>),
+    // Slash command suggestions. First is the string that was matched. Second is the commands matched, as (usage, description)
+    Command(String, Vec<(String, String)>),
 }
 
 impl SuggestionType {
@@ -33,6 +41,9 @@ impl SuggestionType {
                 pattern.clone(),
                 format!("{}#{}", v[index].username(), v[index].short_id()),
             ),
+            SuggestionType::Command(pattern, v) => {
+                (pattern.clone(), command_replacement(&v[index].0))
+            }
         }
     }
 
@@ -41,9 +52,115 @@ impl SuggestionType {
             SuggestionType::None => true,
             SuggestionType::Emoji(_, v) => v.is_empty(),
             SuggestionType::Tag(_, v) => v.is_empty(),
+            SuggestionType::Command(_, v) => v.is_empty(),
         }
     }
 }
+
+enum FormatLabel {
+    Text(&'static str),
+    Icon(icons::outline::Shape),
+}
+
+// a button of the formatting toolbar
+struct Format {
+    label: FormatLabel,
+    /// the name of the format, in the aria label and locale keys
+    name: &'static str,
+    before: &'static str,
+    after: &'static str,
+    /// whether the selected lines are prefixed instead of the selection being wrapped
+    lines: bool,
+}
+
+const FORMATS: [Format; 10] = [
+    Format {
+        label: FormatLabel::Text("B"),
+        name: "bold",
+        before: "**",
+        after: "**",
+        lines: false,
+    },
+    Format {
+        label: FormatLabel::Text("I"),
+        name: "italic",
+        before: "*",
+        after: "*",
+        lines: false,
+    },
+    Format {
+        label: FormatLabel::Text("S"),
+        name: "strikethrough",
+        before: "~~",
+        after: "~~",
+        lines: false,
+    },
+    Format {
+        label: FormatLabel::Icon(icons::outline::Shape::CodeBracket),
+        name: "code",
+        before: "`",
+        after: "`",
+        lines: false,
+    },
+    Format {
+        label: FormatLabel::Icon(icons::outline::Shape::CodeBracketSquare),
+        name: "code-block",
+        before: "```\n",
+        after: "\n```",
+        lines: false,
+    },
+    Format {
+        label: FormatLabel::Icon(icons::outline::Shape::Link),
+        name: "link",
+        before: "[",
+        after: "](https://)",
+        lines: false,
+    },
+    Format {
+        label: FormatLabel::Icon(icons::outline::Shape::ChatBubbleBottomCenterText),
+        name: "quote",
+        before: "> ",
+        after: "",
+        lines: true,
+    },
+    Format {
+        label: FormatLabel::Icon(icons::outline::Shape::ListBullet),
+        name: "list",
+        before: "- ",
+        after: "",
+        lines: true,
+    },
+    Format {
+        label: FormatLabel::Icon(icons::outline::Shape::CheckCircle),
+        name: "task",
+        before: "- [ ] ",
+        after: "",
+        lines: true,
+    },
+    Format {
+        label: FormatLabel::Icon(icons::outline::Shape::EyeSlash),
+        name: "spoiler",
+        before: "||",
+        after: "||",
+        lines: false,
+    },
+];
+
+fn format_script(id: &str, format: &Format) -> String {
+    // the markup is inserted into the script as string literals
+    let literal = |text: &str| serde_json::to_string(text).unwrap_or_default();
+    include_str!("./format_text.js")
+        .replace("$ID", id)
+        .replace("$BEFORE", &literal(format.before))
+        .replace("$AFTER", &literal(format.after))
+        .replace("$LINES", &format.lines.to_string())
+}
+
+// the usage looks like "/me <action>". only the name is inserted into the chatbar
+fn command_replacement(usage: &str) -> String {
+    let name = usage.split_whitespace().next().unwrap_or_default();
+    format!("{name} ")
+}
 #[derive(Clone, PartialEq)]
 pub struct Route {
     pub to: To,
@@ -77,6 +194,8 @@ pub struct Props<'a> {
     suggestions: &'a SuggestionType,
     oncursor_update: Option<EventHandler<'a, (String, i64)>>,
     on_suggestion_click: Option<EventHandler<'a, (String, String, i64)>>,
+    markdown: Option<bool>,
+    transform_ascii_emojis: Option<bool>,
 }
 
 #[derive(Props)]
@@ -176,6 +295,16 @@ pub fn Chatbar<'a>(cx: Scope<'a, Props<'a>>) -> Element<'a> {
     let arrow_selected = use_ref(cx, || false);
     let is_suggestion_modal_closed: &UseRef<bool> = use_ref(cx, || false);
     let eval = use_eval(cx);
+    let markdown = cx.props.markdown.unwrap_or_default();
+    let show_preview = use_state(cx, || false);
+    // the editor doesn't update `value` while typing, so the preview keeps its own copy
+    let preview_text: &UseRef<String> = use_ref(cx, String::new);
+    use_effect(cx, &cx.props.value, |value| {
+        to_owned![preview_text];
+        async move {
+            *preview_text.write() = value.unwrap_or_default();
+        }
+    });
 
     cx.render(rsx!(
         div {
@@ -184,6 +313,73 @@ pub fn Chatbar<'a>(cx: Scope<'a, Props<'a>>) -> Element<'a> {
             cx.props.with_file_upload.as_ref(),
             div{
                 class: "chatbar-group",
+                markdown.then(|| rsx!(
+                    div {
+                        class: "chatbar-format",
+                        aria_label: "chatbar-format",
+                        FORMATS.iter().map(|format| {
+                            let script = format_script(controlled_input_id, format);
+                            let tooltip = cx.render(rsx!(Tooltip {
+                                arrow_position: ArrowPosition::Bottom,
+                                text: get_local_text(&format!("messages.format-{}", format.name)),
+                            }));
+                            let onpress = move |_| {
+                                let _ = eval(&script);
+                            };
+                            match format.label {
+                                FormatLabel::Text(text) => rsx!(Button {
+                                    key: "{format.name}",
+                                    small: true,
+                                    text: text.into(),
+                                    aria_label: format!("format-{}", format.name),
+                                    appearance: Appearance::Transparent,
+                                    tooltip: tooltip,
+                                    disabled: cx.props.is_disabled,
+                                    onpress: onpress,
+                                }),
+                                FormatLabel::Icon(icon) => rsx!(Button {
+                                    key: "{format.name}",
+                                    small: true,
+                                    icon: icon,
+                                    aria_label: format!("format-{}", format.name),
+                                    appearance: Appearance::Transparent,
+                                    tooltip: tooltip,
+                                    disabled: cx.props.is_disabled,
+                                    onpress: onpress,
+                                }),
+                            }
+                        }),
+                        Button {
+                            small: true,
+                            icon: icons::outline::Shape::Eye,
+                            aria_label: "format-preview".into(),
+                            appearance: if *show_preview.get() { Appearance::Primary } else { Appearance::Transparent },
+                            tooltip: cx.render(rsx!(Tooltip {
+                                arrow_position: ArrowPosition::Bottom,
+                                text: get_local_text("messages.preview"),
+                            })),
+                            onpress: move |_| {
+                                show_preview.set(!show_preview.get());
+                            },
+                        },
+                    }
+                )),
+                (markdown && *show_preview.get() && !preview_text.read().trim().is_empty()).then(|| {
+                    let preview = format_text(
+                        &preview_text.read(),
+                        true,
+                        cx.props.transform_ascii_emojis.unwrap_or_default(),
+                        None,
+                    );
+                    rsx!(div {
+                        class: "chatbar-preview message",
+                        aria_label: "chatbar-preview",
+                        div {
+                            class: "text",
+                            dangerous_inner_html: "{preview}",
+                        }
+                    })
+                }),
                 textarea::InputRich {
                     key: "{controlled_input_id}",
                     id: controlled_input_id.clone(),
@@ -213,6 +409,11 @@ pub fn Chatbar<'a>(cx: Scope<'a, Props<'a>>) -> Element<'a> {
                         }
                     },
                     onchange: move |(v, _)| {
+                        if *show_preview.get() {
+                            *preview_text.write() = v.clone();
+                        } else {
+                            *preview_text.write_silent() = v.clone();
+                        }
                         cx.props.onchange.call(v);
                         *is_suggestion_modal_closed.write_silent() = false;
                     },
@@ -244,6 +445,7 @@ pub fn Chatbar<'a>(cx: Scope<'a, Props<'a>>) -> Element<'a> {
                                 SuggestionType::None => 0,
                                 SuggestionType::Emoji(_, v) => v.len(),
                                 SuggestionType::Tag(_, v) => v.len(),
+                                SuggestionType::Command(_, v) => v.len(),
                             };
                             if amount == 0 {
                                 *selected_suggestion.write_silent() = None;
@@ -380,6 +582,42 @@ fn SuggestionsMenu<'a>(cx: Scope<'a, SuggestionProps<'a>>) -> Element<'a> {
             }).collect();
             (get_local_text("messages.username-suggestion"), component)
         }
+        SuggestionType::Command(pattern, commands) => {
+            let component = commands.iter().enumerate().map(|(num, (usage, description))| {
+                let replacement = command_replacement(usage);
+                rsx!(div {
+                    class: format_args!("{} {}", "chatbar-suggestion command-suggestion", match cx.props.selected.read().as_ref() {
+                        Some(v) => if *v == num {"chatbar-selected"} else {""},
+                        None => ""
+                    }),
+                    aria_label: {
+                        format_args!(
+                            "command-suggested-{}", replacement.trim_end(),
+                        )
+                    },
+                    onclick: move |_| {
+                        cx.props.on_click.call((replacement.clone(), pattern.clone()))
+                    },
+                    onmouseover: move |_| {
+                        cx.props.arrow_selected.with_mut(|arrow|{
+                            if !*arrow {
+                                *cx.props.selected.write() = Some(num);
+                            }
+                            *arrow = false
+                        });
+                    },
+                    span {
+                        class: "command-usage",
+                        "{usage}"
+                    },
+                    span {
+                        class: "command-description",
+                        "{description}"
+                    }
+                })
+            }).collect();
+            (get_local_text("messages.command-suggestion"), component)
+        }
     };
     cx.render(rsx!(div {
         id: "chatbar-suggestions",
@@ -14,14 +14,30 @@ use overlay::OverlayDom;
 use warp::multipass;
 
 pub fn use_warp_runner(cx: &ScopeState) {
-    cx.use_hook(|| {
-        // Now turn on the warp runner and save it to the hook so it doesn't get dropped
+    // Now turn on the warp runner and save it to a shared state so it doesn't get dropped.
+    // the login pages use it to restart warp after switching profiles or restoring a backup
+    use_shared_state_provider(cx, || {
         let mut runner = warp_runner::WarpRunner::new();
         runner.run();
         runner
     });
 }
 
+/// Stops warp, runs `f` while nothing holds the warp store open and then boots a new WarpRunner.
+/// Only valid while no account is logged in.
+pub async fn restart_warp_runner<T: Send + 'static>(
+    runner: &UseSharedState<warp_runner::WarpRunner>,
+    f: impl FnOnce() -> T + Send + 'static,
+) -> Result<T, tokio::task::JoinError> {
+    let old = std::mem::take(&mut *runner.write_silent());
+    old.stop().await;
+    let r = tokio::task::spawn_blocking(f).await;
+    let mut new = warp_runner::WarpRunner::new();
+    new.run();
+    *runner.write_silent() = new;
+    r
+}
+
 pub(crate) fn use_bootstrap<'a>(
     cx: &'a ScopeState,
     identity: &multipass::identity::Identity,
@@ -66,6 +82,7 @@ pub(crate) fn use_bootstrap<'a>(
 
 pub fn set_app_panic_hook() {
     panic::set_hook(Box::new(|panic_info| {
+        common::state::drafts::flush();
         let logs = logger::dump_logs();
         let crash_report = format!("{panic_info}\n{logs}\n");
         let backtrace = Backtrace::force_capture();
@@ -0,0 +1,183 @@
+use common::icons::outline::Shape as Icon;
+use common::icons::Icon as IconElement;
+use common::language::{get_local_text, get_local_text_with_args};
+use common::state::call_history::{CallRecord, CallStatus};
+use common::state::{call, Action, State};
+use common::warp_runner::{BlinkCmd, WarpCmd};
+use common::WARP_CMD_CH;
+use dioxus::prelude::*;
+use futures::channel::oneshot;
+use kit::elements::{
+    button::Button,
+    tooltip::{ArrowPosition, Tooltip},
+    Appearance,
+};
+use tracing::log;
+use uuid::Uuid;
+
+use crate::utils::format_timestamp::format_duration;
+
+/// Calls everyone else in the chat again
+pub fn call_back(state: UseSharedState<State>, cx: &ScopeState, conversation_id: Uuid) {
+    let Some(chat) = state.read().get_chat_by_id(conversation_id) else {
+        return;
+    };
+    let own_did = state.read().did_key();
+    let participants: Vec<_> = chat
+        .participants
+        .into_iter()
+        .filter(|did| *did != own_did)
+        .collect();
+    cx.spawn({
+        to_owned![state];
+        async move {
+            let (tx, rx) = oneshot::channel();
+            if let Err(e) = WARP_CMD_CH.tx.send(WarpCmd::Blink(BlinkCmd::OfferCall {
+                conversation_id,
+                participants: participants.clone(),
+                rsp: tx,
+            })) {
+                log::error!("failed to send blink command: {e}");
+                return;
+            }
+            match rx.await {
+                Ok(Ok(call_id)) => state.write().mutate(Action::OfferCall(call::Call::new(
+                    call_id,
+                    conversation_id,
+                    participants,
+                    own_did,
+                ))),
+                Ok(Err(e)) => log::error!("BlinkCmd::OfferCall failed: {e}"),
+                Err(e) => log::error!("failed to offer call: {e}"),
+            }
+        }
+    });
+}
+
+#[derive(Props)]
+pub struct Props<'a> {
+    record: CallRecord,
+    // the recent calls list names the chat of each call
+    show_chat: bool,
+    onclick: Option<EventHandler<'a>>,
+}
+
+/// A finished call, shown in the chat it belongs to and in the recent calls
+#[allow(non_snake_case)]
+pub fn CallRecordEntry<'a>(cx: Scope<'a, Props<'a>>) -> Element<'a> {
+    let state = use_shared_state::<State>(cx)?;
+    let record = &cx.props.record;
+    let own_did = state.read().did_key();
+    let outgoing = record.is_outgoing(&own_did);
+
+    let username = |did| {
+        state
+            .read()
+            .get_identity(did)
+            .map(|identity| identity.username())
+            .unwrap_or_else(|| get_local_text("uplink.unknown"))
+    };
+    let caller = vec![("name", username(&record.caller))];
+    let (icon, title) = match record.status {
+        CallStatus::Answered if outgoing => (
+            Icon::PhoneArrowUpRight,
+            get_local_text("call-history.outgoing"),
+        ),
+        CallStatus::Answered => (
+            Icon::PhoneArrowDownLeft,
+            get_local_text_with_args("call-history.incoming", caller),
+        ),
+        CallStatus::Missed => (
+            Icon::PhoneXMark,
+            get_local_text_with_args("call-history.missed", caller),
+        ),
+        CallStatus::Rejected => (
+            Icon::PhoneXMark,
+            get_local_text_with_args("call-history.rejected", caller),
+        ),
+        CallStatus::NoAnswer => (
+            Icon::PhoneArrowUpRight,
+            get_local_text("call-history.no-answer"),
+        ),
+    };
+    let status_class = match record.status {
+        CallStatus::Missed | CallStatus::Rejected => "missed",
+        _ => "",
+    };
+
+    let chat = state.read().get_chat_by_id(record.conversation_id);
+    let chat_name = chat.as_ref().filter(|_| cx.props.show_chat).map(|chat| {
+        chat.conversation_name
+            .clone()
+            .unwrap_or_else(|| State::join_usernames(&state.read().chat_participants(chat)))
+    });
+
+    let mut details = vec![record
+        .started
+        .with_timezone(&chrono::Local)
+        .format(&get_local_text("uplink.date-time-format"))
+        .to_string()];
+    if let Some(duration) = record.duration() {
+        details.push(format_duration(duration));
+    }
+    let joined: Vec<String> = record
+        .participants_joined
+        .iter()
+        .filter(|did| **did != own_did)
+        .map(username)
+        .collect();
+    if !joined.is_empty() {
+        details.push(get_local_text_with_args(
+            "call-history.joined",
+            vec![("names", joined.join(", "))],
+        ));
+    }
+    let details = details.join(" · ");
+
+    let call_in_progress = state.read().ui.call_info.active_call().is_some();
+    let can_call = state.read().configuration.developer.experimental_features
+        && chat.is_some()
+        && !call_in_progress;
+
+    cx.render(rsx!(div {
+        class: "call-record {status_class}",
+        aria_label: "call-record",
+        onclick: move |_| {
+            if let Some(f) = cx.props.onclick.as_ref() {
+                f.call(())
+            }
+        },
+        IconElement {
+            icon: icon,
+        },
+        div {
+            class: "call-record-content",
+            chat_name.map(|name| rsx!(p {
+                class: "call-record-chat ellipsis-overflow",
+                "{name}"
+            })),
+            p {
+                class: "call-record-title",
+                "{title}"
+            },
+            p {
+                class: "call-record-details",
+                "{details}"
+            }
+        },
+        Button {
+            icon: Icon::PhoneArrowUpRight,
+            aria_label: "call-back-button".into(),
+            appearance: Appearance::Secondary,
+            small: true,
+            disabled: !can_call,
+            tooltip: cx.render(rsx!(Tooltip {
+                arrow_position: ArrowPosition::Right,
+                text: get_local_text("call-history.call-back"),
+            })),
+            onpress: move |_| {
+                call_back(state.clone(), cx, cx.props.record.conversation_id);
+            }
+        }
+    }))
+}
@@ -0,0 +1,188 @@
+use std::time::Duration;
+
+use common::language::{get_local_text, get_local_text_with_args};
+use common::state::call_stats::{DegradationSource, ParticipantStats, Sample, HISTORY_LEN};
+use common::state::State;
+use dioxus::prelude::*;
+use warp::{blink::ParticipantState, crypto::DID};
+
+use crate::utils::format_timestamp::format_duration;
+
+/// Per-participant diagnostics for the active call. Refreshes every second, when a new sample is taken.
+#[allow(non_snake_case)]
+pub fn CallStatsPanel(cx: Scope) -> Element {
+    let state = use_shared_state::<State>(cx)?;
+    let update_fn = cx.schedule_update();
+    use_future(cx, (), |_| async move {
+        loop {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            update_fn();
+        }
+    });
+
+    let active_call = state.read().ui.call_info.active_call()?;
+    let own_id = state.read().did_key();
+    let username = |did: &DID| {
+        state
+            .read()
+            .get_identity(did)
+            .map(|identity| identity.username())
+            .unwrap_or_else(|| get_local_text("uplink.unknown"))
+    };
+
+    let summary = match active_call.stats.degradation_source(&own_id) {
+        None => get_local_text("call-stats.no-issues"),
+        Some(DegradationSource::Local) => get_local_text("call-stats.issue-local"),
+        Some(DegradationSource::Remote(peers)) => get_local_text_with_args(
+            "call-stats.issue-remote",
+            vec![(
+                "names",
+                peers.iter().map(username).collect::<Vec<_>>().join(", "),
+            )],
+        ),
+        Some(DegradationSource::Unknown) => get_local_text("call-stats.issue-unknown"),
+    };
+
+    let mut rows = vec![(
+        own_id.clone(),
+        get_local_text("call-stats.you"),
+        // blink doesn't send our own state
+        Some(ParticipantState {
+            muted: active_call.call.self_muted,
+            deafened: active_call.call.call_silenced,
+            ..Default::default()
+        }),
+    )];
+    rows.extend(
+        active_call
+            .call
+            .participants
+            .iter()
+            .filter(|did| **did != own_id)
+            .map(|did| {
+                (
+                    did.clone(),
+                    username(did),
+                    active_call.call.participants_joined.get(did).cloned(),
+                )
+            }),
+    );
+
+    cx.render(rsx!(div {
+        class: "call-stats",
+        aria_label: "call-stats",
+        p {
+            class: "call-stats-summary",
+            aria_label: "call-stats-summary",
+            "{summary}"
+        },
+        rows.into_iter().map(|(did, name, participant_state)| {
+            let stats = active_call.stats.get(&did);
+            let connected = did == own_id || stats.connected_since.is_some();
+            rsx!(ParticipantStatsRow {
+                key: "{did}",
+                name: name,
+                connected: connected,
+                participant_state: participant_state,
+                stats: stats,
+            })
+        }),
+        p {
+            class: "call-stats-note",
+            get_local_text("call-stats.note")
+        }
+    }))
+}
+
+#[derive(PartialEq, Props)]
+struct RowProps {
+    name: String,
+    connected: bool,
+    participant_state: Option<ParticipantState>,
+    stats: ParticipantStats,
+}
+
+#[allow(non_snake_case)]
+fn ParticipantStatsRow(cx: Scope<RowProps>) -> Element {
+    let stats = &cx.props.stats;
+
+    let connection = match (&cx.props.participant_state, cx.props.connected) {
+        (_, false) => get_local_text("call-stats.not-connected"),
+        (Some(s), true) if s.deafened => get_local_text("call-stats.deafened"),
+        (Some(s), true) if s.muted => get_local_text("call-stats.muted"),
+        _ => get_local_text("call-stats.connected"),
+    };
+    let mut details = vec![connection];
+    if let Some(since) = stats.connected_since {
+        details.push(format_duration(
+            chrono::Duration::from_std(since.elapsed())
+                .unwrap_or_else(|_| chrono::Duration::zero()),
+        ));
+    }
+    if stats.reconnects > 0 {
+        details.push(get_local_text_with_args(
+            "call-stats.reconnects",
+            vec![("count", stats.reconnects)],
+        ));
+    }
+    let details = details.join(" · ");
+
+    let activity = (stats.activity() * 100.0).round() as u32;
+    let degraded = (stats.degraded_ratio() * 100.0).round() as u32;
+    let degradations = match stats.last_degradation {
+        Some(last) => get_local_text_with_args(
+            "call-stats.degradations",
+            vec![
+                ("count", stats.degradations.to_string()),
+                ("ago", last.elapsed().as_secs().to_string()),
+            ],
+        ),
+        None => get_local_text("call-stats.no-degradations"),
+    };
+
+    // empty bars until the history is full, so it always scrolls in from the right
+    let padding = HISTORY_LEN.saturating_sub(stats.history.len());
+    let bars = std::iter::repeat(None)
+        .take(padding)
+        .chain(stats.history.iter().copied().map(Some));
+    let bar_class = |sample: Option<Sample>| match sample {
+        None => "",
+        Some(s) if s.degraded => "degraded",
+        Some(s) if !s.connected => "disconnected",
+        Some(s) if s.speaking => "speaking",
+        Some(_) => "idle",
+    };
+
+    cx.render(rsx!(div {
+        class: "call-stats-participant",
+        aria_label: "call-stats-participant",
+        div {
+            class: "call-stats-header",
+            p {
+                class: "call-stats-name ellipsis-overflow",
+                "{cx.props.name}"
+            },
+            p {
+                class: "call-stats-details",
+                "{details}"
+            }
+        },
+        div {
+            class: "call-stats-values",
+            p { get_local_text_with_args("call-stats.activity", vec![("percent", activity)]) },
+            p { get_local_text_with_args("call-stats.degraded", vec![("percent", degraded)]) },
+            p { "{degradations}" },
+        },
+        div {
+            class: "call-stats-graph",
+            aria_label: "call-stats-graph",
+            bars.enumerate().map(|(i, sample)| {
+                let class = bar_class(sample);
+                rsx!(div {
+                    key: "{i}",
+                    class: "call-stats-bar {class}",
+                })
+            })
+        }
+    }))
+}
@@ -1,4 +1,5 @@
 use std::{
+    path::PathBuf,
     sync::{atomic::AtomicBool, Arc},
     time::Duration,
 };
@@ -23,21 +24,24 @@ use kit::{
 };
 use warp::{blink::ParticipantState, crypto::DID};
 
+use crate::components::media::call_stats::CallStatsPanel;
 use crate::utils::{
     build_participants, build_user_from_identity, format_timestamp::format_timestamp_timeago,
+    keyboard::push_to_talk,
 };
 use common::{
     icons::outline::Shape as Icon,
+    recordings,
     sounds::{ContinuousSound, PlayUntil},
     state::{
         call::{ActiveCall, Call},
         ui::Layout,
     },
     warp_runner::{BlinkCmd, WarpCmd},
-    STATIC_ARGS, WARP_CMD_CH,
+    WARP_CMD_CH,
 };
 use common::{
-    language::get_local_text,
+    language::{get_local_text, get_local_text_with_args},
     state::{Action, State},
 };
 use uuid::Uuid;
@@ -87,6 +91,14 @@ pub fn CallControl(cx: Scope<Props>) -> Element {
     }
 }
 
+fn finish_recording(recording: &UseRef<Option<PathBuf>>) {
+    if let Some(dir) = recording.write().take() {
+        if let Err(e) = recordings::finish(&dir) {
+            log::error!("failed to save recording metadata: {e}");
+        }
+    }
+}
+
 #[derive(PartialEq, Eq, Props)]
 pub struct ActiveCallProps {
     active_call: ActiveCall,
@@ -110,7 +122,9 @@ fn ActiveCallControl(cx: Scope<ActiveCallProps>) -> Element {
     let outgoing = active_call.call.participants_joined.is_empty();
     let update_fn = cx.schedule_update_any();
 
-    let recording = use_ref(cx, || false);
+    // the directory of the recording in progress
+    let recording: &UseRef<Option<PathBuf>> = use_ref(cx, || None);
+    let show_stats = use_state(cx, || false);
 
     use_future(
         cx,
@@ -154,6 +168,8 @@ fn ActiveCallControl(cx: Scope<ActiveCallProps>) -> Element {
 
                         match rx.await {
                             Ok(_) => {
+                                // blink stops recording when the call ends
+                                finish_recording(&recording);
                                 state.write().mutate(Action::EndCall);
                             }
                             Err(e) => {
@@ -239,13 +255,9 @@ fn ActiveCallControl(cx: Scope<ActiveCallProps>) -> Element {
                     }
                     CallDialogCmd::RecordCall => {
                         let (tx, rx) = oneshot::channel();
-                        let time = Local::now().format("%d-%m-%Y_%H-%M-%S").to_string();
+                        let dir = recordings::new_recording_dir();
                         if let Err(e) = warp_cmd_tx.send(WarpCmd::Blink(BlinkCmd::StartRecording {
-                            output_dir: STATIC_ARGS
-                                .recordings
-                                .join(time)
-                                .to_string_lossy()
-                                .to_string(),
+                            output_dir: dir.to_string_lossy().to_string(),
                             rsp: tx,
                         })) {
                             log::error!("failed to send blink command: {e}");
@@ -254,7 +266,17 @@ fn ActiveCallControl(cx: Scope<ActiveCallProps>) -> Element {
 
                         match rx.await {
                             Ok(_) => {
-                                recording.with_mut(|v| *v = true);
+                                // blink only writes the audio. keep the call details for the recordings page
+                                if let Some(call) = state.read().ui.call_info.active_call() {
+                                    if let Err(e) = recordings::start(
+                                        &dir,
+                                        call.call.conversation_id,
+                                        call.call.participants.clone(),
+                                    ) {
+                                        log::error!("failed to save recording metadata: {e}");
+                                    }
+                                }
+                                recording.with_mut(|v| *v = Some(dir));
                             }
                             Err(e) => {
                                 log::error!("warp_runner failed to start recording: {e}");
@@ -272,7 +294,7 @@ fn ActiveCallControl(cx: Scope<ActiveCallProps>) -> Element {
 
                         match rx.await {
                             Ok(_) => {
-                                recording.with_mut(|v| *v = false);
+                                finish_recording(&recording);
                             }
                             Err(e) => {
                                 log::error!("warp_runner failed to stop recording: {e}");
@@ -327,6 +349,15 @@ fn ActiveCallControl(cx: Scope<ActiveCallProps>) -> Element {
     let other_participants = state.read().remove_self(&participants);
     let participants_name = State::join_usernames(&other_participants);
     let self_id = build_user_from_identity(&state.read().get_own_identity());
+    let push_to_talk = state.read().configuration.audiovideo.push_to_talk;
+    let transmitting = !call.self_muted;
+    let push_to_talk_text = if transmitting {
+        get_local_text("remote-controls.transmitting")
+    } else {
+        let key = push_to_talk::key_text(&state.read().settings)
+            .unwrap_or_else(|| get_local_text("settings-keybinds.unbound"));
+        get_local_text_with_args("remote-controls.hold-to-talk", vec![("key", key)])
+    };
 
     use_effect(cx, &other_participants, |in_call| {
         to_owned![ch, state];
@@ -343,7 +374,7 @@ fn ActiveCallControl(cx: Scope<ActiveCallProps>) -> Element {
         id: "remote-controls",
         aria_label: "remote-controls",
         class: format_args!("{}", if cx.props.in_chat {"in-chat"} else {""}),
-        (*recording.read()).then(||{
+        recording.read().is_some().then(||{
             rsx!(
                 div {
                     class: "recording-active",
@@ -414,6 +445,14 @@ fn ActiveCallControl(cx: Scope<ActiveCallProps>) -> Element {
                 }
             }))
         },
+        push_to_talk.then(|| rsx!(div {
+            class: format_args!("push-to-talk-indicator {}", if transmitting {"transmitting"} else {""}),
+            aria_label: "push-to-talk-indicator",
+            IconElement {
+                icon: if transmitting { Icon::MicrophoneWave } else { Icon::Microphone },
+            },
+            p { "{push_to_talk_text}" }
+        })),
         div {
             class: "controls",
             aria_label: "call-controls",
@@ -446,7 +485,7 @@ fn ActiveCallControl(cx: Scope<ActiveCallProps>) -> Element {
                 }
             },
             (!outgoing).then(||{
-                if *recording.read() {
+                if recording.read().is_some() {
                     rsx!(Button {
                         aria_label: "stop-recording-button".into(),
                         icon: Icon::StopCircle,
@@ -478,6 +517,18 @@ fn ActiveCallControl(cx: Scope<ActiveCallProps>) -> Element {
             })
          }
       }),
+            Button {
+                icon: Icon::ChartBar,
+                aria_label: "call-stats-button".into(),
+                appearance: if **show_stats { Appearance::Primary } else { Appearance::Secondary },
+                tooltip: cx.render(rsx!(
+                    Tooltip {
+                        arrow_position: ArrowPosition::Bottom,
+                        text: get_local_text("call-stats.title")
+                    }
+                )),
+                onpress: move |_| show_stats.set(!show_stats.get()),
+            },
             Button {
                 icon: Icon::PhoneXMark,
                 aria_label: "call-hangup-button".into(),
@@ -495,7 +546,8 @@ fn ActiveCallControl(cx: Scope<ActiveCallProps>) -> Element {
                 },
             }*/
 
-        }
+        },
+        show_stats.get().then(|| rsx!(CallStatsPanel {}))
     }))
 }
 
@@ -546,7 +598,7 @@ fn PendingCallDialog(cx: Scope<PendingCallProps>) -> Element {
 
                         match rx.await {
                             Ok(_) => {
-                                state.write().ui.call_info.reject_call(id);
+                                state.write().mutate(Action::RejectCall(id));
                             }
                             Err(e) => {
                                 log::error!("warp_runner failed to answer call: {e}");
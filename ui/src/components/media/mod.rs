@@ -1,3 +1,5 @@
+pub mod call_history;
+pub mod call_stats;
 pub mod calling;
 pub mod player;
 pub mod popout_player;
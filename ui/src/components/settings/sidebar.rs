@@ -18,6 +18,7 @@ use crate::components::media::calling::CallControl;
 pub enum Page {
     About,
     Audio,
+    Recordings,
     Developer,
     Extensions,
     General,
@@ -52,6 +53,7 @@ impl FromStr for Page {
         match input {
             "about" => Ok(Page::About),
             "audio" => Ok(Page::Audio),
+            "recordings" => Ok(Page::Recordings),
             "developer" => Ok(Page::Developer),
             "extensions" => Ok(Page::Extensions),
             //"files" => Ok(Page::Files),
@@ -116,6 +118,12 @@ pub fn Sidebar<'a>(cx: Scope<'a, Props<'a>>) -> Element<'a> {
         icon: Icon::MusicalNote,
         ..UIRoute::default()
     };
+    let recordings = UIRoute {
+        to: "recordings",
+        name: get_local_text("settings.recordings"),
+        icon: Icon::Microphone,
+        ..UIRoute::default()
+    };
     /*let privacy = UIRoute {
         to: "privacy",
         name: get_local_text("settings.privacy"),
@@ -175,6 +183,7 @@ pub fn Sidebar<'a>(cx: Scope<'a, Props<'a>>) -> Element<'a> {
     // To control order of routes, add them here.
     // routes.push(privacy);
     routes.push(audio);
+    routes.push(recordings);
     // routes.push(files);
     routes.push(extensions);
     routes.push(keybinds);
@@ -1,10 +1,12 @@
 use std::time::Duration;
 
-use common::language::get_local_text;
+use common::audio_devices::AUDIO_DEVICE_EVENTS;
+use common::language::{get_local_text, get_local_text_with_args};
 use common::state::ToastNotification;
 use common::warp_runner::{BlinkCmd, WarpCmd};
 use dioxus::prelude::*;
 use futures::{channel::oneshot, StreamExt};
+use tokio::sync::broadcast::error::RecvError;
 
 use kit::elements::button::Button;
 use kit::elements::select::Select;
@@ -13,17 +15,28 @@ use tracing::log;
 use warp::blink::AudioTestEvent;
 
 use crate::components::settings::{SettingSection, SettingSectionSimple};
+use crate::utils::keyboard::push_to_talk;
 use common::state::{action::ConfigAction, Action, State};
 use common::{sounds, WARP_CMD_CH};
 
 // pub const VOL_MIN: f32 = 0.0;
 // pub const VOL_MAX: f32 = 200.0;
 pub const MAX_VOLUME: f32 = 127_f32 * 0.01;
+// the choices for how long push-to-talk keeps the microphone open after the key is released
+const RELEASE_DELAYS_MS: [u64; 5] = [0, 100, 250, 500, 1000];
+
+fn release_delay_text(ms: u64) -> String {
+    if ms == 0 {
+        get_local_text("settings-audio.release-delay-none")
+    } else {
+        format!("{ms} ms")
+    }
+}
 
 enum AudioCmd {
-    FetchOutputDevices,
+    // fetch the devices and the ones in use again
+    Refresh,
     SetOutputDevice(String),
-    FetchInputDevices,
     SetInputDevice(String),
     SetEchoCancellation(bool),
     TestSpeaker,
@@ -36,8 +49,16 @@ pub fn AudioSettings(cx: Scope) -> Element {
     let state = use_shared_state::<State>(cx)?;
     let input_devices = use_ref(cx, Vec::new);
     let output_devices = use_ref(cx, Vec::new);
+    // may differ from the devices saved in the settings while those are unplugged
+    let input_device = use_ref(cx, || None);
+    let output_device = use_ref(cx, || None);
 
     let speaker_volume = use_ref(cx, || 0);
+    let push_to_talk = state.read().configuration.audiovideo.push_to_talk;
+    let voice_activity_text = get_local_text("settings-audio.voice-activity");
+    let push_to_talk_text = get_local_text("settings-audio.push-to-talk");
+    let push_to_talk_key = push_to_talk::key_text(&state.read().settings)
+        .unwrap_or_else(|| get_local_text("settings-keybinds.unbound"));
     let microphone_volume = use_ref(cx, || 0);
 
     let ch = use_coroutine(cx, |mut rx| {
@@ -45,6 +66,8 @@ pub fn AudioSettings(cx: Scope) -> Element {
             state,
             input_devices,
             output_devices,
+            input_device,
+            output_device,
             speaker_volume,
             microphone_volume
         ];
@@ -66,6 +89,11 @@ pub fn AudioSettings(cx: Scope) -> Element {
                         }
                     }
                 };
+                *input_device.write() = audio_config.microphone_device_name();
+                *input_devices.write() =
+                    audio_config.get_available_microphones().unwrap_or_default();
+                *output_device.write() = audio_config.speaker_device_name();
+                *output_devices.write() = audio_config.get_available_speakers().unwrap_or_default();
 
                 while let Some(cmd) = rx.next().await {
                     match cmd {
@@ -103,18 +131,7 @@ pub fn AudioSettings(cx: Scope) -> Element {
                                 }
                             }
                         }
-                        AudioCmd::FetchInputDevices => {
-                            state.write_silent().settings.input_device =
-                                audio_config.microphone_device_name();
-                            *input_devices.write() =
-                                audio_config.get_available_microphones().unwrap_or_default();
-                        }
-                        AudioCmd::FetchOutputDevices => {
-                            state.write_silent().settings.output_device =
-                                audio_config.speaker_device_name();
-                            *output_devices.write() =
-                                audio_config.get_available_speakers().unwrap_or_default();
-                        }
+                        AudioCmd::Refresh => continue 'GET_AUDIO_CONFIG,
                         AudioCmd::SetInputDevice(device_name) => {
                             let device = device_name.clone();
                             let (tx, rx) = oneshot::channel();
@@ -129,10 +146,15 @@ pub fn AudioSettings(cx: Scope) -> Element {
                             }
 
                             match rx.await {
-                                Ok(_) => {
+                                Ok(Ok(_)) => {
+                                    // remembered so the device can be used again after it was unplugged
                                     state.write_silent().settings.input_device = Some(device);
+                                    let _ = state.read().save();
                                     continue 'GET_AUDIO_CONFIG;
                                 }
+                                Ok(Err(e)) => {
+                                    log::error!("failed to set input device: {e}");
+                                }
                                 Err(e) => {
                                     log::error!("warp_runner failed to set input device: {e}");
                                 }
@@ -152,10 +174,15 @@ pub fn AudioSettings(cx: Scope) -> Element {
                             }
 
                             match rx.await {
-                                Ok(_) => {
+                                Ok(Ok(_)) => {
+                                    // remembered so the device can be used again after it was unplugged
                                     state.write_silent().settings.output_device = Some(device);
+                                    let _ = state.read().save();
                                     continue 'GET_AUDIO_CONFIG;
                                 }
+                                Ok(Err(e)) => {
+                                    log::error!("failed to set output device: {e}");
+                                }
                                 Err(e) => {
                                     log::error!("warp_runner failed to set output device: {e}");
                                 }
@@ -232,13 +259,19 @@ pub fn AudioSettings(cx: Scope) -> Element {
         }
     });
 
+    // the app switches devices when they are plugged in or removed. wait a moment so the list shows the result.
     use_future(cx, (), |_| {
         to_owned![ch];
         async move {
+            let mut events = AUDIO_DEVICE_EVENTS.subscribe();
             loop {
-                ch.send(AudioCmd::FetchInputDevices);
-                ch.send(AudioCmd::FetchOutputDevices);
-                tokio::time::sleep(std::time::Duration::from_secs(10)).await;
+                match events.recv().await {
+                    Ok(_) | Err(RecvError::Lagged(_)) => {
+                        tokio::time::sleep(Duration::from_millis(500)).await;
+                        ch.send(AudioCmd::Refresh);
+                    }
+                    Err(RecvError::Closed) => return,
+                }
             }
         }
     });
@@ -252,7 +285,7 @@ pub fn AudioSettings(cx: Scope) -> Element {
                 section_label: get_local_text("settings-audio.input-device"),
                 section_description: get_local_text("settings-audio.input-device-description"),
                 Select {
-                    initial_value: state.read().settings.input_device.as_ref().cloned().unwrap_or("default".into()),
+                    initial_value: input_device.read().clone().unwrap_or("default".into()),
                     options: input_devices.read().clone(),
                     onselect: move |device| {
                         ch.send(AudioCmd::SetInputDevice(device))
@@ -289,7 +322,7 @@ pub fn AudioSettings(cx: Scope) -> Element {
                 section_label: get_local_text("settings-audio.output-device"),
                 section_description: get_local_text("settings-audio.output-device-description"),
                 Select {
-                    initial_value: state.read().settings.output_device.as_ref().cloned().unwrap_or("default".into()),
+                    initial_value: output_device.read().clone().unwrap_or("default".into()),
                     options: output_devices.read().clone(),
                     onselect: move |device| {
                         ch.send(AudioCmd::SetOutputDevice(device));
@@ -365,6 +398,37 @@ pub fn AudioSettings(cx: Scope) -> Element {
                 }
             },
 
+            SettingSection {
+                aria_label: "voice-mode-section".into(),
+                section_label: get_local_text("settings-audio.voice-mode"),
+                section_description: get_local_text_with_args("settings-audio.voice-mode-description", vec![("key", push_to_talk_key)]),
+                Select {
+                    initial_value: if push_to_talk { push_to_talk_text.clone() } else { voice_activity_text.clone() },
+                    options: vec![voice_activity_text.clone(), push_to_talk_text.clone()],
+                    onselect: move |value| {
+                        state.write().mutate(Action::Config(
+                            ConfigAction::SetPushToTalk(value == push_to_talk_text),
+                        ));
+                    }
+                },
+            },
+            push_to_talk.then(|| rsx!(SettingSection {
+                aria_label: "release-delay-section".into(),
+                section_label: get_local_text("settings-audio.release-delay"),
+                section_description: get_local_text("settings-audio.release-delay-description"),
+                Select {
+                    initial_value: release_delay_text(state.read().configuration.audiovideo.push_to_talk_release_delay_ms),
+                    options: RELEASE_DELAYS_MS.iter().map(|ms| release_delay_text(*ms)).collect(),
+                    onselect: move |value| {
+                        if let Some(ms) = RELEASE_DELAYS_MS.into_iter().find(|ms| release_delay_text(*ms) == value) {
+                            state.write().mutate(Action::Config(
+                                ConfigAction::SetPushToTalkReleaseDelay(ms),
+                            ));
+                        }
+                    }
+                },
+            })),
+
             SettingSection {
                 aria_label: "interface-sounds-section".into(),
                 section_label: get_local_text("settings-audio.interface-sounds"),
@@ -1,15 +1,22 @@
+use std::path::PathBuf;
+
+use common::backup;
 use common::language::{change_language, get_available_languages, get_local_text};
 use common::state::utils::{get_available_fonts, get_available_themes};
+use common::state::ToastNotification;
 #[allow(unused_imports)]
 use common::state::{action::ConfigAction, Action, State};
 use common::{icons::outline::Shape as Icon, STATIC_ARGS};
 use dioxus::prelude::*;
+use futures::StreamExt;
 use kit::components::slide_selector::{ButtonsFormat, SlideSelector};
 use kit::components::swatch::ColorSwatch;
 use kit::elements::button::Button;
+use kit::elements::input::{Input, Options};
 use kit::elements::tooltip::{ArrowPosition, Tooltip};
 #[allow(unused_imports)]
 use kit::elements::{select::Select, switch::Switch};
+use rfd::FileDialog;
 use tracing::log;
 
 use crate::components::settings::{SettingSection, SettingSectionSimple};
@@ -36,6 +43,44 @@ pub fn GeneralSettings(cx: Scope) -> Element {
         }
     };
 
+    let backup_passphrase = use_ref(cx, String::new);
+    let backup_in_progress = use_state(cx, || false);
+
+    let backup_ch = use_coroutine(cx, |mut rx: UnboundedReceiver<(String, PathBuf)>| {
+        to_owned![state, backup_in_progress];
+        async move {
+            while let Some((passphrase, dest)) = rx.next().await {
+                // make sure the backup contains the latest settings
+                if let Err(e) = state.read().save() {
+                    log::error!("failed to save state before backup: {e}");
+                }
+                let r =
+                    tokio::task::spawn_blocking(move || backup::create_backup(&passphrase, &dest))
+                        .await;
+                backup_in_progress.set(false);
+                let msg = match r {
+                    Ok(Ok(_)) => get_local_text("settings-general.backup-created"),
+                    Ok(Err(e)) => {
+                        log::error!("failed to create backup: {e}");
+                        get_local_text("settings-general.backup-failed")
+                    }
+                    Err(e) => {
+                        log::error!("backup task failed: {e}");
+                        get_local_text("settings-general.backup-failed")
+                    }
+                };
+                state
+                    .write()
+                    .mutate(Action::AddToastNotification(ToastNotification::init(
+                        "".into(),
+                        msg,
+                        None,
+                        2,
+                    )));
+            }
+        }
+    });
+
     // TODO: This could go into a config file but I think the better approach is to allow the user to create and remove their own custom colors to create rudementary themes. Until we get there, this is fine.
     let available_colors = vec![
         (255, 95, 87),   // Red
@@ -197,6 +242,43 @@ pub fn GeneralSettings(cx: Scope) -> Element {
                     }
                 }
             },
+            SettingSection {
+                aria_label: "backup-section".into(),
+                section_label: get_local_text("settings-general.backup"),
+                section_description: get_local_text("settings-general.backup-description"),
+                Input {
+                    aria_label: "backup-passphrase-input".into(),
+                    is_password: true,
+                    disable_onblur: true,
+                    placeholder: get_local_text("settings-general.backup-passphrase"),
+                    options: Options {
+                        with_clear_btn: true,
+                        clear_on_submit: false,
+                        ..Default::default()
+                    },
+                    onchange: move |(val, _): (String, bool)| {
+                        *backup_passphrase.write() = val;
+                    },
+                },
+                Button {
+                    icon: Icon::ArchiveBoxArrowDown,
+                    aria_label: "create-backup-button".into(),
+                    text: get_local_text("settings-general.backup-create"),
+                    disabled: *backup_in_progress.get() || backup_passphrase.read().is_empty(),
+                    loading: *backup_in_progress.get(),
+                    onpress: move |_| {
+                        let file_name = format!("uplink-backup-{}.uplinkbackup", chrono::Local::now().format("%Y-%m-%d"));
+                        if let Some(dest) = FileDialog::new()
+                            .set_directory(dirs::home_dir().unwrap_or(".".into()))
+                            .set_file_name(&file_name)
+                            .save_file()
+                        {
+                            backup_in_progress.set(true);
+                            backup_ch.send((backup_passphrase.read().clone(), dest));
+                        }
+                    },
+                },
+            },
         }
     ))
 }
@@ -1,9 +1,11 @@
 #[allow(unused_imports)]
 use common::icons::outline::Shape as Icon;
-use common::language::get_local_text;
-use common::state::default_keybinds::get_keycode_and_modifier_from_a_shortcut;
-use common::state::settings::{GlobalShortcut, Shortcut};
-use common::state::Action;
+use common::language::{get_local_text, get_local_text_with_args};
+use common::state::default_keybinds::{
+    get_keycode_and_modifier_from_a_shortcut, get_reserved_shortcuts,
+};
+use common::state::settings::{GlobalShortcut, Keymap, Shortcut, ShortcutCategory};
+use common::state::{Action, ToastNotification};
 use common::utils::lifecycle::use_component_lifecycle;
 use common::{icons::Icon as IconElement, state::State};
 use dioxus::{html::GlobalAttributes, prelude::*};
@@ -18,8 +20,11 @@ use kit::elements::{
     tooltip::{ArrowPosition, Tooltip},
 };
 use muda::accelerator::Modifiers;
+use rfd::FileDialog;
+use tracing::log;
 
 use crate::components::settings::SettingSection;
+use crate::utils::keyboard::is_x11_session;
 
 const AVOID_INPUT_ON_DIV: &str = r#"
     document.getElementById("$UUID").addEventListener("keypress", function (event) {
@@ -76,16 +81,25 @@ pub struct KeybindSectionProps {
     pub aria_label: Option<String>,
 }
 
-pub fn check_for_conflicts(shortcut: Shortcut, shortcuts: Vec<(GlobalShortcut, Shortcut)>) -> bool {
-    let mut instances = 0;
-
-    for sc in shortcuts {
-        if sc.1.get_keys_and_modifiers_as_string() == shortcut.get_keys_and_modifiers_as_string() {
-            instances += 1;
-        }
-    }
+fn shortcut_label(shortcut: &GlobalShortcut) -> String {
+    get_local_text(&format!("settings-keybinds.{}", shortcut.label_key()))
+}
 
-    instances > 1
+/// Returns the names of the other keybinds and the reserved shortcuts which use the same keys as `shortcut`
+pub fn find_conflicts(
+    global_shortcut: &GlobalShortcut,
+    shortcut: &Shortcut,
+    bindings: &[(GlobalShortcut, Shortcut)],
+) -> Vec<String> {
+    let keybinds = bindings
+        .iter()
+        .filter(|(gs, sc)| gs != global_shortcut && sc.matches(&shortcut.keys, &shortcut.modifiers))
+        .map(|(gs, _)| shortcut_label(gs));
+    let reserved = get_reserved_shortcuts()
+        .into_iter()
+        .filter(|(_, sc)| sc.matches(&shortcut.keys, &shortcut.modifiers))
+        .map(|(name, _)| get_local_text(&format!("settings-keybinds.{name}")));
+    keybinds.chain(reserved).collect()
 }
 
 pub fn KeybindSection(cx: Scope<KeybindSectionProps>) -> Element {
@@ -159,7 +173,8 @@ pub fn KeybindSection(cx: Scope<KeybindSectionProps>) -> Element {
         state.write().settings.is_recording_new_keybind = true;
     }
 
-    let has_conflicts = check_for_conflicts(sc, cx.props.bindings.clone());
+    let conflicts = find_conflicts(&cx.props.shortcut, &sc, &cx.props.bindings);
+    let has_conflicts = !conflicts.is_empty();
 
     if has_conflicts {
         keybind_class.push_str(" conflicting");
@@ -224,12 +239,18 @@ pub fn KeybindSection(cx: Scope<KeybindSectionProps>) -> Element {
                     is_recording.set(false);
                     state.write().settings.is_recording_new_keybind = false;
                 },
-                if has_conflicts {
+                if bindings.is_empty() && !**is_recording {
+                    rsx!(p {
+                        class: "keybind-unbound",
+                        aria_label: "keybind-unbound",
+                        get_local_text("settings-keybinds.unbound")
+                    })
+                } else if has_conflicts {
                     rsx!(TooltipWrap {
                         tooltip: cx.render(rsx!(
                             Tooltip {
                                 arrow_position: ArrowPosition::Top,
-                                text: get_local_text("settings-keybinds.conflicting-keybinds")
+                                text: get_local_text_with_args("settings-keybinds.conflicts-with", vec![("names", conflicts.join(", "))])
                             }
                         )),
                         Keybind {
@@ -266,6 +287,7 @@ pub fn KeybindSection(cx: Scope<KeybindSectionProps>) -> Element {
 pub fn KeybindSettings(cx: Scope) -> Element {
     let state: &UseSharedState<State> = use_shared_state::<State>(cx)?;
     let bindings = state.read().settings.keybinds.clone();
+    let x11 = is_x11_session();
     let state2 = state.clone();
     let state3 = state.clone();
 
@@ -308,59 +330,135 @@ pub fn KeybindSettings(cx: Scope) -> Element {
                     appearance: kit::elements::Appearance::Secondary
                 },
             },
-            KeybindSection {
-                aria_label: "increase-font-size-section".into(),
-                id: format!("{:?}", GlobalShortcut::IncreaseFontSize),
-                section_label: get_local_text("settings-keybinds.increase-font-size"),
-                bindings: bindings.clone(),
-                shortcut: GlobalShortcut::IncreaseFontSize
-            }
-            KeybindSection {
-                aria_label: "decrease-font-size-section".into(),
-                id: format!("{:?}", GlobalShortcut::DecreaseFontSize),
-                section_label: get_local_text("settings-keybinds.decrease-font-size"),
-                bindings: bindings.clone(),
-                shortcut: GlobalShortcut::DecreaseFontSize
-            }
-            KeybindSection {
-                aria_label: "toggle-mute-section".into(),
-                id: format!("{:?}", GlobalShortcut::ToggleMute),
-                section_label: get_local_text("settings-keybinds.toggle-mute"),
-                bindings: bindings.clone(),
-                shortcut: GlobalShortcut::ToggleMute
-            }
-            KeybindSection {
-                aria_label: "toggle-deafen-section".into(),
-                id: format!("{:?}", GlobalShortcut::ToggleDeafen),
-                section_label: get_local_text("settings-keybinds.toggle-deafen"),
-                bindings: bindings.clone(),
-                shortcut: GlobalShortcut::ToggleDeafen
-            }
-            KeybindSection {
-                aria_label: "open-close-dev-tools-section".into(),
-                id: format!("{:?}", GlobalShortcut::OpenCloseDevTools),
-                section_label: get_local_text("settings-keybinds.open-close-dev-tools"),
-                bindings: bindings.clone(),
-                shortcut: GlobalShortcut::OpenCloseDevTools
-            }
-            KeybindSection {
-                aria_label: "toggle-devmode-section".into(),
-                id: format!("{:?}", GlobalShortcut::ToggleDevmode),
-                section_label: get_local_text("settings-keybinds.toggle-devmode"),
-                bindings: bindings.clone(),
-                shortcut: GlobalShortcut::ToggleDevmode
-            }
-            KeybindSection {
-                aria_label: "hide-focus-uplink-section".into(),
-                id: format!("{:?}", GlobalShortcut::SetAppVisible),
-                section_label: get_local_text("settings-keybinds.hide-focus-uplink"),
-                bindings: bindings.clone(),
-                shortcut: GlobalShortcut::SetAppVisible
-            }
+            SettingSection {
+                aria_label: "keymap-section".into(),
+                section_label: get_local_text("settings-keybinds.keymap"),
+                section_description: get_local_text("settings-keybinds.keymap-description"),
+                div {
+                    class: "keymap-buttons",
+                    Button {
+                        aria_label: "export-keymap-button".into(),
+                        icon: Icon::ArrowUpTray,
+                        onpress: move |_| export_keymap(state),
+                        text: get_local_text("settings-keybinds.export-keymap"),
+                        appearance: kit::elements::Appearance::Secondary
+                    },
+                    Button {
+                        aria_label: "import-keymap-button".into(),
+                        icon: Icon::ArrowDownTray,
+                        onpress: move |_| import_keymap(state),
+                        text: get_local_text("settings-keybinds.import-keymap"),
+                        appearance: kit::elements::Appearance::Secondary
+                    },
+                }
+            },
+            cfg!(target_os = "linux").then(|| rsx!(SettingSection {
+                aria_label: "system-shortcuts-section".into(),
+                section_label: get_local_text("settings-keybinds.system-shortcuts"),
+                section_description: if x11 {
+                    get_local_text("settings-keybinds.system-shortcuts-description")
+                } else {
+                    get_local_text("settings-keybinds.system-shortcuts-unavailable")
+                },
+                Switch {
+                    active: x11 && state.read().settings.linux_system_shortcuts,
+                    disabled: !x11,
+                    onflipped: move |e| {
+                        state.write().mutate(Action::SetLinuxSystemShortcuts(e));
+                    }
+                }
+            })),
+            ShortcutCategory::ALL.iter().map(|category| {
+                let sections = GlobalShortcut::ALL
+                    .iter()
+                    .filter(|gs| gs.category() == *category)
+                    .map(|gs| rsx!(KeybindSection {
+                        key: "{gs:?}",
+                        aria_label: format!("{}-section", gs.label_key()),
+                        id: format!("{:?}", gs),
+                        section_label: shortcut_label(gs),
+                        bindings: bindings.clone(),
+                        shortcut: gs.clone()
+                    }));
+                rsx!(
+                    div {
+                        key: "{category:?}",
+                        class: "keybind-category",
+                        aria_label: "keybind-category",
+                        p {
+                            class: "keybind-category-label",
+                            get_local_text(&format!("settings-keybinds.{}", category.label_key()))
+                        },
+                        sections
+                    }
+                )
+            })
         }
     ))
 }
 
+fn export_keymap(state: &UseSharedState<State>) {
+    let keymap = Keymap::new(state.read().settings.keybinds.clone());
+    let Some(path) = FileDialog::new()
+        .set_file_name("uplink-keymap.json")
+        .add_filter("JSON", &["json"])
+        .save_file()
+    else {
+        return;
+    };
+    let result = serde_json::to_string_pretty(&keymap)
+        .map_err(|e| e.to_string())
+        .and_then(|json| std::fs::write(path, json).map_err(|e| e.to_string()));
+    let text = match result {
+        Ok(_) => get_local_text("settings-keybinds.keymap-exported"),
+        Err(e) => {
+            log::error!("failed to export keymap: {e}");
+            get_local_text("settings-keybinds.keymap-export-failed")
+        }
+    };
+    state
+        .write()
+        .mutate(Action::AddToastNotification(ToastNotification::init(
+            "".into(),
+            text,
+            None,
+            2,
+        )));
+}
+
+fn import_keymap(state: &UseSharedState<State>) {
+    let Some(path) = FileDialog::new().add_filter("JSON", &["json"]).pick_file() else {
+        return;
+    };
+    let result = std::fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|json| serde_json::from_str::<Keymap>(&json).map_err(|e| e.to_string()));
+    let text = match result {
+        Ok(keymap) if keymap.version <= Keymap::VERSION => {
+            state
+                .write()
+                .mutate(Action::SetKeybinds(keymap.into_keybinds()));
+            get_local_text("settings-keybinds.keymap-imported")
+        }
+        Ok(keymap) => {
+            log::error!("unsupported keymap version: {}", keymap.version);
+            get_local_text("settings-keybinds.keymap-import-failed")
+        }
+        Err(e) => {
+            log::error!("failed to import keymap: {e}");
+            get_local_text("settings-keybinds.keymap-import-failed")
+        }
+    };
+    state
+        .write()
+        .mutate(Action::AddToastNotification(ToastNotification::init(
+            "".into(),
+            text,
+            None,
+            2,
+        )));
+}
+
 fn return_string_from_modifier(modifiers: Modifiers) -> Vec<String> {
     let mut modifier_string = vec![];
     for modifier in modifiers {
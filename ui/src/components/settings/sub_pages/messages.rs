@@ -1,17 +1,30 @@
+use common::icons::outline::Shape as Icon;
 use common::{
     language::get_local_text,
-    state::{Action, State},
+    state::{action::ConfigAction, configuration::LinkPreviews, Action, State, ToastNotification},
 };
 use dioxus::prelude::*;
-use kit::elements::switch::Switch;
+use kit::components::embeds::link_embed;
+use kit::elements::{button::Button, select::Select, switch::Switch, Appearance};
 use tracing::log;
 
 use crate::components::settings::SettingSection;
 
+const LINK_PREVIEWS: [LinkPreviews; 3] = [LinkPreviews::All, LinkPreviews::Sent, LinkPreviews::Off];
+
+fn link_previews_text(previews: LinkPreviews) -> String {
+    match previews {
+        LinkPreviews::All => get_local_text("settings-messages.link-previews-all"),
+        LinkPreviews::Sent => get_local_text("settings-messages.link-previews-sent"),
+        LinkPreviews::Off => get_local_text("settings-messages.link-previews-off"),
+    }
+}
+
 #[allow(non_snake_case)]
 pub fn Messages(cx: Scope) -> Element {
     log::trace!("Messages settings page rendered.");
     let state = use_shared_state::<State>(cx)?;
+    let link_previews = state.read().configuration.privacy.link_previews;
     cx.render(rsx!(
         div {
             id: "settings-messages",
@@ -37,6 +50,40 @@ pub fn Messages(cx: Scope) -> Element {
                         state.write().mutate(Action::SetTransformMarkdownText(flag));
                     }
                 }
+            },
+            SettingSection {
+                aria_label: "link-previews-section".into(),
+                section_label: get_local_text("settings-messages.link-previews"),
+                section_description: get_local_text("settings-messages.link-previews-description"),
+                Select {
+                    initial_value: link_previews_text(link_previews),
+                    options: LINK_PREVIEWS.into_iter().map(link_previews_text).collect(),
+                    onselect: move |value: String| {
+                        if let Some(previews) = LINK_PREVIEWS.into_iter().find(|previews| link_previews_text(*previews) == value) {
+                            state.write().mutate(Action::Config(ConfigAction::SetLinkPreviews(previews)));
+                        }
+                    }
+                }
+            },
+            SettingSection {
+                aria_label: "clear-link-previews-section".into(),
+                section_label: get_local_text("settings-messages.clear-link-previews"),
+                section_description: get_local_text("settings-messages.clear-link-previews-description"),
+                Button {
+                    text: get_local_text("settings-messages.clear"),
+                    aria_label: "clear-link-previews-button".into(),
+                    appearance: Appearance::Secondary,
+                    icon: Icon::Trash,
+                    onpress: move |_| {
+                        link_embed::clear_cache();
+                        state.write().mutate(Action::AddToastNotification(ToastNotification::init(
+                            "".into(),
+                            get_local_text("settings-messages.link-previews-cleared"),
+                            None,
+                            2,
+                        )));
+                    }
+                }
             }
         }
     ))
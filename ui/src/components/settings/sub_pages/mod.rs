@@ -11,3 +11,4 @@ pub mod messages;
 pub mod notifications;
 pub mod privacy;
 pub mod profile;
+pub mod recordings;
@@ -1,11 +1,17 @@
+use chrono::NaiveTime;
 #[allow(unused_imports)]
 use common::icons::outline::Shape as Icon;
 use common::language::get_local_text;
 use common::sounds;
-use common::state::{action::ConfigAction, Action, State};
+use common::state::{action::ConfigAction, notification_rules::QuietHours, Action, State};
 use dioxus::prelude::*;
 #[allow(unused_imports)]
-use kit::elements::{button::Button, switch::Switch};
+use kit::elements::{
+    button::Button,
+    input::{Input, Options},
+    switch::Switch,
+    Appearance,
+};
 
 use crate::components::settings::SettingSection;
 
@@ -75,6 +81,21 @@ pub fn NotificationSettings(cx: Scope) -> Element {
                         }
                     }
                 },
+                SettingSection {
+                    aria_label: "calls-notifications-section".into(),
+                    section_label: get_local_text("settings-notifications.calls"),
+                    section_description: get_local_text("settings-notifications.calls-description"),
+                    Switch {
+                        active: state.read().configuration.notifications.enabled && state.read().configuration.notifications.calls_notifications,
+                        disabled: !state.read().configuration.notifications.enabled,
+                        onflipped: move |e| {
+                            if state.read().configuration.audiovideo.interface_sounds {
+                                sounds::Play(sounds::Sounds::Flip);
+                            }
+                            state.write().mutate(Action::Config(ConfigAction::SetCallsNotificationsEnabled(e)));
+                        }
+                    }
+                },
                 SettingSection {
                     aria_label: "settings-notifications-section".into(),
                     section_label: get_local_text("settings"),
@@ -90,6 +111,178 @@ pub fn NotificationSettings(cx: Scope) -> Element {
                         }
                     }
                 },
+                KeywordAlerts {},
+                QuietHoursSettings {},
+            }
+        }
+    ))
+}
+
+// the format used by <input type="time">
+const TIME_FORMAT: &str = "%H:%M";
+const DAYS: [&str; 7] = ["mon", "tue", "wed", "thu", "fri", "sat", "sun"];
+
+#[allow(non_snake_case)]
+fn KeywordAlerts(cx: Scope) -> Element {
+    let state = use_shared_state::<State>(cx)?;
+    let keywords = state
+        .read()
+        .configuration
+        .notifications
+        .rules
+        .keywords
+        .clone();
+    let set_keywords = move |keywords: Vec<String>| {
+        state
+            .write()
+            .mutate(Action::Config(ConfigAction::SetNotificationKeywords(
+                keywords,
+            )));
+    };
+
+    let chips = keywords.iter().cloned().map(|keyword| {
+        let remaining: Vec<String> = keywords
+            .iter()
+            .filter(|x| **x != keyword)
+            .cloned()
+            .collect();
+        rsx!(
+            div {
+                key: "{keyword}",
+                class: "notification-keyword",
+                aria_label: "notification-keyword",
+                span { "{keyword}" },
+                Button {
+                    aria_label: "remove-keyword-button".into(),
+                    icon: Icon::XMark,
+                    small: true,
+                    appearance: Appearance::Secondary,
+                    onpress: move |_| set_keywords(remaining.clone()),
+                }
+            }
+        )
+    });
+
+    cx.render(rsx!(
+        SettingSection {
+            aria_label: "keyword-alerts-section".into(),
+            section_label: get_local_text("settings-notifications.keywords"),
+            section_description: get_local_text("settings-notifications.keywords-description"),
+            Input {
+                aria_label: "add-keyword-input".into(),
+                placeholder: get_local_text("settings-notifications.add-keyword"),
+                options: Options {
+                    clear_on_submit: true,
+                    react_to_esc_key: true,
+                    ..Options::default()
+                },
+                onreturn: move |(v, _, _): (String, bool, _)| {
+                    let keyword = v.trim().to_string();
+                    let mut keywords = state.read().configuration.notifications.rules.keywords.clone();
+                    if keyword.is_empty() || keywords.iter().any(|x| x.eq_ignore_ascii_case(&keyword)) {
+                        return;
+                    }
+                    keywords.push(keyword);
+                    set_keywords(keywords);
+                },
+            },
+        },
+        div {
+            class: "notification-keywords",
+            chips
+        }
+    ))
+}
+
+#[allow(non_snake_case)]
+fn QuietHoursSettings(cx: Scope) -> Element {
+    let state = use_shared_state::<State>(cx)?;
+    let quiet_hours = state.read().configuration.notifications.rules.quiet_hours;
+    let set_quiet_hours = move |quiet_hours: QuietHours| {
+        state
+            .write()
+            .mutate(Action::Config(ConfigAction::SetQuietHours(quiet_hours)));
+    };
+    let start = quiet_hours.start.format(TIME_FORMAT).to_string();
+    let end = quiet_hours.end.format(TIME_FORMAT).to_string();
+
+    let days = DAYS.iter().enumerate().map(|(idx, day)| {
+        let active = quiet_hours.days[idx];
+        rsx!(Button {
+            key: "{day}",
+            aria_label: format!("quiet-hours-{day}"),
+            text: get_local_text(&format!("settings-notifications.day-{day}")),
+            small: true,
+            appearance: if active {
+                Appearance::Primary
+            } else {
+                Appearance::Secondary
+            },
+            disabled: !quiet_hours.enabled,
+            onpress: move |_| {
+                let mut quiet_hours = quiet_hours;
+                quiet_hours.days[idx] = !active;
+                set_quiet_hours(quiet_hours);
+            },
+        })
+    });
+
+    cx.render(rsx!(
+        SettingSection {
+            aria_label: "quiet-hours-section".into(),
+            section_label: get_local_text("settings-notifications.quiet-hours"),
+            section_description: get_local_text("settings-notifications.quiet-hours-description"),
+            Switch {
+                active: quiet_hours.enabled,
+                onflipped: move |e| {
+                    if state.read().configuration.audiovideo.interface_sounds {
+                        sounds::Play(sounds::Sounds::Flip);
+                    }
+                    set_quiet_hours(QuietHours { enabled: e, ..quiet_hours });
+                }
+            }
+        },
+        div {
+            class: format_args!("quiet-hours {}", if quiet_hours.enabled { "enabled" } else { "disabled" }),
+            SettingSection {
+                aria_label: "quiet-hours-time-section".into(),
+                section_label: get_local_text("settings-notifications.quiet-hours-time"),
+                section_description: get_local_text("settings-notifications.quiet-hours-time-description"),
+                div {
+                    class: "quiet-hours-time",
+                    input {
+                        aria_label: "quiet-hours-start",
+                        "type": "time",
+                        value: "{start}",
+                        disabled: !quiet_hours.enabled,
+                        onchange: move |e| {
+                            if let Ok(start) = NaiveTime::parse_from_str(&e.value, TIME_FORMAT) {
+                                set_quiet_hours(QuietHours { start, ..quiet_hours });
+                            }
+                        }
+                    },
+                    span { "–" },
+                    input {
+                        aria_label: "quiet-hours-end",
+                        "type": "time",
+                        value: "{end}",
+                        disabled: !quiet_hours.enabled,
+                        onchange: move |e| {
+                            if let Ok(end) = NaiveTime::parse_from_str(&e.value, TIME_FORMAT) {
+                                set_quiet_hours(QuietHours { end, ..quiet_hours });
+                            }
+                        }
+                    }
+                }
+            },
+            SettingSection {
+                aria_label: "quiet-hours-days-section".into(),
+                section_label: get_local_text("settings-notifications.quiet-hours-days"),
+                section_description: get_local_text("settings-notifications.quiet-hours-days-description"),
+                div {
+                    class: "quiet-hours-days",
+                    days
+                }
             }
         }
     ))
@@ -1,10 +1,11 @@
 use std::path::PathBuf;
 
 use arboard::Clipboard;
+use chrono::{DateTime, Local, TimeZone, Utc};
 use common::get_images_dir;
 use common::icons::Icon as IconElement;
-use common::language::get_local_text;
-use common::state::{Action, Identity, State, ToastNotification};
+use common::language::{get_local_text, get_local_text_with_args};
+use common::state::{action::ConfigAction, Action, Identity, State, ToastNotification};
 use common::warp_runner::{MultiPassCmd, TesseractCmd, WarpCmd};
 use common::{icons::outline::Shape as Icon, WARP_CMD_CH};
 use dioxus::prelude::*;
@@ -15,7 +16,8 @@ use kit::components::context_menu::{ContextItem, ContextMenu};
 use kit::components::indicator::{Indicator, Platform, Status};
 use kit::elements::checkbox::Checkbox;
 use kit::elements::loader::Loader;
-use kit::elements::select::FancySelect;
+use kit::elements::select::{FancySelect, Select};
+use kit::elements::switch::Switch;
 use kit::elements::tooltip::Tooltip;
 use kit::elements::Appearance;
 use kit::elements::{
@@ -35,6 +37,55 @@ use crate::components::crop_image_tool::circle_format_tool::CropCircleImageModal
 use crate::components::crop_image_tool::rectangle_format_tool::CropRectImageModal;
 use crate::components::settings::{SettingSection, SettingSectionSimple};
 
+// when a new status message clears itself
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum StatusExpiry {
+    Never,
+    Minutes(i64),
+    EndOfDay,
+}
+
+const STATUS_EXPIRY_OPTIONS: [StatusExpiry; 5] = [
+    StatusExpiry::Never,
+    StatusExpiry::Minutes(30),
+    StatusExpiry::Minutes(60),
+    StatusExpiry::Minutes(240),
+    StatusExpiry::EndOfDay,
+];
+
+const AWAY_AFTER_MINUTES_OPTIONS: [u32; 5] = [5, 10, 15, 30, 60];
+
+impl StatusExpiry {
+    fn label(&self) -> String {
+        match self {
+            StatusExpiry::Never => get_local_text("settings-profile.status-expiry-never"),
+            StatusExpiry::Minutes(minutes) if minutes % 60 == 0 => get_local_text_with_args(
+                "settings-profile.status-expiry-hours",
+                vec![("count", minutes / 60)],
+            ),
+            StatusExpiry::Minutes(minutes) => get_local_text_with_args(
+                "settings-profile.status-expiry-minutes",
+                vec![("count", *minutes)],
+            ),
+            StatusExpiry::EndOfDay => get_local_text("settings-profile.status-expiry-today"),
+        }
+    }
+
+    fn expires_at(&self) -> Option<DateTime<Utc>> {
+        match self {
+            StatusExpiry::Never => None,
+            StatusExpiry::Minutes(minutes) => {
+                Some(Utc::now() + chrono::Duration::minutes(*minutes))
+            }
+            StatusExpiry::EndOfDay => Local::now()
+                .date_naive()
+                .and_hms_opt(23, 59, 59)
+                .and_then(|end| Local.from_local_datetime(&end).single())
+                .map(|end| end.with_timezone(&Utc)),
+        }
+    }
+}
+
 #[derive(Clone)]
 enum ChanCmd {
     Profile(Vec<u8>),
@@ -54,7 +105,27 @@ pub fn ProfileSettings(cx: Scope) -> Element {
 
     let identity = state.read().get_own_identity();
     let user_status = identity.status_message().unwrap_or_default();
-    let online_status = identity.identity_status();
+    // an automatic Away or Busy isn't shown, the status picked by the user is
+    let online_status = state
+        .read()
+        .auto_status()
+        .chosen(identity.identity_status());
+    let status_expiry = use_state(cx, || StatusExpiry::Never);
+    let status_expires = state.read().auto_status().status_message_expires;
+    let status_expires_text = match status_expires {
+        Some(expires) if !user_status.is_empty() => get_local_text_with_args(
+            "settings-profile.status-expires",
+            vec![(
+                "time",
+                expires
+                    .with_timezone(&Local)
+                    .format(&get_local_text("uplink.date-time-format"))
+                    .to_string(),
+            )],
+        ),
+        _ => get_local_text("settings-profile.status-expiry-description"),
+    };
+    let presence = state.read().configuration.presence;
     let identity_status_values = [
         IdentityStatus::Online,
         IdentityStatus::Away,
@@ -587,11 +658,32 @@ pub fn ProfileSettings(cx: Scope) -> Element {
                                 return;
                             }
                             if v != user_status {
+                                let expires = if v.is_empty() { None } else { status_expiry.expires_at() };
+                                state.write().mutate(Action::SetStatusMessageExpiry(expires));
                                 ch.send(ChanCmd::StatusMessage(v));
                             }
                         },
                     }
                 },
+                SettingSection {
+                    aria_label: "status-expiry-section".into(),
+                    section_label: get_local_text("settings-profile.status-expiry"),
+                    section_description: status_expires_text,
+                    Select {
+                        initial_value: status_expiry.label(),
+                        options: STATUS_EXPIRY_OPTIONS.iter().map(|expiry| expiry.label()).collect(),
+                        onselect: move |value: String| {
+                            let Some(expiry) = STATUS_EXPIRY_OPTIONS.into_iter().find(|expiry| expiry.label() == value) else {
+                                return;
+                            };
+                            status_expiry.set(expiry);
+                            // applies to the current status message as well
+                            if !state.read().get_own_identity().status_message().unwrap_or_default().is_empty() {
+                                state.write().mutate(Action::SetStatusMessageExpiry(expiry.expires_at()));
+                            }
+                        }
+                    },
+                },
                 SettingSection {
                     aria_label: "online-status-section".into(),
                     section_label: get_local_text("settings-profile.online-status"),
@@ -606,6 +698,42 @@ pub fn ProfileSettings(cx: Scope) -> Element {
                         }
                     },
                 },
+                SettingSection {
+                    aria_label: "auto-away-section".into(),
+                    section_label: get_local_text("settings-profile.auto-away"),
+                    section_description: get_local_text("settings-profile.auto-away-description"),
+                    Switch {
+                        active: presence.auto_away,
+                        onflipped: move |e| {
+                            state.write().mutate(Action::Config(ConfigAction::SetAutoAway(e)));
+                        }
+                    }
+                },
+                presence.auto_away.then(|| rsx!(SettingSection {
+                    aria_label: "away-after-section".into(),
+                    section_label: get_local_text("settings-profile.away-after"),
+                    section_description: get_local_text("settings-profile.away-after-description"),
+                    Select {
+                        initial_value: away_after_text(presence.away_after_minutes),
+                        options: AWAY_AFTER_MINUTES_OPTIONS.iter().map(|minutes| away_after_text(*minutes)).collect(),
+                        onselect: move |value: String| {
+                            if let Some(minutes) = AWAY_AFTER_MINUTES_OPTIONS.into_iter().find(|minutes| away_after_text(*minutes) == value) {
+                                state.write().mutate(Action::Config(ConfigAction::SetAwayAfterMinutes(minutes)));
+                            }
+                        }
+                    },
+                })),
+                SettingSection {
+                    aria_label: "busy-in-calls-section".into(),
+                    section_label: get_local_text("settings-profile.busy-in-calls"),
+                    section_description: get_local_text("settings-profile.busy-in-calls-description"),
+                    Switch {
+                        active: presence.busy_in_calls,
+                        onflipped: move |e| {
+                            state.write().mutate(Action::Config(ConfigAction::SetBusyInCalls(e)));
+                        }
+                    }
+                },
                 if *phrase_exists.get() {rsx!(
                     SettingSection {
                         aria_label: "recovery-seed-section".into(),
@@ -867,6 +995,13 @@ fn get_input_options(validation_options: Validation) -> Options {
     }
 }
 
+fn away_after_text(minutes: u32) -> String {
+    get_local_text_with_args(
+        "settings-profile.status-expiry-minutes",
+        vec![("count", minutes)],
+    )
+}
+
 fn get_status_option<'a>(cx: Scope<'a>, status: &IdentityStatus) -> (String, Element<'a>) {
     let indicator = Status::from(*status);
     (
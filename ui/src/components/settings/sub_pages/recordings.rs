@@ -0,0 +1,322 @@
+use common::icons::outline::Shape as Icon;
+use common::language::{get_local_text, get_local_text_with_args};
+use common::recordings::{self, Recording};
+use common::state::{Action, State, ToastNotification};
+use common::utils::local_file_path::get_fixed_path_to_load_local_file;
+use common::warp_runner::{RayGunCmd, WarpCmd};
+use common::{STATIC_ARGS, WARP_CMD_CH};
+use dioxus::prelude::*;
+use futures::{channel::oneshot, StreamExt};
+use kit::elements::{
+    button::Button,
+    input::{Input, Options},
+    Appearance,
+};
+use tracing::log;
+use uuid::Uuid;
+use warp::raygun::Location;
+
+use crate::components::settings::SettingSection;
+use crate::layouts::storage::functions::format_item_size;
+use crate::utils::async_task_queue::{upload_stream_handler, ListenerAction, ACTION_LISTENER};
+use crate::utils::format_timestamp::format_duration;
+
+enum RecordingsCmd {
+    Share {
+        conversation_id: Uuid,
+        tracks: Vec<std::path::PathBuf>,
+    },
+}
+
+fn toast(state: &UseSharedState<State>, text: String) {
+    state
+        .write()
+        .mutate(Action::AddToastNotification(ToastNotification::init(
+            "".into(),
+            text,
+            None,
+            2,
+        )));
+}
+
+// the upload runs outside of the component, so the result is shown through the action listener
+fn share_result_toast(result: Result<(), String>) {
+    let content = match result {
+        Ok(()) => get_local_text("settings-recordings.shared"),
+        Err(e) => {
+            log::error!("failed to share recording: {e}");
+            get_local_text("settings-recordings.share-failed")
+        }
+    };
+    let _ = ACTION_LISTENER.tx.send(ListenerAction::ToastAction {
+        title: "".into(),
+        content,
+        icon: None,
+        timeout: 2,
+    });
+}
+
+#[allow(non_snake_case)]
+pub fn RecordingsSettings(cx: Scope) -> Element {
+    log::trace!("Recordings settings page rendered.");
+    let recording_list = use_ref(cx, recordings::list);
+    // the recording is only sent once its upload finished
+    let upload_streams = upload_stream_handler(cx, |(), result| share_result_toast(result));
+
+    let ch = use_coroutine(cx, |mut rx: UnboundedReceiver<RecordingsCmd>| {
+        to_owned![upload_streams];
+        async move {
+            let warp_cmd_tx = WARP_CMD_CH.tx.clone();
+            while let Some(cmd) = rx.next().await {
+                match cmd {
+                    RecordingsCmd::Share {
+                        conversation_id,
+                        tracks,
+                    } => {
+                        let (tx, rx) = oneshot::channel();
+                        if let Err(e) = warp_cmd_tx.send(WarpCmd::RayGun(RayGunCmd::SendMessage {
+                            conv_id: conversation_id,
+                            msg: vec![],
+                            attachments: tracks
+                                .into_iter()
+                                .map(|path| Location::Disk { path })
+                                .collect(),
+                            rsp: tx,
+                        })) {
+                            log::error!("failed to send warp command: {}", e);
+                            continue;
+                        }
+
+                        match rx.await.expect("command canceled") {
+                            Ok((id, Some(stream))) => {
+                                upload_streams
+                                    .write()
+                                    .append((conversation_id, id, stream, ()));
+                            }
+                            Ok((_, None)) => share_result_toast(Ok(())),
+                            Err(e) => share_result_toast(Err(e.to_string())),
+                        }
+                    }
+                }
+            }
+        }
+    });
+
+    let list = recording_list.read().clone();
+
+    cx.render(rsx!(
+        div {
+            id: "settings-recordings",
+            aria_label: "settings-recordings",
+            SettingSection {
+                aria_label: "open-recordings-section".into(),
+                section_label: get_local_text("settings-recordings.open-folder"),
+                section_description: get_local_text("settings-recordings.open-folder-description"),
+                div {
+                    class: "recording-buttons",
+                    Button {
+                        icon: Icon::ArrowPath,
+                        aria_label: "refresh-recordings-button".into(),
+                        appearance: Appearance::Secondary,
+                        text: get_local_text("settings-recordings.refresh"),
+                        onpress: move |_| {
+                            *recording_list.write() = recordings::list();
+                        }
+                    },
+                    Button {
+                        icon: Icon::FolderOpen,
+                        aria_label: "open-recordings-folder-button".into(),
+                        text: get_local_text("settings-recordings.open-folder"),
+                        onpress: move |_| {
+                            let _ = std::fs::create_dir_all(&STATIC_ARGS.recordings);
+                            let _ = opener::open(&STATIC_ARGS.recordings);
+                        }
+                    },
+                }
+            },
+            if list.is_empty() {
+                rsx!(p {
+                    class: "recordings-empty",
+                    aria_label: "recordings-empty",
+                    get_local_text("settings-recordings.empty")
+                })
+            }
+            list.into_iter().map(|recording| {
+                let key = recording.path.to_string_lossy().to_string();
+                rsx!(RecordingItem {
+                    key: "{key}",
+                    recording: recording,
+                    onchange: move |_| {
+                        *recording_list.write() = recordings::list();
+                    },
+                    onshare: move |(conversation_id, tracks)| {
+                        ch.send(RecordingsCmd::Share { conversation_id, tracks });
+                    }
+                })
+            })
+        }
+    ))
+}
+
+#[derive(Props)]
+struct RecordingItemProps<'a> {
+    recording: Recording,
+    // the recording was renamed or deleted
+    onchange: EventHandler<'a, ()>,
+    onshare: EventHandler<'a, (Uuid, Vec<std::path::PathBuf>)>,
+}
+
+#[allow(non_snake_case)]
+fn RecordingItem<'a>(cx: Scope<'a, RecordingItemProps<'a>>) -> Element<'a> {
+    let state = use_shared_state::<State>(cx)?;
+    let playing = use_state(cx, || false);
+    let renaming = use_state(cx, || false);
+    let confirm_delete = use_state(cx, || false);
+
+    let recording = &cx.props.recording;
+    let metadata = &recording.metadata;
+    let chat = metadata
+        .conversation_id
+        .and_then(|id| state.read().get_chat_by_id(id));
+    let chat_name = chat.as_ref().map(|chat| {
+        chat.conversation_name
+            .clone()
+            .unwrap_or_else(|| State::join_usernames(&state.read().chat_participants(chat)))
+    });
+    let participants: Vec<String> = metadata
+        .participants
+        .iter()
+        .filter_map(|did| state.read().get_identity(did))
+        .map(|identity| identity.username())
+        .collect();
+
+    let started = metadata
+        .started
+        .with_timezone(&chrono::Local)
+        .format(&get_local_text("uplink.date-time-format"))
+        .to_string();
+    let mut details = vec![started];
+    if let Some(duration) = recording.duration() {
+        details.push(format_duration(duration));
+    }
+    details.push(format_item_size(recording.size as usize));
+    if let Some(name) = chat_name {
+        details.push(name);
+    }
+    if !participants.is_empty() {
+        details.push(get_local_text_with_args(
+            "settings-recordings.participants",
+            vec![("names", participants.join(", "))],
+        ));
+    }
+
+    cx.render(rsx!(
+        div {
+            class: "recording",
+            aria_label: "recording",
+            SettingSection {
+                aria_label: "recording-section".into(),
+                section_label: recording.name(),
+                section_description: details.join(" · "),
+                div {
+                    class: "recording-buttons",
+                    if **renaming {
+                        rsx!(Input {
+                            aria_label: "recording-name-input".into(),
+                            placeholder: get_local_text("settings-recordings.name-placeholder"),
+                            default_text: recording.name(),
+                            focus: true,
+                            options: Options {
+                                react_to_esc_key: true,
+                                ..Options::default()
+                            },
+                            onreturn: move |(v, _, _): (String, bool, _)| {
+                                renaming.set(false);
+                                if v.trim().is_empty() || v == cx.props.recording.name() {
+                                    return;
+                                }
+                                match recordings::rename(&cx.props.recording, &v) {
+                                    Ok(_) => cx.props.onchange.call(()),
+                                    Err(e) => {
+                                        log::error!("failed to rename recording: {e}");
+                                        toast(state, get_local_text("settings-recordings.rename-failed"));
+                                    }
+                                }
+                            }
+                        })
+                    } else {
+                        rsx!(
+                            Button {
+                                icon: if **playing { Icon::Pause } else { Icon::Play },
+                                aria_label: "play-recording-button".into(),
+                                appearance: if **playing { Appearance::Primary } else { Appearance::Secondary },
+                                disabled: recording.tracks.is_empty(),
+                                onpress: move |_| playing.set(!playing.get()),
+                            },
+                            Button {
+                                icon: Icon::Pencil,
+                                aria_label: "rename-recording-button".into(),
+                                appearance: Appearance::Secondary,
+                                onpress: move |_| renaming.set(true),
+                            },
+                            Button {
+                                icon: Icon::Share,
+                                aria_label: "share-recording-button".into(),
+                                appearance: Appearance::Secondary,
+                                // the chat may have been left since
+                                disabled: chat.is_none() || recording.tracks.is_empty(),
+                                onpress: move |_| {
+                                    if let Some(id) = cx.props.recording.metadata.conversation_id {
+                                        cx.props.onshare.call((id, cx.props.recording.tracks.clone()));
+                                    }
+                                },
+                            },
+                            Button {
+                                icon: Icon::Trash,
+                                aria_label: "delete-recording-button".into(),
+                                appearance: if **confirm_delete { Appearance::Danger } else { Appearance::Secondary },
+                                text: if **confirm_delete { get_local_text("settings-recordings.confirm-delete") } else { String::new() },
+                                onpress: move |_| {
+                                    if !**confirm_delete {
+                                        confirm_delete.set(true);
+                                        return;
+                                    }
+                                    confirm_delete.set(false);
+                                    match recordings::delete(&cx.props.recording) {
+                                        Ok(_) => cx.props.onchange.call(()),
+                                        Err(e) => {
+                                            log::error!("failed to delete recording: {e}");
+                                            toast(state, get_local_text("settings-recordings.delete-failed"));
+                                        }
+                                    }
+                                },
+                            }
+                        )
+                    }
+                }
+            },
+            playing.then(|| rsx!(
+                div {
+                    class: "recording-player",
+                    aria_label: "recording-player",
+                    recording.tracks.iter().map(|track| {
+                        let src = get_fixed_path_to_load_local_file(track.clone());
+                        let name = track
+                            .file_name()
+                            .map(|x| x.to_string_lossy().to_string())
+                            .unwrap_or_default();
+                        rsx!(div {
+                            key: "{src}",
+                            class: "recording-track",
+                            p { "{name}" },
+                            audio {
+                                controls: true,
+                                src: "{src}",
+                            }
+                        })
+                    })
+                }
+            ))
+        }
+    ))
+}
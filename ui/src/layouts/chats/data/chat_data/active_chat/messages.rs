@@ -1,5 +1,5 @@
 use chrono::{DateTime, Utc};
-use common::warp_runner::ui_adapter;
+use common::warp_runner::{search_index, ui_adapter};
 use std::collections::{HashMap, HashSet, VecDeque};
 use uuid::Uuid;
 
@@ -186,6 +186,17 @@ impl Messages {
     pub fn bottom(&self) -> Option<Uuid> {
         self.all.back().map(|x| x.inner.id())
     }
+
+    // the search index knows about replies which aren't loaded. the loaded ones are counted in case a reply was
+    // added to the chat before it was indexed
+    pub fn thread_replies(&self, root: Uuid) -> usize {
+        let loaded = self
+            .all
+            .iter()
+            .filter(|x| x.thread_root == Some(root))
+            .count();
+        loaded.max(search_index::thread_replies(root))
+    }
 }
 
 impl Messages {
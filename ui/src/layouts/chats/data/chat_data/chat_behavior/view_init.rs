@@ -1,5 +1,6 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
 use crate::layouts::chats::data::DEFAULT_MESSAGES_TO_TAKE;
 
@@ -11,6 +12,9 @@ pub struct ViewInit {
     pub msg_time: Option<DateTime<Utc>>,
     // fetch at most `limit` messages starting at `earliest_time` or now() (if it's none)
     pub limit: usize,
+    // briefly highlighted once the view is scrolled. used when jumping to a search result
+    #[serde(default)]
+    pub highlight: Option<Uuid>,
 }
 
 impl Default for ViewInit {
@@ -19,6 +23,7 @@ impl Default for ViewInit {
             scroll_to: ScrollTo::MostRecent,
             msg_time: None,
             limit: DEFAULT_MESSAGES_TO_TAKE,
+            highlight: None,
         }
     }
 }
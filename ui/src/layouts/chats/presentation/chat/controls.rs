@@ -10,7 +10,7 @@ use kit::{
     layout::modal::Modal,
 };
 
-use super::pinned_messages::PinnedMessages;
+use super::{pinned_messages::PinnedMessages, search::ConversationSearch};
 use crate::layouts::chats::data::{ChatData, ChatProps};
 
 use common::{
@@ -49,6 +49,7 @@ pub fn get_controls(cx: Scope<ChatProps>) -> Element {
     let call_in_progress = active_call.is_some(); // active_chat.map(|chat| chat.id) == active_call.map(|call| call.conversation_id);
 
     let show_pinned = use_state(cx, || false);
+    let show_search = use_state(cx, || false);
 
     use_effect(cx, &minimal, |_| {
         to_owned![show_more];
@@ -82,10 +83,12 @@ pub fn get_controls(cx: Scope<ChatProps>) -> Element {
                         let res = rx.await.expect("warp runner failed");
                         match res {
                             Ok(call_id) => {
+                                let caller = state.read().did_key();
                                 state.write().mutate(Action::OfferCall(call::Call::new(
                                     call_id,
                                     conversation_id,
                                     participants,
+                                    caller,
                                 )));
                             }
                             Err(e) => {
@@ -211,6 +214,17 @@ pub fn get_controls(cx: Scope<ChatProps>) -> Element {
                 }
             }
         },
+        Button {
+            icon: Icon::MagnifyingGlass,
+            aria_label: "search-chat-label".into(),
+            appearance: if *show_search.clone() { Appearance::Primary } else { Appearance::Secondary },
+            text: text_builder("messages.search"),
+            tooltip: tooltip_builder("messages.search", arrow_top),
+            onpress: move |_| {
+                show_search.set(true);
+                show_more.set(false);
+            }
+        }
         Button {
             icon: Icon::Pin,
             aria_label: "pin-label".into(),
@@ -266,6 +280,22 @@ pub fn get_controls(cx: Scope<ChatProps>) -> Element {
         }
     )),));
 
+    let search = cx.render(rsx!(show_search.then(|| rsx!(
+        Modal {
+            open: true,
+            right: "8px",
+            transparent: true,
+            change_horizontal_position: true,
+            with_title: get_local_text("messages.search"),
+            onclose: move |_| {
+                show_search.set(false);
+            },
+            if chat_data.read().active_chat.is_initialized {
+                rsx!(ConversationSearch{ show_search: show_search.clone()})
+            }
+        }
+    )),));
+
     if minimal {
         return cx.render(rsx!(
             div {
@@ -300,8 +330,9 @@ pub fn get_controls(cx: Scope<ChatProps>) -> Element {
                         buttons
                     })
             }),
-            pinned
+            pinned,
+            search
         ));
     }
-    cx.render(rsx!(buttons, pinned))
+    cx.render(rsx!(buttons, pinned, search))
 }
@@ -1,7 +1,7 @@
 use anyhow::bail;
 use chrono::{DateTime, Utc};
 use common::{
-    state::State,
+    state::{ui::JumpToMessage, State},
     warp_runner::{
         ui_adapter::{self, MessageEvent},
         FetchMessagesConfig, FetchMessagesResponse, RayGunCmd, WarpCmd, WarpEvent,
@@ -9,7 +9,7 @@ use common::{
     WARP_CMD_CH, WARP_EVENT_CH,
 };
 use dioxus::prelude::*;
-use futures::channel::oneshot;
+use futures::{channel::oneshot, StreamExt};
 use uuid::Uuid;
 
 use crate::layouts::chats::data::{self, ChatBehavior, ChatData};
@@ -114,7 +114,12 @@ pub fn init_chat_data<'a>(
                 Some(x) => x,
             };
 
-            let behavior = chat_data.read().get_chat_behavior(conv_id);
+            // a search result may have opened this chat. start at the matching message instead of where the chat was left
+            let jump = take_jump_to_message(&state, conv_id);
+            let behavior = match jump {
+                Some(jump) => jump_behavior(&jump),
+                None => chat_data.read().get_chat_behavior(conv_id),
+            };
             let config = behavior.messages_config();
 
             let r = match config {
@@ -142,6 +147,73 @@ pub fn init_chat_data<'a>(
     })
 }
 
+/// Scrolls to a search result when it is in the chat which is already open. Chats which still have to be loaded
+/// pick up the jump in init_chat_data.
+pub fn jump_to_message<'a>(
+    cx: &Scoped<'a>,
+    state: &'a UseSharedState<State>,
+    chat_data: &'a UseSharedState<ChatData>,
+    init: &'a UseFuture<()>,
+) {
+    let ch = use_coroutine(cx, |mut rx: UnboundedReceiver<JumpToMessage>| {
+        to_owned![state, chat_data];
+        async move {
+            while let Some(jump) = rx.next().await {
+                let r = fetch_window(
+                    jump.conversation_id,
+                    jump_behavior(&jump),
+                    jump.date,
+                    data::DEFAULT_MESSAGES_TO_TAKE / 2,
+                )
+                .await;
+                match r {
+                    Ok((messages, behavior)) => {
+                        chat_data.write().set_active_chat(
+                            &state.read(),
+                            &jump.conversation_id,
+                            behavior,
+                            messages,
+                        );
+                    }
+                    Err(e) => log::error!("{e}"),
+                }
+            }
+        }
+    });
+
+    let active_chat_id = chat_data.read().active_chat.id();
+    if init.value().is_some() {
+        if let Some(jump) = take_jump_to_message(state, active_chat_id) {
+            ch.send(jump);
+        }
+    }
+}
+
+fn take_jump_to_message(state: &UseSharedState<State>, conv_id: Uuid) -> Option<JumpToMessage> {
+    let mut state = state.write_silent();
+    match state.ui.jump_to_message.as_ref() {
+        Some(jump) if jump.conversation_id == conv_id => state.ui.jump_to_message.take(),
+        _ => None,
+    }
+}
+
+fn jump_behavior(jump: &JumpToMessage) -> ChatBehavior {
+    ChatBehavior {
+        view_init: data::ViewInit {
+            scroll_to: data::ScrollTo::ScrollUp {
+                view_top: jump.message_id,
+            },
+            msg_time: Some(jump.date),
+            limit: data::DEFAULT_MESSAGES_TO_TAKE,
+            highlight: Some(jump.message_id),
+        },
+        // these fields will be overwritten by fetch_window
+        on_scroll_end: data::ScrollBehavior::FetchMore,
+        on_scroll_top: data::ScrollBehavior::FetchMore,
+        ..Default::default()
+    }
+}
+
 pub async fn fetch_window<'a>(
     conv_id: Uuid,
     chat_behavior: ChatBehavior,
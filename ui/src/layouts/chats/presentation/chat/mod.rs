@@ -4,12 +4,15 @@ mod edit_group;
 mod group_settings;
 mod group_users;
 mod pinned_messages;
+mod search;
+mod thread;
 mod topbar;
 
 use dioxus::prelude::*;
 
 use kit::{
     components::message_group::MessageGroupSkeletal,
+    elements::{button::Button, Appearance},
     layout::{modal::Modal, topbar::Topbar},
 };
 
@@ -18,14 +21,18 @@ use crate::{
     layouts::chats::{
         data::{self, ChatData, MessagesToSend, ScrollBtn},
         presentation::{
-            chat::{edit_group::EditGroup, group_settings::GroupSettings, group_users::GroupUsers},
+            chat::{
+                edit_group::EditGroup, group_settings::GroupSettings, group_users::GroupUsers,
+                thread::Thread,
+            },
             chatbar::get_chatbar,
             messages::get_messages,
         },
-        scripts::{DISABLE_RELOAD, SHOW_CONTEXT, USER_TAG_SCRIPT},
+        scripts::{DISABLE_RELOAD, LINK_CLICK_SCRIPT, SHOW_CONTEXT, USER_TAG_SCRIPT},
     },
 };
 
+use common::icons::outline::Shape as Icon;
 use common::state::{ui, Action, Identity, State};
 
 use common::language::get_local_text;
@@ -45,6 +52,7 @@ pub fn Compose(cx: Scope) -> Element {
 
     let init = coroutines::init_chat_data(cx, state, chat_data);
     coroutines::handle_warp_events(cx, state, chat_data);
+    coroutines::jump_to_message(cx, state, chat_data, init);
 
     state.write_silent().ui.current_layout = ui::Layout::Compose;
 
@@ -57,6 +65,7 @@ pub fn Compose(cx: Scope) -> Element {
     let quickprofile_data: &UseRef<Option<(f64, f64, Identity, bool)>> = use_ref(cx, || None);
     let update_script = use_state(cx, String::new);
     let identity_profile = use_state(cx, DID::default);
+    let pending_link: &UseState<Option<String>> = use_state(cx, || None);
 
     let eval_provider = use_eval(cx);
     let script = DISABLE_RELOAD;
@@ -86,6 +95,19 @@ pub fn Compose(cx: Scope) -> Element {
             }
         }
     });
+    // Named links are confirmed before they are opened, since their text can differ from where they lead
+    use_effect(cx, (), |_| {
+        to_owned![eval_provider, pending_link];
+        async move {
+            if let Ok(eval) = eval_provider(LINK_CLICK_SCRIPT) {
+                loop {
+                    if let Ok(url) = eval.recv().await {
+                        pending_link.set(url.as_str().map(|url| url.to_string()));
+                    }
+                }
+            }
+        }
+    });
     use_effect(cx, quickprofile_data, |data| {
         to_owned![quick_profile_uuid, update_script, identity_profile];
         async move {
@@ -108,6 +130,12 @@ pub fn Compose(cx: Scope) -> Element {
     let chat_id = chat_data.read().active_chat.id();
     let user_did: DID = state.read().did_key();
     let is_owner = creator.map(|id| id == user_did).unwrap_or_default();
+    let open_thread = state
+        .read()
+        .chats
+        .all
+        .get(&chat_id)
+        .and_then(|chat| chat.open_thread);
 
     if init.value().is_some() {
         if let Some(chat) = state.read().get_active_chat() {
@@ -203,6 +231,67 @@ pub fn Compose(cx: Scope) -> Element {
                     }
                 }
         )),
+        open_thread.map(|root| rsx!(
+                Modal {
+                    open: true,
+                    right: "8px",
+                    transparent: true,
+                    change_horizontal_position: true,
+                    with_title: get_local_text("messages.thread"),
+                    onclose: move |_| {
+                        state.write().mutate(Action::CloseThread(chat_id));
+                    },
+                    Thread {
+                        key: "{root}",
+                        conv_id: chat_id,
+                        root: root,
+                    }
+                }
+        )),
+        pending_link.get().as_ref().map(|url| rsx!(
+                Modal {
+                    open: true,
+                    with_title: get_local_text("messages.open-link"),
+                    onclose: move |_| {
+                        pending_link.set(None);
+                    },
+                    div {
+                        class: "open-link-modal",
+                        p {
+                            get_local_text("messages.open-link-description")
+                        },
+                        span {
+                            class: "open-link-url",
+                            aria_label: "open-link-url",
+                            "{url}"
+                        },
+                        div {
+                            class: "open-link-buttons",
+                            Button {
+                                text: get_local_text("uplink.cancel"),
+                                aria_label: "open-link-cancel".into(),
+                                appearance: Appearance::Secondary,
+                                onpress: move |_| {
+                                    pending_link.set(None);
+                                },
+                            },
+                            Button {
+                                text: get_local_text("messages.open"),
+                                aria_label: "open-link-confirm".into(),
+                                icon: Icon::ArrowTopRightOnSquare,
+                                onpress: move |_| {
+                                    if let Some(url) = pending_link.get() {
+                                        if let Err(e) = open::that(url) {
+                                            log::error!("failed to open link: {e}");
+                                        }
+                                    }
+                                    pending_link.set(None);
+                                },
+                            },
+                        }
+                    }
+                }
+        )),
         CallControl {
             in_chat: true
         },
@@ -91,6 +91,7 @@ pub fn PinnedMessages(cx: Scope<'_, Props>) -> Element<'_> {
                             },
                             msg_time: Some(message_date),
                             limit: data::DEFAULT_MESSAGES_TO_TAKE,
+                            highlight: Some(message_id),
                         };
                         let behavior = data::ChatBehavior {
                             view_init,
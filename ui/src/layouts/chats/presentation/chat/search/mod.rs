@@ -0,0 +1,321 @@
+use chrono::{Duration, Utc};
+use common::{
+    icons::outline::Shape as Icon,
+    language::get_local_text,
+    state::{ui::JumpToMessage, State},
+    warp_runner::{
+        search_index::{self, SearchQuery, SearchResult},
+        RayGunCmd, WarpCmd,
+    },
+    WARP_CMD_CH,
+};
+use dioxus::prelude::*;
+
+use kit::{
+    components::user_image::UserImage,
+    elements::{
+        checkbox::Checkbox,
+        input::{Input, Options},
+        select::Select,
+    },
+};
+use warp::crypto::DID;
+
+use tracing::log;
+
+use crate::layouts::chats::data::ChatData;
+
+// the results list is not meant to page through every match
+const MAX_RESULTS: usize = 100;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Period {
+    Any,
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+impl Period {
+    const ALL: [Period; 5] = [
+        Period::Any,
+        Period::Day,
+        Period::Week,
+        Period::Month,
+        Period::Year,
+    ];
+
+    fn label(&self) -> String {
+        get_local_text(match self {
+            Period::Any => "messages.search-any-time",
+            Period::Day => "messages.search-past-day",
+            Period::Week => "messages.search-past-week",
+            Period::Month => "messages.search-past-month",
+            Period::Year => "messages.search-past-year",
+        })
+    }
+
+    fn duration(&self) -> Option<Duration> {
+        match self {
+            Period::Any => None,
+            Period::Day => Some(Duration::days(1)),
+            Period::Week => Some(Duration::weeks(1)),
+            Period::Month => Some(Duration::days(30)),
+            Period::Year => Some(Duration::days(365)),
+        }
+    }
+}
+
+#[derive(Props, PartialEq)]
+pub struct Props {
+    show_search: UseState<bool>,
+}
+
+/// Searches the history of the active chat in RayGun. Clicking a result scrolls the chat to it.
+#[allow(non_snake_case)]
+pub fn ConversationSearch(cx: Scope<Props>) -> Element {
+    log::trace!("rendering conversation search");
+    let state = use_shared_state::<State>(cx)?;
+    let chat_data = use_shared_state::<ChatData>(cx)?;
+    let conv_id = chat_data.read().active_chat.id();
+
+    let text = use_state(cx, String::new);
+    let sender: &UseState<Option<DID>> = use_state(cx, || None);
+    let period = use_state(cx, || Period::Any);
+    let attachments_only = use_state(cx, || false);
+
+    let query = SearchQuery {
+        text: text.get().clone(),
+        sender: sender.get().clone(),
+        after: period.duration().map(|d| Utc::now() - d),
+        has_attachment: (*attachments_only.get()).then_some(true),
+        limit: MAX_RESULTS,
+        ..Default::default()
+    };
+    let is_empty = query.is_empty();
+
+    // `after` changes with every render, so the period is the dependency instead
+    let results = use_future(
+        cx,
+        (
+            &conv_id,
+            text.get(),
+            sender.get(),
+            period.get(),
+            attachments_only.get(),
+        ),
+        move |(conv_id, _, _, _, _)| async move {
+            if query.is_empty() {
+                return Some(vec![]);
+            }
+            let (tx, rx) = futures::channel::oneshot::channel();
+            if let Err(e) = WARP_CMD_CH
+                .tx
+                .send(WarpCmd::RayGun(RayGunCmd::SearchConversation {
+                    conv_id,
+                    query,
+                    rsp: tx,
+                }))
+            {
+                log::error!("failed to send warp command: {}", e);
+                return None;
+            }
+            match rx.await.expect("command canceled") {
+                Ok(results) => Some(results),
+                Err(e) => {
+                    log::error!("failed to search conversation: {e}");
+                    None
+                }
+            }
+        },
+    );
+
+    let mut participants = chat_data.read().active_chat.other_participants();
+    participants.push(chat_data.read().active_chat.my_id());
+    let anyone = get_local_text("messages.search-anyone");
+    let sender_name = sender
+        .get()
+        .as_ref()
+        .and_then(|did| participants.iter().find(|id| &id.did_key() == did))
+        .map(|id| id.username())
+        .unwrap_or_else(|| anyone.clone());
+    let mut sender_options = vec![anyone.clone()];
+    sender_options.extend(participants.iter().map(|id| id.username()));
+
+    // results are matched by word, so only the first word typed is highlighted
+    let highlight_term = search_index::tokenize(text.get())
+        .into_iter()
+        .next()
+        .unwrap_or_default();
+
+    let list = match results.value() {
+        _ if is_empty => rsx!(div {
+            class: "conversation-search-empty",
+            get_local_text("messages.search-hint")
+        }),
+        None => rsx!(div {
+            class: "conversation-search-empty",
+            get_local_text("messages.loading")
+        }),
+        Some(None) => rsx!(div {
+            class: "conversation-search-empty",
+            aria_label: "conversation-search-failed",
+            get_local_text("messages.search-failed")
+        }),
+        Some(Some(results)) if results.is_empty() => rsx!(div {
+            class: "conversation-search-empty",
+            aria_label: "conversation-search-no-results",
+            get_local_text("messages.search-no-results")
+        }),
+        Some(Some(results)) => rsx!(results.iter().map(|result| {
+            let key = result.message_id;
+            let result2 = result.clone();
+            rsx!(SearchHit {
+                key: "{key}",
+                result: result.clone(),
+                highlight_term: highlight_term.clone(),
+                onclick: move |_| {
+                    // the chat view loads the messages around the result and scrolls to it
+                    state.write().ui.jump_to_message = Some(JumpToMessage {
+                        conversation_id: result2.conversation_id,
+                        message_id: result2.message_id,
+                        date: result2.date,
+                    });
+                    cx.props.show_search.set(false);
+                },
+            })
+        })),
+    };
+
+    cx.render(rsx!(
+        div {
+            id: "conversation-search-container",
+            aria_label: "conversation-search-container",
+            Input {
+                aria_label: "conversation-search-input".into(),
+                placeholder: get_local_text("messages.search-placeholder"),
+                focus: true,
+                icon: Icon::MagnifyingGlass,
+                options: Options {
+                    with_clear_btn: true,
+                    react_to_esc_key: true,
+                    ..Options::default()
+                },
+                onchange: move |(v, _): (String, _)| {
+                    if v.trim().is_empty() {
+                        text.set(String::new());
+                    }
+                },
+                onreturn: move |(v, _, _): (String, bool, _)| {
+                    text.set(v.trim().to_string());
+                },
+            },
+            div {
+                class: "conversation-search-filters",
+                Select {
+                    initial_value: sender_name,
+                    options: sender_options,
+                    onselect: move |name: String| {
+                        let did = participants
+                            .iter()
+                            .find(|id| id.username() == name)
+                            .map(|id| id.did_key());
+                        sender.set(did);
+                    }
+                },
+                Select {
+                    initial_value: period.label(),
+                    options: Period::ALL.iter().map(Period::label).collect(),
+                    onselect: move |label: String| {
+                        if let Some(p) = Period::ALL.iter().find(|p| p.label() == label) {
+                            period.set(*p);
+                        }
+                    }
+                },
+                div {
+                    class: "conversation-search-attachments",
+                    Checkbox {
+                        disabled: false,
+                        is_checked: *attachments_only.get(),
+                        aria_label: "conversation-search-attachments".into(),
+                        on_click: move |_| {
+                            attachments_only.set(!attachments_only.get());
+                        }
+                    },
+                    p {
+                        get_local_text("messages.search-attachments")
+                    }
+                }
+            },
+            div {
+                class: "conversation-search-results",
+                aria_label: "conversation-search-results",
+                list
+            }
+        }
+    ))
+}
+
+#[derive(Props)]
+struct SearchHitProps<'a> {
+    result: SearchResult,
+    highlight_term: String,
+    onclick: EventHandler<'a, ()>,
+}
+
+#[allow(non_snake_case)]
+fn SearchHit<'a>(cx: Scope<'a, SearchHitProps<'a>>) -> Element<'a> {
+    let state = use_shared_state::<State>(cx)?;
+    let result = &cx.props.result;
+    let sender = state.read().get_identity(&result.sender);
+    let time = result
+        .date
+        .format(&get_local_text("uplink.date-time-format"))
+        .to_string();
+    let snippet = &result.snippet;
+    let highlight = search_index::find_highlight(snippet, &cx.props.highlight_term);
+
+    cx.render(rsx!(div {
+        class: "conversation-search-hit",
+        aria_label: "conversation-search-hit",
+        onclick: move |_| cx.props.onclick.call(()),
+        sender.as_ref().map(|sender| rsx!(UserImage {
+            image: sender.profile_picture(),
+            platform: sender.platform().into(),
+        })),
+        div {
+            class: "conversation-search-hit-content",
+            div {
+                class: "conversation-search-hit-header",
+                sender.as_ref().map(|sender| rsx!(p {
+                    class: "ellipsis-overflow",
+                    sender.username()
+                })),
+                p {
+                    class: "conversation-search-hit-time",
+                    "{time}"
+                }
+            },
+            p {
+                class: "conversation-search-hit-snippet",
+                if let Some((start, end)) = highlight {
+                    rsx!(
+                        span { &snippet[0..start] },
+                        span {
+                            class: "highlight-search-typed-chars",
+                            &snippet[start..end]
+                        },
+                        span { &snippet[end..] },
+                    )
+                } else {
+                    rsx!(span { "{snippet}" })
+                }
+            },
+            result.has_attachment.then(|| rsx!(p {
+                class: "conversation-search-hit-attachment",
+                get_local_text("messages.search-has-attachment")
+            }))
+        }
+    }))
+}
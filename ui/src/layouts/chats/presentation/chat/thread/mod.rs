@@ -0,0 +1,186 @@
+use common::{
+    language::get_local_text,
+    state::{Identity, State},
+    warp_runner::{ui_adapter, RayGunCmd, WarpCmd},
+    WARP_CMD_CH,
+};
+use dioxus::prelude::*;
+
+use futures::StreamExt;
+use kit::{
+    components::{message::ChatText, user_image::UserImage},
+    elements::input::{Input, Options},
+};
+use uuid::Uuid;
+
+use tracing::log;
+
+struct ThreadReply {
+    conv_id: Uuid,
+    root: Uuid,
+    msg: Vec<String>,
+}
+
+#[derive(Props, PartialEq)]
+pub struct Props {
+    conv_id: Uuid,
+    root: Uuid,
+}
+
+/// Shows the root message of a thread followed by every reply to it. Replies sent from here reply to the root.
+#[allow(non_snake_case)]
+pub fn Thread(cx: Scope<Props>) -> Element {
+    log::trace!("rendering thread");
+    let state = use_shared_state::<State>(cx)?;
+    let conv_id = cx.props.conv_id;
+    let root = cx.props.root;
+
+    // refetch whenever a message arrives in the chat
+    let latest = state
+        .read()
+        .chats
+        .all
+        .get(&conv_id)
+        .and_then(|chat| chat.messages.back().map(|msg| msg.inner.id()));
+
+    let thread = use_future(
+        cx,
+        (&conv_id, &root, &latest),
+        |(conv_id, root, _)| async move {
+            let (tx, rx) = futures::channel::oneshot::channel();
+            if let Err(e) = WARP_CMD_CH.tx.send(WarpCmd::RayGun(RayGunCmd::FetchThread {
+                conv_id,
+                root_id: root,
+                rsp: tx,
+            })) {
+                log::error!("failed to send warp command: {}", e);
+                return None;
+            }
+            match rx.await.expect("command canceled") {
+                Ok(messages) => Some(messages),
+                Err(e) => {
+                    log::error!("failed to fetch thread: {e}");
+                    None
+                }
+            }
+        },
+    );
+
+    let ch = use_coroutine(cx, |mut rx: UnboundedReceiver<ThreadReply>| async move {
+        let warp_cmd_tx = WARP_CMD_CH.tx.clone();
+        while let Some(ThreadReply { conv_id, root, msg }) = rx.next().await {
+            let (tx, rx) = futures::channel::oneshot::channel();
+            if let Err(e) = warp_cmd_tx.send(WarpCmd::RayGun(RayGunCmd::Reply {
+                conv_id,
+                reply_to: root,
+                msg,
+                attachments: vec![],
+                rsp: tx,
+            })) {
+                log::error!("failed to send warp command: {}", e);
+                continue;
+            }
+            if let Err(e) = rx.await.expect("command canceled") {
+                log::error!("failed to reply in thread: {e}");
+            }
+        }
+    });
+
+    let messages = match thread.value() {
+        None => rsx!(div {
+            class: "thread-empty",
+            get_local_text("messages.loading")
+        }),
+        Some(None) => rsx!(div {
+            class: "thread-empty",
+            aria_label: "thread-unavailable",
+            get_local_text("messages.thread-unavailable")
+        }),
+        Some(Some(messages)) => rsx!(messages.iter().map(|message| {
+            let key = message.inner.id();
+            rsx!(ThreadMessage {
+                key: "{key}",
+                message: message.clone(),
+                sender: state.read().get_identity(&message.inner.sender()),
+                is_root: key == root,
+            })
+        })),
+    };
+
+    cx.render(rsx!(
+        div {
+            id: "thread-container",
+            aria_label: "thread-container",
+            div {
+                class: "thread-messages",
+                messages
+            },
+            Input {
+                aria_label: "thread-input".into(),
+                placeholder: get_local_text("messages.thread-placeholder"),
+                options: Options {
+                    clear_on_submit: true,
+                    react_to_esc_key: true,
+                    ..Options::default()
+                },
+                onreturn: move |(v, _, _): (String, bool, _)| {
+                    if v.trim().is_empty() {
+                        return;
+                    }
+                    let msg = v.lines().map(|x| x.to_string()).collect();
+                    ch.send(ThreadReply { conv_id, root, msg });
+                },
+            }
+        }
+    ))
+}
+
+#[derive(Props, PartialEq)]
+struct ThreadMessageProps {
+    message: ui_adapter::Message,
+    #[props(!optional)]
+    sender: Option<Identity>,
+    is_root: bool,
+}
+
+#[allow(non_snake_case)]
+fn ThreadMessage(cx: Scope<ThreadMessageProps>) -> Element {
+    let state = use_shared_state::<State>(cx)?;
+    let message = &cx.props.message.inner;
+    let time = message
+        .date()
+        .format(&get_local_text("uplink.date-time-format"))
+        .to_string();
+
+    cx.render(rsx!(div {
+        class: format_args!("thread-message {}", if cx.props.is_root { "thread-root" } else { "" }),
+        aria_label: "thread-message",
+        cx.props.sender.as_ref().map(|sender| rsx!(UserImage {
+            image: sender.profile_picture(),
+            platform: sender.platform().into(),
+        })),
+        div {
+            class: "thread-message-content",
+            div {
+                class: "thread-sender",
+                cx.props.sender.as_ref().map(|sender| rsx!(p {
+                    class: "ellipsis-overflow",
+                    sender.username()
+                })),
+                p {
+                    class: "thread-time",
+                    "{time}"
+                }
+            },
+            ChatText {
+                text: message.lines().join("\n"),
+                remote: true,
+                pending: false,
+                state: state,
+                chat: message.conversation_id(),
+                markdown: state.read().ui.should_transform_markdown_text(),
+                ascii_emoji: state.read().ui.should_transform_ascii_emojis(),
+            }
+        }
+    }))
+}
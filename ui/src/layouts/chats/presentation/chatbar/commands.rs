@@ -0,0 +1,268 @@
+//! Slash commands typed into the chatbar, ex: `/shrug`. The built in commands are listed in `BUILTINS`. Enabled
+//! extensions can add their own by implementing `extensions::Extension::commands`.
+
+use common::{language::get_local_text, state::State};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use uuid::Uuid;
+use warp::crypto::DID;
+
+// matches a command which is still being typed
+pub static COMMAND_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^/[\w-]*$").unwrap());
+
+const SHRUG: &str = r"¯\\_(ツ)\_/¯";
+const TABLE_FLIP: &str = "(╯°□°)╯︵ ┻━┻";
+const UNFLIP: &str = "┬─┬ノ( º _ ºノ)";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Builtin {
+    Me,
+    Shrug,
+    TableFlip,
+    Unflip,
+    Pin,
+    Mute,
+    Call,
+}
+
+// (command, name, args). the description is the locale key `commands.<name>`
+const BUILTINS: &[(Builtin, &str, &str)] = &[
+    (Builtin::Me, "me", "<action>"),
+    (Builtin::Shrug, "shrug", "[text]"),
+    (Builtin::TableFlip, "tableflip", "[text]"),
+    (Builtin::Unflip, "unflip", "[text]"),
+    (Builtin::Pin, "pin", ""),
+    (Builtin::Mute, "mute", ""),
+    (Builtin::Call, "call", ""),
+];
+
+/// Commands which need warp. Handled by the chatbar's command coroutine
+pub enum CommandCmd {
+    Pin {
+        conv_id: Uuid,
+        msg_id: Uuid,
+    },
+    ToggleMute {
+        muted: bool,
+    },
+    Call {
+        conv_id: Uuid,
+        participants: Vec<DID>,
+    },
+}
+
+/// What submitting the draft should do when it is a command
+pub enum Outcome {
+    // send this instead of the draft
+    Send(Vec<String>),
+    Run(CommandCmd),
+    // the command ran and there is nothing to send
+    Done,
+    // the draft is kept so it can be fixed
+    Invalid(String),
+}
+
+fn usage(name: &str, args: &str) -> String {
+    if args.is_empty() {
+        format!("/{name}")
+    } else {
+        format!("/{name} {args}")
+    }
+}
+
+/// Returns (usage, description) for every command starting with `typed`, which includes the slash
+pub fn suggestions(state: &State, typed: &str) -> Vec<(String, String)> {
+    let Some(prefix) = typed.strip_prefix('/') else {
+        return vec![];
+    };
+    let prefix = prefix.to_lowercase();
+    let builtins = BUILTINS
+        .iter()
+        .filter(|(_, name, _)| name.starts_with(&prefix))
+        .map(|(_, name, args)| {
+            (
+                usage(name, args),
+                get_local_text(&format!("commands.{name}")),
+            )
+        });
+    let extensions = state
+        .ui
+        .extensions
+        .values()
+        .filter(|(enabled, _)| *enabled)
+        .flat_map(|(_, ext)| ext.commands().to_vec())
+        .filter(|cmd| cmd.name.starts_with(&prefix) && !is_builtin(cmd.name))
+        .map(|cmd| (usage(cmd.name, cmd.args), cmd.description.to_string()));
+    builtins.chain(extensions).collect()
+}
+
+fn is_builtin(name: &str) -> bool {
+    BUILTINS.iter().any(|(_, n, _)| *n == name)
+}
+
+/// Splits `/name rest` into the name and everything after it. Returns None if `text` isn't a command.
+fn split_command(text: &str) -> Option<(String, &str)> {
+    let text = text.strip_prefix('/')?;
+    let end = text.find(char::is_whitespace).unwrap_or(text.len());
+    let (name, rest) = text.split_at(end);
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+    {
+        return None;
+    }
+    Some((name.to_lowercase(), rest.trim()))
+}
+
+/// Splits on whitespace. Double quotes group words into one argument.
+fn split_args(rest: &str) -> Vec<String> {
+    let mut args = vec![];
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut has_arg = false;
+    for c in rest.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                has_arg = true;
+            }
+            c if c.is_whitespace() && !quoted => {
+                if has_arg {
+                    args.push(std::mem::take(&mut current));
+                    has_arg = false;
+                }
+            }
+            c => {
+                current.push(c);
+                has_arg = true;
+            }
+        }
+    }
+    if has_arg {
+        args.push(current);
+    }
+    args
+}
+
+// puts the emoticon at the end of the last line
+fn with_suffix(rest: &str, suffix: &str) -> Vec<String> {
+    let mut lines: Vec<String> = rest.lines().map(|x| x.to_string()).collect();
+    match lines.last_mut() {
+        Some(line) => {
+            line.push(' ');
+            line.push_str(suffix);
+        }
+        None => lines.push(suffix.to_string()),
+    }
+    lines
+}
+
+/// Runs the command in `text`, sent to `conv_id`. Returns None if `text` should be sent as a normal message.
+/// A leading `//` sends the text with one slash removed.
+pub fn run(state: &State, conv_id: Uuid, text: &str) -> Option<Outcome> {
+    if let Some(escaped) = text.strip_prefix("//") {
+        return Some(Outcome::Send(
+            format!("/{escaped}")
+                .lines()
+                .map(|x| x.to_string())
+                .collect(),
+        ));
+    }
+    let (name, rest) = split_command(text)?;
+    match BUILTINS.iter().find(|(_, n, _)| *n == name) {
+        Some((builtin, _, _)) => Some(run_builtin(state, conv_id, *builtin, rest)),
+        None => run_extension(state, &name, rest),
+    }
+}
+
+fn run_builtin(state: &State, conv_id: Uuid, builtin: Builtin, rest: &str) -> Outcome {
+    match builtin {
+        Builtin::Me => {
+            if rest.is_empty() {
+                return Outcome::Invalid(get_local_text("commands.me-usage"));
+            }
+            let username = state.get_own_identity().username();
+            let action = rest.lines().collect::<Vec<_>>().join(" ");
+            Outcome::Send(vec![format!("_{username} {action}_")])
+        }
+        Builtin::Shrug => Outcome::Send(with_suffix(rest, SHRUG)),
+        Builtin::TableFlip => Outcome::Send(with_suffix(rest, TABLE_FLIP)),
+        Builtin::Unflip => Outcome::Send(with_suffix(rest, UNFLIP)),
+        Builtin::Pin => {
+            let last = state
+                .get_chat_by_id(conv_id)
+                .and_then(|chat| chat.messages.back().map(|msg| msg.inner.id()));
+            match last {
+                Some(msg_id) => Outcome::Run(CommandCmd::Pin { conv_id, msg_id }),
+                None => Outcome::Invalid(get_local_text("commands.nothing-to-pin")),
+            }
+        }
+        Builtin::Mute => match state.ui.call_info.active_call() {
+            Some(active) => Outcome::Run(CommandCmd::ToggleMute {
+                muted: active.call.self_muted,
+            }),
+            None => Outcome::Invalid(get_local_text("commands.not-in-call")),
+        },
+        Builtin::Call => {
+            if state.ui.call_info.active_call().is_some() {
+                return Outcome::Invalid(get_local_text("commands.already-in-call"));
+            }
+            let own_id = state.did_key();
+            let participants = state
+                .get_chat_by_id(conv_id)
+                .map(|chat| {
+                    chat.participants
+                        .into_iter()
+                        .filter(|did| *did != own_id)
+                        .collect()
+                })
+                .unwrap_or_default();
+            Outcome::Run(CommandCmd::Call {
+                conv_id,
+                participants,
+            })
+        }
+    }
+}
+
+fn run_extension(state: &State, name: &str, rest: &str) -> Option<Outcome> {
+    let ext = state
+        .ui
+        .extensions
+        .values()
+        .filter(|(enabled, _)| *enabled)
+        .map(|(_, ext)| ext)
+        .find(|ext| ext.commands().iter().any(|cmd| cmd.name == name))?;
+    let outcome = match ext.run_command(name, split_args(rest)) {
+        Some(msg) => Outcome::Send(msg.lines().map(|x| x.to_string()).collect()),
+        None => Outcome::Done,
+    };
+    Some(outcome)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_split_command() {
+        assert_eq!(
+            split_command("/Shrug  hello\nthere"),
+            Some(("shrug".into(), "hello\nthere"))
+        );
+        assert_eq!(split_command("/me"), Some(("me".into(), "")));
+        assert_eq!(split_command("/usr/bin"), None);
+        assert_eq!(split_command("/ hi"), None);
+        assert_eq!(split_command("hi /me"), None);
+    }
+
+    #[test]
+    fn test_split_args() {
+        assert_eq!(
+            split_args(r#"one "two three"  "" four"#),
+            vec!["one", "two three", "", "four"]
+        );
+        assert!(split_args("  ").is_empty());
+    }
+}
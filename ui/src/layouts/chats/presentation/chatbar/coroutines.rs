@@ -1,24 +1,24 @@
 use std::time::{Duration, Instant};
 
 use common::{
-    state::{Action, State},
-    warp_runner::{RayGunCmd, WarpCmd},
+    state::{call, outbox::OutboxMessage, pending_message::FileLocation, Action, State},
+    warp_runner::{BlinkCmd, RayGunCmd, WarpCmd},
     STATIC_ARGS, WARP_CMD_CH,
 };
 use dioxus::prelude::*;
 use futures::{channel::oneshot, StreamExt};
 use uuid::Uuid;
-use warp::raygun;
+use warp::raygun::{self, PinState};
 
 use crate::{
     layouts::chats::data::{self, ChatProps, MsgChInput, TypingInfo, DEFAULT_MESSAGES_TO_TAKE},
-    utils::async_task_queue::chat_upload_stream_handler,
+    utils::async_task_queue::outbox_upload_stream_handler,
 };
 
-use super::TypingIndicator;
+use super::{commands::CommandCmd, TypingIndicator};
 
 pub fn get_msg_ch(cx: &ScopeState, state: &UseSharedState<State>) -> Coroutine<MsgChInput> {
-    let upload_streams = chat_upload_stream_handler(cx);
+    let upload_streams = outbox_upload_stream_handler(cx);
     use_coroutine(cx, |mut rx: UnboundedReceiver<MsgChInput>| {
         to_owned![state, upload_streams];
         async move {
@@ -35,6 +35,12 @@ pub fn get_msg_ch(cx: &ScopeState, state: &UseSharedState<State>) -> Coroutine<M
                     .get_active_chat()
                     .map(|f| f.files_attached_to_send)
                     .unwrap_or_default();
+                // kept for the outbox, in case the message can't be sent
+                let files: Vec<FileLocation> = attachments
+                    .iter()
+                    .cloned()
+                    .map(FileLocation::from)
+                    .collect();
                 let cmd = match replying_to {
                     Some(reply_to) => RayGunCmd::Reply {
                         conv_id,
@@ -63,11 +69,103 @@ pub fn get_msg_ch(cx: &ScopeState, state: &UseSharedState<State>) -> Coroutine<M
                     Ok((id, stream)) => {
                         state.write().increment_outgoing_messages(id, msg.clone());
                         if let Some(stream) = stream {
-                            upload_streams.write().append((conv_id, id, stream));
+                            upload_streams.write().append((
+                                conv_id,
+                                id,
+                                stream,
+                                Box::new(move |e| {
+                                    OutboxMessage::new(conv_id, msg, replying_to, files, e)
+                                }),
+                            ));
                         }
                     }
                     Err(e) => {
                         log::error!("failed to send message: {}", e);
+                        state
+                            .write()
+                            .mutate(Action::QueueOutgoingMessage(OutboxMessage::new(
+                                conv_id,
+                                msg,
+                                replying_to,
+                                files,
+                                e.to_string(),
+                            )));
+                    }
+                }
+            }
+        }
+    })
+    .clone()
+}
+
+// runs the slash commands which need warp
+pub fn get_command_ch(cx: &ScopeState, state: &UseSharedState<State>) -> Coroutine<CommandCmd> {
+    use_coroutine(cx, |mut rx: UnboundedReceiver<CommandCmd>| {
+        to_owned![state];
+        async move {
+            let warp_cmd_tx = WARP_CMD_CH.tx.clone();
+            while let Some(cmd) = rx.next().await {
+                match cmd {
+                    CommandCmd::Pin { conv_id, msg_id } => {
+                        let (tx, rx) = oneshot::channel();
+                        if let Err(e) = warp_cmd_tx.send(WarpCmd::RayGun(RayGunCmd::Pin {
+                            conversation_id: conv_id,
+                            message_id: msg_id,
+                            pinstate: PinState::Pin,
+                            rsp: tx,
+                        })) {
+                            log::error!("failed to send warp command: {}", e);
+                            continue;
+                        }
+
+                        let res = rx.await.expect("command canceled");
+                        if let Err(e) = res {
+                            log::error!("failed to pin message: {}", e);
+                        }
+                    }
+                    CommandCmd::ToggleMute { muted } => {
+                        let (tx, rx) = oneshot::channel();
+                        let cmd = if muted {
+                            BlinkCmd::UnmuteSelf { rsp: tx }
+                        } else {
+                            BlinkCmd::MuteSelf { rsp: tx }
+                        };
+                        if let Err(e) = warp_cmd_tx.send(WarpCmd::Blink(cmd)) {
+                            log::error!("failed to send blink command: {e}");
+                            continue;
+                        }
+
+                        match rx.await {
+                            Ok(_) => state.write().mutate(Action::ToggleMute),
+                            Err(e) => log::error!("warp_runner failed to toggle mute: {e}"),
+                        }
+                    }
+                    CommandCmd::Call {
+                        conv_id,
+                        participants,
+                    } => {
+                        let (tx, rx) = oneshot::channel();
+                        if let Err(e) = warp_cmd_tx.send(WarpCmd::Blink(BlinkCmd::OfferCall {
+                            conversation_id: conv_id,
+                            participants: participants.clone(),
+                            rsp: tx,
+                        })) {
+                            log::error!("failed to send command to warp_runner: {e}");
+                            continue;
+                        }
+
+                        match rx.await.expect("warp runner failed") {
+                            Ok(call_id) => {
+                                let caller = state.read().did_key();
+                                state.write().mutate(Action::OfferCall(call::Call::new(
+                                    call_id,
+                                    conv_id,
+                                    participants,
+                                    caller,
+                                )));
+                            }
+                            Err(e) => log::error!("BlinkCmd::OfferCall failed: {e}"),
+                        }
                     }
                 }
             }
@@ -1,13 +1,19 @@
+mod commands;
 pub mod coroutines;
+mod scheduled;
 
 use std::{path::PathBuf, time::Duration};
 
+use chrono::{DateTime, Utc};
+
 use common::{
     icons::{self},
     language::{get_local_text, get_local_text_with_args},
     state::{
+        pending_message::FileLocation,
+        scheduled_messages::ScheduledMessage,
         utils::{mention_to_did_key, parse_mentions},
-        Action, Identity, State,
+        Action, Identity, State, ToastNotification,
     },
     MAX_FILES_PER_MESSAGE, STATIC_ARGS,
 };
@@ -55,6 +61,8 @@ use crate::{
         },
     },
 };
+use commands::{Outcome, COMMAND_REGEX};
+use scheduled::{ScheduledMessagesList, SendAtPicker};
 
 pub fn get_chatbar<'a>(cx: &'a Scoped<'a, ChatProps>) -> Element<'a> {
     log::trace!("get_chatbar");
@@ -78,6 +86,7 @@ pub fn get_chatbar<'a>(cx: &'a Scoped<'a, ChatProps>) -> Element<'a> {
     let update_script = use_state(cx, String::new);
     let upload_button_menu_uuid = &*cx.use_hook(|| Uuid::new_v4().to_string());
     let show_storage_modal = use_state(cx, || false);
+    let show_send_at = use_state(cx, || false);
 
     let suggestions = use_state(cx, || SuggestionType::None);
     let mentions = use_ref(cx, Vec::new);
@@ -144,6 +153,7 @@ pub fn get_chatbar<'a>(cx: &'a Scoped<'a, ChatProps>) -> Element<'a> {
     // this is used to scroll to the bottom of the chat.
     let scroll_ch = coroutines::get_scroll_ch(cx, chat_data, state);
     let msg_ch: Coroutine<MsgChInput> = coroutines::get_msg_ch(cx, state);
+    let command_ch = coroutines::get_command_ch(cx, state);
     let messages_to_send = &to_send.read().messages_to_send.clone();
     if !messages_to_send.is_empty() {
         for (txt, files) in messages_to_send {
@@ -164,6 +174,7 @@ pub fn get_chatbar<'a>(cx: &'a Scoped<'a, ChatProps>) -> Element<'a> {
     }
     let local_typing_ch = coroutines::get_typing_ch(cx);
     let local_typing_ch2 = local_typing_ch.clone();
+    let local_typing_ch3 = local_typing_ch.clone();
 
     // drives the sending of TypingIndicator
     let local_typing_ch1 = local_typing_ch.clone();
@@ -228,11 +239,9 @@ pub fn get_chatbar<'a>(cx: &'a Scoped<'a, ChatProps>) -> Element<'a> {
     let chat_participants_2 = chat_participants.clone();
     let chat_participants_3 = chat_participants.clone();
 
-    let submit_fn = move || {
-        local_typing_ch.send(TypingIndicator::NotTyping);
-        let active_chat_id = chat_data.read().active_chat.id();
-
-        let msg = state
+    // the draft of the active chat, with mentions replaced by DIDs
+    let draft_lines = move || {
+        state
             .read()
             .get_active_chat()
             .as_ref()
@@ -245,7 +254,35 @@ pub fn get_chatbar<'a>(cx: &'a Scoped<'a, ChatProps>) -> Element<'a> {
             .unwrap_or_default()
             .lines()
             .map(|x| x.trim_end().to_string())
-            .collect::<Vec<String>>();
+            .collect::<Vec<String>>()
+    };
+    let draft_lines2 = draft_lines.clone();
+
+    let submit_fn = move || {
+        local_typing_ch.send(TypingIndicator::NotTyping);
+        let active_chat_id = chat_data.read().active_chat.id();
+
+        let mut msg = draft_lines();
+
+        // slash commands either replace the draft or run instead of sending it
+        let mut ran_command = false;
+        if !active_chat_id.is_nil() {
+            let outcome = commands::run(&state.read(), active_chat_id, &msg.join("\n"));
+            match outcome {
+                Some(Outcome::Send(lines)) => msg = lines,
+                Some(Outcome::Run(cmd)) => {
+                    command_ch.send(cmd);
+                    ran_command = true;
+                }
+                Some(Outcome::Done) => ran_command = true,
+                Some(Outcome::Invalid(e)) => {
+                    let toast = ToastNotification::init("".into(), e, None, 2);
+                    state.write().mutate(Action::AddToastNotification(toast));
+                    return;
+                }
+                None => {}
+            }
+        }
 
         if !active_chat_id.is_nil() {
             state
@@ -256,7 +293,7 @@ pub fn get_chatbar<'a>(cx: &'a Scoped<'a, ChatProps>) -> Element<'a> {
         suggestions.set(SuggestionType::None);
         mentions.set(vec![]);
 
-        if !msg_valid(&msg) || active_chat_id.is_nil() {
+        if ran_command || !msg_valid(&msg) || active_chat_id.is_nil() {
             return;
         }
 
@@ -278,6 +315,42 @@ pub fn get_chatbar<'a>(cx: &'a Scoped<'a, ChatProps>) -> Element<'a> {
 
     let submit_fn2 = submit_fn.clone();
 
+    // queues the draft instead of sending it. the attachments are sent with it.
+    let schedule_fn = move |send_at: DateTime<Utc>| {
+        local_typing_ch3.send(TypingIndicator::NotTyping);
+        let active_chat_id = chat_data.read().active_chat.id();
+        let msg = draft_lines2();
+        show_send_at.set(false);
+        if !msg_valid(&msg) || active_chat_id.is_nil() {
+            return;
+        }
+        let attachments = state
+            .read()
+            .get_active_chat()
+            .map(|f| f.files_attached_to_send)
+            .unwrap_or_default()
+            .into_iter()
+            .map(FileLocation::from)
+            .collect();
+        state
+            .write()
+            .mutate(Action::ScheduleMessage(ScheduledMessage::new(
+                active_chat_id,
+                msg,
+                attachments,
+                send_at,
+            )));
+        state
+            .write()
+            .mutate(Action::SetChatDraft(active_chat_id, String::new()));
+        state
+            .write()
+            .mutate(Action::ClearChatAttachments(active_chat_id));
+        suggestions.set(SuggestionType::None);
+        mentions.set(vec![]);
+        can_send.set(false);
+    };
+
     let extensions = &state.read().ui.extensions;
     let ext_renders = extensions
         .values()
@@ -357,11 +430,17 @@ pub fn get_chatbar<'a>(cx: &'a Scoped<'a, ChatProps>) -> Element<'a> {
             },
             value: state.read().get_active_chat().as_ref().and_then(|d| d.draft.clone()).unwrap_or_default(),
             onreturn: move |_| submit_fn(),
+            markdown: state.read().ui.should_transform_markdown_text(),
+            transform_ascii_emojis: state.read().ui.should_transform_ascii_emojis(),
             extensions: cx.render(rsx!(for node in ext_renders { rsx!(node) })),
             suggestions: suggestions,
             oncursor_update: move |(mut v, p): (String, i64)| {
                 if !active_chat_id.is_nil() {
                     let sub: String = v.chars().take(p as usize).collect();
+                    if COMMAND_REGEX.is_match(&sub) {
+                        suggestions.set(SuggestionType::Command(sub.clone(), commands::suggestions(&state.read(), &sub)));
+                        return;
+                    }
                     let emoji_capture = EMOJI_REGEX.captures(&sub);
                     if let Some(emoji) = emoji_capture {
                             let emoji = &emoji[0];
@@ -439,6 +518,17 @@ pub fn get_chatbar<'a>(cx: &'a Scoped<'a, ChatProps>) -> Element<'a> {
             },
             controls: cx.render(
                 rsx!(
+                    Button {
+                        icon: icons::outline::Shape::Clock,
+                        disabled: is_loading || disabled || !*can_send.get(),
+                        appearance: Appearance::Secondary,
+                        aria_label: "send-later-button".into(),
+                        onpress: move |_| show_send_at.set(!*show_send_at.get()),
+                        tooltip: cx.render(rsx!(Tooltip {
+                            arrow_position: ArrowPosition::Bottom,
+                            text: get_local_text("messages.send-later"),
+                        })),
+                    },
                     Button {
                         icon: icons::outline::Shape::ChevronDoubleRight,
                         disabled: is_loading || disabled,
@@ -588,6 +678,16 @@ pub fn get_chatbar<'a>(cx: &'a Scoped<'a, ChatProps>) -> Element<'a> {
                 })
             })
         },
+        ScheduledMessagesList {
+            key: "{active_chat_id}-scheduled",
+            chat_id: active_chat_id,
+        },
+        (*show_send_at.get() && !disabled).then(|| rsx!(
+            SendAtPicker {
+                onschedule: move |send_at| schedule_fn(send_at),
+                oncancel: move |_| show_send_at.set(false),
+            }
+        )),
         Attachments {
             chat_id: active_chat_id,
             files_to_attach: state.read().get_active_chat().map(|f| f.files_attached_to_send).unwrap_or_default(),
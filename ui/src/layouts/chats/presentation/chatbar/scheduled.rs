@@ -0,0 +1,222 @@
+use chrono::{DateTime, Duration, Local, NaiveDateTime, TimeZone, Utc};
+use common::{
+    icons::{outline::Shape as Icon, Icon as IconElement},
+    language::{get_local_text, get_local_text_with_args},
+    state::{scheduled_messages::ScheduledMessage, Action, State},
+};
+use dioxus::prelude::*;
+use kit::elements::{
+    button::Button,
+    tooltip::{ArrowPosition, Tooltip},
+    Appearance,
+};
+use uuid::Uuid;
+
+// the format used by <input type="datetime-local">
+const INPUT_FORMAT: &str = "%Y-%m-%dT%H:%M";
+
+fn to_input_value(date: DateTime<Utc>) -> String {
+    date.with_timezone(&Local).format(INPUT_FORMAT).to_string()
+}
+
+// returns None if the value isn't a valid time in the future
+fn parse_input_value(value: &str) -> Option<DateTime<Utc>> {
+    let naive = NaiveDateTime::parse_from_str(value, INPUT_FORMAT).ok()?;
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(|x| x.with_timezone(&Utc))
+        .filter(|x| *x > Utc::now())
+}
+
+/// Picks when the draft in the chatbar should be sent
+#[component]
+pub fn SendAtPicker<'a>(
+    cx: Scope<'a>,
+    onschedule: EventHandler<'a, DateTime<Utc>>,
+    oncancel: EventHandler<'a, ()>,
+) -> Element<'a> {
+    let value = use_state(cx, || to_input_value(Utc::now() + Duration::hours(1)));
+    let send_at = parse_input_value(value.get());
+
+    cx.render(rsx!(
+        div {
+            class: "send-at-picker",
+            aria_label: "send-at-picker",
+            label {
+                get_local_text("messages.send-at")
+            },
+            input {
+                aria_label: "send-at-input",
+                "type": "datetime-local",
+                value: "{value}",
+                oninput: move |e| value.set(e.value.clone()),
+            },
+            send_at.is_none().then(|| rsx!(
+                span {
+                    class: "error",
+                    get_local_text("messages.send-at-invalid")
+                }
+            )),
+            Button {
+                aria_label: "cancel-send-at-button".into(),
+                icon: Icon::XMark,
+                appearance: Appearance::Secondary,
+                onpress: move |_| oncancel.call(()),
+            },
+            Button {
+                aria_label: "confirm-send-at-button".into(),
+                text: get_local_text("messages.schedule"),
+                disabled: send_at.is_none(),
+                onpress: move |_| {
+                    if let Some(send_at) = send_at {
+                        onschedule.call(send_at);
+                    }
+                },
+            },
+        }
+    ))
+}
+
+/// Lists the messages waiting to be sent to this chat
+#[component]
+pub fn ScheduledMessagesList(cx: Scope, chat_id: Uuid) -> Element {
+    let state = use_shared_state::<State>(cx)?;
+    let expanded = use_state(cx, || false);
+    let editing: &UseState<Option<Uuid>> = use_state(cx, || None);
+    let edit_text = use_state(cx, String::new);
+    let edit_time = use_state(cx, String::new);
+
+    let scheduled = state.read().scheduled_messages().for_chat(*chat_id);
+    if scheduled.is_empty() {
+        return None;
+    }
+    let num = scheduled.len();
+
+    let items = scheduled.into_iter().map(|msg: ScheduledMessage| {
+        let id = msg.id;
+        let text = msg.lines.join("\n");
+        let time = msg.send_at.with_timezone(&Local).format("%b %e, %H:%M").to_string();
+        if *editing.get() == Some(id) {
+            let send_at = parse_input_value(edit_time.get());
+            rsx!(
+                div {
+                    key: "{id}",
+                    class: "scheduled-message editing",
+                    aria_label: "scheduled-message",
+                    textarea {
+                        aria_label: "scheduled-message-text",
+                        value: "{edit_text}",
+                        oninput: move |e| edit_text.set(e.value.clone()),
+                    },
+                    input {
+                        aria_label: "scheduled-message-time",
+                        "type": "datetime-local",
+                        value: "{edit_time}",
+                        oninput: move |e| edit_time.set(e.value.clone()),
+                    },
+                    div {
+                        class: "controls",
+                        Button {
+                            aria_label: "cancel-edit-scheduled-button".into(),
+                            text: get_local_text("messages.cancel-edit"),
+                            appearance: Appearance::Secondary,
+                            onpress: move |_| editing.set(None),
+                        },
+                        Button {
+                            aria_label: "save-scheduled-button".into(),
+                            text: get_local_text("messages.save-scheduled"),
+                            disabled: send_at.is_none() || edit_text.get().trim().is_empty(),
+                            onpress: move |_| {
+                                if let Some(send_at) = send_at {
+                                    let lines = edit_text.get().lines().map(|x| x.trim_end().to_string()).collect();
+                                    state.write().mutate(Action::EditScheduledMessage(id, lines, send_at));
+                                    editing.set(None);
+                                }
+                            },
+                        },
+                    }
+                }
+            )
+        } else {
+            let num_attachments = msg.attachments.len();
+            let send_at = msg.send_at;
+            let edit_value = text.clone();
+            rsx!(
+                div {
+                    key: "{id}",
+                    class: "scheduled-message",
+                    aria_label: "scheduled-message",
+                    div {
+                        class: "details",
+                        span {
+                            class: "time",
+                            "{time}"
+                        },
+                        p {
+                            class: "text",
+                            "{text}"
+                        },
+                        (num_attachments > 0).then(|| rsx!(
+                            span {
+                                class: "attachments",
+                                get_local_text_with_args("messages.scheduled-attachments", vec![("num", num_attachments)])
+                            }
+                        )),
+                    },
+                    Button {
+                        aria_label: "edit-scheduled-button".into(),
+                        icon: Icon::PencilSquare,
+                        appearance: Appearance::Secondary,
+                        small: true,
+                        onpress: move |_| {
+                            edit_text.set(edit_value.clone());
+                            edit_time.set(to_input_value(send_at));
+                            editing.set(Some(id));
+                        },
+                        tooltip: cx.render(rsx!(Tooltip {
+                            arrow_position: ArrowPosition::Bottom,
+                            text: get_local_text("messages.edit"),
+                        })),
+                    },
+                    Button {
+                        aria_label: "cancel-scheduled-button".into(),
+                        icon: Icon::Trash,
+                        appearance: Appearance::Secondary,
+                        small: true,
+                        onpress: move |_| {
+                            state.write().mutate(Action::RemoveScheduledMessage(id));
+                        },
+                        tooltip: cx.render(rsx!(Tooltip {
+                            arrow_position: ArrowPosition::Bottom,
+                            text: get_local_text("messages.cancel-scheduled"),
+                        })),
+                    },
+                }
+            )
+        }
+    });
+
+    cx.render(rsx!(
+        div {
+            class: "scheduled-messages",
+            aria_label: "scheduled-messages",
+            div {
+                class: "scheduled-messages-header",
+                onclick: move |_| expanded.set(!*expanded.get()),
+                IconElement {
+                    icon: Icon::Clock,
+                },
+                get_local_text_with_args("messages.scheduled-count", vec![("num", num)]),
+            },
+            if *expanded.get() {
+                rsx!(
+                    div {
+                        class: "scheduled-messages-list",
+                        items
+                    }
+                )
+            }
+        }
+    ))
+}
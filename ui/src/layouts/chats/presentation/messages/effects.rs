@@ -80,6 +80,20 @@ pub fn init_msg_scroll(
                 }
             }
 
+            // only highlight once. the behavior is kept when the chat is opened again
+            let highlight = chat_data
+                .write_silent()
+                .chat_behaviors
+                .get_mut(&chat_id)
+                .and_then(|behavior| behavior.view_init.highlight.take());
+            if let Some(id) = highlight {
+                if let Err(e) = eval_provider(
+                    &scripts::HIGHLIGHT_MESSAGE.replace("$MESSAGE_ID", &format!("{id}")),
+                ) {
+                    log::error!("eval failed: {:?}", e);
+                }
+            }
+
             ch.send(());
         }
     });
@@ -1,5 +1,5 @@
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{HashMap, HashSet, VecDeque},
     ffi::OsStr,
     path::PathBuf,
 };
@@ -11,6 +11,7 @@ mod coroutines;
 mod effects;
 
 use common::state::{
+    call_history::CallRecord,
     pending_message::{FileLocation, PendingMessage},
     Action, Identity, State,
 };
@@ -19,6 +20,7 @@ use common::{
     icons::Icon as IconElement,
     language::get_local_text_with_args,
     state::{ui::EmojiDestination, ToastNotification},
+    warp_runner::ui_adapter,
 };
 use kit::{
     components::{
@@ -30,8 +32,10 @@ use kit::{
         user_image::UserImage,
     },
     elements::{
+        button::Button,
         loader::Loader,
         tooltip::{ArrowPosition, Tooltip},
+        Appearance,
     },
 };
 
@@ -49,7 +53,7 @@ use warp::{
 use tracing::log;
 
 use crate::{
-    components::emoji_group::EmojiGroup,
+    components::{emoji_group::EmojiGroup, media::call_history::CallRecordEntry},
     layouts::{
         chats::{
             data::{self, ChatData, MessagesToSend, ScrollBtn},
@@ -104,9 +108,23 @@ pub fn get_messages(
     let _ch = coroutines::handle_warp_commands(cx, state, pending_downloads);
 
     let active_chat_id = chat_data.read().active_chat.id();
+    // the group containing the message starts editing it. see wrap_messages_in_context_menu
+    if edit_last_requested(&state.read(), active_chat_id)
+        && last_own_message(&state.read(), &chat_data.read()).is_none()
+    {
+        if let Some(chat) = state.write_silent().chats.all.get_mut(&active_chat_id) {
+            chat.edit_last_message = false;
+        }
+    }
     // used by the intersection observer to terminate itself.
     let chat_key = chat_data.read().active_chat.key().to_string();
     let chat_behavior = chat_data.read().get_chat_behavior(active_chat_id);
+    let calls = calls_in_view(
+        &state.read(),
+        active_chat_id,
+        &chat_data.read().active_chat.messages(),
+        &chat_behavior,
+    );
     let msg_container_end =
         if matches!(chat_behavior.on_scroll_top, data::ScrollBehavior::FetchMore) {
             rsx!(div {
@@ -176,6 +194,7 @@ pub fn get_messages(
                     msg_container_end,
                     loop_over_message_groups {
                         groups: data::create_message_groups(chat_data.read().active_chat.my_id(), chat_data.read().active_chat.other_participants(), chat_data.read().active_chat.messages()),
+                        calls: calls,
                         active_chat_id: chat_data.read().active_chat.id(),
                         on_context_menu_action: move |(e, mut id): (Event<MouseData>, Identity)| {
                             let own = state.read().get_own_identity().did_key().eq(&id.did_key());
@@ -200,9 +219,35 @@ pub fn get_messages(
     ))
 }
 
+// the call history of the chat, limited to the time span of the loaded messages
+fn calls_in_view(
+    state: &State,
+    conversation_id: Uuid,
+    messages: &VecDeque<ui_adapter::Message>,
+    behavior: &data::ChatBehavior,
+) -> Vec<CallRecord> {
+    let mut calls = state.call_history().for_chat(conversation_id);
+    let first = messages.front().map(|x| x.inner.date());
+    let last = messages.back().map(|x| x.inner.date());
+    if let (Some(first), data::ScrollBehavior::FetchMore) = (first, &behavior.on_scroll_top) {
+        calls.retain(|x| x.started >= first);
+    }
+    if let (Some(last), data::ScrollBehavior::FetchMore) = (last, &behavior.on_scroll_end) {
+        calls.retain(|x| x.started <= last);
+    }
+    calls
+}
+
+enum TimelineItem<'a> {
+    Group(&'a data::MessageGroup),
+    Call(&'a CallRecord),
+}
+
 #[derive(Props)]
 pub struct AllMessageGroupsProps<'a> {
     groups: Vec<data::MessageGroup>,
+    // oldest first
+    calls: Vec<CallRecord>,
     active_chat_id: Uuid,
     on_context_menu_action: EventHandler<'a, (Event<MouseData>, Identity)>,
 }
@@ -211,12 +256,36 @@ pub struct AllMessageGroupsProps<'a> {
 // temporary location
 pub fn loop_over_message_groups<'a>(cx: Scope<'a, AllMessageGroupsProps<'a>>) -> Element<'a> {
     log::trace!("render message groups");
-    cx.render(rsx!(cx.props.groups.iter().map(|_group| {
-        rsx!(render_message_group {
+    // calls are placed before the first group which was sent after they started
+    let mut calls = cx.props.calls.iter().peekable();
+    let mut timeline = vec![];
+    for group in cx.props.groups.iter() {
+        let date = group
+            .messages
+            .first()
+            .map(|x| x.message.inner.date())
+            .unwrap_or_default();
+        while let Some(call) = calls.next_if(|call| call.started < date) {
+            timeline.push(TimelineItem::Call(call));
+        }
+        timeline.push(TimelineItem::Group(group));
+    }
+    timeline.extend(calls.map(TimelineItem::Call));
+
+    cx.render(rsx!(timeline.into_iter().map(|item| match item {
+        TimelineItem::Group(_group) => rsx!(render_message_group {
             group: _group,
             active_chat_id: cx.props.active_chat_id,
             on_context_menu_action: move |e| cx.props.on_context_menu_action.call(e)
-        },)
+        },),
+        TimelineItem::Call(call) => {
+            let key = call.id;
+            rsx!(CallRecordEntry {
+                key: "{key}",
+                record: call.clone(),
+                show_chat: false,
+            })
+        }
     })))
 }
 
@@ -334,6 +403,28 @@ fn render_message_group<'a>(cx: Scope<'a, MessageGroupProps<'a>>) -> Element<'a>
     ))
 }
 
+fn edit_last_requested(state: &State, chat_id: Uuid) -> bool {
+    state
+        .chats
+        .all
+        .get(&chat_id)
+        .map(|chat| chat.edit_last_message)
+        .unwrap_or_default()
+}
+
+// the newest loaded message sent by the user
+fn last_own_message(state: &State, chat_data: &ChatData) -> Option<Uuid> {
+    let own_id = state.did_key();
+    chat_data
+        .active_chat
+        .messages
+        .all
+        .iter()
+        .rev()
+        .find(|msg| msg.inner.sender() == own_id)
+        .map(|msg| msg.inner.id())
+}
+
 #[derive(Props)]
 struct MessagesProps<'a> {
     messages: &'a Vec<data::MessageGroupMsg>,
@@ -356,6 +447,29 @@ fn wrap_messages_in_context_menu<'a>(cx: Scope<'a, MessagesProps<'a>>) -> Elemen
         .enabled_extension(emoji_selector_extension);
 
     let ch = use_coroutine_handle::<MessagesCommand>(cx)?;
+    let chat_data = use_shared_state::<ChatData>(cx)?;
+
+    if !cx.props.is_remote && edit_last_requested(&state.read(), cx.props.active_chat_id) {
+        let last = last_own_message(&state.read(), &chat_data.read());
+        if let Some(id) = last.filter(|id| {
+            cx.props
+                .messages
+                .iter()
+                .any(|m| m.message.inner.id() == *id)
+        }) {
+            if let Some(chat) = state
+                .write_silent()
+                .chats
+                .all
+                .get_mut(&cx.props.active_chat_id)
+            {
+                chat.edit_last_message = false;
+            }
+            state.write_silent().ui.ignore_focus = true;
+            edit_msg.set(Some(id));
+        }
+    }
+
     cx.render(rsx!(cx.props.messages.iter().map(|grouped_message| {
         let message = &grouped_message.message;
         let sender_is_self = message.inner.sender() == state.read().did_key();
@@ -432,6 +546,17 @@ fn wrap_messages_in_context_menu<'a>(cx: Scope<'a, MessagesProps<'a>>) -> Elemen
                             .mutate(Action::StartReplying(&cx.props.active_chat_id, message));
                     }
                 },
+                ContextItem {
+                    icon: Icon::ChatBubbleLeftRight,
+                    aria_label: "messages-reply-in-thread".into(),
+                    text: get_local_text("messages.reply-in-thread"),
+                    onpress: move |_| {
+                        let root = message.thread_root.unwrap_or(message.inner.id());
+                        state
+                            .write()
+                            .mutate(Action::OpenThread(conversation_id, root));
+                    }
+                },
                 ContextItem {
                     icon: Icon::FaceSmile,
                     aria_label: "messages-react".into(),
@@ -578,6 +703,19 @@ fn render_message<'a>(cx: Scope<'a, MessageProps<'a>>) -> Element<'a> {
     let msg_lines = message.inner.lines().join("\n");
 
     let is_mention = message.clone().is_mention_self(&user_did);
+    let conv_id = message.inner.conversation_id();
+    let msg_id = message.inner.id();
+    let thread_summary = message.thread_root.is_none().then(|| {
+        let replies = chat_data.read().active_chat.messages.thread_replies(msg_id);
+        let unread = state
+            .read()
+            .chats
+            .all
+            .get(&conv_id)
+            .map(|chat| chat.thread_unreads(&msg_id))
+            .unwrap_or_default();
+        (replies, unread)
+    });
     let preview_file_in_the_message: &UseState<(bool, Option<File>)> =
         use_state(cx, || (false, None));
 
@@ -586,6 +724,8 @@ fn render_message<'a>(cx: Scope<'a, MessageProps<'a>>) -> Element<'a> {
         reply_user = state.read().get_identity(&info.2).unwrap_or_default();
     }
     let to_send = use_shared_state::<MessagesToSend>(cx)?;
+    // set for messages which couldn't be sent yet
+    let outbox = state.read().outbox().get(msg_id).cloned();
 
     cx.render(rsx!(
         div {
@@ -662,6 +802,10 @@ fn render_message<'a>(cx: Scope<'a, MessageProps<'a>>) -> Element<'a> {
                 },
                 pending: cx.props.pending,
                 pinned: message.inner.pinned(),
+                thread_summary: thread_summary,
+                on_open_thread: move |_| {
+                    state.write().mutate(Action::OpenThread(conv_id, msg_id));
+                },
                 attachments_pending_uploads: pending_uploads,
                 on_resend: move |(txt, file): (Option<String>, FileLocation)|{
                     match txt.clone() {
@@ -702,6 +846,44 @@ fn render_message<'a>(cx: Scope<'a, MessageProps<'a>>) -> Element<'a> {
                     ch.send(MessagesCommand::EditMessage { conv_id: message.inner.conversation_id(), msg_id: message.inner.id(), msg})
                 }
             },
+            outbox.map(|outbox| {
+                let id = outbox.id;
+                let error = outbox.error.unwrap_or_default();
+                let status = if outbox.failed {
+                    get_local_text("messages.send-failed")
+                } else {
+                    get_local_text("messages.send-waiting")
+                };
+                rsx!(div {
+                    class: format_args!("outbox-status {}", if outbox.failed { "failed" } else { "" }),
+                    aria_label: "outbox-status",
+                    title: "{error}",
+                    IconElement {
+                        icon: Icon::ExclamationCircle,
+                    },
+                    span {
+                        "{status}"
+                    },
+                    Button {
+                        small: true,
+                        text: get_local_text("messages.send-retry"),
+                        aria_label: "outbox-retry".into(),
+                        appearance: Appearance::Secondary,
+                        onpress: move |_| {
+                            state.write().mutate(Action::RetryOutgoingMessage(id));
+                        },
+                    },
+                    Button {
+                        small: true,
+                        text: get_local_text("messages.send-discard"),
+                        aria_label: "outbox-discard".into(),
+                        appearance: Appearance::Secondary,
+                        onpress: move |_| {
+                            state.write().mutate(Action::RemoveOutgoingMessage(id));
+                        },
+                    },
+                })
+            }),
             script {
                 r#"
                 (() => {{
@@ -728,8 +910,18 @@ pub fn render_pending_messages_listener<'a>(
         Some(c) => c,
         None => return cx.render(rsx!(())),
     };
+    // messages waiting in the outbox are older than the ones being sent
+    let did = state.read().did_key();
+    let mut msg: Vec<PendingMessage> = state
+        .read()
+        .outbox()
+        .for_chat(chat.id)
+        .into_iter()
+        .map(|outbox| PendingMessage::new(chat.id, did.clone(), outbox.id, outbox.lines))
+        .collect();
+    msg.extend(chat.pending_outgoing_messages);
     cx.render(rsx!(pending_wrapper {
-        msg: chat.pending_outgoing_messages,
+        msg: msg,
         on_context_menu_action: move |e| cx.props.on_context_menu_action.call(e)
     }))
 }
@@ -1,10 +1,20 @@
 mod create_group;
+mod recent_calls;
 mod search;
 
+use chrono::{Duration, Utc};
 use common::language::{get_local_text, get_local_text_with_args};
-use common::state::ui::Layout;
-use common::state::{self, identity_search_result, Action, Chat, Identity, State};
-use common::warp_runner::{RayGunCmd, WarpCmd};
+use common::state::ui::{JumpToMessage, Layout};
+use common::state::{
+    self,
+    action::ConfigAction,
+    identity_search_result,
+    notification_rules::{ChatRule, Mute},
+    Action, Chat, Identity, State, ToastNotification,
+};
+use common::warp_runner::export::ExportFormat;
+use common::warp_runner::search_index::{self, SearchQuery, SearchResult};
+use common::warp_runner::{OtherCmd, RayGunCmd, WarpCmd};
 use common::{icons::outline::Shape as Icon, WARP_CMD_CH};
 use dioxus::html::input_data::keyboard_types::Code;
 use dioxus::prelude::*;
@@ -30,6 +40,8 @@ use kit::{
     },
     layout::sidebar::Sidebar as ReusableSidebar,
 };
+use rfd::FileDialog;
+use std::path::PathBuf;
 use uuid::Uuid;
 use warp::raygun::ConversationType;
 use warp::{
@@ -46,10 +58,23 @@ use crate::layouts::chats::presentation::sidebar::create_group::CreateGroup;
 use crate::utils::build_participants;
 use crate::UplinkRoute;
 
+// the sidebar dropdown is not meant to page through every match
+const MAX_MESSAGE_RESULTS: usize = 20;
+
 #[allow(clippy::large_enum_variant)]
 enum MessagesCommand {
-    CreateConversation { recipient: DID },
-    DeleteConversation { conv_id: Uuid },
+    CreateConversation {
+        recipient: DID,
+    },
+    DeleteConversation {
+        conv_id: Uuid,
+    },
+    ExportConversation {
+        conv_id: Uuid,
+        conversation_name: String,
+        format: ExportFormat,
+        dest: PathBuf,
+    },
 }
 
 #[derive(PartialEq, Props)]
@@ -64,6 +89,7 @@ pub fn Sidebar(cx: Scope<SidebarProps>) -> Element {
     let search_results = use_state(cx, Vec::<identity_search_result::Entry>::new);
     let search_results_friends_identities = use_state(cx, Vec::<Identity>::new);
     let search_results_chats = use_state(cx, Vec::<Chat>::new);
+    let search_results_messages = use_state(cx, Vec::<SearchResult>::new);
     let chat_with: &UseState<Option<Uuid>> = use_state(cx, || None);
     let reset_searchbar = use_state(cx, || false);
     let router = use_navigator(cx);
@@ -79,7 +105,12 @@ pub fn Sidebar(cx: Scope<SidebarProps>) -> Element {
     }
 
     let ch = use_coroutine(cx, |rx: UnboundedReceiver<MessagesCommand>| {
-        conversation_coroutine(rx, chat_with.clone(), show_delete_conversation.clone())
+        conversation_coroutine(
+            rx,
+            state.clone(),
+            chat_with.clone(),
+            show_delete_conversation.clone(),
+        )
     });
 
     let select_identifier = move |id: identity_search_result::Identifier| match id {
@@ -156,6 +187,7 @@ pub fn Sidebar(cx: Scope<SidebarProps>) -> Element {
                         onchange: move |(v, _): (String, _)| {
                             if v.is_empty() {
                                 search_results.set(Vec::new());
+                                search_results_messages.set(Vec::new());
                                 *search_friends_is_focused.write_silent() = false;
                             } else {
                                 let (mut friends_entries, friends_identities) = state.read().search_identities(&v);
@@ -165,6 +197,7 @@ pub fn Sidebar(cx: Scope<SidebarProps>) -> Element {
                                 search_results.set(friends_entries);
                                 search_results_friends_identities.set(friends_identities);
                                 search_results_chats.set(chats);
+                                search_results_messages.set(search_messages(&state.read(), &v));
                                 *search_typed_chars.write_silent() = v;
                                 *search_friends_is_focused.write_silent() = true;
                                 on_search_dropdown_hover.with_mut(|i| *i = false);
@@ -205,10 +238,25 @@ pub fn Sidebar(cx: Scope<SidebarProps>) -> Element {
                     identities: search_results.clone(),
                     friends_identities: search_results_friends_identities.clone(),
                     chats: search_results_chats.clone(),
+                    messages: search_results_messages.clone(),
                     search_dropdown_hover: on_search_dropdown_hover.clone(),
                     onclick: move |identifier: identity_search_result::Identifier| {
                         select_identifier(identifier);
                         search_results.set(Vec::new());
+                        search_results_messages.set(Vec::new());
+                        reset_searchbar.set(true);
+                        on_search_dropdown_hover.with_mut(|i| *i = false);
+                    },
+                    onclick_message: move |result: SearchResult| {
+                        // the chat view scrolls to the message once the conversation is open
+                        state.write().ui.jump_to_message = Some(JumpToMessage {
+                            conversation_id: result.conversation_id,
+                            message_id: result.message_id,
+                            date: result.date,
+                        });
+                        select_identifier(identity_search_result::Identifier::Uuid(result.conversation_id));
+                        search_results.set(Vec::new());
+                        search_results_messages.set(Vec::new());
                         reset_searchbar.set(true);
                         on_search_dropdown_hover.with_mut(|i| *i = false);
                     }
@@ -218,6 +266,7 @@ pub fn Sidebar(cx: Scope<SidebarProps>) -> Element {
             for node in ext_renders {
                 rsx!(node)
             },
+            recent_calls::RecentCalls {},
             div {
                 id: "chats",
                 aria_label: "Chats",
@@ -296,6 +345,33 @@ pub fn Sidebar(cx: Scope<SidebarProps>) -> Element {
                     let is_active = state.read().get_active_chat().map(|c| c.id) == Some(chat.id);
                     let chat_with = chat.clone();
                     let clear_unreads = chat.clone();
+                    let export_id = chat.id;
+                    let export_chat = move |format: ExportFormat| {
+                        let Some(chat) = state.read().get_chat_by_id(export_id) else {
+                            return;
+                        };
+                        let conversation_name = name_for_export(&state.read(), &chat);
+                        let dest = FileDialog::new()
+                            .set_directory(dirs::home_dir().unwrap_or(".".into()))
+                            .set_file_name(&format!("{}.zip", conversation_name.replace(['/', '\\'], "_")))
+                            .add_filter("zip", &["zip"])
+                            .save_file();
+                        if let Some(dest) = dest {
+                            ch.send(MessagesCommand::ExportConversation {
+                                conv_id: export_id,
+                                conversation_name,
+                                format,
+                                dest,
+                            });
+                        }
+                    };
+                    let rule = state.read().configuration.notifications.rules.chat(chat.id);
+                    let is_muted = rule.is_muted(Utc::now());
+                    let set_rule = move |rule: ChatRule| {
+                        state.write().mutate(Action::Config(ConfigAction::SetChatNotificationRule(key, rule)));
+                    };
+                    let mute = move |muted: Mute| set_rule(ChatRule { muted: Some(muted), ..rule });
+                    let mute_for = move |hours: i64| mute(Mute::Until(Utc::now() + Duration::hours(hours)));
                     let markdown = false;
                     let should_transform_ascii_emojis = state.read().ui.should_transform_ascii_emojis();
 
@@ -305,19 +381,31 @@ pub fn Sidebar(cx: Scope<SidebarProps>) -> Element {
                         None => State::join_usernames(&other_participants)
                     };
 
-                    let subtext_val = match unwrapped_message.lines().iter().map(|x| x.trim()).find(|x| !x.is_empty()) {
-                        Some(v) => {
-                            format_text(v, markdown, should_transform_ascii_emojis, Some((&state.read(), &chat.id, true)))
-                        }
-                        _ => match &unwrapped_message.attachments()[..] {
-                            [] => get_local_text("sidebar.chat-new"),
-                            [ file ] => file.name(),
-                            _ => match participants.iter().find(|p| p.did_key()  == unwrapped_message.sender()).map(|x| x.username()) {
-                                Some(name) => get_local_text_with_args("sidebar.subtext", vec![("user", name)]),
-                                None => {
-                                    log::error!("error calculating subtext for sidebar chat");
-                                    // Still return default message
-                                    get_local_text("sidebar.chat-new")
+                    let draft = (!is_active && state.read().chat_has_draft(&chat.id))
+                        .then(|| chat.draft.clone())
+                        .flatten();
+                    let subtext_val = if let Some(draft) = draft {
+                        let line = draft.lines().map(|x| x.trim()).find(|x| !x.is_empty()).unwrap_or_default();
+                        format!(
+                            "<span class=\"draft\">{}</span> {}",
+                            get_local_text("sidebar.draft"),
+                            format_text(line, markdown, should_transform_ascii_emojis, Some((&state.read(), &chat.id, true)))
+                        )
+                    } else {
+                        match unwrapped_message.lines().iter().map(|x| x.trim()).find(|x| !x.is_empty()) {
+                            Some(v) => {
+                                format_text(v, markdown, should_transform_ascii_emojis, Some((&state.read(), &chat.id, true)))
+                            }
+                            _ => match &unwrapped_message.attachments()[..] {
+                                [] => get_local_text("sidebar.chat-new"),
+                                [ file ] => file.name(),
+                                _ => match participants.iter().find(|p| p.did_key()  == unwrapped_message.sender()).map(|x| x.username()) {
+                                    Some(name) => get_local_text_with_args("sidebar.subtext", vec![("user", name)]),
+                                    None => {
+                                        log::error!("error calculating subtext for sidebar chat");
+                                        // Still return default message
+                                        get_local_text("sidebar.chat-new")
+                                    }
                                 }
                             }
                         }
@@ -343,6 +431,40 @@ pub fn Sidebar(cx: Scope<SidebarProps>) -> Element {
                                         state.write().mutate(Action::ClearUnreads(clear_unreads.id));
                                     }
                                 },
+                                ContextItem {
+                                    icon: Icon::Bell,
+                                    aria_label: "chats-unmute".into(),
+                                    text: get_local_text("uplink.unmute-chat"),
+                                    should_render: is_muted,
+                                    onpress: move |_| set_rule(ChatRule { muted: None, ..rule })
+                                },
+                                ContextItem {
+                                    icon: Icon::BellSnooze,
+                                    aria_label: "chats-mute-hour".into(),
+                                    text: get_local_text("uplink.mute-chat-hour"),
+                                    should_render: !is_muted,
+                                    onpress: move |_| mute_for(1)
+                                },
+                                ContextItem {
+                                    icon: Icon::BellSnooze,
+                                    aria_label: "chats-mute-8-hours".into(),
+                                    text: get_local_text("uplink.mute-chat-8-hours"),
+                                    should_render: !is_muted,
+                                    onpress: move |_| mute_for(8)
+                                },
+                                ContextItem {
+                                    icon: Icon::BellSlash,
+                                    aria_label: "chats-mute-forever".into(),
+                                    text: get_local_text("uplink.mute-chat-forever"),
+                                    should_render: !is_muted,
+                                    onpress: move |_| mute(Mute::Forever)
+                                },
+                                ContextItem {
+                                    icon: Icon::AtSymbol,
+                                    aria_label: "chats-mentions-only".into(),
+                                    text: if rule.mentions_only { get_local_text("uplink.notify-all") } else { get_local_text("uplink.notify-mentions") },
+                                    onpress: move |_| set_rule(ChatRule { mentions_only: !rule.mentions_only, ..rule })
+                                },
                                 ContextItem {
                                     icon: Icon::EyeSlash,
                                     aria_label: "chats-hide-chat".into(),
@@ -351,6 +473,24 @@ pub fn Sidebar(cx: Scope<SidebarProps>) -> Element {
                                         state.write().mutate(Action::RemoveFromSidebar(chat.id));
                                     }
                                 },
+                                ContextItem {
+                                    icon: Icon::ArchiveBoxArrowDown,
+                                    aria_label: "chats-export-json".into(),
+                                    text: get_local_text("uplink.export-json"),
+                                    onpress: move |_| export_chat(ExportFormat::Json)
+                                },
+                                ContextItem {
+                                    icon: Icon::ArchiveBoxArrowDown,
+                                    aria_label: "chats-export-html".into(),
+                                    text: get_local_text("uplink.export-html"),
+                                    onpress: move |_| export_chat(ExportFormat::Html)
+                                },
+                                ContextItem {
+                                    icon: Icon::ArchiveBoxArrowDown,
+                                    aria_label: "chats-export-markdown".into(),
+                                    text: get_local_text("uplink.export-markdown"),
+                                    onpress: move |_| export_chat(ExportFormat::Markdown)
+                                },
                                 show_delete_conversation.read().then(||
                                     rsx!(
                                         ContextItem {
@@ -453,8 +593,28 @@ pub fn Sidebar(cx: Scope<SidebarProps>) -> Element {
     ))
 }
 
+// the index may still hold messages from conversations which were left while Uplink was closed
+fn search_messages(state: &State, text: &str) -> Vec<SearchResult> {
+    let query = SearchQuery {
+        limit: MAX_MESSAGE_RESULTS,
+        ..SearchQuery::new(text)
+    };
+    search_index::search(&query)
+        .into_iter()
+        .filter(|r| state.get_chat_by_id(r.conversation_id).is_some())
+        .collect()
+}
+
+// group chats may not have a name
+fn name_for_export(state: &State, chat: &Chat) -> String {
+    chat.conversation_name
+        .clone()
+        .unwrap_or_else(|| State::join_usernames(&state.chat_participants(chat)))
+}
+
 async fn conversation_coroutine(
     mut rx: UnboundedReceiver<MessagesCommand>,
+    state: UseSharedState<State>,
     chat_with: UseState<Option<Uuid>>,
     show_delete_conversation: UseRef<bool>,
 ) {
@@ -500,6 +660,43 @@ async fn conversation_coroutine(
                 }
                 *show_delete_conversation.write_silent() = true;
             }
+            MessagesCommand::ExportConversation {
+                conv_id,
+                conversation_name,
+                format,
+                dest,
+            } => {
+                let identities = state.read().get_usernames();
+                let (tx, rx) = oneshot::channel();
+                if let Err(e) = warp_cmd_tx.send(WarpCmd::Other(OtherCmd::ExportConversation {
+                    conv_id,
+                    conversation_name,
+                    identities,
+                    format,
+                    dest,
+                    rsp: tx,
+                })) {
+                    log::error!("failed to send warp command: {}", e);
+                    continue;
+                }
+
+                let res = rx.await.expect("command canceled");
+                let text = match res {
+                    Ok(_) => get_local_text("uplink.export-complete"),
+                    Err(e) => {
+                        log::error!("failed to export conversation: {e}");
+                        get_local_text("uplink.export-failed")
+                    }
+                };
+                state
+                    .write()
+                    .mutate(Action::AddToastNotification(ToastNotification::init(
+                        "".into(),
+                        text,
+                        None,
+                        2,
+                    )));
+            }
         };
     }
 }
@@ -0,0 +1,63 @@
+use common::language::get_local_text;
+use common::state::{notifications::NotificationKind, Action, State};
+use dioxus::prelude::*;
+use dioxus_router::prelude::use_navigator;
+use kit::elements::label::Label;
+
+use crate::components::media::call_history::CallRecordEntry;
+use crate::UplinkRoute;
+
+// the rest of the history is in each chat
+const MAX_RECENT_CALLS: usize = 3;
+
+/// The latest calls across every chat
+#[allow(non_snake_case)]
+pub fn RecentCalls(cx: Scope) -> Element {
+    let state = use_shared_state::<State>(cx)?;
+    let router = use_navigator(cx);
+    let calls = state.read().call_history().recent(MAX_RECENT_CALLS);
+    if calls.is_empty() {
+        return None;
+    }
+    let missed_calls = state
+        .read()
+        .ui
+        .notifications
+        .get(NotificationKind::MissedCall);
+
+    cx.render(rsx!(
+        div {
+            id: "recent-calls",
+            aria_label: "recent-calls",
+            div {
+                class: "sidebar-chats-header",
+                Label {
+                    text: get_local_text("call-history.recent-calls"),
+                    aria_label: "recent-calls-label".into(),
+                },
+            },
+            calls.into_iter().map(|record| {
+                let key = record.id;
+                let conversation_id = record.conversation_id;
+                rsx!(CallRecordEntry {
+                    key: "{key}",
+                    record: record,
+                    show_chat: true,
+                    onclick: move |_| {
+                        if missed_calls > 0 {
+                            state.write().mutate(Action::ClearNotification(NotificationKind::MissedCall));
+                        }
+                        if state.read().get_chat_by_id(conversation_id).is_none() {
+                            return;
+                        }
+                        state.write().mutate(Action::ChatWith(&conversation_id, true));
+                        if state.read().ui.is_minimal_view() {
+                            state.write().mutate(Action::SidebarHidden(true));
+                        }
+                        router.replace(UplinkRoute::ChatLayout {});
+                    }
+                })
+            })
+        }
+    ))
+}
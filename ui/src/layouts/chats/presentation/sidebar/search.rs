@@ -2,6 +2,7 @@ use common::icons::outline::Shape as Icon;
 use common::icons::Icon as IconElement;
 use common::language::get_local_text;
 use common::state::{identity_search_result, Chat, Identity, State};
+use common::warp_runner::search_index::{self, SearchResult};
 use dioxus::prelude::*;
 use kit::components::{user_image::UserImage, user_image_group::UserImageGroup};
 
@@ -17,17 +18,27 @@ pub struct SearchProps<'a> {
     identities: UseState<Vec<identity_search_result::Entry>>,
     friends_identities: UseState<Vec<Identity>>,
     chats: UseState<Vec<Chat>>,
+    messages: UseState<Vec<SearchResult>>,
     onclick: EventHandler<'a, identity_search_result::Identifier>,
+    onclick_message: EventHandler<'a, SearchResult>,
 }
 
 pub fn search_friends<'a>(cx: Scope<'a, SearchProps<'a>>) -> Element<'a> {
     let state = use_shared_state::<State>(cx)?;
-    if cx.props.identities.get().is_empty() || !*cx.props.search_friends_is_focused.read() {
+    if (cx.props.identities.get().is_empty() && cx.props.messages.get().is_empty())
+        || !*cx.props.search_friends_is_focused.read()
+    {
         return None;
     }
 
     let mut friends_identities = cx.props.friends_identities.get().clone();
     let chats = cx.props.chats.get().clone();
+    let messages = cx.props.messages.get().clone();
+    // message results are matched by word, so only the first word typed is highlighted
+    let highlight_term = search_index::tokenize(&cx.props.search_typed_chars.read())
+        .into_iter()
+        .next()
+        .unwrap_or_default();
 
     friends_identities.sort_by_key(|identity| identity.username());
 
@@ -289,6 +300,70 @@ pub fn search_friends<'a>(cx: Scope<'a, SearchProps<'a>>) -> Element<'a> {
                     })
                 )
             })
+            if !messages.is_empty() && (!chats.is_empty() || !friends_identities.is_empty()) {
+                rsx!(div { class:"border", })
+            }
+            if !messages.is_empty() {
+                rsx!(
+                    div {
+                        id: "messages-searchdropdown-label",
+                        class: "users-groups-label",
+                        aria_label: "users-groups-label",
+                        p {
+                            get_local_text("uplink.messages")
+                        }
+                    }
+                )
+            }
+            messages.into_iter().map(|result| {
+                let sender = state.read().get_identity(&result.sender).unwrap_or_default();
+                let snippet = result.snippet.clone();
+                let highlight = search_index::find_highlight(&snippet, &highlight_term);
+                rsx!(
+                    div {
+                        class: "identity-header-sidebar",
+                        aria_label: "search-result-message",
+                        prevent_default: "onclick",
+                        onclick: move |evt| {
+                            evt.stop_propagation();
+                            *cx.props.search_friends_is_focused.write_silent() = false;
+                            cx.props.onclick_message.call(result.clone());
+                        },
+                        UserImage {
+                            platform: sender.platform().into(),
+                            status: sender.identity_status().into(),
+                            image: sender.profile_picture()
+                        },
+                        div {
+                            class: "search-friends-dropdown-name search-message-result",
+                            aria_label: "search-message-result",
+                            p {
+                                class: "search-message-sender",
+                                sender.username()
+                            },
+                            p {
+                                class: "search-message-snippet",
+                                if let Some((start, end)) = highlight {
+                                    rsx!(
+                                        span { &snippet[0..start] },
+                                        span {
+                                            class: "highlight-search-typed-chars",
+                                            aria_label: "highlight-search-typed-chars",
+                                            &snippet[start..end]
+                                        },
+                                        span {
+                                            aria_label: "remaining-match-search",
+                                            &snippet[end..]
+                                        },
+                                    )
+                                } else {
+                                    rsx!(span { "{snippet}" })
+                                }
+                            }
+                        }
+                    }
+                )
+            })
         }
     ))
 }
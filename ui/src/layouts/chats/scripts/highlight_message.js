@@ -0,0 +1,10 @@
+// returns for eval
+var message = document.getElementById("$MESSAGE_ID");
+if (message) {
+    message.classList.remove("background-highlight");
+    // restart the animation if the message was highlighted before
+    void message.offsetWidth;
+    message.classList.add("background-highlight");
+    message.addEventListener("animationend", () => message.classList.remove("background-highlight"), { once: true });
+}
+return "done";
\ No newline at end of file
@@ -0,0 +1,13 @@
+// named links show other text than their address, so they are confirmed before they are opened
+if (window.namedLinkHandler) {
+    document.removeEventListener("click", window.namedLinkHandler, true)
+}
+window.namedLinkHandler = (e) => {
+    let link = e.target.closest("a.named-link")
+    if (!link)
+        return
+    e.preventDefault()
+    e.stopPropagation()
+    dioxus.send(link.getAttribute("href"))
+}
+document.addEventListener("click", window.namedLinkHandler, true)
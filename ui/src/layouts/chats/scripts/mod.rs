@@ -7,7 +7,9 @@ pub const SHOW_CONTEXT: &str = include_str!("./show_context.js");
 pub const SCROLL_TO_TOP: &str = include_str!("./scroll_to_top.js");
 pub const SCROLL_TO_BOTTOM: &str = include_str!("./scroll_to_bottom.js");
 pub const SCROLL_TO_END: &str = include_str!("./scroll_to_end.js");
+pub const HIGHLIGHT_MESSAGE: &str = include_str!("./highlight_message.js");
 pub const OBSERVER_SCRIPT: &str = include_str!("./observer_script.js");
 pub const READ_SCROLL: &str = include_str!("./read_scroll.js");
 pub const USER_TAG_SCRIPT: &str = include_str!("./user_tag_click_handler.js");
 pub const DISABLE_RELOAD: &str = include_str!("./disable_reload_hotkeys.js");
+pub const LINK_CLICK_SCRIPT: &str = include_str!("./link_click_handler.js");
@@ -1,7 +1,7 @@
 use common::{language::get_local_text, state::State};
 use dioxus::prelude::*;
 use dioxus_desktop::{use_window, LogicalSize};
-use kit::elements::{button::Button, label::Label};
+use kit::elements::{button::Button, label::Label, Appearance};
 
 use crate::get_app_style;
 
@@ -49,6 +49,14 @@ pub fn Layout(cx: Scope, page: UseState<AuthPages>) -> Element {
                         page.set(AuthPages::EnterSeedWords);
                     }
                 },
+                Button {
+                    aria_label: "restore-backup-button".into(),
+                    text: get_local_text("create-or-recover.restore"),
+                    appearance: Appearance::Secondary,
+                    onpress: move |_| {
+                        page.set(AuthPages::RestoreBackup);
+                    }
+                },
             }
         }
     ))
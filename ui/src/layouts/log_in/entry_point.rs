@@ -3,8 +3,9 @@ use std::fs;
 use common::{
     get_images_dir,
     language::{get_local_text, get_local_text_with_args},
+    profiles::ProfileRegistry,
     state::{configuration::Configuration, State},
-    warp_runner::TesseractCmd,
+    warp_runner::{TesseractCmd, WarpRunner},
     STATIC_ARGS,
 };
 use dioxus::prelude::*;
@@ -17,9 +18,11 @@ use kit::{
         button::Button,
         input::{Input, Options, Validation},
         label::LabelWithEllipsis,
+        select::Select,
         tooltip::{ArrowPosition, Tooltip},
     },
 };
+use uuid::Uuid;
 use warp::multipass;
 
 use tracing::log;
@@ -31,7 +34,10 @@ use common::{
     WARP_CMD_CH,
 };
 
-use crate::{bootstrap::create_uplink_dirs, get_app_style, AuthPages};
+use crate::{
+    bootstrap::{create_uplink_dirs, restart_warp_runner},
+    get_app_style, AuthPages,
+};
 
 enum UnlockError {
     ValidationError,
@@ -49,6 +55,11 @@ impl UnlockError {
     }
 }
 
+enum ProfileCmd {
+    Switch(Uuid),
+    Create(String),
+}
+
 // todo: go to the auth page if no account has been created
 #[component]
 pub fn Layout(cx: Scope, page: UseState<AuthPages>, pin: UseRef<String>) -> Element {
@@ -65,6 +76,11 @@ pub fn Layout(cx: Scope, page: UseState<AuthPages>, pin: UseRef<String>) -> Elem
     let first_render = use_ref(cx, || true);
     let state = use_ref(cx, State::load);
     let reset_input = use_state(cx, || false);
+    let runner = use_shared_state::<WarpRunner>(cx)?;
+    let profiles = use_ref(cx, ProfileRegistry::load);
+    let adding_profile = use_state(cx, || false);
+    // incremented whenever a new WarpRunner is started
+    let runner_generation = use_state(cx, || 0_u32);
 
     // On windows, is necessary use state on topbar controls, without using use_shared_state
     // So state is loaded there to use window_maximized and offer better UX
@@ -75,7 +91,8 @@ pub fn Layout(cx: Scope, page: UseState<AuthPages>, pin: UseRef<String>) -> Elem
     }
 
     // this will be needed later
-    use_future(cx, (), |_| {
+    let generation = *runner_generation.get();
+    use_future(cx, &generation, |_| {
         to_owned![account_exists];
         async move {
             if account_exists.current().is_some() {
@@ -149,6 +166,66 @@ pub fn Layout(cx: Scope, page: UseState<AuthPages>, pin: UseRef<String>) -> Elem
         }
     });
 
+    // warp is restarted for the other profile. the pin belongs to the account of the new profile.
+    let profile_ch = use_coroutine(cx, |mut rx: UnboundedReceiver<ProfileCmd>| {
+        to_owned![
+            runner,
+            profiles,
+            state,
+            account_exists,
+            runner_generation,
+            cmd_in_progress,
+            error,
+            shown_error
+        ];
+        async move {
+            while let Some(cmd) = rx.next().await {
+                let mut registry = profiles.read().clone();
+                let r = restart_warp_runner(&runner, move || -> anyhow::Result<ProfileRegistry> {
+                    let id = match cmd {
+                        ProfileCmd::Switch(id) => id,
+                        ProfileCmd::Create(name) => registry.add(&name)?.id,
+                    };
+                    registry.switch_to(id)?;
+                    Ok(registry)
+                })
+                .await;
+                match r {
+                    Ok(Ok(registry)) => {
+                        *profiles.write() = registry;
+                        shown_error.set(String::new());
+                    }
+                    Ok(Err(e)) => {
+                        log::error!("failed to switch profile: {e}");
+                        shown_error.set(get_local_text("profiles.switch-failed"));
+                    }
+                    Err(e) => {
+                        log::error!("profile switch task failed: {e}");
+                        shown_error.set(get_local_text("profiles.switch-failed"));
+                    }
+                }
+                *state.write() = State::load();
+                error.set(None);
+                account_exists.set(None);
+                runner_generation.modify(|x| x.wrapping_add(1));
+                cmd_in_progress.set(false);
+            }
+        }
+    });
+
+    let active_profile = profiles.read().active;
+    let active_profile_name = profiles
+        .read()
+        .active_profile()
+        .map(|p| p.name.clone())
+        .unwrap_or_default();
+    let profile_names: Vec<String> = profiles
+        .read()
+        .profiles
+        .iter()
+        .map(|p| p.name.clone())
+        .collect();
+
     // Set up validation options for the input field
     let pin_validation = Validation {
         // The input should have a maximum length of 32
@@ -259,6 +336,56 @@ pub fn Layout(cx: Scope, page: UseState<AuthPages>, pin: UseRef<String>) -> Elem
                             get_local_text("unlock.notice")
                         }
                     },
+                    div {
+                        class: "profile-picker",
+                        aria_label: "profile-picker",
+                        Select {
+                            key: "{active_profile}",
+                            initial_value: active_profile_name,
+                            options: profile_names,
+                            onselect: move |name: String| {
+                                let id = profiles.read().profiles.iter().find(|p| p.name == name).map(|p| p.id);
+                                if let Some(id) = id.filter(|id| *id != active_profile) {
+                                    cmd_in_progress.set(true);
+                                    profile_ch.send(ProfileCmd::Switch(id));
+                                }
+                            }
+                        },
+                        if *adding_profile.get() {
+                            rsx!(Input {
+                                aria_label: "new-profile-input".into(),
+                                focus: true,
+                                disable_onblur: true,
+                                disabled: *cmd_in_progress.get(),
+                                placeholder: get_local_text("profiles.new-profile"),
+                                options: Options {
+                                    with_clear_btn: true,
+                                    ..Default::default()
+                                },
+                                onreturn: move |(name, _, _): (String, bool, _)| {
+                                    adding_profile.set(false);
+                                    if !name.trim().is_empty() {
+                                        cmd_in_progress.set(true);
+                                        profile_ch.send(ProfileCmd::Create(name));
+                                    }
+                                }
+                            })
+                        } else {
+                            rsx!(Button {
+                                aria_label: "add-profile-button".into(),
+                                icon: Icon::UserPlus,
+                                appearance: kit::elements::Appearance::Secondary,
+                                disabled: *cmd_in_progress.get(),
+                                onpress: move |_| adding_profile.set(true),
+                                tooltip: cx.render(rsx!(
+                                    Tooltip {
+                                        arrow_position: ArrowPosition::Top,
+                                        text: get_local_text("profiles.add"),
+                                    }
+                                )),
+                            })
+                        }
+                    },
                     Button {
                         text: match account_exists.current().unwrap_or(true) {
                             true => if *cmd_in_progress.get() {get_local_text("unlock.logging-in")} else {get_local_text("unlock.unlock-account")},
@@ -301,6 +428,7 @@ pub fn Layout(cx: Scope, page: UseState<AuthPages>, pin: UseRef<String>) -> Elem
                                     error.set(None);
                                     account_exists.set(Some(false));
                                     create_uplink_dirs();
+                                    *profiles.write() = ProfileRegistry::load();
                                 }
                             },
                         )),
@@ -4,6 +4,7 @@ mod enter_seed_words;
 mod enter_username;
 mod entry_point;
 mod recover_account;
+mod restore_backup;
 
 use dioxus::prelude::*;
 use dioxus_desktop::{use_window, LogicalSize};
@@ -16,6 +17,7 @@ pub const APP_STYLE: &str = include_str!("../../compiled_styles.css");
 // EntryPoint -> login
 // EntryPoint -> CreateOrRecover -> EnterSeedWords -> login or fail
 // EntryPoint -> CreateOrRecover -> CopySeedWords -> EnterUserName -> login
+// EntryPoint -> CreateOrRecover -> RestoreBackup -> EntryPoint
 // serve as a sort of router while the user logs in]
 #[allow(clippy::large_enum_variant)]
 #[derive(PartialEq, Eq)]
@@ -25,6 +27,7 @@ pub enum AuthPages {
     EnterUserName,
     EnterSeedWords,
     CopySeedWords,
+    RestoreBackup,
     Success(multipass::identity::Identity),
 }
 
@@ -68,6 +71,7 @@ pub fn AuthGuard(cx: Scope, page: UseState<AuthPages>) -> Element {
                 AuthPages::CreateOrRecover => rsx!(create_or_recover::Layout { page: page.clone() }),
                 AuthPages::EnterSeedWords => rsx!(enter_seed_words::Layout { page: page.clone(), pin: pin.clone(), }),
                 AuthPages::CopySeedWords => rsx!(copy_seed_words::Layout { page: page.clone(), seed_words: seed_words.clone() }),
+                AuthPages::RestoreBackup => rsx!(restore_backup::Layout { page: page.clone() }),
                 _ => unreachable!("this view should disappear when an account is unlocked or created"),
             }
         }
@@ -0,0 +1,177 @@
+use std::path::PathBuf;
+
+use common::{
+    backup::{self, BackupError},
+    icons,
+    language::{get_local_text, get_local_text_with_args},
+    state::State,
+    warp_runner::WarpRunner,
+};
+use dioxus::prelude::*;
+use dioxus_desktop::{use_window, LogicalSize};
+use futures::StreamExt;
+use kit::elements::{
+    button::Button,
+    input::{Input, Options},
+    label::Label,
+    Appearance,
+};
+use rfd::FileDialog;
+
+use crate::{bootstrap::restart_warp_runner, get_app_style};
+
+use super::AuthPages;
+
+struct Cmd {
+    path: PathBuf,
+    passphrase: String,
+}
+
+fn error_text(e: &BackupError) -> String {
+    match e {
+        BackupError::NotABackup => get_local_text("restore-backup.not-a-backup"),
+        BackupError::Decrypt => get_local_text("restore-backup.wrong-passphrase"),
+        BackupError::Incompatible(version) => get_local_text_with_args(
+            "restore-backup.incompatible",
+            vec![("version", version.clone())],
+        ),
+        BackupError::Other(_) => get_local_text("restore-backup.failed"),
+    }
+}
+
+// styles for this layout are in layouts/style.scss
+#[component]
+pub fn Layout(cx: Scope, page: UseState<AuthPages>) -> Element {
+    let state = use_ref(cx, State::load);
+    let path: &UseState<Option<PathBuf>> = use_state(cx, || None);
+    let passphrase = use_ref(cx, String::new);
+    let loading = use_state(cx, || false);
+    let error: &UseState<Option<String>> = use_state(cx, || None);
+    let window = use_window(cx);
+    let runner = use_shared_state::<WarpRunner>(cx)?;
+
+    if !matches!(&*page.current(), AuthPages::Success(_)) {
+        window.set_inner_size(LogicalSize {
+            width: 500.0,
+            height: 350.0,
+        });
+    }
+
+    let ch = use_coroutine(cx, |mut rx: UnboundedReceiver<Cmd>| {
+        to_owned![loading, page, error, runner];
+        async move {
+            while let Some(Cmd { path, passphrase }) = rx.next().await {
+                // warp has to let go of the old store before it is replaced
+                let r = restart_warp_runner(&runner, move || {
+                    backup::restore_backup(&passphrase, &path)
+                })
+                .await;
+                loading.set(false);
+                match r {
+                    Ok(Ok(manifest)) => {
+                        log::info!("restored backup made by Uplink {}", manifest.app_version);
+                        // the restored account is unlocked with the pin it had when the backup was made
+                        page.set(AuthPages::EntryPoint);
+                    }
+                    Ok(Err(e)) => {
+                        log::error!("failed to restore backup: {e}");
+                        error.set(Some(error_text(&e)));
+                    }
+                    Err(e) => {
+                        log::error!("restore task failed: {e}");
+                        error.set(Some(get_local_text("restore-backup.failed")));
+                    }
+                }
+            }
+        }
+    });
+
+    let file_name = path
+        .get()
+        .as_ref()
+        .and_then(|p| p.file_name())
+        .map(|x| x.to_string_lossy().to_string())
+        .unwrap_or_else(|| get_local_text("restore-backup.choose-file"));
+
+    cx.render(rsx!(
+        style {get_app_style(&state.read())},
+        div {
+            id: "restore-backup-layout",
+            aria_label: "restore-backup-layout",
+            Label {
+                aria_label: "restore-backup".into(),
+                text: get_local_text("restore-backup")
+            },
+            div {
+                class: "instructions",
+                aria_label: "restore-backup-instructions",
+                get_local_text("restore-backup.instructions")
+            },
+            Button {
+                aria_label: "choose-backup-button".into(),
+                text: file_name,
+                icon: icons::outline::Shape::FolderOpen,
+                appearance: Appearance::Secondary,
+                onpress: move |_| {
+                    if let Some(p) = FileDialog::new()
+                        .set_directory(dirs::home_dir().unwrap_or(".".into()))
+                        .pick_file()
+                    {
+                        error.set(None);
+                        path.set(Some(p));
+                    }
+                }
+            },
+            Input {
+                aria_label: "backup-passphrase-input".into(),
+                is_password: true,
+                icon: icons::outline::Shape::Key,
+                disable_onblur: true,
+                placeholder: get_local_text("restore-backup.passphrase"),
+                options: Options {
+                    with_clear_btn: true,
+                    clear_on_submit: false,
+                    ..Default::default()
+                },
+                onchange: move |(val, _): (String, bool)| {
+                    *passphrase.write() = val;
+                    if error.get().is_some() {
+                        error.set(None);
+                    }
+                },
+            },
+            error.get().as_ref().map(|e| rsx!(
+                span {
+                    aria_label: "input-error",
+                    class: "error",
+                    "{e}"
+                }
+            )),
+            div {
+                class: "button-container",
+                Button {
+                    aria_label: "back-button".into(),
+                    text: get_local_text("uplink.go-back"),
+                    icon: icons::outline::Shape::ChevronLeft,
+                    onpress: move |_| page.set(AuthPages::CreateOrRecover),
+                    appearance: Appearance::Secondary
+                },
+                Button {
+                    aria_label: "restore-backup-button".into(),
+                    text: get_local_text("restore-backup.restore"),
+                    disabled: *loading.get() || path.get().is_none() || passphrase.read().is_empty(),
+                    loading: *loading.get(),
+                    onpress: move |_| {
+                        if let Some(p) = path.get().clone() {
+                            loading.set(true);
+                            ch.send(Cmd {
+                                path: p,
+                                passphrase: passphrase.read().clone(),
+                            });
+                        }
+                    }
+                }
+            }
+        }
+    ))
+}
@@ -17,6 +17,7 @@ use crate::{
             // files::FilesSettings,
             // privacy::PrivacySettings,
             profile::ProfileSettings,
+            recordings::RecordingsSettings,
         },
     },
     layouts::slimbar::SlimbarLayout,
@@ -49,6 +50,7 @@ pub fn SettingsLayout(cx: Scope) -> Element {
         Page::Accessibility => rsx!(AccessibilitySettings {}),
         Page::Profile => rsx!(ProfileSettings {}),
         Page::Audio => rsx!(AudioSettings {}),
+        Page::Recordings => rsx!(RecordingsSettings {}),
         // Page::Privacy => rsx!(PrivacySettings {}),
         // Page::Files => rsx!(FilesSettings {}),
         Page::Extensions => rsx!(ExtensionSettings {}),
@@ -5,13 +5,18 @@
 #![allow(non_snake_case)]
 // the above macro will make uplink be a "window" application instead of a  "console" application for Windows.
 
+use chrono::Utc;
 use clap::Parser;
+use common::audio_devices::{self, AUDIO_DEVICE_EVENTS};
 use common::icons::outline::Shape as Icon;
 use common::icons::Icon as IconElement;
 use common::language::{get_local_text, get_local_text_with_args};
 use common::notifications::{NotificationAction, NOTIFICATION_LISTENER};
 use common::profile_update_channel::PROFILE_CHANNEL_LISTENER;
 use common::state::data_transfer::{TrackerType, TransferTracker};
+use common::state::drafts;
+use common::state::notifications::NotificationKind;
+use common::state::outbox::OutboxMessage;
 use common::state::settings::GlobalShortcut;
 use common::state::ui::Layout;
 use common::state::ToastNotification;
@@ -40,6 +45,7 @@ use kit::layout::modal::Modal;
 use notify::{RecommendedWatcher, RecursiveMode, Watcher};
 use once_cell::sync::Lazy;
 use tokio::sync::broadcast::error::RecvError;
+use warp::raygun::Location;
 
 use std::collections::HashMap;
 
@@ -61,8 +67,12 @@ use crate::layouts::log_in::{AuthGuard, AuthPages};
 use crate::layouts::settings::SettingsLayout;
 use crate::layouts::storage::files_layout::FilesLayout;
 use crate::misc_scripts::*;
-use crate::utils::async_task_queue::{ListenerAction, ACTION_LISTENER};
+use crate::utils::async_task_queue::{
+    outbox_upload_stream_handler, ListenerAction, ACTION_LISTENER,
+};
+use crate::utils::keyboard::push_to_talk::PushToTalk;
 use crate::utils::keyboard::shortcut_handlers::audio::ToggleType;
+use crate::utils::keyboard::shortcut_handlers::call::{answer_call, reject_call};
 use crate::utils::keyboard::KeyboardShortcuts;
 use dioxus_desktop::wry::application::event::Event as WryEvent;
 use dioxus_desktop::{use_wry_event_handler, DesktopService, PhysicalSize};
@@ -241,9 +251,11 @@ fn app_layout(cx: Scope) -> Element {
 
     use_auto_updater(cx)?;
     use_app_coroutines(cx)?;
+    utils::presence::use_presence(cx)?;
     use_router_notification_listener(cx)?;
 
     let state = use_shared_state::<State>(cx)?;
+    let router = use_navigator(cx);
 
     render! {
         AppStyle {}
@@ -259,11 +271,27 @@ fn app_layout(cx: Scope) -> Element {
                         GlobalShortcut::OpenCloseDevTools => utils::keyboard::shortcut_handlers::dev::open_close_dev_tools(cx),
                         GlobalShortcut::ToggleDevmode => utils::keyboard::shortcut_handlers::dev::toggle_devmode(state.clone()),
                         GlobalShortcut::SetAppVisible => utils::keyboard::shortcut_handlers::navigation::set_app_visible(cx),
+                        GlobalShortcut::NextChat => utils::keyboard::shortcut_handlers::navigation::cycle_chat(state.clone(), router, 1),
+                        GlobalShortcut::PreviousChat => utils::keyboard::shortcut_handlers::navigation::cycle_chat(state.clone(), router, -1),
+                        GlobalShortcut::JumpToUnread => utils::keyboard::shortcut_handlers::navigation::jump_to_unread(state.clone(), router),
+                        GlobalShortcut::OpenChats => { router.replace(UplinkRoute::ChatLayout {}); },
+                        GlobalShortcut::OpenFriends => { router.replace(UplinkRoute::FriendsLayout {}); },
+                        GlobalShortcut::OpenFiles => { router.replace(UplinkRoute::FilesLayout {}); },
+                        GlobalShortcut::OpenSettings => { router.replace(UplinkRoute::SettingsLayout {}); },
+                        GlobalShortcut::EditLastMessage => utils::keyboard::shortcut_handlers::chat::edit_last_message(state.clone()),
+                        GlobalShortcut::ReplyToLastMessage => utils::keyboard::shortcut_handlers::chat::reply_to_last_message(state.clone()),
+                        GlobalShortcut::MarkAllRead => utils::keyboard::shortcut_handlers::chat::mark_all_read(state.clone()),
+                        GlobalShortcut::ToggleFavorite => utils::keyboard::shortcut_handlers::chat::toggle_favorite(state.clone()),
+                        GlobalShortcut::AnswerCall => utils::keyboard::shortcut_handlers::call::answer(state.clone(), cx),
+                        GlobalShortcut::HangUp => utils::keyboard::shortcut_handlers::call::hang_up(state.clone(), cx),
+                        // held instead of pressed, see `PushToTalk`
+                        GlobalShortcut::PushToTalk => {},
                         GlobalShortcut::Unknown => log::error!("Unknown `Shortcut` called!")
                     }
                     log::debug!("shortcut called {:?}", shortcut);
                 }
             },
+            PushToTalk {},
             Toasts {},
             Outlet::<UplinkRoute>{},
             AppLogger {},
@@ -431,15 +459,20 @@ fn use_app_coroutines(cx: &ScopeState) -> Option<()> {
                     if *focused {
                         state.write().ui.notifications.clear_badge();
                         let _ = state.write().save();
+                    } else {
+                        drafts::flush();
                     }
                 }
             }
             WryEvent::WindowEvent {
                 event: WindowEvent::CloseRequested,
                 ..
-            } => state
-                .write()
-                .mutate(Action::ClearAllPopoutWindows(desktop.clone())),
+            } => {
+                drafts::flush();
+                state
+                    .write()
+                    .mutate(Action::ClearAllPopoutWindows(desktop.clone()))
+            }
             WryEvent::WindowEvent {
                 event: WindowEvent::Moved(_),
                 ..
@@ -617,7 +650,7 @@ fn use_app_coroutines(cx: &ScopeState) -> Option<()> {
                         content: _,
                         icon: _,
                         timeout: _
-                    }
+                    } | ListenerAction::QueueOutgoingMessage { .. }
                 );
                 match action {
                     ListenerAction::ToastAction {
@@ -675,6 +708,12 @@ fn use_app_coroutines(cx: &ScopeState) -> Option<()> {
                             },
                         );
                     }
+                    ListenerAction::QueueOutgoingMessage { pending_id, msg } => {
+                        state
+                            .write()
+                            .decrement_outgoing_messages(msg.conversation_id, pending_id);
+                        state.write().mutate(Action::QueueOutgoingMessage(msg));
+                    }
                 }
                 if transfer {
                     if let Some(v) = state.read().scope_ids.file_transfer {
@@ -703,6 +742,26 @@ fn use_app_coroutines(cx: &ScopeState) -> Option<()> {
         }
     });
 
+    // switch audio devices when they are plugged in or removed
+    use_future(cx, (), |_| {
+        to_owned![state];
+        async move {
+            tokio::spawn(audio_devices::watch());
+            let mut ch = AUDIO_DEVICE_EVENTS.subscribe();
+            loop {
+                let event = match ch.recv().await {
+                    Ok(event) => event,
+                    Err(RecvError::Closed) => {
+                        log::debug!("RecvError::Closed while reading from AUDIO_DEVICE_EVENTS");
+                        return;
+                    }
+                    Err(RecvError::Lagged(_)) => continue,
+                };
+                utils::audio_devices::handle_event(&state, event).await;
+            }
+        }
+    });
+
     //Update active call
     use_future(cx, (), |_| {
         to_owned![state];
@@ -730,6 +789,174 @@ fn use_app_coroutines(cx: &ScopeState) -> Option<()> {
         }
     });
 
+    // send scheduled messages once they are due. messages which came due while Uplink was closed are sent at startup.
+    let scheduled_upload_streams = outbox_upload_stream_handler(cx);
+    use_future(cx, (), |_| {
+        to_owned![state, scheduled_upload_streams];
+        async move {
+            // the chats have to be loaded first
+            while !state.read().initialized {
+                sleep(Duration::from_millis(100)).await;
+            }
+            let warp_cmd_tx = WARP_CMD_CH.tx.clone();
+            loop {
+                let due = state.read().scheduled_messages().due(Utc::now());
+                for msg in due {
+                    if !state.read().chats().all.contains_key(&msg.conversation_id) {
+                        log::warn!(
+                            "dropping scheduled message for a conversation which no longer exists"
+                        );
+                        state.write().mutate(Action::RemoveScheduledMessage(msg.id));
+                        continue;
+                    }
+                    if STATIC_ARGS.use_mock {
+                        state
+                            .write()
+                            .mutate(Action::MockSend(msg.conversation_id, msg.lines));
+                        state.write().mutate(Action::RemoveScheduledMessage(msg.id));
+                        continue;
+                    }
+                    let (tx, rx) = oneshot::channel();
+                    if let Err(e) = warp_cmd_tx.send(WarpCmd::RayGun(RayGunCmd::SendMessage {
+                        conv_id: msg.conversation_id,
+                        msg: msg.lines.clone(),
+                        attachments: msg
+                            .attachments
+                            .iter()
+                            .cloned()
+                            .map(Location::from)
+                            .collect(),
+                        rsp: tx,
+                    })) {
+                        log::error!("failed to send warp command: {}", e);
+                        break;
+                    }
+                    match rx.await.expect("command canceled") {
+                        Ok((id, stream)) => {
+                            state.write().mutate(Action::RemoveScheduledMessage(msg.id));
+                            state.write().increment_outgoing_messages_for(
+                                msg.conversation_id,
+                                id,
+                                msg.lines.clone(),
+                            );
+                            if let Some(stream) = stream {
+                                scheduled_upload_streams.write().append((
+                                    msg.conversation_id,
+                                    id,
+                                    stream,
+                                    Box::new(move |e| {
+                                        OutboxMessage::new(
+                                            msg.conversation_id,
+                                            msg.lines,
+                                            None,
+                                            msg.attachments,
+                                            e,
+                                        )
+                                    }),
+                                ));
+                            }
+                        }
+                        // from now on it is retried like any other message which couldn't be sent
+                        Err(e) => {
+                            log::error!("failed to send scheduled message: {e}");
+                            state.write().mutate(Action::RemoveScheduledMessage(msg.id));
+                            state
+                                .write()
+                                .mutate(Action::QueueOutgoingMessage(OutboxMessage::new(
+                                    msg.conversation_id,
+                                    msg.lines,
+                                    None,
+                                    msg.attachments,
+                                    e.to_string(),
+                                )));
+                        }
+                    }
+                }
+                sleep(Duration::from_secs(5)).await;
+            }
+        }
+    });
+
+    // retry messages which couldn't be sent. they wait longer after every attempt, unless a participant comes online.
+    let outbox_upload_streams = outbox_upload_stream_handler(cx);
+    use_future(cx, (), |_| {
+        to_owned![state, outbox_upload_streams];
+        async move {
+            while !state.read().initialized {
+                sleep(Duration::from_millis(100)).await;
+            }
+            let warp_cmd_tx = WARP_CMD_CH.tx.clone();
+            loop {
+                let due = state.read().outbox().due(Utc::now());
+                for msg in due {
+                    if !state.read().chats().all.contains_key(&msg.conversation_id) {
+                        log::warn!(
+                            "dropping unsent message for a conversation which no longer exists"
+                        );
+                        state.write().mutate(Action::RemoveOutgoingMessage(msg.id));
+                        continue;
+                    }
+                    let (tx, rx) = oneshot::channel();
+                    let attachments = msg
+                        .attachments
+                        .iter()
+                        .cloned()
+                        .map(Location::from)
+                        .collect();
+                    let cmd = match msg.replying_to {
+                        Some(reply_to) => RayGunCmd::Reply {
+                            conv_id: msg.conversation_id,
+                            reply_to,
+                            msg: msg.lines.clone(),
+                            attachments,
+                            rsp: tx,
+                        },
+                        None => RayGunCmd::SendMessage {
+                            conv_id: msg.conversation_id,
+                            msg: msg.lines.clone(),
+                            attachments,
+                            rsp: tx,
+                        },
+                    };
+                    if let Err(e) = warp_cmd_tx.send(WarpCmd::RayGun(cmd)) {
+                        log::error!("failed to send warp command: {}", e);
+                        break;
+                    }
+                    match rx.await.expect("command canceled") {
+                        Ok((id, stream)) => {
+                            state.write().mutate(Action::RemoveOutgoingMessage(msg.id));
+                            state.write().increment_outgoing_messages_for(
+                                msg.conversation_id,
+                                id,
+                                msg.lines.clone(),
+                            );
+                            if let Some(stream) = stream {
+                                outbox_upload_streams.write().append((
+                                    msg.conversation_id,
+                                    id,
+                                    stream,
+                                    // counts as another failed attempt
+                                    Box::new(move |e| {
+                                        let mut msg = msg;
+                                        msg.attempt_failed(e, Utc::now());
+                                        msg
+                                    }),
+                                ));
+                            }
+                        }
+                        Err(e) => {
+                            log::warn!("failed to resend message: {e}");
+                            state
+                                .write()
+                                .mutate(Action::OutgoingMessageFailed(msg.id, e.to_string()));
+                        }
+                    }
+                }
+                sleep(Duration::from_secs(1)).await;
+            }
+        }
+    });
+
     // periodically refresh message timestamps and friend's status messages
     use_future(cx, (), |_| {
         to_owned![state];
@@ -1187,6 +1414,41 @@ fn use_router_notification_listener(cx: &ScopeState) -> Option<()> {
                         // in this case, the layout would be FriendRoute::Pending
                         navigator.replace(UplinkRoute::FriendsLayout {});
                     }
+                    NotificationAction::AcceptCall(call_id) => {
+                        // the PendingCallDialog may have been used in the meantime
+                        let Some(conversation_id) = state
+                            .read()
+                            .ui
+                            .call_info
+                            .pending_calls()
+                            .iter()
+                            .find(|call| call.id == call_id)
+                            .map(|call| call.conversation_id)
+                        else {
+                            continue;
+                        };
+                        if state.read().ui.call_info.active_call().is_some() {
+                            continue;
+                        }
+                        if answer_call(state.clone(), call_id).await {
+                            state
+                                .write()
+                                .mutate(Action::ChatWith(&conversation_id, true));
+                            navigator.replace(UplinkRoute::ChatLayout {});
+                        }
+                    }
+                    NotificationAction::DeclineCall(call_id) => {
+                        let pending = state
+                            .read()
+                            .ui
+                            .call_info
+                            .pending_calls()
+                            .iter()
+                            .any(|call| call.id == call_id);
+                        if pending {
+                            reject_call(state.clone(), call_id).await;
+                        }
+                    }
                     _ => {}
                 }
             }
@@ -1286,7 +1548,12 @@ fn AppNav<'a>(
         .chats_sidebar()
         .iter()
         .map(|c| c.unreads())
-        .sum();
+        .sum::<u32>()
+        + state
+            .read()
+            .ui
+            .notifications
+            .get(NotificationKind::MissedCall);
     let file_progress = tracker.read().total_progress();
     let file_progress_ctx = file_progress >= 0 && state.read().ui.current_layout != Layout::Storage;
 
@@ -1305,6 +1572,9 @@ fn AppNav<'a>(
                 text: get_local_text("uplink.clear-unreads"),
                 onpress: move |_| {
                     state.write().mutate(Action::ClearAllUnreads);
+                    state
+                        .write()
+                        .mutate(Action::ClearNotification(NotificationKind::MissedCall));
                 }
             },))
         }),
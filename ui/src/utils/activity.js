@@ -0,0 +1,22 @@
+// reports user activity at most every few seconds
+if (window.uplinkActivityListeners) {
+    for (const [name, listener] of window.uplinkActivityListeners) {
+        window.removeEventListener(name, listener, true);
+    }
+}
+
+let lastSent = 0;
+const onActivity = () => {
+    const now = Date.now();
+    if (now - lastSent < 5000) {
+        return;
+    }
+    lastSent = now;
+    dioxus.send("active");
+};
+
+window.uplinkActivityListeners = ["mousemove", "mousedown", "keydown", "wheel", "focus"]
+    .map((name) => [name, onActivity]);
+for (const [name, listener] of window.uplinkActivityListeners) {
+    window.addEventListener(name, listener, true);
+}
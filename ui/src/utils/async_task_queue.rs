@@ -3,6 +3,7 @@ use common::{
     language::get_local_text_with_args,
     state::{
         data_transfer::{TransferState, TransferStates},
+        outbox::OutboxMessage,
         pending_message::FileProgression,
     },
     warp_runner::{ui_adapter::MessageEvent, WarpEvent},
@@ -50,6 +51,11 @@ pub enum ListenerAction {
         id: Uuid,
         download: bool,
     },
+    /// the upload of a message failed. its pending message is replaced by `msg`
+    QueueOutgoingMessage {
+        pending_id: Uuid,
+        msg: OutboxMessage,
+    },
 }
 
 pub struct ListenerChannel {
@@ -107,34 +113,95 @@ pub fn chat_upload_stream_handler(
 ) -> &UseRef<AsyncRef<(Uuid, Uuid, AttachmentEventStream)>> {
     async_queue(
         cx,
-        |(conv_id, message_id, mut stream): (Uuid, Uuid, AttachmentEventStream)| async move {
-            while let Some(kind) = stream.next().await {
-                match kind {
-                    AttachmentKind::Pending(res) => {
-                        if let Err(e) = res {
-                            log::debug!("Error uploading file {}", e);
-                        }
-                        return;
-                    }
-                    AttachmentKind::AttachedProgress(location, progress) => {
-                        let progress = progress.into();
-                        if let Err(e) = WARP_EVENT_CH.tx.send(WarpEvent::Message(
-                            MessageEvent::AttachmentProgress {
-                                progress,
-                                location,
-                                conversation_id: conv_id,
-                                msg: message_id,
-                            },
-                        )) {
-                            log::error!("failed to send warp_event: {e}");
-                        }
-                    }
-                }
+        |(conv_id, message_id, stream): (Uuid, Uuid, AttachmentEventStream)| async move {
+            let _ = forward_upload_progress(conv_id, message_id, stream).await;
+        },
+    )
+}
+
+/// Builds the outbox entry for a message whose upload failed, from the error
+pub type QueueOnFailure = Box<dyn Send + FnOnce(String) -> OutboxMessage>;
+
+/// Like chat_upload_stream_handler, but a message whose upload fails is queued in the outbox, to be sent again later
+pub fn outbox_upload_stream_handler(
+    cx: &ScopeState,
+) -> &UseRef<AsyncRef<(Uuid, Uuid, AttachmentEventStream, QueueOnFailure)>> {
+    async_queue(
+        cx,
+        |(conv_id, message_id, stream, queue): (
+            Uuid,
+            Uuid,
+            AttachmentEventStream,
+            QueueOnFailure,
+        )| async move {
+            if let Err(e) = forward_upload_progress(conv_id, message_id, stream).await {
+                let _ = ACTION_LISTENER
+                    .tx
+                    .send(ListenerAction::QueueOutgoingMessage {
+                        pending_id: message_id,
+                        msg: queue(e),
+                    });
+            }
+        },
+    )
+}
+
+/// Like chat_upload_stream_handler, but `on_done` is called with `context` and the result once the message was sent
+/// or its upload failed.
+pub fn upload_stream_handler<C, F>(
+    cx: &ScopeState,
+    on_done: F,
+) -> &UseRef<AsyncRef<(Uuid, Uuid, AttachmentEventStream, C)>>
+where
+    C: Send + 'static,
+    F: Fn(C, Result<(), String>) + Clone + Send + 'static,
+{
+    async_queue(
+        cx,
+        move |(conv_id, message_id, stream, context): (Uuid, Uuid, AttachmentEventStream, C)| {
+            let on_done = on_done.clone();
+            async move {
+                let result = forward_upload_progress(conv_id, message_id, stream).await;
+                on_done(context, result);
             }
         },
     )
 }
 
+// polls the stream until the message is sent, which is what makes the upload progress
+async fn forward_upload_progress(
+    conv_id: Uuid,
+    message_id: Uuid,
+    mut stream: AttachmentEventStream,
+) -> Result<(), String> {
+    while let Some(kind) = stream.next().await {
+        match kind {
+            AttachmentKind::Pending(res) => {
+                if let Err(e) = &res {
+                    log::debug!("Error uploading file {}", e);
+                }
+                return res.map_err(|e| e.to_string());
+            }
+            AttachmentKind::AttachedProgress(location, progress) => {
+                let progress = progress.into();
+                if let Err(e) =
+                    WARP_EVENT_CH
+                        .tx
+                        .send(WarpEvent::Message(MessageEvent::AttachmentProgress {
+                            progress,
+                            location,
+                            conversation_id: conv_id,
+                            msg: message_id,
+                        }))
+                {
+                    log::error!("failed to send warp_event: {e}");
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
 pub struct DownloadStreamData {
     pub stream: warp::constellation::ConstellationProgressStream,
     pub file: String,
@@ -0,0 +1,132 @@
+//! Reacts to the events of `common::audio_devices::watch`. `settings.input_device` and `settings.output_device` hold the
+//! devices picked by the user. When one of them is unplugged blink falls back to the system default, and the picked
+//! device is used again once it is plugged back in.
+
+use common::audio_devices::{default_device, AudioDeviceEvent, AudioDeviceKind};
+use common::icons::outline::Shape as Icon;
+use common::language::{get_local_text, get_local_text_with_args};
+use common::state::{Action, State, ToastNotification};
+use common::warp_runner::{BlinkCmd, WarpCmd};
+use common::WARP_CMD_CH;
+use dioxus::prelude::UseSharedState;
+use futures::channel::oneshot;
+use tracing::log;
+
+/// the device blink is currently using
+pub async fn current_device(kind: AudioDeviceKind) -> Option<String> {
+    let (tx, rx) = oneshot::channel();
+    if let Err(e) = WARP_CMD_CH
+        .tx
+        .send(WarpCmd::Blink(BlinkCmd::GetAudioDeviceConfig { rsp: tx }))
+    {
+        log::error!("failed to send blink command: {e}");
+        return None;
+    }
+    match rx.await {
+        Ok(Ok(config)) => match kind {
+            AudioDeviceKind::Input => config.microphone_device_name(),
+            AudioDeviceKind::Output => config.speaker_device_name(),
+        },
+        Ok(Err(e)) => {
+            log::debug!("failed to get audio config: {e}");
+            None
+        }
+        Err(e) => {
+            log::error!("warp_runner failed to get audio config: {e}");
+            None
+        }
+    }
+}
+
+pub async fn set_device(kind: AudioDeviceKind, device_name: String) -> bool {
+    let (tx, rx) = oneshot::channel();
+    let cmd = match kind {
+        AudioDeviceKind::Input => BlinkCmd::SetMicrophone {
+            device_name,
+            rsp: tx,
+        },
+        AudioDeviceKind::Output => BlinkCmd::SetSpeaker {
+            device_name,
+            rsp: tx,
+        },
+    };
+    if let Err(e) = WARP_CMD_CH.tx.send(WarpCmd::Blink(cmd)) {
+        log::error!("failed to send blink command: {e}");
+        return false;
+    }
+    match rx.await {
+        Ok(Ok(_)) => true,
+        Ok(Err(e)) => {
+            log::error!("failed to set {kind} device: {e}");
+            false
+        }
+        Err(e) => {
+            log::error!("warp_runner failed to set {kind} device: {e}");
+            false
+        }
+    }
+}
+
+fn preferred_device(state: &UseSharedState<State>, kind: AudioDeviceKind) -> Option<String> {
+    let settings = &state.read().settings;
+    match kind {
+        AudioDeviceKind::Input => settings.input_device.clone(),
+        AudioDeviceKind::Output => settings.output_device.clone(),
+    }
+}
+
+fn toast(state: &UseSharedState<State>, text: String) {
+    // outside of a call the change is only visible in the audio settings
+    if state.read().ui.call_info.active_call().is_none() {
+        return;
+    }
+    state
+        .write()
+        .mutate(Action::AddToastNotification(ToastNotification::init(
+            get_local_text("settings-audio.devices-changed"),
+            text,
+            Some(Icon::Speaker),
+            4,
+        )));
+}
+
+pub async fn handle_event(state: &UseSharedState<State>, event: AudioDeviceEvent) {
+    match event {
+        AudioDeviceEvent::Removed(kind, name) => {
+            let in_use = current_device(kind).await.as_ref() == Some(&name)
+                || preferred_device(state, kind).as_ref() == Some(&name);
+            if !in_use {
+                return;
+            }
+            let Some(fallback) = default_device(kind) else {
+                log::warn!("no {kind} device left to fall back to");
+                return;
+            };
+            if set_device(kind, fallback.clone()).await {
+                toast(
+                    state,
+                    get_local_text_with_args(
+                        "settings-audio.device-disconnected",
+                        vec![("name", name), ("fallback", fallback)],
+                    ),
+                );
+            }
+        }
+        AudioDeviceEvent::Added(kind, name) => {
+            if preferred_device(state, kind).as_ref() != Some(&name)
+                || current_device(kind).await.as_ref() == Some(&name)
+            {
+                return;
+            }
+            if set_device(kind, name.clone()).await {
+                toast(
+                    state,
+                    get_local_text_with_args(
+                        "settings-audio.device-restored",
+                        vec![("name", name)],
+                    ),
+                );
+            }
+        }
+    }
+}
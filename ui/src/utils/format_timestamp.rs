@@ -19,3 +19,14 @@ pub fn format_timestamp_timeago(datetime: DateTime<Utc>, active_language: &str)
     };
     formatter.convert(duration)
 }
+
+/// 1:05:09 or 05:09
+pub fn format_duration(duration: chrono::Duration) -> String {
+    let secs = duration.num_seconds();
+    let (hours, minutes, seconds) = (secs / 3600, (secs % 3600) / 60, secs % 60);
+    if hours > 0 {
+        format!("{hours}:{minutes:02}:{seconds:02}")
+    } else {
+        format!("{minutes:02}:{seconds:02}")
+    }
+}
@@ -0,0 +1,67 @@
+//! Matches keybinds against key presses in the webview. Used for the keybinds which aren't registered with the OS.
+
+use common::state::settings::Shortcut;
+use dioxus::prelude::*;
+use serde::Deserialize;
+
+use tracing::log;
+
+const IN_WINDOW_SHORTCUTS: &str = include_str!("./in_window_shortcuts.js");
+
+// a keydown event, sent by IN_WINDOW_SHORTCUTS
+#[derive(Debug, Deserialize)]
+pub struct KeyPress {
+    code: String,
+    ctrl: bool,
+    shift: bool,
+    alt: bool,
+    meta: bool,
+}
+
+impl KeyPress {
+    pub fn matches(&self, shortcut: &Shortcut) -> bool {
+        let mut pressed = vec![self.code.clone()];
+        for (held, name) in [
+            (self.ctrl, "Ctrl"),
+            (self.shift, "Shift"),
+            (self.alt, "Alt"),
+            (self.meta, "Meta"),
+        ] {
+            if held {
+                pressed.push(name.into());
+            }
+        }
+        let (keys, modifiers) = Shortcut::string_to_keycode_and_modifiers_state(pressed);
+        shortcut.matches(&keys, &modifiers)
+    }
+
+    /// ignores the modifiers, which may be released before the key
+    pub fn matches_key(&self, shortcut: &Shortcut) -> bool {
+        let (keys, _) = Shortcut::string_to_keycode_and_modifiers_state(vec![self.code.clone()]);
+        !keys.is_empty() && shortcut.keys == keys
+    }
+}
+
+/// Listens for key presses in the window. Returns the latest key press, once.
+pub fn use_key_press(cx: &ScopeState) -> Option<KeyPress> {
+    let pressed: &UseRef<Option<KeyPress>> = use_ref(cx, || None);
+    let eval = use_eval(cx);
+
+    use_effect(cx, (), |_| {
+        to_owned![eval, pressed];
+        async move {
+            let Ok(eval) = eval(IN_WINDOW_SHORTCUTS) else {
+                log::error!("failed to listen for shortcuts");
+                return;
+            };
+            while let Ok(s) = eval.recv().await {
+                match serde_json::from_str::<KeyPress>(s.as_str().unwrap_or_default()) {
+                    Ok(key_press) => pressed.set(Some(key_press)),
+                    Err(e) => log::error!("failed to deserialize key press: {}: {}", s, e),
+                }
+            }
+        }
+    });
+
+    pressed.write_silent().take()
+}
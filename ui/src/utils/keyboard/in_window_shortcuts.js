@@ -0,0 +1,19 @@
+// replaces the listener of a previous mount, which can no longer send anything
+if (window.uplinkShortcutListener) {
+    document.removeEventListener("keydown", window.uplinkShortcutListener, true);
+}
+
+window.uplinkShortcutListener = (event) => {
+    if (event.repeat || !(event.ctrlKey || event.altKey || event.shiftKey || event.metaKey)) {
+        return;
+    }
+    dioxus.send(JSON.stringify({
+        code: event.code,
+        ctrl: event.ctrlKey,
+        shift: event.shiftKey,
+        alt: event.altKey,
+        meta: event.metaKey,
+    }));
+};
+
+document.addEventListener("keydown", window.uplinkShortcutListener, true);
@@ -1,4 +1,6 @@
-use common::state::settings::{key_code_to_str, modifier_state_to_string, GlobalShortcut};
+use common::state::settings::{
+    key_code_to_str, modifier_state_to_string, GlobalShortcut, Shortcut,
+};
 use common::state::State;
 use dioxus::prelude::*;
 use dioxus_desktop::use_global_shortcut;
@@ -7,6 +9,8 @@ use once_cell::sync::Lazy;
 
 use parking_lot::RwLock;
 
+mod in_window;
+pub mod push_to_talk;
 pub mod shortcut_handlers;
 
 static CALL_COUNT: Lazy<RwLock<u32>> = Lazy::new(|| RwLock::new(0));
@@ -17,77 +21,101 @@ const NAVIGATE_AND_HIGHLIGHT_KEYBINDS: &str = include_str!("./navigate_and_highl
 pub struct Props<'a> {
     is_on_auth_pages: Option<bool>,
     on_global_shortcut: EventHandler<'a, GlobalShortcut>,
-    // TODO: overrides: Vec<(String, String)> allow for overriding the default bindings
+}
+
+/// Returns true if global shortcuts can be grabbed through X11
+pub fn is_x11_session() -> bool {
+    match std::env::var("XDG_SESSION_TYPE") {
+        Ok(session) => session == "x11",
+        Err(_) => {
+            std::env::var_os("WAYLAND_DISPLAY").is_none() && std::env::var_os("DISPLAY").is_some()
+        }
+    }
+}
+
+// Keybinds which aren't registered with the OS only work while uplink is focused. `use_global_shortcut` grabs keys
+// for the whole desktop and doesn't work under Wayland, so on Linux it is only used if `linux_system_shortcuts` is set.
+fn is_registered_globally(
+    global_shortcut: &GlobalShortcut,
+    shortcut: &Shortcut,
+    linux_system_shortcuts: bool,
+) -> bool {
+    if global_shortcut.is_app_action() {
+        false
+    } else if cfg!(target_os = "linux") {
+        linux_system_shortcuts && shortcut.system_shortcut && is_x11_session()
+    } else {
+        true
+    }
 }
 
 #[allow(non_snake_case)]
 pub fn KeyboardShortcuts<'a>(cx: Scope<'a, Props>) -> Element<'a> {
-    if cfg!(target_os = "linux") {
+    let key_press = in_window::use_key_press(cx);
+    let eval = use_eval(cx);
+
+    // the auth pages render before the shared state exists
+    let (keybinds, paused, recording, linux_system_shortcuts) =
+        if cx.props.is_on_auth_pages.unwrap_or(false) {
+            let state = use_ref(cx, State::load);
+            let keybinds = state.read().settings.keybinds.clone();
+            (keybinds, false, false, false)
+        } else {
+            let state = use_shared_state::<State>(cx)?;
+            let settings = state.read().settings.clone();
+            (
+                settings.keybinds,
+                settings.pause_global_keybinds,
+                settings.is_recording_new_keybind,
+                settings.linux_system_shortcuts,
+            )
+        };
+
+    if recording {
         return None;
     }
 
-    if cx.props.is_on_auth_pages.unwrap_or(false) {
-        let state = use_ref(cx, State::load);
-        let keybinds = state.read().settings.keybinds.clone();
-        return cx.render(rsx! {
-            for (global_shortcut, shortcut) in keybinds {
-                rsx!{
-                    RenderGlobalShortCuts {
-                        keys: shortcut.keys,
-                        modifiers: shortcut.modifiers,
-                        on_global_shortcut: move |global_shortcut: GlobalShortcut| {
-                            // If global shortcuts are paused (for example, on the keybinds settings page) don't callback
-                            cx.props.on_global_shortcut.call(global_shortcut);
-                        },
-                        global_shortcut: global_shortcut.clone(),
-                    }
-                }
-            }
+    // If global shortcuts are paused (for example, on the keybinds settings page) the keybind is highlighted instead
+    let on_shortcut = move |global_shortcut: GlobalShortcut| {
+        if paused {
+            let scroll_script = NAVIGATE_AND_HIGHLIGHT_KEYBINDS.replace(
+                "$SHORTCUT_PRESSED",
+                format!("{:?}", global_shortcut).as_str(),
+            );
+            let _ = eval(&scroll_script);
+        } else {
+            cx.props.on_global_shortcut.call(global_shortcut);
+        }
+    };
+
+    let (global, in_window): (Vec<_>, Vec<_>) = keybinds
+        .into_iter()
+        .filter(|(_, shortcut)| !shortcut.keys.is_empty())
+        .partition(|(global_shortcut, shortcut)| {
+            is_registered_globally(global_shortcut, shortcut, linux_system_shortcuts)
         });
-    }
 
-    let state = use_shared_state::<State>(cx)?;
-    let eval = use_eval(cx);
+    if let Some(key_press) = key_press {
+        if let Some((global_shortcut, _)) = in_window
+            .iter()
+            .find(|(_, shortcut)| key_press.matches(shortcut))
+        {
+            on_shortcut(global_shortcut.clone());
+        }
+    }
 
-    if !state.read().settings.pause_global_keybinds {
-        let keybinds = state.read().settings.keybinds.clone();
-        return cx.render(rsx! {
-            for (global_shortcut, shortcut) in keybinds {
-                rsx!{
-                    RenderGlobalShortCuts {
-                        keys: shortcut.keys,
-                        modifiers: shortcut.modifiers,
-                        on_global_shortcut: move |global_shortcut: GlobalShortcut| {
-                            // If global shortcuts are paused (for example, on the keybinds settings page) don't callback
-                            cx.props.on_global_shortcut.call(global_shortcut);
-                        },
-                        global_shortcut: global_shortcut.clone(),
-                    }
+    cx.render(rsx! {
+        for (global_shortcut, shortcut) in global {
+            rsx!{
+                RenderGlobalShortCuts {
+                    keys: shortcut.keys,
+                    modifiers: shortcut.modifiers,
+                    on_global_shortcut: move |global_shortcut: GlobalShortcut| on_shortcut(global_shortcut),
+                    global_shortcut: global_shortcut.clone(),
                 }
             }
-        });
-    } else if !state.read().settings.is_recording_new_keybind {
-        let keybinds = state.read().settings.keybinds.clone();
-        return cx.render(rsx! {
-            for (global_shortcut, shortcut) in keybinds {
-                rsx!{
-                    RenderGlobalShortCuts {
-                        keys: shortcut.keys,
-                        modifiers: shortcut.modifiers,
-                        on_global_shortcut: move |global_shortcut: GlobalShortcut| {
-                                let scroll_script = NAVIGATE_AND_HIGHLIGHT_KEYBINDS.to_string().replace("$SHORTCUT_PRESSED", format!("{:?}", global_shortcut).as_str());
-                                let _ = eval(&scroll_script);
-                        },
-                        global_shortcut: global_shortcut.clone(),
-                    }
-                }
-            }
-        });
-    } else {
-        println!("rendering keyboard shortcuts - 3");
-
-        None
-    }
+        }
+    })
 }
 
 #[derive(Props)]
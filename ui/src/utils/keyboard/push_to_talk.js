@@ -0,0 +1,42 @@
+// replaces the listeners of a previous mount, which can no longer send anything
+if (window.uplinkPushToTalkListeners) {
+    for (const [name, listener] of window.uplinkPushToTalkListeners) {
+        window.removeEventListener(name, listener, true);
+    }
+}
+
+const sendKey = (pressed) => (event) => {
+    if (event.repeat) {
+        return;
+    }
+    dioxus.send(JSON.stringify({
+        code: event.code,
+        ctrl: event.ctrlKey,
+        shift: event.shiftKey,
+        alt: event.altKey,
+        meta: event.metaKey,
+        pressed: pressed,
+    }));
+};
+
+// the key release is never seen once the window lost focus
+const onBlur = () => {
+    dioxus.send(JSON.stringify({
+        code: "",
+        ctrl: false,
+        shift: false,
+        alt: false,
+        meta: false,
+        pressed: false,
+        blur: true,
+    }));
+};
+
+window.uplinkPushToTalkListeners = [
+    ["keydown", sendKey(true)],
+    ["keyup", sendKey(false)],
+    ["blur", onBlur],
+];
+for (const [name, listener] of window.uplinkPushToTalkListeners) {
+    window.addEventListener(name, listener, true);
+}
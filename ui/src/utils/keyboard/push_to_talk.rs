@@ -0,0 +1,167 @@
+//! While push-to-talk is enabled the microphone is muted for the whole call and only opened while the
+//! `GlobalShortcut::PushToTalk` keybind is held. Only works while uplink is focused, because the key release is
+//! reported by the webview.
+
+use common::state::settings::{GlobalShortcut, Settings, Shortcut};
+use common::state::{Action, State};
+use common::warp_runner::{BlinkCmd, WarpCmd};
+use common::WARP_CMD_CH;
+use dioxus::prelude::*;
+use futures::channel::oneshot;
+use futures::StreamExt;
+use serde::Deserialize;
+use tokio::time::{Duration, Instant};
+use tracing::log;
+
+use super::in_window::KeyPress;
+
+const PUSH_TO_TALK: &str = include_str!("./push_to_talk.js");
+
+// a keydown or keyup event, sent by PUSH_TO_TALK
+#[derive(Debug, Deserialize)]
+struct PushToTalkEvent {
+    #[serde(flatten)]
+    key: KeyPress,
+    pressed: bool,
+    // the window lost focus
+    #[serde(default)]
+    blur: bool,
+}
+
+/// the bound push-to-talk key, as shown to the user
+pub fn key_text(settings: &Settings) -> Option<String> {
+    shortcut(settings)
+        .map(|shortcut| shortcut.get_keys_and_modifiers_as_string())
+        .filter(|keys| !keys.is_empty())
+        .map(|keys| keys.join(" + "))
+}
+
+fn shortcut(settings: &Settings) -> Option<Shortcut> {
+    settings
+        .keybinds
+        .iter()
+        .find(|(global_shortcut, _)| *global_shortcut == GlobalShortcut::PushToTalk)
+        .map(|(_, shortcut)| shortcut.clone())
+}
+
+async fn set_transmitting(state: &UseSharedState<State>, transmitting: bool) {
+    let Some(self_muted) = state
+        .read()
+        .ui
+        .call_info
+        .active_call()
+        .map(|call| call.call.self_muted)
+    else {
+        return;
+    };
+    if self_muted != transmitting {
+        return;
+    }
+
+    let (tx, rx) = oneshot::channel();
+    let cmd = if transmitting {
+        BlinkCmd::UnmuteSelf { rsp: tx }
+    } else {
+        BlinkCmd::MuteSelf { rsp: tx }
+    };
+    if let Err(e) = WARP_CMD_CH.tx.send(WarpCmd::Blink(cmd)) {
+        log::error!("failed to send blink command: {e}");
+        return;
+    }
+    match rx.await {
+        Ok(Ok(_)) => state.write().mutate(Action::ToggleMute),
+        Ok(Err(e)) => log::error!("failed to toggle push-to-talk: {e}"),
+        Err(e) => log::error!("warp_runner failed to toggle push-to-talk: {e}"),
+    }
+}
+
+/// Listens for the push-to-talk key during calls
+#[allow(non_snake_case)]
+pub fn PushToTalk(cx: Scope) -> Element {
+    let state = use_shared_state::<State>(cx)?;
+    let enabled = state.read().configuration.audiovideo.push_to_talk
+        && state.read().ui.call_info.active_call().is_some();
+    if !enabled {
+        return None;
+    }
+    render!(PushToTalkListener {})
+}
+
+#[allow(non_snake_case)]
+fn PushToTalkListener(cx: Scope) -> Element {
+    let state = use_shared_state::<State>(cx)?;
+    let eval = use_eval(cx);
+
+    // receives whether the key is held
+    let ch = use_coroutine(cx, |mut rx: UnboundedReceiver<bool>| {
+        to_owned![state];
+        async move {
+            // the call starts muted
+            set_transmitting(&state, false).await;
+            let mut release_at: Option<Instant> = None;
+            loop {
+                let pressed = match release_at {
+                    Some(deadline) => match tokio::time::timeout_at(deadline, rx.next()).await {
+                        Ok(pressed) => pressed,
+                        Err(_) => {
+                            release_at = None;
+                            set_transmitting(&state, false).await;
+                            continue;
+                        }
+                    },
+                    None => rx.next().await,
+                };
+                let Some(pressed) = pressed else {
+                    break;
+                };
+                if pressed {
+                    release_at = None;
+                    set_transmitting(&state, true).await;
+                } else if release_at.is_none() {
+                    let delay = state
+                        .read()
+                        .configuration
+                        .audiovideo
+                        .push_to_talk_release_delay_ms;
+                    release_at = Some(Instant::now() + Duration::from_millis(delay));
+                }
+            }
+        }
+    });
+
+    use_effect(cx, (), |_| {
+        to_owned![eval, state, ch];
+        async move {
+            let Ok(eval) = eval(PUSH_TO_TALK) else {
+                log::error!("failed to listen for the push-to-talk key");
+                return;
+            };
+            while let Ok(s) = eval.recv().await {
+                let event =
+                    match serde_json::from_str::<PushToTalkEvent>(s.as_str().unwrap_or_default()) {
+                        Ok(event) => event,
+                        Err(e) => {
+                            log::error!("failed to deserialize key event: {}: {}", s, e);
+                            continue;
+                        }
+                    };
+                // the key may be rebound on the keybinds page
+                if state.read().settings.is_recording_new_keybind {
+                    continue;
+                }
+                let Some(shortcut) = shortcut(&state.read().settings) else {
+                    continue;
+                };
+                if event.pressed {
+                    if event.key.matches(&shortcut) {
+                        ch.send(true);
+                    }
+                } else if event.blur || event.key.matches_key(&shortcut) {
+                    ch.send(false);
+                }
+            }
+        }
+    });
+
+    None
+}
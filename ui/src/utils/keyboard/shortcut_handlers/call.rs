@@ -0,0 +1,96 @@
+use common::state::{Action, State};
+use common::{
+    warp_runner::{BlinkCmd, WarpCmd},
+    WARP_CMD_CH,
+};
+use dioxus_core::ScopeState;
+use dioxus_hooks::{to_owned, UseSharedState};
+use futures::channel::oneshot;
+use uuid::Uuid;
+
+/// Answers the oldest incoming call, unless the user is already in a call
+pub fn answer(state: UseSharedState<State>, cx: &ScopeState) {
+    if state.read().ui.call_info.active_call().is_some() {
+        return;
+    }
+    let Some(call_id) = state
+        .read()
+        .ui
+        .call_info
+        .pending_calls()
+        .first()
+        .map(|call| call.id)
+    else {
+        return;
+    };
+    cx.spawn({
+        to_owned![state];
+        async move { answer_call(state, call_id).await }
+    });
+}
+
+/// Answers a pending call. Returns false if it failed
+pub async fn answer_call(state: UseSharedState<State>, call_id: Uuid) -> bool {
+    let (tx, rx) = oneshot::channel();
+    if let Err(e) = WARP_CMD_CH
+        .tx
+        .send(WarpCmd::Blink(BlinkCmd::AnswerCall { call_id, rsp: tx }))
+    {
+        log::error!("failed to send blink command: {e}");
+        return false;
+    }
+    match rx.await {
+        Ok(Ok(_)) => {
+            state.write().mutate(Action::AnswerCall(call_id));
+            true
+        }
+        Ok(Err(e)) => {
+            log::error!("warp_runner failed to answer call: {e}");
+            false
+        }
+        Err(e) => {
+            log::error!("failed to answer call: {e}");
+            false
+        }
+    }
+}
+
+pub async fn reject_call(state: UseSharedState<State>, call_id: Uuid) {
+    let (tx, rx) = oneshot::channel();
+    if let Err(e) = WARP_CMD_CH
+        .tx
+        .send(WarpCmd::Blink(BlinkCmd::RejectCall { call_id, rsp: tx }))
+    {
+        log::error!("failed to send blink command: {e}");
+        return;
+    }
+    match rx.await {
+        Ok(Ok(_)) => state.write().mutate(Action::RejectCall(call_id)),
+        Ok(Err(e)) => log::error!("warp_runner failed to reject call: {e}"),
+        Err(e) => log::error!("failed to reject call: {e}"),
+    }
+}
+
+pub fn hang_up(state: UseSharedState<State>, cx: &ScopeState) {
+    if state.read().ui.call_info.active_call().is_none() {
+        return;
+    }
+    cx.spawn({
+        to_owned![state];
+        async move {
+            let (tx, rx) = oneshot::channel();
+            if let Err(e) = WARP_CMD_CH
+                .tx
+                .send(WarpCmd::Blink(BlinkCmd::LeaveCall { rsp: tx }))
+            {
+                log::error!("failed to send blink command: {e}");
+                return;
+            }
+            match rx.await {
+                Ok(Ok(_)) => state.write().mutate(Action::EndCall),
+                Ok(Err(e)) => log::error!("warp_runner failed to leave call: {e}"),
+                Err(e) => log::error!("failed to leave call: {e}"),
+            }
+        }
+    });
+}
@@ -0,0 +1,43 @@
+use common::state::{ui::Layout, Action, State};
+use dioxus_hooks::UseSharedState;
+use uuid::Uuid;
+
+// the chat actions only apply to the chat which is on screen
+fn visible_chat(state: &UseSharedState<State>) -> Option<Uuid> {
+    let state = state.read();
+    if state.ui.current_layout != Layout::Compose {
+        return None;
+    }
+    state.chats.active
+}
+
+pub fn edit_last_message(state: UseSharedState<State>) {
+    if let Some(id) = visible_chat(&state) {
+        state.write().mutate(Action::EditLastMessage(id));
+    }
+}
+
+pub fn reply_to_last_message(state: UseSharedState<State>) {
+    let Some(id) = visible_chat(&state) else {
+        return;
+    };
+    let last = state
+        .read()
+        .chats
+        .all
+        .get(&id)
+        .and_then(|chat| chat.messages.back().cloned());
+    if let Some(message) = last {
+        state.write().mutate(Action::StartReplying(&id, &message));
+    }
+}
+
+pub fn mark_all_read(state: UseSharedState<State>) {
+    state.write().mutate(Action::ClearAllUnreads);
+}
+
+pub fn toggle_favorite(state: UseSharedState<State>) {
+    if let Some(id) = visible_chat(&state) {
+        state.write().mutate(Action::ToggleFavorite(&id));
+    }
+}
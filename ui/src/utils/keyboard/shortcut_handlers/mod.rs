@@ -1,4 +1,6 @@
 pub mod audio;
+pub mod call;
+pub mod chat;
 pub mod dev;
 pub mod font;
 pub mod navigation;
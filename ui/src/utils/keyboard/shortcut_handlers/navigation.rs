@@ -1,5 +1,11 @@
+use common::state::{Action, State};
 use dioxus_core::ScopeState;
 use dioxus_desktop::use_window;
+use dioxus_hooks::UseSharedState;
+use dioxus_router::prelude::Navigator;
+use uuid::Uuid;
+
+use crate::UplinkRoute;
 
 /// The functionality will operate correctly only when the application is not in fullscreen mode.
 ///
@@ -22,3 +28,36 @@ pub fn set_app_visible(cx: &ScopeState) {
         window.set_visible(false);
     }
 }
+
+/// Opens the chat `offset` places below the active chat in the sidebar, wrapping around at either end
+pub fn cycle_chat(state: UseSharedState<State>, router: &Navigator, offset: isize) {
+    let chats: Vec<Uuid> = state.read().chats.in_sidebar.iter().cloned().collect();
+    if chats.is_empty() {
+        return;
+    }
+    let current = state
+        .read()
+        .chats
+        .active
+        .and_then(|active| chats.iter().position(|id| *id == active));
+    let idx = match current {
+        Some(idx) => (idx as isize + offset).rem_euclid(chats.len() as isize) as usize,
+        None => 0,
+    };
+    state.write().mutate(Action::ChatWith(&chats[idx], false));
+    router.replace(UplinkRoute::ChatLayout {});
+}
+
+/// Opens the first chat in the sidebar with unread messages
+pub fn jump_to_unread(state: UseSharedState<State>, router: &Navigator) {
+    let unread = state
+        .read()
+        .chats_sidebar()
+        .iter()
+        .find(|chat| chat.unreads() > 0)
+        .map(|chat| chat.id);
+    if let Some(id) = unread {
+        state.write().mutate(Action::ChatWith(&id, false));
+        router.replace(UplinkRoute::ChatLayout {});
+    }
+}
@@ -10,6 +10,7 @@ use kit::User as UserInfo;
 use crate::{window_manager::WindowManagerCmd, WINDOW_CMD_CH};
 
 pub mod async_task_queue;
+pub mod audio_devices;
 pub mod auto_updater;
 pub mod clipboard;
 pub mod download;
@@ -17,6 +18,7 @@ pub mod format_timestamp;
 pub mod get_drag_event;
 pub mod get_font_sizes;
 pub mod keyboard;
+pub mod presence;
 pub mod verify_valid_paths;
 
 pub type EvalProvider = Rc<dyn Fn(&str) -> Result<UseEval, EvalError>>;
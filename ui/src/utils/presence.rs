@@ -0,0 +1,120 @@
+//! Applies the automatic status changes of `common::state::presence` and clears expired status messages.
+
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use common::state::{Action, Identity, State};
+use common::warp_runner::{MultiPassCmd, WarpCmd};
+use common::{STATIC_ARGS, WARP_CMD_CH};
+use dioxus::prelude::*;
+use futures::channel::oneshot;
+use tracing::log;
+use warp::multipass::identity::IdentityStatus;
+
+const ACTIVITY: &str = include_str!("./activity.js");
+// how often the status is checked
+const CHECK_INTERVAL: Duration = Duration::from_secs(15);
+
+type IdentityRsp = oneshot::Sender<Result<Identity, warp::error::Error>>;
+
+// sends a command which updates the own identity and stores the result. returns true on success
+async fn update_identity(
+    state: &UseSharedState<State>,
+    cmd: impl FnOnce(IdentityRsp) -> MultiPassCmd,
+) -> bool {
+    let (tx, rx) = oneshot::channel();
+    if let Err(e) = WARP_CMD_CH.tx.send(WarpCmd::MultiPass(cmd(tx))) {
+        log::error!("failed to send warp command: {e}");
+        return false;
+    }
+    match rx.await {
+        Ok(Ok(mut ident)) => {
+            // like the profile page, keep the pictures which were already loaded
+            let current = state.read().get_own_identity();
+            ident.set_profile_banner(&current.profile_banner());
+            ident.set_profile_picture(&current.profile_picture());
+            state.write().set_own_identity(ident);
+            true
+        }
+        Ok(Err(e)) => {
+            log::error!("failed to update identity: {e}");
+            false
+        }
+        Err(e) => {
+            log::error!("warp_runner failed to update identity: {e}");
+            false
+        }
+    }
+}
+
+async fn set_status(state: &UseSharedState<State>, status: IdentityStatus) {
+    if update_identity(state, |rsp| MultiPassCmd::SetStatus { status, rsp }).await {
+        // the automatic status has to survive a restart to be reverted
+        if let Err(e) = state.read().save() {
+            log::error!("failed to save state: {e}");
+        }
+    }
+}
+
+async fn clear_status_message(state: &UseSharedState<State>) {
+    if update_identity(state, |rsp| MultiPassCmd::UpdateStatusMessage {
+        status: None,
+        rsp,
+    })
+    .await
+    {
+        state.write().mutate(Action::SetStatusMessageExpiry(None));
+    }
+}
+
+pub fn use_presence(cx: &ScopeState) -> Option<()> {
+    let state = use_shared_state::<State>(cx)?;
+    let eval = use_eval(cx);
+    let last_activity = use_ref(cx, Instant::now);
+
+    use_effect(cx, (), |_| {
+        to_owned![eval, last_activity];
+        async move {
+            let Ok(eval) = eval(ACTIVITY) else {
+                log::error!("failed to listen for user activity");
+                return;
+            };
+            while eval.recv().await.is_ok() {
+                *last_activity.write_silent() = Instant::now();
+            }
+        }
+    });
+
+    use_future(cx, (), |_| {
+        to_owned![state, last_activity];
+        async move {
+            loop {
+                tokio::time::sleep(CHECK_INTERVAL).await;
+                if STATIC_ARGS.use_mock || !state.read().initialized {
+                    continue;
+                }
+
+                let away_after = Duration::from_secs(
+                    60 * state.read().configuration.presence.away_after_minutes as u64,
+                );
+                let idle = last_activity.read().elapsed() >= away_after;
+                let in_call = state.read().ui.call_info.active_call().is_some();
+                let target = state.write_silent().update_auto_status(idle, in_call);
+                if let Some(status) = target {
+                    log::debug!("switching to {status:?} automatically");
+                    set_status(&state, status).await;
+                }
+
+                if state
+                    .read()
+                    .auto_status()
+                    .status_message_expired(Utc::now())
+                {
+                    clear_status_message(&state).await;
+                }
+            }
+        }
+    });
+
+    Some(())
+}